@@ -0,0 +1,267 @@
+// analysis of a computed λ grid
+
+// smallest and largest λ, ignoring pixels not computed yet or bailed out
+pub fn lambda_range(lambdas: &[f64]) -> (f64, f64) {
+    lambdas
+        .iter()
+        .filter(|l| l.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &l| {
+            (min.min(l), max.max(l))
+        })
+}
+
+// estimate the fractal dimension of the border between order (λ < 0) and chaos (λ > 0)
+// a pixel belongs to the border if its sign differs from its right or lower neighbour.
+// the grid is covered with boxes of size 1, 2, 4, ... and the slope of log(N) over log(1/size) is fitted.
+// NaN (not yet computed) pixels are ignored. None if there is no border in the grid.
+pub fn box_counting_dimension(lambdas: &[f64], width: usize, height: usize) -> Option<f64> {
+    let border = chaos_border(lambdas, width, height);
+    if !border.iter().any(|&b| b) {
+        return None;
+    }
+
+    // need at least a few boxes along the short side to be meaningful
+    let mut samples = Vec::new();
+    let mut size = 1;
+    while size <= width.min(height) / 4 {
+        let count = count_boxes(&border, width, height, size);
+        samples.push(((1.0 / size as f64).ln(), (count as f64).ln()));
+        size *= 2;
+    }
+
+    if samples.len() < 2 {
+        return None;
+    }
+    Some(slope(&samples))
+}
+
+// crude "is this pretty" score for automatic search: edge density of the chaos border times spread of λ.
+// all order or all chaos scores 0, rich borders with varied shading score high.
+pub fn interestingness(lambdas: &[f64], width: usize, height: usize) -> f64 {
+    let border = chaos_border(lambdas, width, height);
+    let edge_density = border.iter().filter(|&&b| b).count() as f64 / border.len() as f64;
+
+    // clamp, superstable pixels are close to -inf
+    let values: Vec<f64> = lambdas
+        .iter()
+        .filter(|l| !l.is_nan())
+        .map(|l| l.clamp(-5.0, 5.0))
+        .collect();
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|l| (l - mean).powi(2)).sum::<f64>() / values.len() as f64;
+
+    edge_density * variance.sqrt()
+}
+
+// the λ = 0 line between order and chaos as line segments, marching squares over the pixel centers.
+// coordinates in pixels, (0.5, 0.5) is the center of the top left pixel. cells with a NaN corner are skipped.
+pub fn zero_contour(lambdas: &[f64], width: usize, height: usize) -> Vec<[(f64, f64); 2]> {
+    // ±∞ of bailed out and superstable pixels would break the interpolation
+    let value = |x: usize, y: usize| lambdas[y * width + x].clamp(-5.0, 5.0);
+
+    let mut segments = Vec::new();
+    for y in 0..height.saturating_sub(1) {
+        for x in 0..width.saturating_sub(1) {
+            let corners = [
+                value(x, y),
+                value(x + 1, y),
+                value(x + 1, y + 1),
+                value(x, y + 1),
+            ];
+            if corners.iter().any(|v| v.is_nan()) {
+                continue;
+            }
+            let chaotic = corners.map(|v| v > 0.0);
+            let positions = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+
+            // crossing on the edges top, right, bottom, left
+            let mut crossings = [None; 4];
+            for (edge, crossing) in crossings.iter_mut().enumerate() {
+                let (from, to) = (edge, (edge + 1) % 4);
+                if chaotic[from] != chaotic[to] {
+                    let t = corners[from] / (corners[from] - corners[to]);
+                    let ((x0, y0), (x1, y1)) = (positions[from], positions[to]);
+                    *crossing = Some((
+                        x as f64 + 0.5 + x0 + (x1 - x0) * t,
+                        y as f64 + 0.5 + y0 + (y1 - y0) * t,
+                    ));
+                }
+            }
+
+            let [top, right, bottom, left] = crossings;
+            match crossings.iter().flatten().collect::<Vec<_>>()[..] {
+                [&p0, &p1] => segments.push([p0, p1]),
+                // saddle, the mean of the corners decides which diagonal is connected
+                [_, _, _, _] => {
+                    let center_chaotic = corners.iter().sum::<f64>() > 0.0;
+                    let pairs = if center_chaotic == chaotic[0] {
+                        [(top, right), (bottom, left)]
+                    } else {
+                        [(left, top), (right, bottom)]
+                    };
+                    for (p0, p1) in pairs {
+                        segments.extend(p0.zip(p1).map(|(p0, p1)| [p0, p1]));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    segments
+}
+
+// mark every pixel whose regime differs from the right or lower neighbour
+// indices of pixels with a 4-neighbor on the other side of λ = 0, or with |λ| below near_zero themselves.
+// pixels that are not finite (not computed, bailed out, superstable) are never part of it
+pub fn border_pixels(lambdas: &[f64], width: usize, height: usize, near_zero: f64) -> Vec<usize> {
+    let known = |i: usize| lambdas[i].is_finite();
+    let chaotic = |i: usize| lambdas[i] > 0.0;
+    (0..width * height)
+        .filter(|&i| {
+            if !known(i) {
+                return false;
+            }
+            if lambdas[i].abs() < near_zero {
+                return true;
+            }
+            let (x, y) = (i % width, i / width);
+            [
+                (x > 0).then(|| i - 1),
+                (x + 1 < width).then(|| i + 1),
+                (y > 0).then(|| i - width),
+                (y + 1 < height).then(|| i + width),
+            ]
+            .into_iter()
+            .flatten()
+            .any(|n| known(n) && chaotic(n) != chaotic(i))
+        })
+        .collect()
+}
+
+// magnitude of the spatial gradient of λ per pixel in λ per pixel step, central differences where both
+// neighbours are finite, one sided next to missing ones, 0 along an axis without any. NaN where λ itself
+// is not finite
+pub fn gradient(lambdas: &[f64], width: usize, height: usize) -> Vec<f64> {
+    let known = |i: usize| lambdas[i].is_finite().then_some(lambdas[i]);
+    let derivative = |before: Option<f64>, center: f64, after: Option<f64>| match (before, after) {
+        (Some(before), Some(after)) => (after - before) / 2.0,
+        (Some(before), None) => center - before,
+        (None, Some(after)) => after - center,
+        (None, None) => 0.0,
+    };
+    (0..width * height)
+        .map(|i| {
+            let Some(center) = known(i) else {
+                return f64::NAN;
+            };
+            let (x, y) = (i % width, i / width);
+            let left = (x > 0).then(|| known(i - 1)).flatten();
+            let right = (x + 1 < width).then(|| known(i + 1)).flatten();
+            let up = (y > 0).then(|| known(i - width)).flatten();
+            let down = (y + 1 < height).then(|| known(i + width)).flatten();
+            derivative(left, center, right).hypot(derivative(up, center, down))
+        })
+        .collect()
+}
+
+fn chaos_border(lambdas: &[f64], width: usize, height: usize) -> Vec<bool> {
+    let chaotic = |i: usize| lambdas[i] > 0.0;
+    let known = |i: usize| !lambdas[i].is_nan();
+
+    let mut border = vec![false; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            let i = y * width + x;
+            if !known(i) {
+                continue;
+            }
+            let right = x + 1 < width && known(i + 1) && chaotic(i) != chaotic(i + 1);
+            let below = y + 1 < height && known(i + width) && chaotic(i) != chaotic(i + width);
+            border[i] = right || below;
+        }
+    }
+    border
+}
+
+// number of size x size boxes containing at least one border pixel
+fn count_boxes(border: &[bool], width: usize, height: usize, size: usize) -> usize {
+    let mut count = 0;
+    for by in (0..height).step_by(size) {
+        for bx in (0..width).step_by(size) {
+            let hit = (by..(by + size).min(height))
+                .any(|y| (bx..(bx + size).min(width)).any(|x| border[y * width + x]));
+            if hit {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+// least squares slope of y over x
+fn slope(samples: &[(f64, f64)]) -> f64 {
+    let n = samples.len() as f64;
+    let mean_x = samples.iter().map(|s| s.0).sum::<f64>() / n;
+    let mean_y = samples.iter().map(|s| s.1).sum::<f64>() / n;
+
+    let covariance: f64 = samples
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = samples.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    covariance / variance
+}
+
+// longest period told apart from chaos and how close a state has to come back to count as repeating
+const LONGEST_PERIOD: usize = 16;
+const PERIOD_TOLERANCE: f64 = 1e-6;
+
+// smallest p with the last states of an orbit repeating every p steps, None for chaos, quasiperiodic
+// orbits and periods beyond LONGEST_PERIOD
+pub fn period(states: &[f64]) -> Option<usize> {
+    let checked = 2 * LONGEST_PERIOD;
+    if states.len() < checked + LONGEST_PERIOD {
+        return None;
+    }
+    let tail = states.len() - checked;
+    (1..=LONGEST_PERIOD).find(|&p| {
+        (tail..states.len()).all(|i| (states[i] - states[i - p]).abs() < PERIOD_TOLERANCE)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: usize = 64;
+
+    fn grid(lambda: impl Fn(usize, usize) -> f64) -> Vec<f64> {
+        (0..SIZE * SIZE)
+            .map(|i| lambda(i % SIZE, i / SIZE))
+            .collect()
+    }
+
+    #[test]
+    fn a_straight_border_has_dimension_one() {
+        let lambdas = grid(|x, _| if x < SIZE / 2 { -1.0 } else { 1.0 });
+        let dimension = box_counting_dimension(&lambdas, SIZE, SIZE).unwrap();
+        assert!((dimension - 1.0).abs() < 1e-9, "{dimension}");
+    }
+
+    #[test]
+    fn a_border_filling_the_grid_has_dimension_two() {
+        let lambdas = grid(|x, y| if (x + y) % 2 == 0 { -1.0 } else { 1.0 });
+        let dimension = box_counting_dimension(&lambdas, SIZE, SIZE).unwrap();
+        assert!((dimension - 2.0).abs() < 0.01, "{dimension}");
+    }
+
+    #[test]
+    fn no_border_and_missing_pixels_give_no_dimension() {
+        assert_eq!(box_counting_dimension(&grid(|_, _| -1.0), SIZE, SIZE), None);
+        let lambdas = grid(|x, _| if x < SIZE / 2 { -1.0 } else { f64::NAN });
+        assert_eq!(box_counting_dimension(&lambdas, SIZE, SIZE), None);
+    }
+}
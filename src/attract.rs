@@ -0,0 +1,182 @@
+// screensaver like attract mode: endlessly show presets and randomly discovered diagrams
+
+use crate::color::{lerp_rgb, Palette};
+use crate::display::Display;
+use crate::frame::Frame;
+use crate::i18n;
+use crate::presets::PRESETS;
+use crate::render::{self, Kernel, Region};
+use crate::rng::Rng;
+use clap::ValueEnum;
+use std::time::{Duration, Instant};
+
+const LINGER: Duration = Duration::from_secs(8);
+const CROSSFADE_FRAMES: u32 = 60; // one second at 60 fps
+const ROWS_PER_UPDATE: usize = 16;
+
+// auto discovery looks at tiny previews and keeps regions showing order and chaos side by side
+const PREVIEW_SIZE: usize = 32;
+const DISCOVERY_ATTEMPTS: usize = 20;
+const MIN_CHAOS: f64 = 0.15;
+const MAX_CHAOS: f64 = 0.85;
+
+pub fn run(display: &mut dyn Display, seed: Option<u64>) {
+    let mut rng = Rng::seeded(seed);
+
+    // the first diagram grows on screen, later ones are rendered behind the current one and crossfaded
+    let (kernel, region, palette) = pick(&mut rng);
+    show_title(display, &kernel, &region);
+    let mut current = display_frame(display);
+    if !crate::render_progressive(display, &mut current, &region, &kernel, palette, None) {
+        return;
+    }
+
+    loop {
+        if !linger(display) {
+            return;
+        }
+
+        // every diagram is rendered at the current window size
+        let (kernel, region, palette) = pick(&mut rng);
+        let mut next = display_frame(display);
+        if !render_hidden(display, &mut next, &region, &kernel, palette) {
+            return;
+        }
+        show_title(display, &kernel, &region);
+        if next.size() == current.size() && !crossfade(display, &current, &next) {
+            return;
+        }
+        current = next;
+        display.present(&current.buffer, current.width, current.height);
+    }
+}
+
+fn display_frame(display: &dyn Display) -> Frame {
+    let (width, height) = display.size();
+    Frame::new(width.max(1), height.max(1))
+}
+
+// keep the finished diagram on screen for a while
+fn linger(display: &mut dyn Display) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < LINGER {
+        if !display.is_open() {
+            return false;
+        }
+        display.poll_events();
+    }
+    true
+}
+
+// render into a back buffer while the window keeps showing the current diagram
+fn render_hidden(
+    display: &mut dyn Display,
+    next: &mut Frame,
+    region: &Region,
+    kernel: &Kernel,
+    palette: Palette,
+) -> bool {
+    let (width, height) = next.size();
+    for start in (0..height).step_by(ROWS_PER_UPDATE) {
+        if !display.is_open() {
+            return false;
+        }
+        let rows = start..(start + ROWS_PER_UPDATE).min(height);
+        render::render_rows(
+            &mut next.lambdas,
+            width,
+            height,
+            region,
+            kernel,
+            rows.clone(),
+        );
+        next.colorize_rows(rows, palette);
+        display.poll_events();
+    }
+    true
+}
+
+// both frames need the same size
+fn crossfade(display: &mut dyn Display, from: &Frame, to: &Frame) -> bool {
+    let mut buffer = from.buffer.clone();
+    for step in 1..=CROSSFADE_FRAMES {
+        if !display.is_open() {
+            return false;
+        }
+        let t = step as f64 / CROSSFADE_FRAMES as f64;
+        for (pixel, (&c1, &c2)) in buffer.iter_mut().zip(from.buffer.iter().zip(&to.buffer)) {
+            *pixel = lerp_rgb(c1, c2, t);
+        }
+        display.present(&buffer, from.width, from.height);
+        display.poll_events();
+    }
+    true
+}
+
+fn show_title(display: &mut dyn Display, kernel: &Kernel, region: &Region) {
+    let sequence: String = kernel.sequence_rule.iter().collect();
+    display.set_title(&format!(
+        "{sequence} a {:.4}..{:.4} b {:.4}..{:.4} - {}",
+        region.x_min,
+        region.x_max,
+        region.y_min,
+        region.y_max,
+        i18n::tr("press ESC to exit")
+    ));
+}
+
+// half of the time a preset, otherwise try to discover something new in a random palette
+fn pick(rng: &mut Rng) -> (Kernel, Region, Palette) {
+    if rng.next_f64() >= 0.5 {
+        if let Some((kernel, region)) = discover(rng) {
+            let palettes = Palette::value_variants();
+            return (kernel, region, palettes[rng.below(palettes.len())]);
+        }
+    }
+
+    let preset = &PRESETS[rng.below(PRESETS.len())];
+    (
+        Kernel::new(preset.sequence.chars().collect()),
+        preset.region,
+        preset.palette,
+    )
+}
+
+// random sequence and region, accepted if a small preview is neither all order nor all chaos
+fn discover(rng: &mut Rng) -> Option<(Kernel, Region)> {
+    let mut preview = vec![f64::NAN; PREVIEW_SIZE * PREVIEW_SIZE];
+
+    for _ in 0..DISCOVERY_ATTEMPTS {
+        let kernel = Kernel::new(random_sequence(rng));
+
+        let size = rng.range(0.2, 2.0);
+        let x_min = rng.range(0.0, 4.0 - size);
+        let y_min = rng.range(0.0, 4.0 - size);
+        let region = Region::new(x_min, x_min + size, y_min, y_min + size);
+
+        render::render_rows(
+            &mut preview,
+            PREVIEW_SIZE,
+            PREVIEW_SIZE,
+            &region,
+            &kernel,
+            0..PREVIEW_SIZE,
+        );
+        let chaos = preview.iter().filter(|&&l| l > 0.0).count() as f64 / preview.len() as f64;
+        if (MIN_CHAOS..=MAX_CHAOS).contains(&chaos) {
+            return Some((kernel, region));
+        }
+    }
+    None
+}
+
+// 2..=10 letters, always using both A and B
+fn random_sequence(rng: &mut Rng) -> Vec<char> {
+    let len = 2 + rng.below(9);
+    let mut sequence: Vec<char> = (0..len)
+        .map(|_| if rng.next_f64() < 0.5 { 'A' } else { 'B' })
+        .collect();
+    sequence[0] = 'A';
+    sequence[len - 1] = 'B';
+    sequence
+}
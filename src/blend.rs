@@ -0,0 +1,118 @@
+// blend subcommand: frames morphing from one diagram into another
+// by default both λ grids are rendered once and interpolated.
+// with --params the ranges are interpolated and every frame is rendered, the λ of both sequences is blended.
+
+use crate::cache::stable_hash;
+use crate::cli::BlendArgs;
+use crate::color::colorize;
+use crate::error::Error;
+use crate::i18n;
+use crate::interrupt;
+use crate::render::{self, parse_sequence, Kernel, Region};
+use crate::video::Frames;
+use crate::{HEIGHT, WIDTH};
+
+pub fn run(args: &BlendArgs) -> Result<(), Error> {
+    interrupt::install();
+    let (from_kernel, from_region) = parse_view(&args.from)?;
+    let (to_kernel, to_region) = parse_view(&args.to)?;
+    let frames = args.frames as usize;
+    let mut sink = Frames::new(
+        &args.out,
+        "blend",
+        args.video.as_deref(),
+        args.fps,
+        (WIDTH, HEIGHT),
+        args.resume_animation,
+    )?;
+
+    let mut from = vec![f64::NAN; WIDTH * HEIGHT];
+    let mut to = vec![f64::NAN; WIDTH * HEIGHT];
+    let mut buffer = vec![0u32; WIDTH * HEIGHT];
+    if !args.params {
+        render::render_rows(
+            &mut from,
+            WIDTH,
+            HEIGHT,
+            &from_region,
+            &from_kernel,
+            0..HEIGHT,
+        );
+        render::render_rows(&mut to, WIDTH, HEIGHT, &to_region, &to_kernel, 0..HEIGHT);
+    }
+
+    for frame in 0..frames {
+        let t = frame as f64 / (frames - 1) as f64;
+        let hash = stable_hash(&format!(
+            "blend {} {} {} {t} {WIDTH}x{HEIGHT}",
+            args.from, args.to, args.params
+        ));
+        if let Some(path) = sink.is_done(frame, hash) {
+            println!(
+                "{}",
+                i18n::trf(
+                    "frame {0}/{1}: {2} (done)",
+                    &[&(frame + 1), &frames, &path.display()]
+                )
+            );
+            continue;
+        }
+        if args.params {
+            let region = lerp_region(&from_region, &to_region, t);
+            render::render_rows(&mut from, WIDTH, HEIGHT, &region, &from_kernel, 0..HEIGHT);
+            if from_kernel == to_kernel {
+                to.copy_from_slice(&from);
+            } else {
+                render::render_rows(&mut to, WIDTH, HEIGHT, &region, &to_kernel, 0..HEIGHT);
+            }
+        }
+
+        for (pixel, (&l1, &l2)) in buffer.iter_mut().zip(from.iter().zip(&to)) {
+            *pixel = colorize(l1 + (l2 - l1) * t);
+        }
+
+        let path = sink.write(frame, &buffer, hash)?;
+        println!(
+            "{}",
+            i18n::trf(
+                "frame {0}/{1}: {2}",
+                &[&(frame + 1), &frames, &path.display()]
+            )
+        );
+        if interrupt::requested() {
+            sink.finish()?;
+            return Err(Error::Interrupted(format!(
+                "{} of {frames} frames, --resume-animation continues",
+                frame + 1
+            )));
+        }
+    }
+    sink.finish()
+}
+
+// SEQUENCE:x_min,x_max,y_min,y_max
+fn parse_view(view: &str) -> Result<(Kernel, Region), Error> {
+    let invalid = || {
+        Error::usage(i18n::trf(
+            "invalid view '{0}', expected e.g. BBBBBBAAAAAA:3.4,4.0,2.5,3.4",
+            &[&view],
+        ))
+    };
+
+    let (sequence, ranges) = view.split_once(':').ok_or_else(invalid)?;
+    let region = Region::parse(ranges).ok_or_else(invalid)?;
+    region
+        .check()
+        .map_err(|e| Error::usage(i18n::trf("invalid view '{0}': {1}", &[&view, &e])))?;
+    Ok((Kernel::new(parse_sequence(sequence)?), region))
+}
+
+fn lerp_region(from: &Region, to: &Region, t: f64) -> Region {
+    let lerp = |v1: f64, v2: f64| v1 + (v2 - v1) * t;
+    Region::new(
+        lerp(from.x_min, to.x_min),
+        lerp(from.x_max, to.x_max),
+        lerp(from.y_min, to.y_min),
+        lerp(from.y_max, to.y_max),
+    )
+}
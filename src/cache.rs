@@ -0,0 +1,286 @@
+// λ values computed before, kept across renders and runs. a view that is panned by whole pixels,
+// recolored or visited again mostly consists of pixels that are already known.
+// keys are the kernel (sequence, depth, map, ...) and a, b rounded to QUANTUM, so the tiny float
+// differences from panning still hit. the least recently used entries go first once it is full.
+// on disk it is a flat file of 32 byte records: kernel hash, a and b as i64 multiples of QUANTUM, λ.
+
+use crate::render::{self, Kernel, Region};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+
+pub const QUANTUM: f64 = 1e-12;
+const RECORD: usize = 32;
+
+pub struct LambdaCache {
+    capacity: usize,
+    entries: HashMap<(u64, i64, i64), (f64, u64)>,
+    // use counter, higher is more recent
+    tick: u64,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl LambdaCache {
+    pub fn new(capacity: usize) -> Self {
+        LambdaCache {
+            capacity,
+            entries: HashMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    // an empty cache if the file does not exist yet
+    pub fn load(path: &Path, capacity: usize) -> io::Result<Self> {
+        let mut cache = LambdaCache::new(capacity);
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(cache),
+            Err(e) => return Err(e),
+        };
+        // the file is written most recent first, the first ones get the highest ticks
+        let mut records = Vec::new();
+        BufReader::new(file).read_to_end(&mut records)?;
+        let count = records.len() / RECORD;
+        for (index, record) in records.chunks_exact(RECORD).take(capacity).enumerate() {
+            let field = |i: usize| <[u8; 8]>::try_from(&record[i * 8..(i + 1) * 8]).unwrap();
+            let key = (
+                u64::from_le_bytes(field(0)),
+                i64::from_le_bytes(field(1)),
+                i64::from_le_bytes(field(2)),
+            );
+            let lambda = f64::from_le_bytes(field(3));
+            cache.entries.insert(key, (lambda, (count - index) as u64));
+        }
+        cache.tick = count as u64;
+        Ok(cache)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_unstable_by_key(|(_, &(_, used))| std::cmp::Reverse(used));
+        let mut out = BufWriter::new(File::create(path)?);
+        for (&(kernel, a, b), &(lambda, _)) in entries {
+            out.write_all(&kernel.to_le_bytes())?;
+            out.write_all(&a.to_le_bytes())?;
+            out.write_all(&b.to_le_bytes())?;
+            out.write_all(&lambda.to_le_bytes())?;
+        }
+        out.flush()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // pixels closer than QUANTUM would share one entry, such views are rendered without the cache
+    pub fn covers(region: &Region, width: usize, height: usize) -> bool {
+        let pixel = ((region.x_max - region.x_min) / width as f64)
+            .abs()
+            .min(((region.y_max - region.y_min) / height as f64).abs());
+        pixel > QUANTUM * 100.0
+    }
+
+    // like render::render_rows, known pixels are taken from the cache and the others computed on the
+    // --threads computing threads
+    pub fn render_rows(
+        &mut self,
+        lambdas: &mut [f64],
+        width: usize,
+        height: usize,
+        region: &Region,
+        kernel: &Kernel,
+        rows: Range<usize>,
+    ) {
+        let kernel_key = kernel.key();
+        let mut missing = Vec::new();
+        for y in rows {
+            for x in 0..width {
+                let (a, b) = region.pixel_to_world(x, y, width, height);
+                let key = (
+                    kernel_key,
+                    (a / QUANTUM).round() as i64,
+                    (b / QUANTUM).round() as i64,
+                );
+                self.tick += 1;
+                match self.entries.get_mut(&key) {
+                    Some((lambda, used)) => {
+                        *used = self.tick;
+                        self.hits += 1;
+                        lambdas[y * width + x] = *lambda;
+                    }
+                    None => missing.push((y * width + x, key, self.tick, (a, b))),
+                }
+            }
+        }
+
+        self.misses += missing.len() as u64;
+        let points: Vec<(f64, f64)> = missing.iter().map(|&(_, _, _, point)| point).collect();
+        let mut computed = vec![f64::NAN; points.len()];
+        render::render_points(&mut computed, &points, kernel);
+        for ((index, key, used, _), lambda) in missing.into_iter().zip(computed) {
+            lambdas[index] = lambda;
+            self.entries.insert(key, (lambda, used));
+        }
+        self.evict();
+    }
+
+    // dropping the oldest tenth at once instead of one entry per insert keeps this rare
+    fn evict(&mut self) {
+        let capacity = self.capacity.max(1);
+        if self.entries.len() <= capacity {
+            return;
+        }
+        let keep = capacity - capacity / 10;
+        let mut used: Vec<u64> = self.entries.values().map(|&(_, used)| used).collect();
+        let drop = used.len() - keep;
+        let (_, &mut threshold, _) = used.select_nth_unstable(drop);
+        self.entries.retain(|_, &mut (_, used)| used >= threshold);
+    }
+}
+
+// FNV-1a, unlike the std hasher the same on every build, so it can be stored
+pub fn stable_hash(text: &str) -> u64 {
+    StableHasher::default().bytes(text.as_bytes()).finish()
+}
+
+// stable_hash fed piece by piece, for keys made of several values. numbers go in as their little
+// endian bytes, floats by their bits
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl StableHasher {
+    pub fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+        self
+    }
+
+    pub fn u64(&mut self, value: u64) -> &mut Self {
+        self.bytes(&value.to_le_bytes())
+    }
+
+    pub fn f64(&mut self, value: f64) -> &mut Self {
+        self.u64(value.to_bits())
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: usize = 8;
+
+    fn render(cache: &mut LambdaCache, kernel: &Kernel) -> Vec<f64> {
+        let region = Region::new(2.0, 4.0, 2.0, 4.0);
+        let mut lambdas = vec![f64::NAN; SIZE * SIZE];
+        cache.render_rows(&mut lambdas, SIZE, SIZE, &region, kernel, 0..SIZE);
+        lambdas
+    }
+
+    fn bits(lambdas: &[f64]) -> Vec<u64> {
+        lambdas.iter().map(|lambda| lambda.to_bits()).collect()
+    }
+
+    #[test]
+    fn cached_pixels_are_the_rendered_ones() {
+        let kernel = Kernel::new("AB".chars().collect());
+        let region = Region::new(2.0, 4.0, 2.0, 4.0);
+        let mut rendered = vec![f64::NAN; SIZE * SIZE];
+        render::render_rows(&mut rendered, SIZE, SIZE, &region, &kernel, 0..SIZE);
+
+        let mut cache = LambdaCache::new(1000);
+        assert_eq!(bits(&render(&mut cache, &kernel)), bits(&rendered));
+        assert_eq!((cache.hits, cache.misses), (0, 64));
+        assert_eq!(bits(&render(&mut cache, &kernel)), bits(&rendered));
+        assert_eq!((cache.hits, cache.misses), (64, 64));
+    }
+
+    #[test]
+    fn other_kernels_miss() {
+        let mut cache = LambdaCache::new(1000);
+        render(&mut cache, &Kernel::new("AB".chars().collect()));
+        render(&mut cache, &Kernel::new("AAB".chars().collect()));
+        assert_eq!((cache.hits, cache.misses), (0, 128));
+        let deeper = Kernel {
+            depth: 400,
+            ..Kernel::new("AB".chars().collect())
+        };
+        render(&mut cache, &deeper);
+        assert_eq!(cache.hits, 0);
+    }
+
+    #[test]
+    fn least_recently_used_entries_go_first() {
+        let mut cache = LambdaCache::new(100);
+        let old = Kernel::new("AB".chars().collect());
+        let new = Kernel::new("AAB".chars().collect());
+        render(&mut cache, &old);
+        render(&mut cache, &new);
+        assert!(cache.len() <= 100);
+        render(&mut cache, &new);
+        assert_eq!(cache.hits, 64);
+    }
+
+    #[test]
+    fn entries_survive_saving() {
+        let path = std::env::temp_dir().join(format!("ljapunow_cache_{}.bin", std::process::id()));
+        let kernel = Kernel::new("AB".chars().collect());
+        let mut cache = LambdaCache::new(1000);
+        let rendered = render(&mut cache, &kernel);
+        cache.save(&path).unwrap();
+
+        let mut loaded = LambdaCache::load(&path, 1000).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.len(), 64);
+        assert_eq!(bits(&render(&mut loaded, &kernel)), bits(&rendered));
+        assert_eq!(loaded.misses, 0);
+    }
+
+    #[test]
+    fn kernel_keys_tell_kernels_apart() {
+        let kernel = Kernel::new("AB".chars().collect());
+        assert_eq!(kernel.key(), kernel.clone().key());
+        let others = [
+            Kernel {
+                start: 0.25,
+                ..kernel.clone()
+            },
+            Kernel {
+                superstable: Some(1e-6),
+                ..kernel.clone()
+            },
+            Kernel {
+                alphabet: render::Alphabet::parse("A=a,B=b,C=(a+b)/2").unwrap(),
+                ..kernel.clone()
+            },
+            Kernel {
+                alphabet: render::Alphabet::parse("A=a,B=b,C=(a-b)/2").unwrap(),
+                ..kernel.clone()
+            },
+        ];
+        let mut keys: Vec<u64> = others.iter().map(Kernel::key).collect();
+        keys.push(kernel.key());
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(keys.len(), others.len() + 1);
+    }
+}
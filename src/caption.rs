@@ -0,0 +1,85 @@
+// self-documenting exports: a plain margin around the image and below it a caption with the parameters
+// of the view on the left and a signature on the right, on separate lines if they do not fit side by side
+
+use crate::font::{self, draw_text};
+
+const BACKGROUND: u32 = 0xFFFFFF;
+const INK: u32 = 0x000000;
+// text is drawn this large unless a line would not fit the image width
+const TEXT_SCALE: usize = 2;
+// between the image and the text and between text lines, at least as wide as the margin
+const LINE_GAP: usize = 8;
+// between caption and signature on one line
+const COLUMN_GAP: usize = 24;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Caption {
+    pub margin: usize,
+    pub text: Option<String>,
+    pub signature: Option<String>,
+}
+
+impl Caption {
+    // the image surrounded by the margin with the text lines below, as 0RGB buffer with its size
+    pub fn compose(
+        &self,
+        (buffer, width, height): (Vec<u32>, usize, usize),
+    ) -> (Vec<u32>, usize, usize) {
+        let text = self.text.as_deref().unwrap_or("");
+        let signature = self.signature.as_deref().unwrap_or("");
+        let widest = font::text_size(text, 1)
+            .0
+            .max(font::text_size(signature, 1).0);
+        let scale = if widest * TEXT_SCALE <= width {
+            TEXT_SCALE
+        } else {
+            1
+        };
+        let (text_width, line_height) = font::text_size(text, scale);
+        let signature_width = font::text_size(signature, scale).0;
+        let lines = match (text.is_empty(), signature.is_empty()) {
+            (true, true) => 0,
+            (false, false) if text_width + COLUMN_GAP + signature_width > width => 2,
+            _ => 1,
+        };
+
+        let margin = self.margin;
+        let gap = LINE_GAP.max(margin);
+        let text_height = match lines {
+            0 => 0,
+            _ => lines * line_height + (lines - 1) * LINE_GAP + gap,
+        };
+        let total_width = width + 2 * margin;
+        let total_height = height + 2 * margin + text_height;
+        let size = (total_width, total_height);
+        let mut composed = vec![BACKGROUND; total_width * total_height];
+        for y in 0..height {
+            let start = (margin + y) * total_width + margin;
+            composed[start..start + width].copy_from_slice(&buffer[y * width..(y + 1) * width]);
+        }
+
+        let first = (margin + height + gap) as i64;
+        draw_text(
+            &mut composed,
+            size,
+            (margin as i64, first),
+            text,
+            INK,
+            scale,
+        );
+        let signature_y = match lines {
+            2 => first + (line_height + LINE_GAP) as i64,
+            _ => first,
+        };
+        let signature_x = (margin + width).saturating_sub(signature_width) as i64;
+        draw_text(
+            &mut composed,
+            size,
+            (signature_x, signature_y),
+            signature,
+            INK,
+            scale,
+        );
+        (composed, total_width, total_height)
+    }
+}
@@ -0,0 +1,1029 @@
+// command line definition
+
+use crate::color::Palette;
+use crate::config::Config;
+use crate::contrast::Transfer;
+use crate::error::Error;
+use crate::filter::Filter;
+use crate::grid_file::Storage;
+use crate::i18n::{self, Lang};
+use crate::measure::Measure;
+use crate::plugin;
+use crate::presets::{self, Preset};
+
+use crate::render::{
+    parse_letter, Alphabet, Bailout, Drift, Forcing, LogZero, Region, Spectrum, DEFAULT_BAILOUT,
+    WARMUP,
+};
+use crate::scaling::Upscale;
+use crate::screen;
+use crate::split::PaneSpec;
+use crate::template;
+use crate::term::TermMode;
+use clap::builder::StyledStr;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
+
+// monitors per row and column of --span
+const MAX_SPAN: usize = 16;
+
+const EXAMPLES: &str = "\
+Examples:
+  ljapunow                                     a random preset, its name is printed to show it again
+  ljapunow AB --region 2,4,2,4                 the classic AB diagram
+  ljapunow --preset jellyfish --palette ramp   a preset with its own palette replaced
+  ljapunow --default-preset                    always start with zircon-city instead of a random preset
+  ljapunow --attract                           screensaver mode
+  ljapunow --attract --fullscreen              exhibition / projector display
+  ljapunow --attract --fbdev                   kiosk on the Linux framebuffer
+  ljapunow --fullscreen --display-seconds 30   show the finished diagram for 30 s, then exit
+  ljapunow --fullscreen --span 3x2             one diagram over a video wall of six monitors
+  ljapunow --fullscreen --touch                touchscreen kiosk, drag to pan and pinch to zoom
+  ljapunow --scale 3                           big blocky pixels, quick previews of deep views
+  ljapunow --scale 3 --upscale lanczos         quick previews without the blocks
+  ljapunow --render-scale 0.5 --upscale bilinear   explore at half resolution, Enter for the full one
+  ljapunow --preset swallow --out swallow.svg  figure with λ = 0 contour and axes
+  ljapunow --out a.png --ridge border.geojson  the order/chaos border as sub-pixel lines for GIS tools
+  ljapunow --out a.png --components windows.csv --color-components --min-area 20   count the stability windows
+  ljapunow --clahe 4 --clahe-tile 32           local contrast in wide stable areas
+  ljapunow --transfer sqrt                     more colors for the fine structure near λ = 0
+  ljapunow --chaos-gradient                    structure inside the chaotic seas instead of black
+  ljapunow --out print.png --filter unsharp --filter local-contrast:40   sharpened for print
+  ljapunow --out a.png --caption --signature J.Doe --margin 24   self-documenting image to share
+  ljapunow sweep --param x0 --from 0.1 --to 0.9 --steps 16   how λ depends on the start value
+  ljapunow sweep --param x0 --from 0.1 --to 0.9 --steps 64 --track windows.csv --min-area 20   windows over x0
+  ljapunow --palettes colors.toml --palette sunset   own color ramp from [palettes.sunset]
+  ljapunow --colorizer ./libspiral.so --palette spiral   palette computed by a plugin
+  ljapunow --out a.png --trace a.json          where the time goes, open a.json in ui.perfetto.dev
+  ljapunow --out a.png --threads 2             leave the other cores to the rest of the machine
+  ljapunow --out big.png --out-size 8000x8000 --nice   long render in the background
+  ljapunow --out layer.png --alpha-chaos       stable regions on a transparent background
+  ljapunow heightmap terrain.png --clamp -2,0.5   16 bit heightmap and normal map for Blender
+  ljapunow --terrain                           fly over the diagram as a shaded landscape
+  ljapunow --out 3d.png --stereo anaglyph      terrain view for red-cyan glasses
+  ljapunow --sonify wave                       hear the orbit under the cursor, chaos hisses, cycles hum
+  ljapunow --pane AB --pane AABAB:dual         two sequences side by side, zooming one zooms both
+  ljapunow --midi Synth                        draw a path with the left button, its orbits play on a MIDI synth
+  ljapunow --fullscreen --gamepad              exhibition steered with a game controller
+  ljapunow --histogram --palette dual          λ distribution under the diagram, for tuning color ranges
+  ljapunow --colorbar --transfer sigmoid       which color stands for which λ, with the tone mapping applied
+  ljapunow --workspace study.json              pick up where the last session stopped, bookmarks included
+  ljapunow --autosave-dir journal              every view that settles as PNG and parameters, a visual diary
+  ljapunow --record tour.json                  log an exploration, --replay tour.json plays it back
+  ljapunow --remote 127.0.0.1:7400             drive the window from TouchOSC or a script, see README
+  ljapunow AB --region 2,4,2,4 --noise 0.01    noisy diagram averaged over 8 realizations
+  ljapunow AB --coupling 0.1                   largest exponent of two coupled logistic maps
+  ljapunow AB --coupling 0.1 --spectrum sum    dissipation of the coupled pair, see --spectrum
+  ljapunow --circle                            Arnold tongues of the circle map
+  ljapunow AB --drift sine:0.05                swept diagram, a and b swing by ±0.05 during the orbit
+  ljapunow --complex --on-bailout clamp        λ inside the logistic Mandelbrot set, escape rate outside
+  ljapunow ABCA --values A=a,B=b,C=3.74        a third letter with a fixed r
+  ljapunow ABCA --values A=a,B=b,C=3.74 --x-axis C --y-axis A   the fixed letter on an axis instead
+  ljapunow ABCA --letter C=(a+b)/2             a third letter forcing with the mean of a and b
+  ljapunow --circle --measure winding          rotation numbers, the devil's staircase of the tongues
+  ljapunow --measure entropy --palette dual    permutation entropy, tells chaotic regimes apart
+  ljapunow --measure frequency --depth 1044    dominant frequency from a 1024 step FFT of the orbit
+  ljapunow --wasm henon.wasm --region -2,2,-2,2   own dynamical system, sandboxed
+  ljapunow --preset jellyfish --term           preview in the terminal, --term sixel for real pixels
+  ljapunow blend AB:2,4,2,4 AABAB:2,4,2,4      30 frames morphing AB into AABAB
+  ljapunow evolve AB --generations 10          search for interesting sequences
+  ljapunow evolve AB --seed 42                 the same search again, every random choice repeats
+  ljapunow diff AB AABAB                       where AABAB is more stable (blue) or chaotic (red) than AB
+  ljapunow sensitivity AABAB --position 3      what flipping the third letter does to λ
+  ljapunow script scripts/zoom.rhai            frames from a script, see ljapunow script --help
+  ljapunow dump zircon.lgrid --size 40000x40000   gigapixel λ grid for other tools
+  ljapunow tiles zircon --size 16384x16384     Deep Zoom tile pyramid for OpenSeadragon
+  ljapunow stream --listen 0.0.0.0:9001        watch the render from a browser, see scripts/stream.html
+  ljapunow completions bash > ljapunow.bash    shell completions
+  ljapunow --lang de --help                    help, messages and window titles in German
+
+Keys: C toggles palette cycling, P switches the palette, . and , raise or lower the
+iteration depth, M the minimap (click it to jump), H the λ histogram, K the colorbar, E histogram
+equalization of the colors, 3 the terrain view
+(left drag turns and tilts it), L a path scan (click vertices, Enter writes λ along them as CSV),
+B bookmarks the view and N goes to the next bookmark, mouse wheel zooms, middle or right drag pans,
+S saves the view as SVG, F1 lists these keys in the window, ESC exits.";
+
+/// Ljapunow-Markus diagrams of the logistic map forced by a sequence of A and B.
+///
+/// Every pixel (a, b) iterates x → r·x·(1−x) with r taken from the sequence (A → a, B → b)
+/// and is colored by the Ljapunow exponent λ: order (λ < 0) in colors, chaos (λ > 0) black.
+#[derive(Debug, Parser)]
+#[command(version, after_help = EXAMPLES)]
+pub struct Cli {
+    /// Forcing sequence, only the letters A and B unless --values defines others [default: from the preset]
+    pub sequence: Option<String>,
+
+    /// Letters of the sequence and their values, a and b follow the axes, e.g. A=a,B=b,C=3.74 [default: A=a,B=b]
+    #[arg(long, value_name = "LETTER=VALUE,...", value_parser = Alphabet::parse)]
+    pub values: Option<Alphabet>,
+
+    /// Letter whose r is a formula of a and b, e.g. C=(a+b)/2 or D=a*b, repeatable
+    #[arg(long = "letter", value_name = "LETTER=FORMULA", value_parser = parse_letter)]
+    pub letters: Vec<(char, Forcing)>,
+
+    /// Letter whose value runs along the horizontal axis (a), the letter there takes its old value [default: A]
+    #[arg(long, value_name = "LETTER", requires = "y_axis")]
+    pub x_axis: Option<char>,
+
+    /// Letter whose value runs along the vertical axis (b), the letter there takes its old value [default: B]
+    #[arg(long, value_name = "LETTER", requires = "x_axis")]
+    pub y_axis: Option<char>,
+
+    /// Parameter range as a_min,a_max,b_min,b_max [default: from the preset]
+    #[arg(long, value_parser = parse_region, allow_hyphen_values = true)]
+    pub region: Option<Region>,
+
+    /// Color scheme for the stable regions [default: from the preset]
+    #[arg(long, value_enum)]
+    pub palette: Option<Palette>,
+
+    /// Named view bundling sequence, ranges and palette, see --list-presets [default: a random one in the window, zircon-city otherwise]
+    #[arg(long, value_parser = parse_preset)]
+    pub preset: Option<&'static Preset>,
+
+    /// Start with zircon-city instead of a random preset when neither sequence, region nor preset is given
+    #[arg(long, conflicts_with = "preset")]
+    pub default_preset: bool,
+
+    /// Print all presets and exit
+    #[arg(long)]
+    pub list_presets: bool,
+
+    /// Screensaver: endlessly show featured and randomly discovered diagrams
+    #[arg(long)]
+    pub attract: bool,
+
+    /// Threads computing rows of the diagram, e.g. 1 for reproducible benchmarks [default: all cores]
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(1..=1024))]
+    pub threads: Option<u32>,
+
+    /// Compute at the lowest OS priority, so long renders do not make the desktop sluggish
+    #[arg(long, global = true)]
+    pub nice: bool,
+
+    /// Seed of everything random (noise, attract mode, evolve), the same seed repeats a run exactly
+    #[arg(long = "seed", global = true)]
+    pub rng_seed: Option<u64>,
+
+    /// Language of help, messages and window titles [default: from LC_ALL, LC_MESSAGES or LANG]
+    #[arg(long, value_enum, global = true)]
+    pub lang: Option<Lang>,
+
+    /// TOML file with [palettes.NAME] color ramps for --palette, the --watch file may define them too
+    #[arg(long, value_name = "FILE", global = true)]
+    pub palettes: Option<PathBuf>,
+
+    /// Shared library computing a palette, registered under the name it reports for --palette
+    #[arg(long, value_name = "FILE", global = true)]
+    pub colorizer: Option<PathBuf>,
+
+    /// Write the time spent per pipeline stage, FILE.folded as folded stacks for flamegraphs, else Chrome trace JSON for perfetto
+    #[arg(long, value_name = "FILE", global = true)]
+    pub trace: Option<PathBuf>,
+
+    /// Print the diagram to the terminal instead of opening a window
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "ansi", conflicts_with_all = ["attract", "fullscreen"])]
+    pub term: Option<TermMode>,
+
+    /// Draw into a Linux framebuffer device instead of a window, e.g. /dev/fb0 on a Raspberry Pi
+    #[arg(long, num_args = 0..=1, default_missing_value = "/dev/fb0", conflicts_with_all = ["term", "fullscreen"])]
+    pub fbdev: Option<PathBuf>,
+
+    /// Render without a window into FILE, SVG with vector contour and axes if it ends in .svg, PNG otherwise
+    #[arg(long, value_name = "FILE", value_parser = parse_out, conflicts_with_all = ["attract", "term", "fbdev", "fullscreen"])]
+    pub out: Option<PathBuf>,
+
+    /// Image size for --out as WIDTHxHEIGHT
+    #[arg(long, default_value = "800x800", value_parser = parse_screen_size, requires = "out")]
+    pub out_size: (usize, usize),
+
+    /// Name of views saved with S in the window, {seq} {amin} {amax} {bmin} {bmax} {iters} {palette} {n}
+    /// are replaced, the same placeholders work in --out
+    #[arg(long, value_name = "TEMPLATE", default_value = template::DEFAULT_TEMPLATE, value_parser = template::validate)]
+    pub output_template: String,
+
+    /// Add a/b axes with ticks and a colorbar for λ to the --out image
+    #[arg(long, requires = "out")]
+    pub legend: bool,
+
+    /// Equalize λ within tiles before coloring (CLAHE), STRENGTH limits the contrast gain (1 = off)
+    #[arg(long, value_name = "STRENGTH", num_args = 0..=1, default_missing_value = "3", value_parser = parse_positive)]
+    pub clahe: Option<f64>,
+
+    /// Tile edge length of --clahe in pixels, smaller tiles bring out more local structure
+    #[arg(long, default_value_t = 64, value_parser = clap::value_parser!(u32).range(8..), requires = "clahe")]
+    pub clahe_tile: u32,
+
+    /// Tone map λ before the palette with log, sqrt (more colors near the border) or sigmoid[:K] (mid range)
+    #[arg(long, value_name = "FUNCTION", value_parser = parse_transfer)]
+    pub transfer: Option<Transfer>,
+
+    /// Shade chaotic regions (λ > 0) by how fast λ changes there instead of flat black
+    #[arg(long)]
+    pub chaos_gradient: bool,
+
+    /// Post process the --out image, NAME[:RADIUS[:AMOUNT]] with unsharp (1, 0.6) or local-contrast (30, 0.3), repeat to chain
+    #[arg(long = "filter", value_name = "FILTER", value_parser = parse_filter, requires = "out")]
+    pub filters: Vec<Filter>,
+
+    /// Caption below the --out image, placeholders as in --output-template, without TEXT the sequence, ranges and depth
+    #[arg(long, value_name = "TEXT", num_args = 0..=1, default_missing_value = template::DEFAULT_CAPTION, value_parser = template::validate, requires = "out")]
+    pub caption: Option<String>,
+
+    /// Signature written at the lower right below the --out image
+    #[arg(long, value_name = "TEXT", requires = "out")]
+    pub signature: Option<String>,
+
+    /// White margin in pixels around the --out image
+    #[arg(long, value_name = "PIXELS", default_value_t = 0, requires = "out")]
+    pub margin: u32,
+
+    /// Start in the terrain view, the λ grid as shaded landscape (3 toggles it, drag to turn and tilt)
+    #[arg(long, conflicts_with_all = ["out", "term", "attract"])]
+    pub terrain: bool,
+
+    /// Write the --out image as stereo pair of the terrain view instead of the flat diagram
+    #[arg(long, value_enum, requires = "out", conflicts_with_all = ["legend", "histogram"])]
+    pub stereo: Option<Stereo>,
+
+    /// Distance of the eyes of --stereo in diagram sizes, more is deeper
+    #[arg(long, default_value_t = 0.03, value_parser = parse_positive, requires = "stereo")]
+    pub eye_separation: f64,
+
+    /// Play the orbit under the mouse cursor as sound, chaos sounds noisy and periodic orbits tonal (needs the audio feature)
+    #[arg(long, value_enum, conflicts_with_all = ["out", "term", "attract"])]
+    pub sonify: Option<Sonify>,
+
+    /// Draw a path with the left button and play its orbit periods on the MIDI output port containing PORT (needs the midi feature)
+    #[arg(long, value_name = "PORT", num_args = 0..=1, default_missing_value = "", conflicts_with_all = ["out", "term", "attract"])]
+    pub midi: Option<String>,
+
+    /// Steer the window with a game controller: stick pans, triggers zoom, A palette, d-pad presets and depth (needs the gamepad feature)
+    #[arg(long, conflicts_with_all = ["out", "term", "attract", "panes"])]
+    pub gamepad: bool,
+
+    /// Split the window into panes over the same region, SEQUENCE[:PALETTE] each, give it 2 or 4 times
+    #[arg(long = "pane", value_name = "SEQUENCE[:PALETTE]", value_parser = parse_pane, conflicts_with_all = ["out", "term", "attract", "terrain", "watch", "record", "replay"])]
+    pub panes: Vec<PaneSpec>,
+
+    /// Leave chaotic pixels (λ > 0) transparent in the --out image, for compositing over other artwork
+    #[arg(long, requires = "out", conflicts_with = "alpha_stable")]
+    pub alpha_chaos: bool,
+
+    /// Leave ordered pixels (λ < 0) transparent in the --out image
+    #[arg(long, requires = "out")]
+    pub alpha_stable: bool,
+
+    /// Show the distribution of λ as a histogram panel (H toggles it), below the image with --out
+    #[arg(long)]
+    pub histogram: bool,
+
+    /// Show a colorbar mapping the colors to λ at the right edge of the window (K toggles it)
+    #[arg(long, conflicts_with = "out")]
+    pub colorbar: bool,
+
+    /// Follow a TOML file with sequence, region, palette and depth, re-rendering whenever it is saved
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["attract", "term", "out", "replay"])]
+    pub watch: Option<PathBuf>,
+
+    /// Take commands (sequence, region, palette, depth, save) from other programs, one per line over TCP or as OSC over UDP on this address
+    #[arg(long, value_name = "ADDRESS", conflicts_with_all = ["attract", "term", "out", "panes"])]
+    pub remote: Option<String>,
+
+    /// Log every navigation, palette and parameter change with timestamps to a JSON file
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["attract", "term", "replay"])]
+    pub record: Option<PathBuf>,
+
+    /// Replay a session written by --record, its starting state replaces sequence, region and palette
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["attract", "term"])]
+    pub replay: Option<PathBuf>,
+
+    /// Also write every replayed state as a numbered PNG into this directory
+    #[arg(long, value_name = "DIR", requires = "replay")]
+    pub replay_frames: Option<PathBuf>,
+
+    /// Compute λ on tile borders and interpolate tiles that are clearly ordered or chaotic all around
+    #[arg(long)]
+    pub quadtree: bool,
+
+    /// Render with a quarter of the depth first, then the order/chaos border with QUALITY times the depth and QUALITY² samples
+    #[arg(long, value_name = "QUALITY", num_args = 0..=1, default_missing_value = "2", value_parser = clap::value_parser!(u32).range(1..=8))]
+    pub adaptive_boundary: Option<u32>,
+
+    /// Trace λ = 0 between neighbouring pixels by bisection and write it as lines in a/b, FILE.geojson or FILE.csv
+    #[arg(long, value_name = "FILE", requires = "out")]
+    pub ridge: Option<PathBuf>,
+
+    /// Bisection steps per crossing of --ridge, each halves the uncertainty along the pixel spacing
+    #[arg(long, value_name = "N", default_value_t = 16, value_parser = clap::value_parser!(u32).range(1..=52), requires = "ridge")]
+    pub ridge_steps: u32,
+
+    /// Label the connected stable regions and report their number and sizes, with FILE.csv their areas and bounds
+    #[arg(long, value_name = "FILE", num_args = 0..=1, requires = "out")]
+    pub components: Option<Option<PathBuf>>,
+
+    /// Give every stable region of --out a color of its own
+    #[arg(long, requires = "out")]
+    pub color_components: bool,
+
+    /// Leave stable regions smaller than N pixels out of --components and --color-components, specks along the border
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub min_area: u32,
+
+    /// After each render, compute the order/chaos border again with FACTOR times the depth in the background
+    #[arg(long, value_name = "FACTOR", num_args = 0..=1, default_missing_value = "8", value_parser = clap::value_parser!(u32).range(2..))]
+    pub refine: Option<u32>,
+
+    /// Keep computed λ in this file across runs, revisited and panned views only compute the new pixels
+    #[arg(long, value_name = "FILE")]
+    pub cache: Option<PathBuf>,
+
+    /// Restore view, panels, bookmarks and λ grid from this file and save them there when the window closes
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["out", "term", "attract", "replay", "panes"])]
+    pub workspace: Option<PathBuf>,
+
+    /// Keep a journal of the exploration: every view left alone for 3 seconds is written to DIR as PNG and pipe parameters
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["out", "term", "attract", "panes"])]
+    pub autosave_dir: Option<PathBuf>,
+
+    /// Entries of --cache (32 bytes each on disk), the least recently used ones are dropped
+    #[arg(long, default_value_t = 4_000_000, requires = "cache")]
+    pub cache_size: usize,
+
+    /// Borderless window covering the whole screen, rendered at its real resolution
+    #[arg(long)]
+    pub fullscreen: bool,
+
+    /// Show every diagram pixel as N×N screen pixels, renders faster. HiDPI screens are detected (GDK_SCALE overrides)
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=64), conflicts_with_all = ["term", "fbdev", "out"])]
+    pub scale: u32,
+
+    /// Filter bringing the frames of --scale and --render-scale up to the screen
+    #[arg(long, value_enum, default_value_t = Upscale::Nearest, conflicts_with_all = ["term", "fbdev", "out"])]
+    pub upscale: Upscale,
+
+    /// Compute the views while exploring at this fraction of the window size, Enter computes the current one at full size
+    #[arg(long, value_name = "FRACTION", value_parser = parse_render_scale, conflicts_with_all = ["term", "out"])]
+    pub render_scale: Option<f64>,
+
+    /// Screen size for --fullscreen as WIDTHxHEIGHT [default: detected]
+    #[arg(long, value_parser = parse_screen_size, requires = "fullscreen")]
+    pub screen_size: Option<(usize, usize)>,
+
+    /// Span the diagram over a wall of COLUMNSxROWS equal monitors, one --fullscreen window on each
+    #[arg(long, value_name = "COLUMNSxROWS", value_parser = parse_span, requires = "fullscreen")]
+    pub span: Option<(usize, usize)>,
+
+    /// Open the window through winit for touchscreens: drag pans, pinch zooms, tap clicks (needs the touch feature)
+    #[arg(long, conflicts_with_all = ["out", "term", "fbdev", "span", "screen_size"])]
+    pub touch: bool,
+
+    /// Close the window as soon as the diagram (and a --refine pass) is finished
+    #[arg(long, conflicts_with = "attract")]
+    pub exit_after_render: bool,
+
+    /// Show the finished diagram for SECONDS, then close the window
+    #[arg(long, value_name = "SECONDS", value_parser = parse_positive, conflicts_with = "attract")]
+    pub display_seconds: Option<f64>,
+
+    /// Stop summing λ once it leaves ±bailout (divergent or superstable orbits)
+    #[arg(long, default_value_t = DEFAULT_BAILOUT, value_parser = parse_positive)]
+    pub bailout: f64,
+
+    /// What to do with pixels that bailed out
+    #[arg(long, value_enum, default_value_t = Bailout::Stop)]
+    pub on_bailout: Bailout,
+
+    /// What to do when the orbit hits the critical point x = 0.5 and log|r(1-2x)| would be -∞
+    #[arg(long, value_enum, default_value_t = LogZero::Keep)]
+    pub on_log_zero: LogZero,
+
+    /// Highlight superstable orbits, those passing closer than TOLERANCE to x = 0.5, as a white skeleton
+    #[arg(long, value_name = "TOLERANCE", num_args = 0..=1, default_missing_value = "0.001", value_parser = parse_positive)]
+    pub superstable: Option<f64>,
+
+    /// Add uniform noise of ±AMPLITUDE to every iteration and average λ over several realizations
+    #[arg(long, value_name = "AMPLITUDE", value_parser = parse_positive)]
+    pub noise: Option<f64>,
+
+    /// Seed of the noise, the same seed renders the same noisy diagram [default: --seed or 0]
+    #[arg(long, requires = "noise")]
+    pub noise_seed: Option<u64>,
+
+    /// Noise realizations averaged per pixel
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u32).range(1..), requires = "noise")]
+    pub realizations: u32,
+
+    /// Iterate two logistic maps coupled with strength EPSILON (0..1) and show their largest exponent
+    #[arg(long, value_name = "EPSILON", value_parser = parse_unit)]
+    pub coupling: Option<f64>,
+
+    /// Circle map θ → θ + Ω − K/2π·sin(2πθ) with Ω along a and K along b instead of the forced logistic map
+    #[arg(long, conflicts_with = "coupling")]
+    pub circle: bool,
+
+    /// Complex r = a + ib in z → r·z·(1−z), escaping orbits count as bailed out (see --on-bailout)
+    #[arg(long, conflicts_with_all = ["coupling", "circle"])]
+    pub complex: bool,
+
+    /// Let a and b drift while iterating, linear by RATE over all iterations or sine with amplitude RATE, e.g. linear:0.1 or sine:0.05,0
+    #[arg(long, value_name = "SHAPE:RATE[,B_RATE]", value_parser = Drift::parse, conflicts_with_all = ["circle", "complex"])]
+    pub drift: Option<Drift>,
+
+    /// Iterations per pixel, also the orbit length (minus 20 warmup steps) of the measures
+    #[arg(long, value_parser = clap::value_parser!(u32).range(WARMUP as i64 + 2..))]
+    pub depth: Option<u32>,
+
+    /// Quantity computed per pixel and colored like λ
+    #[arg(long, value_enum, default_value_t = Measure::Lyapunov)]
+    pub measure: Measure,
+
+    /// WebAssembly module computing the value of every pixel instead of the built in maps
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["coupling", "circle", "complex", "noise", "drift"])]
+    pub wasm: Option<PathBuf>,
+
+    /// Which part of the Ljapunow spectrum of the coupled maps to show
+    #[arg(long, value_enum, default_value_t = Spectrum::Largest, requires = "coupling")]
+    pub spectrum: Spectrum,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Write PNG frames morphing one diagram into another
+    Blend(BlendArgs),
+    /// Experimental genetic search for interesting sequences
+    Evolve(EvolveArgs),
+    /// Write a tile pyramid (Deep Zoom or z/x/y) for zoomable browser viewers
+    Tiles(TilesArgs),
+    /// Render one diagram per value of a swept parameter into a labeled montage
+    Sweep(SweepArgs),
+    /// Serve the progressive render to browsers over WebSocket
+    Stream(StreamArgs),
+    /// Write the raw λ grid of any size into a memory mapped file
+    Dump(DumpArgs),
+    /// Write the λ field as 16 bit heightmap and normal map for rendering it as terrain
+    Heightmap(HeightmapArgs),
+    /// Show the signed λ difference of two sequences over the same region
+    Diff(DiffArgs),
+    /// Show how λ changes when one letter of the sequence is flipped
+    Sensitivity(SensitivityArgs),
+    /// Render frames whose parameters and colors come from a Rhai script
+    Script(ScriptArgs),
+    /// Render one JSON parameter set per stdin line, to its "out" file or as PNG bytes to stdout
+    #[command(after_help = "\
+Every line may set sequence, region ([a_min, a_max, b_min, b_max]), palette, depth,
+size ([width, height]) and out, everything else comes from the default preset.
+
+Example:
+  echo '{\"sequence\": \"AB\", \"region\": [2, 4, 2, 4], \"size\": [400, 400]}' | ljapunow pipe > ab.png")]
+    Pipe,
+    /// Print shell completions to stdout
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+}
+
+#[derive(Debug, Args)]
+#[command(
+    after_help = "Example:\n  ljapunow blend BBBBBBAAAAAA:3.4,4.0,2.5,3.4 AB:2,4,2,4 --frames 60"
+)]
+pub struct BlendArgs {
+    /// First view as SEQUENCE:a_min,a_max,b_min,b_max
+    pub from: String,
+
+    /// Last view as SEQUENCE:a_min,a_max,b_min,b_max
+    pub to: String,
+
+    /// Number of frames including first and last
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(2..))]
+    pub frames: u32,
+
+    /// Output directory, created if missing
+    #[arg(long, default_value = "frames")]
+    pub out: PathBuf,
+
+    /// Interpolate the ranges and render every frame instead of blending the two λ grids
+    #[arg(long)]
+    pub params: bool,
+
+    /// Encode all frames into this video (mp4, webm, gif, ...) with ffmpeg instead of writing PNGs
+    #[arg(long, value_name = "FILE")]
+    pub video: Option<PathBuf>,
+
+    /// Frames per second of --video
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..), requires = "video")]
+    pub fps: u32,
+
+    /// Skip frames an earlier run already wrote with the same parameters (see the manifest in the output directory)
+    #[arg(long, conflicts_with = "video")]
+    pub resume_animation: bool,
+}
+
+#[derive(Debug, Args)]
+#[command(
+    after_help = "Example:\n  ljapunow evolve BBBBBBAAAAAA --region 3.4,4.0,2.5,3.4 --population 12"
+)]
+pub struct EvolveArgs {
+    /// Sequence to start the search from
+    #[arg(default_value = "AB")]
+    pub seed: String,
+
+    /// Parameter range as a_min,a_max,b_min,b_max
+    #[arg(long, default_value = "2,4,2,4", value_parser = parse_region, allow_hyphen_values = true)]
+    pub region: Region,
+
+    /// Number of generations
+    #[arg(long, default_value_t = 20)]
+    pub generations: u32,
+
+    /// Survivors kept per generation
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u32).range(1..))]
+    pub population: u32,
+
+    /// Output directory for thumbnails and log.txt
+    #[arg(long, default_value = "evolve")]
+    pub out: PathBuf,
+}
+
+#[derive(Debug, Args)]
+#[command(
+    after_help = "Blue where the second sequence has the lower λ (more stable), red where it is higher,\nscaled to the 98th percentile of the differences in view.\n\nExample:\n  ljapunow diff AB AABAB --region 2,4,2,4 --out diff.png"
+)]
+pub struct DiffArgs {
+    /// Sequence the difference is taken from
+    pub first: String,
+
+    /// Sequence compared to the first, shown is λ(second) - λ(first)
+    pub second: String,
+
+    /// Parameter range as a_min,a_max,b_min,b_max
+    #[arg(long, default_value = "2,4,2,4", value_parser = parse_region, allow_hyphen_values = true)]
+    pub region: Region,
+
+    /// Write a PNG instead of opening a window
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+
+    /// Image size for --out as WIDTHxHEIGHT
+    #[arg(long, default_value = "800x800", value_parser = parse_screen_size)]
+    pub size: (usize, usize),
+}
+
+#[derive(Debug, Args)]
+#[command(
+    after_help = "Shown is λ(flipped) - λ(sequence) with the colors of diff, blue where the flip stabilizes.\n\nExample:\n  ljapunow sensitivity AABAB --position 3 --out flip3.png"
+)]
+pub struct SensitivityArgs {
+    /// Forcing sequence, only the letters A and B
+    pub sequence: String,
+
+    /// Letter to flip A <-> B, counted from 1
+    #[arg(long, default_value_t = 1)]
+    pub position: u32,
+
+    /// Parameter range as a_min,a_max,b_min,b_max
+    #[arg(long, default_value = "2,4,2,4", value_parser = parse_region, allow_hyphen_values = true)]
+    pub region: Region,
+
+    /// Write a PNG instead of opening a window
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+
+    /// Image size for --out as WIDTHxHEIGHT
+    #[arg(long, default_value = "800x800", value_parser = parse_screen_size)]
+    pub size: (usize, usize),
+}
+
+#[derive(Debug, Args)]
+#[command(after_help = "\
+The script defines on_frame(t), called for every frame with t from 0 to 1. It returns a map with
+any of region: [a_min, a_max, b_min, b_max], sequence: \"AB\", palette: \"gradient\", depth: 300,
+missing ones keep their previous value. An optional colorize(lambda) returns 0xRRGGBB per pixel.
+
+Example: ljapunow script scripts/zoom.rhai --frames 120 --video zoom.mp4")]
+pub struct ScriptArgs {
+    /// Rhai script defining on_frame(t) and optionally colorize(lambda)
+    pub script: PathBuf,
+
+    /// Number of frames
+    #[arg(long, default_value_t = 60, value_parser = clap::value_parser!(u32).range(1..))]
+    pub frames: u32,
+
+    /// Frame size as WIDTHxHEIGHT
+    #[arg(long, default_value = "800x800", value_parser = parse_screen_size)]
+    pub size: (usize, usize),
+
+    /// Output directory, created if missing
+    #[arg(long, default_value = "frames")]
+    pub out: PathBuf,
+
+    /// Encode all frames into this video (mp4, webm, gif, ...) with ffmpeg instead of writing PNGs
+    #[arg(long, value_name = "FILE")]
+    pub video: Option<PathBuf>,
+
+    /// Frames per second of --video
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..), requires = "video")]
+    pub fps: u32,
+
+    /// Skip frames an earlier run already wrote with the same parameters (see the manifest in the output directory)
+    #[arg(long, conflicts_with = "video")]
+    pub resume_animation: bool,
+}
+
+#[derive(Debug, Args)]
+#[command(
+    after_help = "The file starts with a 64 byte header (magic LJAPGRID, version, width, height, a_min, a_max, b_min, b_max),\nfollowed by width×height little endian f64, row by row.\nWith --storage f16 or u16 the header grows by the λ range of u16 to 80 bytes and λ takes 2 bytes.\n\nExample:\n  ljapunow dump huge.lgrid --size 40000x40000 --storage u16 --lambda-range -2,1"
+)]
+pub struct DumpArgs {
+    /// Output file
+    pub out: PathBuf,
+
+    /// Grid size as WIDTHxHEIGHT
+    #[arg(long, default_value = "800x800", value_parser = parse_screen_size)]
+    pub size: (usize, usize),
+
+    /// Forcing sequence, only the letters A and B
+    #[arg(long, default_value = "BBBBBBAAAAAA")]
+    pub sequence: String,
+
+    /// Parameter range as a_min,a_max,b_min,b_max
+    #[arg(long, default_value = "3.4,4.0,2.5,3.4", value_parser = parse_region, allow_hyphen_values = true)]
+    pub region: Region,
+
+    /// How λ is stored: f64, f16 for half or u16 quantized for a quarter of the size
+    #[arg(long, value_enum, default_value_t = Storage::F64)]
+    pub storage: Storage,
+
+    /// λ range of u16 storage as MIN,MAX, values outside are clamped [default: from a preview]
+    #[arg(long, value_parser = parse_lambda_range, allow_hyphen_values = true)]
+    pub lambda_range: Option<(f64, f64)>,
+}
+
+#[derive(Debug, Args)]
+#[command(
+    after_help = "Next to FILE.png goes FILE_normal.png, a tangent space normal map (OpenGL convention, green up).\n\nExample:\n  ljapunow heightmap terrain.png --size 2048x2048 --clamp -2,0.5 --scale 100"
+)]
+pub struct HeightmapArgs {
+    /// Output PNG, 16 bit grayscale with λ growing upwards
+    pub out: PathBuf,
+
+    /// Grid size as WIDTHxHEIGHT
+    #[arg(long, default_value = "1024x1024", value_parser = parse_screen_size)]
+    pub size: (usize, usize),
+
+    /// Forcing sequence, only the letters A and B
+    #[arg(long, default_value = "BBBBBBAAAAAA")]
+    pub sequence: String,
+
+    /// Parameter range as a_min,a_max,b_min,b_max
+    #[arg(long, default_value = "3.4,4.0,2.5,3.4", value_parser = parse_region, allow_hyphen_values = true)]
+    pub region: Region,
+
+    /// λ range mapped onto the heights as MIN,MAX, values outside are clamped [default: the range of the grid]
+    #[arg(long, value_parser = parse_lambda_range, allow_hyphen_values = true)]
+    pub clamp: Option<(f64, f64)>,
+
+    /// Relief of the normal map, the full height range rises this many pixels
+    #[arg(long, default_value_t = 64.0, value_parser = parse_positive)]
+    pub scale: f64,
+}
+
+#[derive(Debug, Args)]
+#[command(
+    after_help = "Every tile is rendered from its own part of the region, so the deepest level is as sharp as a render of\nthe full size. dzi is read by OpenSeadragon, xyz by Leaflet (z/x/y.png with square levels).\n\nExample:\n  ljapunow tiles zircon --size 16384x16384 && open zircon/ljapunow.dzi in OpenSeadragon"
+)]
+pub struct TilesArgs {
+    /// Output directory, created if missing
+    pub out: PathBuf,
+
+    /// Size of the full resolution image as WIDTHxHEIGHT
+    #[arg(long, default_value = "8192x8192", value_parser = parse_screen_size)]
+    pub size: (usize, usize),
+
+    /// Tile layout
+    #[arg(long, value_enum, default_value_t = TileFormat::Dzi)]
+    pub format: TileFormat,
+
+    /// Name of the .dzi file and its _files directory
+    #[arg(long, default_value = "ljapunow")]
+    pub name: String,
+
+    /// Forcing sequence, only the letters A and B
+    #[arg(long, default_value = "BBBBBBAAAAAA")]
+    pub sequence: String,
+
+    /// Parameter range as a_min,a_max,b_min,b_max
+    #[arg(long, default_value = "3.4,4.0,2.5,3.4", value_parser = parse_region, allow_hyphen_values = true)]
+    pub region: Region,
+
+    /// Color scheme
+    #[arg(long, value_enum, default_value_t = Palette::Ramp)]
+    pub palette: Palette,
+
+    /// Keep tiles already in the output directory, e.g. after an interrupted run with the same parameters
+    #[arg(long)]
+    pub resume: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+#[command(
+    after_help = "Open scripts/stream.html?ws=ws://HOST:PORT in a browser to watch. Every client gets its own render.\n\nExample:\n  ljapunow stream --listen 0.0.0.0:9001 --size 4000x4000"
+)]
+pub struct StreamArgs {
+    /// Address and port to accept WebSocket clients on
+    #[arg(long, default_value = "127.0.0.1:9001")]
+    pub listen: String,
+
+    /// Diagram size as WIDTHxHEIGHT
+    #[arg(long, default_value = "800x800", value_parser = parse_screen_size)]
+    pub size: (usize, usize),
+
+    /// Forcing sequence, only the letters A and B
+    #[arg(long, default_value = "BBBBBBAAAAAA")]
+    pub sequence: String,
+
+    /// Parameter range as a_min,a_max,b_min,b_max
+    #[arg(long, default_value = "3.4,4.0,2.5,3.4", value_parser = parse_region, allow_hyphen_values = true)]
+    pub region: Region,
+
+    /// Color scheme
+    #[arg(long, value_enum, default_value_t = Palette::Ramp)]
+    pub palette: Palette,
+}
+
+#[derive(Debug, Args)]
+#[command(
+    after_help = "All cells share one λ range, so equal colors mean equal λ across the montage.\n\nExample:\n  ljapunow sweep --param x0 --from 0.1 --to 0.9 --steps 16 --montage 4x4"
+)]
+pub struct SweepArgs {
+    /// Parameter varied from cell to cell
+    #[arg(long, value_enum)]
+    pub param: SweepParam,
+
+    /// Value in the first cell
+    #[arg(long, allow_hyphen_values = true)]
+    pub from: f64,
+
+    /// Value in the last cell
+    #[arg(long, allow_hyphen_values = true)]
+    pub to: f64,
+
+    /// Number of cells including first and last
+    #[arg(long, default_value_t = 16, value_parser = clap::value_parser!(u32).range(2..))]
+    pub steps: u32,
+
+    /// Cells of the montage as COLUMNSxROWS [default: as square as possible]
+    #[arg(long, value_parser = parse_screen_size)]
+    pub montage: Option<(usize, usize)>,
+
+    /// Size of one cell as WIDTHxHEIGHT
+    #[arg(long, default_value = "256x256", value_parser = parse_screen_size)]
+    pub cell_size: (usize, usize),
+
+    /// Output PNG
+    #[arg(long, default_value = "sweep.png")]
+    pub out: PathBuf,
+
+    /// Forcing sequence, only the letters A and B
+    #[arg(long, default_value = "BBBBBBAAAAAA")]
+    pub sequence: String,
+
+    /// Parameter range as a_min,a_max,b_min,b_max
+    #[arg(long, default_value = "3.4,4.0,2.5,3.4", value_parser = parse_region, allow_hyphen_values = true)]
+    pub region: Region,
+
+    /// Color scheme
+    #[arg(long, value_enum, default_value_t = Palette::Ramp)]
+    pub palette: Palette,
+
+    /// Follow the stable regions from cell to cell and write their births, merges, splits and deaths to FILE.csv
+    #[arg(long, value_name = "FILE")]
+    pub track: Option<PathBuf>,
+
+    /// Leave stable regions smaller than N pixels out of --track
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..), requires = "track")]
+    pub min_area: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SweepParam {
+    /// start value x₀ of the orbits
+    X0,
+    /// coupling ε of two logistic maps, see --coupling
+    Coupling,
+    /// iteration depth, rounded to whole iterations
+    Depth,
+    /// noise amplitude, see --noise
+    Noise,
+}
+
+impl SweepParam {
+    // short name for the cell labels
+    pub fn label(self) -> &'static str {
+        match self {
+            SweepParam::X0 => "x0",
+            SweepParam::Coupling => "coupling",
+            SweepParam::Depth => "depth",
+            SweepParam::Noise => "noise",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Stereo {
+    /// red-cyan glasses, red from the left eye
+    Anaglyph,
+    /// left and right view next to each other, for parallel viewing or stereo displays
+    SideBySide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Sonify {
+    /// the states as waveform, periodic orbits hum at a pitch set by their period, chaos hisses
+    Wave,
+    /// one short note per state, a repeating melody for periodic orbits and a random one for chaos
+    Pitch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TileFormat {
+    /// Deep Zoom Image, <name>.dzi and <name>_files/<level>/<col>_<row>.png
+    Dzi,
+    /// slippy map tiles <z>/<x>/<y>.png
+    Xyz,
+}
+
+impl Cli {
+    // clap prints help and errors while parsing, so --lang has to be looked up by hand before
+    // custom palettes have to be registered before --palette is parsed, their files are looked up the same way
+    pub fn parse_localized() -> Result<Self, Error> {
+        let args: Vec<String> = std::env::args().collect();
+        let lang = prescan(&args, "--lang")
+            .and_then(|value| Lang::from_str(value, true).ok())
+            .unwrap_or_else(i18n::detect);
+        i18n::set(lang);
+        if let Some(path) = prescan(&args, "--palettes") {
+            let path = Path::new(path);
+            Config::load(path)?
+                .register_palettes()
+                .map_err(|e| Error::usage(format!("{}: {e}", path.display())))?;
+        }
+        if let Some(path) = prescan(&args, "--colorizer") {
+            plugin::load(Path::new(path))?;
+        }
+        // a broken --watch file is reported when the window loads it, it opens anyway
+        if let Some(config) =
+            prescan(&args, "--watch").and_then(|path| Config::load(Path::new(path)).ok())
+        {
+            let _ = config.register_palettes();
+        }
+
+        let matches = localized(Cli::command()).get_matches();
+        Ok(Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit()))
+    }
+}
+
+// value of --option VALUE or --option=VALUE
+fn prescan<'a>(args: &'a [String], option: &str) -> Option<&'a str> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        match arg
+            .strip_prefix(option)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            Some(value) => Some(value),
+            None if arg == option => args.get(i + 1).map(String::as_str),
+            None => None,
+        }
+    })
+}
+
+// help texts of the command, its arguments and subcommands through the catalog
+fn localized(mut command: clap::Command) -> clap::Command {
+    let translate = |text: &StyledStr| i18n::tr(&text.to_string()).to_string();
+    if let Some(about) = command.get_about().map(translate) {
+        command = command.about(about);
+    }
+    if let Some(about) = command.get_long_about().map(translate) {
+        command = command.long_about(about);
+    }
+    command
+        .mut_args(|arg| match arg.get_help().map(translate) {
+            Some(help) => arg.help(help),
+            None => arg,
+        })
+        .mut_subcommands(localized)
+}
+
+fn parse_region(ranges: &str) -> Result<Region, String> {
+    let region = Region::parse(ranges).ok_or_else(|| {
+        i18n::tr("expected a_min,a_max,b_min,b_max e.g. 3.4,4.0,2.5,3.4").to_string()
+    })?;
+    region.check()?;
+    Ok(region)
+}
+
+fn parse_lambda_range(range: &str) -> Result<(f64, f64), String> {
+    range
+        .split_once(',')
+        .and_then(|(min, max)| Some((min.trim().parse().ok()?, max.trim().parse().ok()?)))
+        .filter(|(min, max): &(f64, f64)| min < max)
+        .ok_or_else(|| i18n::tr("expected MIN,MAX with MIN < MAX e.g. -2,1").into())
+}
+
+fn parse_transfer(spec: &str) -> Result<Transfer, String> {
+    Transfer::parse(spec)
+        .ok_or_else(|| i18n::tr("expected log, sqrt or sigmoid[:K] e.g. sigmoid:8").into())
+}
+
+fn parse_filter(spec: &str) -> Result<Filter, String> {
+    Filter::parse(spec).ok_or_else(|| {
+        i18n::tr("expected unsharp or local-contrast, optionally with :RADIUS and :AMOUNT e.g. unsharp:1.5:0.8").into()
+    })
+}
+
+fn parse_pane(spec: &str) -> Result<PaneSpec, String> {
+    PaneSpec::parse(spec)
+        .ok_or_else(|| i18n::tr("expected SEQUENCE or SEQUENCE:PALETTE e.g. AB:dual").into())
+}
+
+fn parse_preset(name: &str) -> Result<&'static Preset, String> {
+    presets::find(name).ok_or_else(|| {
+        format!(
+            "{} {}",
+            i18n::tr("unknown preset, known are:"),
+            presets::names().collect::<Vec<_>>().join(", ")
+        )
+    })
+}
+
+// file names take the placeholders of --output-template
+fn parse_out(path: &str) -> Result<PathBuf, String> {
+    template::validate(path).map(PathBuf::from)
+}
+
+fn parse_screen_size(size: &str) -> Result<(usize, usize), String> {
+    screen::parse_size(size).ok_or_else(|| i18n::tr("expected WIDTHxHEIGHT e.g. 1920x1080").into())
+}
+
+fn parse_span(grid: &str) -> Result<(usize, usize), String> {
+    screen::parse_size(grid)
+        .filter(|&(columns, rows)| columns <= MAX_SPAN && rows <= MAX_SPAN)
+        .ok_or_else(|| i18n::trf("expected COLUMNSxROWS up to {0}x{0} e.g. 3x2", &[&MAX_SPAN]))
+}
+
+fn parse_unit(value: &str) -> Result<f64, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|v: &f64| (0.0..=1.0).contains(v))
+        .ok_or_else(|| i18n::tr("expected a number between 0 and 1").into())
+}
+
+fn parse_render_scale(value: &str) -> Result<f64, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|&v: &f64| v > 0.0 && v <= 1.0)
+        .ok_or_else(|| i18n::tr("expected a number above 0 and at most 1").into())
+}
+
+fn parse_positive(value: &str) -> Result<f64, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|&v: &f64| v > 0.0)
+        .ok_or_else(|| i18n::tr("expected a positive number").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_flags_go_before_the_subcommand() {
+        let cli = Cli::try_parse_from(["ljapunow", "--seed", "3", "evolve", "AB"]).unwrap();
+        assert_eq!(cli.rng_seed, Some(3));
+        assert!(matches!(cli.command, Some(Command::Evolve(_))));
+        let cli = Cli::try_parse_from(["ljapunow", "--threads", "2", "tiles", "t3"]).unwrap();
+        assert_eq!(cli.threads, Some(2));
+        assert!(matches!(cli.command, Some(Command::Tiles(_))));
+    }
+
+    #[test]
+    fn global_flags_go_after_the_subcommand() {
+        let cli = Cli::try_parse_from(["ljapunow", "evolve", "AB", "--seed", "3"]).unwrap();
+        assert_eq!(cli.rng_seed, Some(3));
+    }
+
+    #[test]
+    fn a_sequence_is_no_subcommand() {
+        let cli = Cli::try_parse_from(["ljapunow", "AB"]).unwrap();
+        assert_eq!(cli.sequence.as_deref(), Some("AB"));
+        assert!(cli.command.is_none());
+    }
+}
@@ -0,0 +1,427 @@
+// mapping of λ values to colors
+
+use crate::i18n;
+use crate::render::map;
+use clap::builder::PossibleValue;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock, RwLock};
+
+const RED_SHIFT: u32 = 16;
+const GREEN_SHIFT: u32 = 8;
+const BLUE_SHIFT: u32 = 0;
+
+// pixels marked by Bailout::Sentinel
+const SENTINEL_COLOR: u32 = 0x1C2A6E;
+// superstable orbits (λ = -∞), the skeleton curves the stable regions grow around
+const SUPERSTABLE_COLOR: u32 = 0xF4F4F4;
+
+// λ range assumed by scaled palettes when the range of the diagram is not known
+const DEFAULT_RANGE: (f64, f64) = (-2.5, 1.0);
+
+// color schemes for the stable regions, chaos is black except for Dual and custom ramps reaching above 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Palette {
+    #[default]
+    Ramp,
+    Gradient,
+    Dual,
+    // registered with register or register_colorizer, the index into CUSTOM
+    Custom(usize),
+}
+
+const BUILTIN: [Palette; 3] = [Palette::Ramp, Palette::Gradient, Palette::Dual];
+
+// custom palettes by name, the stops may be replaced while running (--watch)
+static CUSTOM: RwLock<Vec<(&'static str, Custom)>> = RwLock::new(Vec::new());
+// a colorizer maps λ within the (λ_min, λ_max) range of the diagram to a 0RGB color. called from
+// several render threads at once
+pub type Colorizer = Arc<dyn Fn(f64, (f64, f64)) -> u32 + Send + Sync>;
+
+enum Custom {
+    Ramp(ColorRamp),
+    // from a plugin (--colorizer), always scaled
+    Colorizer(Colorizer),
+}
+
+// all palettes, fixed the first time clap or P asks for them
+static VARIANTS: OnceLock<Vec<Palette>> = OnceLock::new();
+
+// defined by hand instead of derived, custom palettes are only known at run time
+impl clap::ValueEnum for Palette {
+    fn value_variants<'a>() -> &'a [Self] {
+        VARIANTS.get_or_init(|| {
+            let custom = CUSTOM.read().map_or(0, |custom| custom.len());
+            BUILTIN
+                .into_iter()
+                .chain((0..custom).map(Palette::Custom))
+                .collect()
+        })
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let help = match self {
+            Palette::Ramp => "red to yellow RGB ramp",
+            Palette::Gradient => "dark blue over purple and rose to yellow",
+            Palette::Dual => {
+                "blues for order scaled on λ_min..0, reds for chaos scaled on 0..λ_max"
+            }
+            Palette::Custom(index) if Palette::Custom(*index).is_scaled() => "colorizer plugin",
+            Palette::Custom(_) => "custom color ramp",
+        };
+        Some(PossibleValue::new(self.name()).help(help))
+    }
+}
+
+impl Palette {
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Ramp => "ramp",
+            Palette::Gradient => "gradient",
+            Palette::Dual => "dual",
+            Palette::Custom(index) => CUSTOM
+                .read()
+                .ok()
+                .and_then(|custom| custom.get(index).map(|(name, _)| *name))
+                .unwrap_or("custom"),
+        }
+    }
+
+    // the following palette, wrapping around after the last one
+    pub fn next(self) -> Palette {
+        let palettes = <Palette as clap::ValueEnum>::value_variants();
+        let index = palettes.iter().position(|&p| p == self).unwrap_or(0);
+        palettes[(index + 1) % palettes.len()]
+    }
+
+    // colors depend on the λ range of the whole diagram, see colorize_in
+    pub fn is_scaled(self) -> bool {
+        match self {
+            Palette::Dual => true,
+            Palette::Custom(index) => CUSTOM
+                .read()
+                .is_ok_and(|custom| matches!(custom.get(index), Some((_, Custom::Colorizer(_))))),
+            _ => false,
+        }
+    }
+
+    pub fn colorize(self, lambda: f64) -> u32 {
+        self.colorize_in(lambda, DEFAULT_RANGE)
+    }
+
+    // range is (λ_min, λ_max) of the diagram, only used by scaled palettes
+    pub fn colorize_in(self, lambda: f64, range: (f64, f64)) -> u32 {
+        if lambda == f64::INFINITY {
+            SENTINEL_COLOR
+        } else if lambda == f64::NEG_INFINITY {
+            SUPERSTABLE_COLOR
+        } else if lambda.is_nan() {
+            0x00
+        } else {
+            match self {
+                Palette::Dual => color_dual(lambda, range),
+                Palette::Custom(index) => CUSTOM
+                    .read()
+                    .ok()
+                    .and_then(|custom| match &custom.get(index)?.1 {
+                        Custom::Ramp(ramp) => Some(ramp.colorize(lambda)),
+                        Custom::Colorizer(colorize) => Some(colorize(lambda, range)),
+                    })
+                    .unwrap_or(0x00),
+                _ if lambda > 0.0 => 0x00,
+                Palette::Ramp => color_ramp(lambda),
+                Palette::Gradient => color_gradient(lambda),
+            }
+        }
+    }
+}
+
+// makes a palette usable by name, a known name gets the new ramp. new names have to be registered
+// before the command line is parsed, later ones are rejected
+pub fn register(name: &str, ramp: ColorRamp) -> Result<(), String> {
+    register_custom(name, Custom::Ramp(ramp))
+}
+
+// the same for a palette computed by a plugin instead of interpolated between stops
+pub fn register_colorizer(name: &str, colorizer: Colorizer) -> Result<(), String> {
+    register_custom(name, Custom::Colorizer(colorizer))
+}
+
+fn register_custom(name: &str, palette: Custom) -> Result<(), String> {
+    if BUILTIN.iter().any(|builtin| builtin.name() == name) {
+        return Err(i18n::trf("palette {0} is built in", &[&name]));
+    }
+    let mut custom = CUSTOM.write().map_err(|e| e.to_string())?;
+    if let Some((_, known)) = custom.iter_mut().find(|(known, _)| *known == name) {
+        *known = palette;
+        return Ok(());
+    }
+    if VARIANTS.get().is_some() {
+        return Err(i18n::trf("palette {0} is new, restart to use it", &[&name]));
+    }
+    // lives as long as the program, like the built in names
+    custom.push((Box::leak(name.into()), palette));
+    Ok(())
+}
+
+// color space the stops of a custom ramp are interpolated in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Space {
+    #[default]
+    Rgb,
+    // hue the short way around
+    Hsl,
+    // perceptually uniform, no muddy middle between complementary colors
+    Oklab,
+}
+
+// a palette of color stops at λ positions. below the first stop the first color, above the last one
+// the last color, except that chaos (λ > 0) stays black unless a stop lies above 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorRamp {
+    stops: Vec<(f64, u32)>,
+    space: Space,
+}
+
+impl ColorRamp {
+    pub fn new(stops: Vec<(f64, u32)>, space: Space) -> Result<Self, String> {
+        if stops.len() < 2 {
+            return Err("a palette needs at least two stops".into());
+        }
+        if stops.iter().any(|(position, _)| !position.is_finite()) {
+            return Err("stop positions have to be finite".into());
+        }
+        if stops.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err("stop positions have to increase".into());
+        }
+        Ok(ColorRamp { stops, space })
+    }
+
+    fn colorize(&self, lambda: f64) -> u32 {
+        let (first, last) = (self.stops[0], self.stops[self.stops.len() - 1]);
+        if lambda > 0.0 && last.0 <= 0.0 {
+            return 0x00;
+        }
+        if lambda <= first.0 {
+            return first.1;
+        }
+        if lambda >= last.0 {
+            return last.1;
+        }
+        let pos = self
+            .stops
+            .partition_point(|(position, _)| *position < lambda);
+        let ((p0, c0), (p1, c1)) = (self.stops[pos - 1], self.stops[pos]);
+        let t = map(lambda, p0, p1, 0.0, 1.0);
+        match self.space {
+            Space::Rgb => lerp_rgb(c0, c1, t),
+            Space::Hsl => lerp_hsl(c0, c1, t),
+            Space::Oklab => lerp_oklab(c0, c1, t),
+        }
+    }
+}
+
+// #RRGGBB or RRGGBB
+pub fn parse_color(text: &str) -> Option<u32> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+// default coloring of the diagram
+pub fn colorize(lambda: f64) -> u32 {
+    Palette::default().colorize(lambda)
+}
+
+// map to a byte range and shift in target range. 0 for values outside of range.
+fn map_byte(val: f64, start1: f64, stop1: f64, start2: f64, stop2: f64, shift: u32) -> u32 {
+    if val < start1 || val > stop1 {
+        return 0;
+    }
+
+    (map(val, start1, stop1, start2, stop2)
+        .round()
+        .clamp(0.0, 255.0) as u32)
+        << shift
+}
+
+// simple RGB ramp
+fn color_ramp(lambda: f64) -> u32 {
+    map_byte(lambda, -2.0, 0.5, 196.0, 255.0, RED_SHIFT)
+        + map_byte(lambda, -0.5, 0.0, 0.0, 255.0, GREEN_SHIFT)
+        + map_byte(lambda, -2.5, 0.5, 10.0, 55.0, BLUE_SHIFT)
+}
+
+// interpolate along a color gradient
+fn color_gradient(lambda: f64) -> u32 {
+    let gradient = [0x161c31, 0x613c62, 0xb75f74, 0xf29a6b, 0xfaec70];
+    let ranges = [-2.5, -1.5, -0.8, -0.2, 0.0, 4.0];
+
+    // find the range via simple search, no need for binary
+    let mut pos = 1;
+    while pos < ranges.len() && ranges[pos] < lambda {
+        pos += 1;
+    }
+
+    // -0.3 -> pos 3 -> gradient[2]..gradient[3]
+    // todo: interpolate in hsl or lab space, rgb is not good for linear interpolation
+    let t = map(lambda, ranges[pos - 1], ranges[pos], 0.0, 1.0).clamp(0.0, 1.0);
+    lerp_rgb(gradient[pos - 1], gradient[pos], t)
+}
+
+// two ramps joined at λ = 0, deep blue at λ_min and dark red at λ_max
+fn color_dual(lambda: f64, (lambda_min, lambda_max): (f64, f64)) -> u32 {
+    let (zero, order, chaos) = (0xfdf6e3, 0x0b1d51, 0x7a0a0a);
+    if lambda <= 0.0 {
+        let t = if lambda_min < 0.0 {
+            lambda / lambda_min
+        } else {
+            0.0
+        };
+        lerp_rgb(zero, order, t.clamp(0.0, 1.0))
+    } else {
+        let t = if lambda_max > 0.0 {
+            lambda / lambda_max
+        } else {
+            1.0
+        };
+        lerp_rgb(zero, chaos, t.clamp(0.0, 1.0))
+    }
+}
+
+// signed values on a blue - white - red scale symmetric around 0, ±scale and beyond fully saturated.
+// values that are not finite (bailed out or not computed) are gray.
+pub fn color_diverging(value: f64, scale: f64) -> u32 {
+    if !value.is_finite() {
+        return 0x404040;
+    }
+    let t = if scale > 0.0 {
+        (value / scale).clamp(-1.0, 1.0)
+    } else {
+        0.0
+    };
+    if t < 0.0 {
+        lerp_rgb(0xF7F7F7, 0x2166AC, -t)
+    } else {
+        lerp_rgb(0xF7F7F7, 0xB2182B, t)
+    }
+}
+
+// a color for the i-th of many things, neighbouring i far apart on the hue circle by the golden angle
+pub fn distinct(i: usize) -> u32 {
+    let hue = (i as f64 * 137.507_764).rem_euclid(360.0);
+    from_channels(from_hsl([hue, 0.75, 0.55]))
+}
+
+fn channels(color: u32) -> [f64; 3] {
+    [RED_SHIFT, GREEN_SHIFT, BLUE_SHIFT].map(|shift| ((color >> shift) & 0xFF) as f64 / 255.0)
+}
+
+fn from_channels(channels: [f64; 3]) -> u32 {
+    [RED_SHIFT, GREEN_SHIFT, BLUE_SHIFT]
+        .iter()
+        .zip(channels)
+        .map(|(&shift, v)| ((v * 255.0).round().clamp(0.0, 255.0) as u32) << shift)
+        .sum()
+}
+
+// hue, saturation, lightness with the hue taking the shorter way around the circle
+fn lerp_hsl(c1: u32, c2: u32, t: f64) -> u32 {
+    let ([h1, s1, l1], [h2, s2, l2]) = (to_hsl(channels(c1)), to_hsl(channels(c2)));
+    let dh = (h2 - h1 + 540.0).rem_euclid(360.0) - 180.0;
+    from_channels(from_hsl([
+        (h1 + dh * t).rem_euclid(360.0),
+        s1 + (s2 - s1) * t,
+        l1 + (l2 - l1) * t,
+    ]))
+}
+
+fn to_hsl([r, g, b]: [f64; 3]) -> [f64; 3] {
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let l = (max + min) / 2.0;
+    let d = max - min;
+    if d == 0.0 {
+        return [0.0, 0.0, l];
+    }
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+    [h, s, l]
+}
+
+fn from_hsl([h, s, l]: [f64; 3]) -> [f64; 3] {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((h / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let m = l - c / 2.0;
+    let (r, g, b) = match (h / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [r + m, g + m, b + m]
+}
+
+// through linear light into Oklab (Björn Ottosson) and back
+fn lerp_oklab(c1: u32, c2: u32, t: f64) -> u32 {
+    let (a, b) = (to_oklab(channels(c1)), to_oklab(channels(c2)));
+    from_channels(from_oklab([0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t)))
+}
+
+fn to_oklab(srgb: [f64; 3]) -> [f64; 3] {
+    let [r, g, b] = srgb.map(|v| {
+        if v <= 0.04045 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        }
+    });
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+fn from_oklab([ok_l, ok_a, ok_b]: [f64; 3]) -> [f64; 3] {
+    let l = (ok_l + 0.3963377774 * ok_a + 0.2158037573 * ok_b).powi(3);
+    let m = (ok_l - 0.1055613458 * ok_a - 0.0638541728 * ok_b).powi(3);
+    let s = (ok_l - 0.0894841775 * ok_a - 1.2914855480 * ok_b).powi(3);
+    [
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.5105368049 * m + 1.7076392666 * s,
+    ]
+    .map(|v| {
+        let v = v.clamp(0.0, 1.0);
+        if v <= 0.0031308 {
+            12.92 * v
+        } else {
+            1.055 * v.powf(1.0 / 2.4) - 0.055
+        }
+    })
+}
+
+// interpolate each channel separately
+pub fn lerp_rgb(c1: u32, c2: u32, t: f64) -> u32 {
+    [RED_SHIFT, GREEN_SHIFT, BLUE_SHIFT]
+        .iter()
+        .map(|&shift| {
+            let v1 = ((c1 >> shift) & 0xFF) as f64;
+            let v2 = ((c2 >> shift) & 0xFF) as f64;
+            ((v1 + (v2 - v1) * t).round() as u32) << shift
+        })
+        .sum()
+}
@@ -0,0 +1,367 @@
+// the stability windows of a λ grid: connected regions of order (λ < 0, superstable included), four
+// neighbours apart at most. counting and measuring them shows how the windows grow, split and merge as
+// the parameters change.
+
+use crate::color;
+use crate::render::Region;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component {
+    // 1 and up, 0 marks pixels outside any component
+    pub label: usize,
+    pub area: usize,
+    // x_min, y_min, x_max, y_max in pixels, inclusive
+    pub bounds: (usize, usize, usize, usize),
+    // mean pixel position
+    pub centroid: (f64, f64),
+}
+
+impl Component {
+    // the centroid in a/b, pixels count at their centers
+    pub fn center(&self, region: &Region, width: usize, height: usize) -> (f64, f64) {
+        let (x, y) = self.centroid;
+        (
+            region.x_min + (region.x_max - region.x_min) * (x + 0.5) / width as f64,
+            region.y_min + (region.y_max - region.y_min) * (y + 0.5) / height as f64,
+        )
+    }
+}
+
+pub struct Components {
+    // per pixel
+    pub labels: Vec<usize>,
+    pub width: usize,
+    pub height: usize,
+    // label i at index i - 1, numbered in the order their first pixel is met row by row
+    pub components: Vec<Component>,
+}
+
+impl Components {
+    // pixels not computed (NaN) belong to none
+    pub fn label(lambdas: &[f64], width: usize, height: usize) -> Self {
+        let _span = tracing::info_span!("components", width, height).entered();
+        let stable = |i: usize| lambdas[i] < 0.0;
+        let mut labels = vec![0; width * height];
+        let mut components = Vec::new();
+        let mut stack = Vec::new();
+        for start in 0..width * height {
+            if !stable(start) || labels[start] != 0 {
+                continue;
+            }
+            let label = components.len() + 1;
+            let (x, y) = (start % width, start / width);
+            let mut component = Component {
+                label,
+                area: 0,
+                bounds: (x, y, x, y),
+                centroid: (0.0, 0.0),
+            };
+            labels[start] = label;
+            stack.push(start);
+            while let Some(i) = stack.pop() {
+                let (x, y) = (i % width, i / width);
+                let (x_min, y_min, x_max, y_max) = component.bounds;
+                component.bounds = (x_min.min(x), y_min.min(y), x_max.max(x), y_max.max(y));
+                component.area += 1;
+                component.centroid.0 += x as f64;
+                component.centroid.1 += y as f64;
+                let neighbours = [
+                    (x > 0).then(|| i - 1),
+                    (x + 1 < width).then(|| i + 1),
+                    (y > 0).then(|| i - width),
+                    (y + 1 < height).then(|| i + width),
+                ];
+                for n in neighbours.into_iter().flatten() {
+                    if stable(n) && labels[n] == 0 {
+                        labels[n] = label;
+                        stack.push(n);
+                    }
+                }
+            }
+            let area = component.area as f64;
+            component.centroid = (component.centroid.0 / area, component.centroid.1 / area);
+            components.push(component);
+        }
+        Components {
+            labels,
+            width,
+            height,
+            components,
+        }
+    }
+
+    // the ones of at least min_area pixels, largest first
+    pub fn largest(&self, min_area: usize) -> Vec<&Component> {
+        let mut largest: Vec<&Component> = self
+            .components
+            .iter()
+            .filter(|component| component.area >= min_area)
+            .collect();
+        largest.sort_by_key(|component| std::cmp::Reverse(component.area));
+        largest
+    }
+
+    // every component of at least min_area pixels in a color of its own, the rest of the image stays
+    pub fn colorize(&self, buffer: &mut [u32], min_area: usize) {
+        for (pixel, &label) in buffer.iter_mut().zip(&self.labels) {
+            if label != 0 && self.components[label - 1].area >= min_area {
+                *pixel = color::distinct(label);
+            }
+        }
+    }
+
+    // one row per component of at least min_area pixels, largest first, with the bounds and centroid in a/b as well
+    pub fn save(&self, path: &Path, region: &Region, min_area: usize) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_csv(&mut out, region, min_area)?;
+        out.flush()
+    }
+
+    pub fn write_csv(
+        &self,
+        out: &mut impl Write,
+        region: &Region,
+        min_area: usize,
+    ) -> io::Result<()> {
+        let (width, height) = (self.width, self.height);
+        writeln!(
+            out,
+            "label,area,x_min,y_min,x_max,y_max,a_min,a_max,b_min,b_max,a,b"
+        )?;
+        for component in self.largest(min_area) {
+            let (x_min, y_min, x_max, y_max) = component.bounds;
+            // outer edges of the border pixels
+            let (a_min, b_min) = region.pixel_to_world(x_min, y_min, width, height);
+            let (a_max, b_max) = region.pixel_to_world(x_max + 1, y_max + 1, width, height);
+            let (a, b) = component.center(region, width, height);
+            writeln!(
+                out,
+                "{},{},{x_min},{y_min},{x_max},{y_max},{a_min},{a_max},{b_min},{b_max},{a},{b}",
+                component.label, component.area
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// what happened to a window from one frame to the next
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Birth,
+    Continue,
+    // with the tracks that ended in it
+    Merge(Vec<usize>),
+    // off the track it came from, which goes on in the largest part
+    Split(usize),
+    // the window of the track is gone in this frame
+    Death,
+}
+
+pub struct Step {
+    pub track: usize,
+    pub event: Event,
+    // None for a death
+    pub component: Option<Component>,
+}
+
+// follows the windows through frames of the same view. windows are matched by the pixels they share: a
+// window goes on in the successor it overlaps most, further successors split off and further predecessors
+// merge into it. windows that move by more than their own size from one frame to the next are not followed
+pub struct Tracker {
+    min_area: usize,
+    // the last frame and the track of each of its labels, 0 for ones below min_area
+    previous: Option<(Components, Vec<usize>)>,
+    tracks: usize,
+}
+
+impl Tracker {
+    pub fn new(min_area: usize) -> Self {
+        Tracker {
+            min_area,
+            previous: None,
+            tracks: 0,
+        }
+    }
+
+    // deaths first, then the windows of the frame in label order. windows below min_area are ignored
+    pub fn step(&mut self, frame: Components) -> Vec<Step> {
+        let large = |components: &Components, label: usize| {
+            label != 0 && components.components[label - 1].area >= self.min_area
+        };
+        // pixels shared by window p before and window n now
+        let mut overlaps: HashMap<(usize, usize), usize> = HashMap::new();
+        if let Some((previous, _)) = &self.previous {
+            for (&p, &n) in previous.labels.iter().zip(&frame.labels) {
+                if large(previous, p) && large(&frame, n) {
+                    *overlaps.entry((p, n)).or_default() += 1;
+                }
+            }
+        }
+        // the successor every window goes on in
+        let mut heirs: HashMap<usize, (usize, usize)> = HashMap::new();
+        for (&(p, n), &overlap) in &overlaps {
+            let heir = heirs.entry(p).or_insert((n, overlap));
+            if (overlap, std::cmp::Reverse(n)) > (heir.1, std::cmp::Reverse(heir.0)) {
+                *heir = (n, overlap);
+            }
+        }
+
+        let mut steps = Vec::new();
+        let mut tracks = vec![0; frame.components.len() + 1];
+        if let Some((previous, previous_tracks)) = &self.previous {
+            for component in &previous.components {
+                if large(previous, component.label) && !heirs.contains_key(&component.label) {
+                    steps.push(Step {
+                        track: previous_tracks[component.label],
+                        event: Event::Death,
+                        component: None,
+                    });
+                }
+            }
+        }
+        for component in &frame.components {
+            let n = component.label;
+            if !large(&frame, n) {
+                continue;
+            }
+            let mut predecessors: Vec<(usize, usize)> = overlaps
+                .iter()
+                .filter(|&(&(_, to), _)| to == n)
+                .map(|(&(p, _), &overlap)| (p, overlap))
+                .collect();
+            // most overlap first, ties by label
+            predecessors.sort_by_key(|&(p, overlap)| (std::cmp::Reverse(overlap), p));
+            let track_of = |p: usize| self.previous.as_ref().map_or(0, |(_, tracks)| tracks[p]);
+            let inherited = predecessors.iter().find(|&&(p, _)| heirs[&p].0 == n);
+            let (track, event) = match (predecessors.first(), inherited) {
+                (None, _) => {
+                    self.tracks += 1;
+                    (self.tracks, Event::Birth)
+                }
+                (Some(&(p, _)), None) => {
+                    self.tracks += 1;
+                    (self.tracks, Event::Split(track_of(p)))
+                }
+                (Some(_), Some(&(p, _))) => {
+                    let merged: Vec<usize> = predecessors
+                        .iter()
+                        .filter(|&&(other, _)| other != p && heirs[&other].0 == n)
+                        .map(|&(other, _)| track_of(other))
+                        .collect();
+                    if merged.is_empty() {
+                        (track_of(p), Event::Continue)
+                    } else {
+                        (track_of(p), Event::Merge(merged))
+                    }
+                }
+            };
+            tracks[n] = track;
+            steps.push(Step {
+                track,
+                event,
+                component: Some(component.clone()),
+            });
+        }
+        self.previous = Some((frame, tracks));
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // # stable, . chaotic, ? not computed
+    fn label(rows: &[&str]) -> Components {
+        let lambdas: Vec<f64> = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .map(|c| match c {
+                '#' => -1.0,
+                '.' => 1.0,
+                _ => f64::NAN,
+            })
+            .collect();
+        Components::label(&lambdas, rows[0].len(), rows.len())
+    }
+
+    #[test]
+    fn windows_are_connected_through_edges_only() {
+        let components = label(&["##..#", "#..#.", "..?.."]);
+        let areas: Vec<usize> = components.components.iter().map(|c| c.area).collect();
+        assert_eq!(areas, [3, 1, 1]);
+        assert_eq!(components.labels[..5], [1, 1, 0, 0, 2]);
+        assert_eq!(components.labels[12], 0);
+    }
+
+    #[test]
+    fn bounds_and_centroid_of_a_window() {
+        let components = label(&[".....", ".###.", ".#...", "....."]);
+        let window = &components.components[0];
+        assert_eq!(window.area, 4);
+        assert_eq!(window.bounds, (1, 1, 3, 2));
+        assert_eq!(window.centroid, (1.75, 1.25));
+        let region = Region::new(0.0, 5.0, 0.0, 4.0);
+        assert_eq!(window.center(&region, 5, 4), (2.25, 1.75));
+    }
+
+    #[test]
+    fn largest_first_and_small_ones_left_out() {
+        let components = label(&["#.###", "#.###", "..#.."]);
+        let largest: Vec<usize> = components.largest(2).iter().map(|c| c.label).collect();
+        assert_eq!(largest, [2, 1]);
+        assert!(components.largest(8).is_empty());
+    }
+
+    fn events(tracker: &mut Tracker, rows: &[&str]) -> Vec<(usize, Event)> {
+        let steps = tracker.step(label(rows));
+        steps
+            .into_iter()
+            .map(|step| (step.track, step.event))
+            .collect()
+    }
+
+    #[test]
+    fn windows_are_born_go_on_and_die() {
+        let mut tracker = Tracker::new(1);
+        assert_eq!(
+            events(&mut tracker, &["##...", "##..#"]),
+            [(1, Event::Birth), (2, Event::Birth)]
+        );
+        assert_eq!(
+            events(&mut tracker, &[".##..", ".##.."]),
+            [(2, Event::Death), (1, Event::Continue)]
+        );
+    }
+
+    #[test]
+    fn windows_split_and_merge() {
+        let mut tracker = Tracker::new(1);
+        events(&mut tracker, &["#####", "#####"]);
+        assert_eq!(
+            events(&mut tracker, &["###.#", "###.#"]),
+            [(1, Event::Continue), (2, Event::Split(1))]
+        );
+        assert_eq!(
+            events(&mut tracker, &["#####", "#####"]),
+            [(1, Event::Merge(vec![2]))]
+        );
+    }
+
+    #[test]
+    fn small_windows_are_not_followed() {
+        let mut tracker = Tracker::new(3);
+        assert_eq!(
+            events(&mut tracker, &["##..#", "##..."]),
+            [(1, Event::Birth)]
+        );
+        assert_eq!(
+            events(&mut tracker, &["....#", "....#"]),
+            [(1, Event::Death)]
+        );
+    }
+}
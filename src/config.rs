@@ -0,0 +1,174 @@
+// --watch: a small TOML file with the view, loaded again whenever it is saved.
+//
+//     sequence = "AB"
+//     region = [2.0, 4.0, 2.0, 4.0]
+//     palette = "gradient"
+//     depth = 300
+//
+//     [palettes.sunset]
+//     space = "oklab"
+//     stops = [[-2.5, "#161c31"], [-0.8, "#b75f74"], [0.0, "#faec70"]]
+//
+// every key is optional, missing ones leave the view as it is. palettes define color ramps by name,
+// stops are λ positions with #RRGGBB colors, interpolated in rgb (default), hsl or oklab. they are
+// registered at start (from --watch and --palettes files), a saved file changes their colors.
+
+use crate::color::{self, ColorRamp, Space};
+use crate::error::Error;
+use crate::i18n;
+use crate::render::{self, Alphabet};
+use crate::session::{self, Action};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub sequence: Option<String>,
+    pub region: Option<[f64; 4]>,
+    pub palette: Option<String>,
+    pub depth: Option<u32>,
+    #[serde(default)]
+    pub palettes: BTreeMap<String, PaletteDef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PaletteDef {
+    pub stops: Vec<(f64, String)>,
+    #[serde(default)]
+    pub space: Space,
+}
+
+impl PaletteDef {
+    pub fn ramp(&self) -> Result<ColorRamp, String> {
+        let stops = self
+            .stops
+            .iter()
+            .map(|(position, text)| {
+                let color = color::parse_color(text)
+                    .ok_or_else(|| i18n::trf("{0} is no color, expected #RRGGBB", &[&text]))?;
+                Ok((*position, color))
+            })
+            .collect::<Result<_, String>>()?;
+        ColorRamp::new(stops, self.space)
+    }
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        let config: Config =
+            toml::from_str(&text).map_err(|e| Error::usage(format!("{}: {e}", path.display())))?;
+        config
+            .check()
+            .map_err(|e| Error::usage(format!("{}: {e}", path.display())))?;
+        Ok(config)
+    }
+
+    // rejected as a whole before anything is applied, so a half edited file leaves the view alone.
+    // the sequence depends on the letters of the window, ConfigWatch checks it
+    fn check(&self) -> Result<(), String> {
+        if let Some(region) = self.region {
+            session::array_to_region(region).check()?;
+        }
+        if self.depth.is_some_and(|depth| depth <= render::WARMUP) {
+            return Err(i18n::trf("depth has to be above {0}", &[&render::WARMUP]));
+        }
+        for (name, palette) in &self.palettes {
+            palette
+                .ramp()
+                .map_err(|e| i18n::trf("palette {0}: {1}", &[&name, &e]))?;
+        }
+        Ok(())
+    }
+
+    // makes the palettes usable by name, see color::register
+    pub fn register_palettes(&self) -> Result<(), String> {
+        for (name, palette) in &self.palettes {
+            color::register(name, palette.ramp()?)?;
+        }
+        Ok(())
+    }
+
+    // changes to apply, in an order where the view is rendered only once
+    pub fn actions(&self) -> Vec<Action> {
+        let mut actions = Vec::new();
+        if let Some(sequence) = &self.sequence {
+            actions.push(Action::Sequence {
+                sequence: sequence.clone(),
+            });
+        }
+        if let Some(region) = self.region {
+            actions.push(Action::View { region });
+        }
+        if let Some(name) = &self.palette {
+            actions.push(Action::Palette { name: name.clone() });
+        }
+        if let Some(depth) = self.depth {
+            actions.push(Action::Depth { depth });
+        }
+        actions
+    }
+}
+
+pub struct ConfigWatch {
+    path: PathBuf,
+    alphabet: Alphabet,
+    // dropping the watcher ends the notifications
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    // load on the first poll, so the file applies from the start
+    pending: bool,
+}
+
+impl ConfigWatch {
+    // the directory is watched, editors often save by writing a new file and renaming it
+    pub fn new(path: &Path, alphabet: &Alphabet) -> Result<Self, Error> {
+        let path = path.to_path_buf();
+        let directory = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)
+            .map_err(|e| Error::io(&path, std::io::Error::other(e)))?;
+        watcher
+            .watch(&directory, RecursiveMode::NonRecursive)
+            .map_err(|e| Error::io(&directory, std::io::Error::other(e)))?;
+
+        Ok(ConfigWatch {
+            path,
+            alphabet: alphabet.clone(),
+            _watcher: watcher,
+            events,
+            pending: true,
+        })
+    }
+
+    // the new config if the file changed since the last call, several events of one save load it once
+    pub fn changed(&mut self) -> Option<Result<Config, Error>> {
+        let name = self.path.file_name();
+        for event in self.events.try_iter().flatten() {
+            if event.kind.is_modify() || event.kind.is_create() {
+                self.pending |= event.paths.iter().any(|p| p.file_name() == name);
+            }
+        }
+        if !self.pending {
+            return None;
+        }
+        self.pending = false;
+        Some(Config::load(&self.path).and_then(|config| {
+            if let Some(sequence) = &config.sequence {
+                self.alphabet
+                    .sequence(sequence)
+                    .map_err(|e| Error::usage(format!("{}: {e}", self.path.display())))?;
+            }
+            Ok(config)
+        }))
+    }
+}
@@ -0,0 +1,244 @@
+// contrast limited adaptive histogram equalization (CLAHE) of the λ field before it is colored.
+// large stable areas span only a sliver of the λ range and come out in a single color; equalizing
+// within tiles spreads their values over the whole range and brings out the faint structure.
+// order (λ < 0) and chaos (λ > 0) are equalized separately, so no pixel changes its side of λ = 0
+// and the palettes still tell them apart. ±∞ and NaN stay as they are.
+
+use crate::analysis;
+
+const BINS: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clahe {
+    // clip limit as a multiple of the mean bin count, 1 changes nothing, larger values more contrast
+    pub strength: f64,
+    // edge length of the tiles in pixels, smaller tiles are more local
+    pub tile: usize,
+}
+
+impl Clahe {
+    // plain histogram equalization of the whole field, a single tile without clip limit
+    pub fn global(width: usize, height: usize) -> Self {
+        Clahe {
+            strength: f64::INFINITY,
+            tile: width.max(height),
+        }
+    }
+
+    pub fn apply(&self, lambdas: &[f64], width: usize, height: usize) -> Vec<f64> {
+        let (min, max) = analysis::lambda_range(lambdas);
+        let mut equalized = lambdas.to_vec();
+        if min < 0.0 {
+            self.equalize_side(&mut equalized, width, height, (min, max.min(0.0)));
+        }
+        if max > 0.0 {
+            self.equalize_side(&mut equalized, width, height, (min.max(0.0), max));
+        }
+        equalized
+    }
+
+    // pixels with λ in lo..=hi (excluding 0 itself) get the clipped cumulative distribution of their
+    // tile, bilinearly interpolated between the four nearest tile centers
+    fn equalize_side(
+        &self,
+        lambdas: &mut [f64],
+        width: usize,
+        height: usize,
+        (lo, hi): (f64, f64),
+    ) {
+        if hi <= lo {
+            return;
+        }
+        let on_side = |l: f64| l.is_finite() && l != 0.0 && (lo..=hi).contains(&l);
+        let position = |l: f64| ((l - lo) / (hi - lo) * BINS as f64).min(BINS as f64 - 1e-9);
+        let bin = |l: f64| position(l) as usize;
+
+        let tile = self.tile.max(1);
+        let (columns, rows) = (width.div_ceil(tile), height.div_ceil(tile));
+        let mut mappings = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let mut histogram = [0usize; BINS];
+                for y in row * tile..((row + 1) * tile).min(height) {
+                    for x in column * tile..((column + 1) * tile).min(width) {
+                        let lambda = lambdas[y * width + x];
+                        if on_side(lambda) {
+                            histogram[bin(lambda)] += 1;
+                        }
+                    }
+                }
+                mappings.push(self.mapping(&histogram));
+            }
+        }
+
+        // tile centers at (column + 0.5) * tile, outside of the outer centers the nearest tile alone
+        let neighbours = |position: usize, count: usize| {
+            let t = (position as f64 + 0.5) / tile as f64 - 0.5;
+            let first = t.floor().clamp(0.0, (count - 1) as f64) as usize;
+            let second = (first + 1).min(count - 1);
+            (first, second, (t - first as f64).clamp(0.0, 1.0))
+        };
+        for y in 0..height {
+            let (r1, r2, ty) = neighbours(y, rows);
+            for x in 0..width {
+                let lambda = lambdas[y * width + x];
+                if !on_side(lambda) {
+                    continue;
+                }
+                let (c1, c2, tx) = neighbours(x, columns);
+                // within the bin linearly from the distribution below it to its own, against banding
+                let (b, within) = (bin(lambda), position(lambda).fract());
+                let at = |row: usize, column: usize| {
+                    let mapping: &[f64; BINS] = &mappings[row * columns + column];
+                    let below = if b == 0 { 0.0 } else { mapping[b - 1] };
+                    below + (mapping[b] - below) * within
+                };
+                let top = at(r1, c1) * (1.0 - tx) + at(r1, c2) * tx;
+                let bottom = at(r2, c1) * (1.0 - tx) + at(r2, c2) * tx;
+                let value = lo + (top * (1.0 - ty) + bottom * ty) * (hi - lo);
+                // never exactly 0, that would move the pixel onto the border
+                lambdas[y * width + x] = if value == 0.0 {
+                    lambda.signum() * f64::MIN_POSITIVE
+                } else {
+                    value
+                };
+            }
+        }
+    }
+
+    // clipped histogram, the excess spread evenly over all bins, as cumulative distribution in 0..=1.
+    // an empty tile maps every bin onto itself
+    fn mapping(&self, histogram: &[usize; BINS]) -> [f64; BINS] {
+        let total: usize = histogram.iter().sum();
+        let mut mapping = [0.0; BINS];
+        if total == 0 {
+            for (i, value) in mapping.iter_mut().enumerate() {
+                *value = (i + 1) as f64 / BINS as f64;
+            }
+            return mapping;
+        }
+        let limit = (self.strength.max(1.0) * total as f64 / BINS as f64).max(1.0);
+        let excess: f64 = histogram
+            .iter()
+            .map(|&count| (count as f64 - limit).max(0.0))
+            .sum();
+        let bonus = excess / BINS as f64;
+        let mut sum = 0.0;
+        for (value, &count) in mapping.iter_mut().zip(histogram) {
+            sum += (count as f64).min(limit) + bonus;
+            *value = sum / total as f64;
+        }
+        mapping
+    }
+}
+
+// tone mapping of λ before the palette lookup. λ clusters near 0, a transfer function stretches or
+// squeezes the range 0..±SCALE of each side so the palette spends more colors near the border (log,
+// sqrt) or on the middle of the stable range (sigmoid). the sign of λ, the border at 0 and ±SCALE stay
+// where they are, ±∞ and NaN are left alone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transfer {
+    Log,
+    Sqrt,
+    // steepness k, larger is more contrast in the middle
+    Sigmoid(f64),
+}
+
+// |λ| mapped onto itself by every transfer function, the stable range the palettes cover
+const SCALE: f64 = 2.5;
+// log(1 + LOG_GAIN·t), larger gains stretch the border further
+const LOG_GAIN: f64 = 9.0;
+
+impl Transfer {
+    // log, sqrt or sigmoid[:K]
+    pub fn parse(spec: &str) -> Option<Self> {
+        match spec.split_once(':') {
+            None if spec == "log" => Some(Transfer::Log),
+            None if spec == "sqrt" => Some(Transfer::Sqrt),
+            None if spec == "sigmoid" => Some(Transfer::Sigmoid(8.0)),
+            Some(("sigmoid", k)) => k
+                .trim()
+                .parse()
+                .ok()
+                .filter(|&k: &f64| k > 0.0 && k.is_finite())
+                .map(Transfer::Sigmoid),
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, lambda: f64) -> f64 {
+        if !lambda.is_finite() {
+            return lambda;
+        }
+        let t = lambda.abs() / SCALE;
+        let mapped = match *self {
+            Transfer::Log => (LOG_GAIN * t).ln_1p() / LOG_GAIN.ln_1p(),
+            Transfer::Sqrt => t.sqrt(),
+            // logistic curve through (0, 0) and (1, 1)
+            Transfer::Sigmoid(k) => {
+                let sigma = |x: f64| 1.0 / (1.0 + (-k * (x - 0.5)).exp());
+                (sigma(t) - sigma(0.0)) / (sigma(1.0) - sigma(0.0))
+            }
+        };
+        lambda.signum() * mapped * SCALE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 16 x 16, most of the stable pixels crowded just below 0, a few deep ones, chaos on the right
+    fn field() -> Vec<f64> {
+        (0..256)
+            .map(|i| match (i % 16, i / 16) {
+                (0, _) => -2.0,
+                (x, y) if x < 12 => -0.1 + (x + y) as f64 * 0.001,
+                (x, _) => x as f64 * 0.01,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn equalizing_keeps_the_side_of_every_pixel() {
+        let mut lambdas = field();
+        lambdas[17] = f64::NEG_INFINITY;
+        lambdas[18] = f64::NAN;
+        lambdas[19] = 0.0;
+        let equalized = Clahe {
+            strength: 3.0,
+            tile: 4,
+        }
+        .apply(&lambdas, 16, 16);
+        for (before, after) in lambdas.iter().zip(&equalized) {
+            if before.is_finite() {
+                assert_eq!(before.signum(), after.signum(), "{before} became {after}");
+            } else {
+                assert_eq!(before.to_bits(), after.to_bits());
+            }
+        }
+        assert_eq!(equalized[19], 0.0);
+    }
+
+    #[test]
+    fn global_equalization_spreads_crowded_values_in_order() {
+        let lambdas = field();
+        let equalized = Clahe::global(16, 16).apply(&lambdas, 16, 16);
+        let spread = |values: &[f64]| {
+            let crowded = values
+                .iter()
+                .zip(&lambdas)
+                .filter(|(_, &l)| l > -1.0 && l < 0.0);
+            let (low, high) = crowded
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), (v, _)| {
+                    (low.min(*v), high.max(*v))
+                });
+            high - low
+        };
+        assert!(spread(&equalized) > 10.0 * spread(&lambdas));
+
+        let mut pairs: Vec<(f64, f64)> = lambdas.iter().copied().zip(equalized).collect();
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+        assert!(pairs.windows(2).all(|w| w[0].1 <= w[1].1 + 1e-12));
+    }
+}
@@ -0,0 +1,157 @@
+// diff subcommand: λ of two sequences over the same region and their signed difference
+// λ(second) - λ(first), blue where the second sequence is more stable, red where it is more chaotic.
+
+use crate::cli::DiffArgs;
+use crate::color;
+use crate::display::{Display, MinifbDisplay};
+use crate::error::Error;
+use crate::export::save_png;
+use crate::i18n;
+use crate::render::{self, parse_sequence, Kernel, Region};
+use crate::scaling::Upscale;
+use crate::ROWS_PER_UPDATE;
+use std::ops::Range;
+use std::path::Path;
+
+// percentile of |Δλ| mapped to full saturation
+const SCALE_PERCENTILE: usize = 98;
+
+struct Diff {
+    width: usize,
+    height: usize,
+    first: Vec<f64>,
+    second: Vec<f64>,
+    buffer: Vec<u32>,
+}
+
+impl Diff {
+    fn new(width: usize, height: usize) -> Self {
+        Diff {
+            width,
+            height,
+            first: vec![f64::NAN; width * height],
+            second: vec![f64::NAN; width * height],
+            buffer: vec![0; width * height],
+        }
+    }
+
+    fn delta(&self) -> impl Iterator<Item = f64> + '_ {
+        self.first.iter().zip(&self.second).map(|(l1, l2)| l2 - l1)
+    }
+
+    fn render_rows(
+        &mut self,
+        region: &Region,
+        first: &Kernel,
+        second: &Kernel,
+        rows: Range<usize>,
+    ) {
+        let (width, height) = (self.width, self.height);
+        render::render_rows(&mut self.first, width, height, region, first, rows.clone());
+        render::render_rows(&mut self.second, width, height, region, second, rows);
+    }
+
+    // scaled symmetric to the differences so far, so everything is colored again.
+    // near superstable orbits give huge outliers, the largest few percent are ignored for the scale
+    fn colorize(&mut self) {
+        let mut magnitudes: Vec<f64> = self
+            .delta()
+            .filter(|d| d.is_finite())
+            .map(f64::abs)
+            .collect();
+        let scale = if magnitudes.is_empty() {
+            0.0
+        } else {
+            let index = (magnitudes.len() - 1) * SCALE_PERCENTILE / 100;
+            *magnitudes.select_nth_unstable_by(index, f64::total_cmp).1
+        };
+        let colors: Vec<u32> = self
+            .delta()
+            .map(|d| color::color_diverging(d, scale))
+            .collect();
+        self.buffer = colors;
+    }
+
+    fn print_stats(&self) {
+        let known = self.delta().filter(|d| d.is_finite()).count().max(1);
+        let flipped = self
+            .first
+            .iter()
+            .zip(&self.second)
+            .filter(|(l1, l2)| (**l1 > 0.0) != (**l2 > 0.0))
+            .count();
+        let mean = self
+            .delta()
+            .filter(|d| d.is_finite())
+            .map(f64::abs)
+            .sum::<f64>()
+            / known as f64;
+        let flipped = 100.0 * flipped as f64 / (self.width * self.height) as f64;
+        println!("{}", i18n::trf("mean |Δλ|: {0}", &[&format!("{mean:.4}")]));
+        println!(
+            "{}",
+            i18n::trf(
+                "order/chaos flipped: {0}% of the pixels",
+                &[&format!("{flipped:.2}")]
+            )
+        );
+    }
+}
+
+pub fn run(args: &DiffArgs) -> Result<(), Error> {
+    let first = Kernel::new(parse_sequence(&args.first)?);
+    let second = Kernel::new(parse_sequence(&args.second)?);
+    let title = format!("Δλ {} - {}", args.second, args.first);
+    compare(
+        &first,
+        &second,
+        &args.region,
+        args.out.as_deref(),
+        args.size,
+        &title,
+    )
+}
+
+// λ(second) - λ(first) into a PNG of the given size, or shown in a window when there is no path
+pub fn compare(
+    first: &Kernel,
+    second: &Kernel,
+    region: &Region,
+    out: Option<&Path>,
+    (width, height): (usize, usize),
+    title: &str,
+) -> Result<(), Error> {
+    if let Some(path) = out {
+        let mut diff = Diff::new(width, height);
+        diff.render_rows(region, first, second, 0..height);
+        diff.colorize();
+        save_png(path, &diff.buffer, width, height).map_err(|e| Error::io(path, e))?;
+        diff.print_stats();
+        return Ok(());
+    }
+
+    let mut display = MinifbDisplay::new(None, 1, Upscale::Nearest)?;
+    display.set_title(title);
+    let (width, height) = display.size();
+    let mut diff = Diff::new(width, height);
+    for start in (0..height).step_by(ROWS_PER_UPDATE) {
+        if !display.is_open() {
+            return Ok(());
+        }
+        diff.render_rows(
+            region,
+            first,
+            second,
+            start..(start + ROWS_PER_UPDATE).min(height),
+        );
+        diff.colorize();
+        display.present(&diff.buffer, width, height);
+        display.poll_events();
+    }
+    diff.print_stats();
+
+    while display.is_open() {
+        display.poll_events();
+    }
+    Ok(())
+}
@@ -0,0 +1,301 @@
+// where finished pixels go. the render loop only talks to a Display, so other frontends
+// (softbuffer+winit, SDL2, terminal) can be added next to the minifb window.
+
+use crate::error::Error;
+use crate::i18n;
+use crate::scaling::{self, Upscale};
+use crate::screen;
+use crate::{HEIGHT, WIDTH};
+use minifb::{KeyRepeat, MouseMode, Scale, ScaleMode, Window, WindowOptions};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    // letters lowercase, digits, ',' and '.'
+    Char(char),
+    Enter,
+    Space,
+    Left,
+    Right,
+    Up,
+    Down,
+    // F1
+    Help,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+}
+
+const MOUSE_BUTTONS: [MouseButton; 3] =
+    [MouseButton::Left, MouseButton::Middle, MouseButton::Right];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    KeyPressed(Key),
+    // position in frame pixels
+    MousePressed {
+        x: usize,
+        y: usize,
+        button: MouseButton,
+    },
+    MouseReleased {
+        button: MouseButton,
+    },
+    MouseMoved {
+        x: usize,
+        y: usize,
+    },
+    // the cursor went outside the display
+    MouseLeft,
+    // mouse wheel turned at a position, positive away from the user
+    Scroll {
+        x: usize,
+        y: usize,
+        delta: f64,
+    },
+}
+
+impl Event {
+    // the same place on a display of size to instead of from
+    pub fn scaled(self, from: (usize, usize), to: (usize, usize)) -> Event {
+        let at = |x: usize, y: usize| (x * to.0 / from.0.max(1), y * to.1 / from.1.max(1));
+        match self {
+            Event::MousePressed { x, y, button } => {
+                let (x, y) = at(x, y);
+                Event::MousePressed { x, y, button }
+            }
+            Event::MouseMoved { x, y } => {
+                let (x, y) = at(x, y);
+                Event::MouseMoved { x, y }
+            }
+            Event::Scroll { x, y, delta } => {
+                let (x, y) = at(x, y);
+                Event::Scroll { x, y, delta }
+            }
+            event => event,
+        }
+    }
+}
+
+pub trait Display {
+    // false once the user closed the display or pressed ESC
+    fn is_open(&self) -> bool;
+
+    // size in pixels a frame should have to be shown without scaling
+    fn size(&self) -> (usize, usize);
+
+    // show a 0RGB buffer
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize);
+
+    // input since the last call. keeps the display responsive, call it every frame
+    fn poll_events(&mut self) -> Vec<Event>;
+
+    fn set_title(&mut self, _title: &str) {}
+}
+
+pub struct MinifbDisplay {
+    window: Window,
+    // present already pumped the window events for this frame
+    presented: bool,
+    // button state and mouse position of the last poll, to report changes only
+    mouse_down: [bool; 3],
+    mouse_position: Option<(usize, usize)>,
+    // screen pixels per window pixel (HiDPI), and per frame pixel (--scale)
+    density: usize,
+    scale: usize,
+    filter: Upscale,
+}
+
+impl MinifbDisplay {
+    // a resizable window, or a borderless one of the given size covering the screen. frames are
+    // rendered at the density of the screen with every frame pixel scale × scale screen pixels, brought
+    // up to them by filter
+    pub fn new(
+        fullscreen: Option<(usize, usize)>,
+        scale: usize,
+        filter: Upscale,
+    ) -> Result<Self, Error> {
+        MinifbDisplay::open(fullscreen.map(|size| (size, (0, 0))), scale, filter)
+    }
+
+    // a borderless window of the given size with its upper left corner at a screen position, both in
+    // screen pixels. one monitor of a --span wall
+    pub fn tile(
+        size: (usize, usize),
+        position: (usize, usize),
+        scale: usize,
+        filter: Upscale,
+    ) -> Result<Self, Error> {
+        MinifbDisplay::open(Some((size, position)), scale, filter)
+    }
+
+    fn open(
+        fullscreen: Option<((usize, usize), (usize, usize))>,
+        scale: usize,
+        filter: Upscale,
+    ) -> Result<Self, Error> {
+        let density = screen::density();
+        // the screen size is in screen pixels, the default size in window pixels
+        let (width, height) = match fullscreen {
+            Some(((width, height), _)) => (width / density, height / density),
+            None => (WIDTH, HEIGHT),
+        };
+        let mut window = Window::new(
+            i18n::tr("Ljapunow-Markus diagram - press ESC to exit"),
+            width,
+            height,
+            WindowOptions {
+                resize: fullscreen.is_none(),
+                borderless: fullscreen.is_some(),
+                title: fullscreen.is_none(),
+                topmost: fullscreen.is_some(),
+                scale: Scale::X1, // scale: Scale::X2,
+                scale_mode: ScaleMode::AspectRatioStretch,
+                ..WindowOptions::default()
+            },
+        )
+        .map_err(Error::WindowInit)?;
+        window.set_target_fps(60);
+        window.set_background_color(0, 0, 20);
+
+        if let Some((_, (x, y))) = fullscreen {
+            window.set_position((x / density) as isize, (y / density) as isize);
+            window.set_cursor_visibility(false);
+        }
+
+        Ok(MinifbDisplay {
+            window,
+            presented: false,
+            mouse_down: [false; 3],
+            mouse_position: None,
+            density,
+            scale: scale.max(1),
+            filter,
+        })
+    }
+}
+
+impl Display for MinifbDisplay {
+    fn is_open(&self) -> bool {
+        self.window.is_open() && !self.window.is_key_down(minifb::Key::Escape)
+    }
+
+    fn size(&self) -> (usize, usize) {
+        let (width, height) = self.window.get_size();
+        (
+            width * self.density / self.scale,
+            height * self.density / self.scale,
+        )
+    }
+
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) {
+        let _span = tracing::info_span!("present", width, height).entered();
+        // minifb would stretch the buffer over the window, scaled here it looks as --upscale asks
+        let scale = self.scale;
+        let scaled;
+        let (buffer, width, height) = if scale > 1 {
+            let size = (width * scale, height * scale);
+            scaled = scaling::resize(buffer, (width, height), size, self.filter);
+            (scaled.as_slice(), width * scale, height * scale)
+        } else {
+            (buffer, width, height)
+        };
+        self.window
+            .update_with_buffer(buffer, width, height)
+            .expect("the buffer has the size it is given with");
+        self.presented = true;
+    }
+
+    fn poll_events(&mut self) -> Vec<Event> {
+        if !self.presented {
+            self.window.update();
+        }
+        self.presented = false;
+
+        let mut events: Vec<Event> = self
+            .window
+            .get_keys_pressed(KeyRepeat::No)
+            .into_iter()
+            .filter_map(map_key)
+            .map(Event::KeyPressed)
+            .collect();
+
+        // in frame pixels
+        let factor = self.density as f32 / self.scale as f32;
+        let position = self
+            .window
+            .get_mouse_pos(MouseMode::Discard)
+            .map(|(x, y)| (x * factor, y * factor));
+        let pixel = position.map(|(x, y)| (x as usize, y as usize));
+        if let Some((x, y)) = pixel.filter(|&p| Some(p) != self.mouse_position) {
+            events.push(Event::MouseMoved { x, y });
+        }
+        if pixel.is_none() && self.mouse_position.is_some() {
+            events.push(Event::MouseLeft);
+        }
+        self.mouse_position = pixel;
+
+        for (i, button) in MOUSE_BUTTONS.into_iter().enumerate() {
+            let down = self.window.get_mouse_down(map_button(button));
+            match (down, self.mouse_down[i], pixel) {
+                (true, false, Some((x, y))) => events.push(Event::MousePressed { x, y, button }),
+                (false, true, _) => events.push(Event::MouseReleased { button }),
+                _ => {}
+            }
+            self.mouse_down[i] = down;
+        }
+
+        // minifb reports one notch as 1 on X11 but 12 on windows, so only the direction is used
+        if let (Some((_, delta)), Some((x, y))) = (self.window.get_scroll_wheel(), position) {
+            if delta != 0.0 {
+                events.push(Event::Scroll {
+                    x: x as usize,
+                    y: y as usize,
+                    delta: delta.signum() as f64,
+                });
+            }
+        }
+        events
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+}
+
+fn map_button(button: MouseButton) -> minifb::MouseButton {
+    match button {
+        MouseButton::Left => minifb::MouseButton::Left,
+        MouseButton::Middle => minifb::MouseButton::Middle,
+        MouseButton::Right => minifb::MouseButton::Right,
+    }
+}
+
+fn map_key(key: minifb::Key) -> Option<Key> {
+    use minifb::Key as K;
+
+    // minifb numbers Key0..Key9 as 0..=9 and A..Z as 10..=35
+    let code = key as u32;
+    if code <= 9 {
+        return char::from_digit(code, 10).map(Key::Char);
+    }
+    if code <= 35 {
+        return char::from_digit(code, 36).map(Key::Char);
+    }
+
+    match key {
+        K::Comma => Some(Key::Char(',')),
+        K::Period => Some(Key::Char('.')),
+        K::Enter | K::NumPadEnter => Some(Key::Enter),
+        K::Space => Some(Key::Space),
+        K::Left => Some(Key::Left),
+        K::Right => Some(Key::Right),
+        K::Up => Some(Key::Up),
+        K::Down => Some(Key::Down),
+        K::F1 => Some(Key::Help),
+        _ => None,
+    }
+}
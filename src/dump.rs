@@ -0,0 +1,80 @@
+// dump subcommand: raw λ grid of any size into a memory mapped file, see grid_file for the format
+
+use crate::analysis;
+use crate::cli::DumpArgs;
+use crate::error::Error;
+use crate::grid_file::{GridWriter, Header, Storage};
+use crate::i18n;
+use crate::interrupt;
+use crate::render::{self, parse_sequence, Kernel, Renderer};
+
+// rows between two flushes and progress messages
+const FLUSH_ROWS: usize = 256;
+// u16 storage without --lambda-range quantizes over the range of a preview of the region
+const PREVIEW_SIZE: usize = 256;
+
+pub fn run(args: &DumpArgs) -> Result<(), Error> {
+    interrupt::install();
+    let (width, height) = args.size;
+    let kernel = Kernel::new(parse_sequence(&args.sequence)?);
+    let range = match (args.storage, args.lambda_range) {
+        (Storage::U16, None) => preview_range(&args.region, &kernel),
+        (_, range) => range.unwrap_or((f64::NAN, f64::NAN)),
+    };
+    let renderer = Renderer::new(width, height, args.region, kernel);
+
+    let header = Header {
+        width,
+        height,
+        region: args.region,
+        storage: args.storage,
+        range,
+    };
+    let mut grid = GridWriter::create(&args.out, header).map_err(|e| Error::io(&args.out, e))?;
+
+    for (y, row) in renderer.rows().enumerate() {
+        grid.write_row(y, &row);
+        if (y + 1) % FLUSH_ROWS == 0 {
+            grid.flush_async().map_err(|e| Error::io(&args.out, e))?;
+            println!("{}/{height} rows", y + 1);
+        }
+        if interrupt::requested() && y + 1 < height {
+            grid.finish().map_err(|e| Error::io(&args.out, e))?;
+            return Err(Error::Interrupted(format!(
+                "{} of {height} rows in {}",
+                y + 1,
+                args.out.display()
+            )));
+        }
+    }
+    grid.finish().map_err(|e| Error::io(&args.out, e))?;
+    println!(
+        "{}",
+        i18n::trf(
+            "{0}x{1} λ written to {2}",
+            &[&width, &height, &args.out.display()]
+        )
+    );
+    Ok(())
+}
+
+fn preview_range(region: &render::Region, kernel: &Kernel) -> (f64, f64) {
+    let mut preview = vec![f64::NAN; PREVIEW_SIZE * PREVIEW_SIZE];
+    render::render_rows(
+        &mut preview,
+        PREVIEW_SIZE,
+        PREVIEW_SIZE,
+        region,
+        kernel,
+        0..PREVIEW_SIZE,
+    );
+    let range = analysis::lambda_range(&preview);
+    println!(
+        "{}",
+        i18n::trf(
+            "λ quantized over {0}..{1}",
+            &[&format!("{:.4}", range.0), &format!("{:.4}", range.1)]
+        )
+    );
+    range
+}
@@ -0,0 +1,123 @@
+// everything that can go wrong, each kind with its own process exit code so scripts can tell them apart
+
+use crate::i18n::{tr, trf};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    // bad command line, exit code 2
+    Usage(String),
+    // sequence with letters other than A and B or without one of them, exit code 3
+    InvalidSequence(String),
+    // no window available (headless machine, no display), exit code 4
+    WindowInit(minifb::Error),
+    // reading or writing a file failed, exit code 5
+    Io { path: PathBuf, source: io::Error },
+    // script did not compile or failed while running, exit code 6
+    Script(String),
+    // SIGINT or SIGTERM stopped a headless render, what was written so far, exit code 7
+    Interrupted(String),
+    // no sound output for --sonify, exit code 8
+    Audio(String),
+    // no MIDI output for --midi, exit code 9
+    Midi(String),
+    // --colorizer library or --wasm module could not be loaded or does not fit, exit code 10
+    Plugin(String),
+    // no gamepad support for --gamepad, exit code 11
+    Gamepad(String),
+}
+
+impl Error {
+    pub fn usage(message: impl Into<String>) -> Self {
+        Error::Usage(message.into())
+    }
+
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        Error::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Usage(_) => 2,
+            Error::InvalidSequence(_) => 3,
+            Error::WindowInit(_) => 4,
+            Error::Io { .. } => 5,
+            Error::Script(_) => 6,
+            Error::Interrupted(_) => 7,
+            Error::Audio(_) => 8,
+            Error::Midi(_) => 9,
+            Error::Plugin(_) => 10,
+            Error::Gamepad(_) => 11,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Usage(message) => write!(f, "{message}"),
+            Error::InvalidSequence(sequence) => {
+                let message = trf("invalid sequence '{0}'", &[sequence]);
+                write!(f, "{message}, {}", sequence_hint(sequence))
+            }
+            Error::WindowInit(error) => {
+                write!(f, "{}", trf("unable to create the window: {0}", &[error]))
+            }
+            Error::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Error::Script(message) => write!(f, "{}", trf("script: {0}", &[message])),
+            Error::Interrupted(kept) => write!(f, "{}", trf("interrupted, {0}", &[kept])),
+            Error::Audio(message) => write!(f, "{}", trf("audio: {0}", &[message])),
+            Error::Midi(message) => write!(f, "{}", trf("MIDI: {0}", &[message])),
+            Error::Plugin(message) => write!(f, "{}", trf("plugin: {0}", &[message])),
+            Error::Gamepad(message) => write!(f, "{}", trf("gamepad: {0}", &[message])),
+        }
+    }
+}
+
+// what is wrong with a sequence and how it could be fixed
+fn sequence_hint(sequence: &str) -> String {
+    let letters: String = sequence
+        .chars()
+        .map(|c| c.to_ascii_uppercase())
+        .filter(|&c| c == 'A' || c == 'B')
+        .collect();
+    let complete = letters.contains('A') && letters.contains('B');
+    if sequence.chars().any(|c| c != 'A' && c != 'B') {
+        if complete {
+            trf(
+                "only the letters A and B are allowed unless --values defines others, did you mean '{0}'?",
+                &[&letters],
+            )
+        } else {
+            tr("only the letters A and B are allowed unless --values defines others (e.g. BBBBBBAAAAAA)")
+                .into()
+        }
+    } else if sequence.is_empty() {
+        tr("it needs at least one A and one B (e.g. BBBBBBAAAAAA)").into()
+    } else {
+        let (missing, axis) = if sequence.contains('A') {
+            ('B', 'b')
+        } else {
+            ('A', 'a')
+        };
+        trf(
+            "without {0} the diagram does not change along {1}, did you mean '{2}'?",
+            &[&missing, &axis, &format!("{sequence}{missing}")],
+        )
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::WindowInit(error) => Some(error),
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
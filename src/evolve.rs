@@ -0,0 +1,107 @@
+// experimental evolve subcommand: genetic search for pretty forcing sequences
+// every survivor spawns mutants (flip, insert or delete a letter), all candidates are rendered
+// as small thumbnails and scored by analysis::interestingness, the best ones survive.
+
+use crate::analysis::interestingness;
+use crate::cli::EvolveArgs;
+use crate::color::colorize;
+use crate::error::Error;
+use crate::export::save_png;
+use crate::render::{self, parse_sequence, Kernel};
+use crate::rng::Rng;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+
+const THUMBNAIL_SIZE: usize = 128;
+const MUTANTS_PER_SURVIVOR: usize = 4;
+const MAX_SEQUENCE_LEN: usize = 16;
+
+pub fn run(args: &EvolveArgs, rng_seed: Option<u64>) -> Result<(), Error> {
+    let seed: String = parse_sequence(&args.seed)?.into_iter().collect();
+    let (region, out) = (args.region, &args.out);
+
+    fs::create_dir_all(out).map_err(|e| Error::io(out, e))?;
+    let log_path = out.join("log.txt");
+    let mut log = File::create(&log_path).map_err(|e| Error::io(&log_path, e))?;
+
+    let mut rng = Rng::seeded(rng_seed);
+    let mut lambdas = vec![f64::NAN; THUMBNAIL_SIZE * THUMBNAIL_SIZE];
+    // rendering is the expensive part, never score a sequence twice
+    let mut scores: HashMap<String, f64> = HashMap::new();
+    let mut survivors = vec![seed];
+
+    for generation in 0..=args.generations {
+        let mut candidates = survivors.clone();
+        if generation > 0 {
+            for parent in &survivors {
+                for _ in 0..MUTANTS_PER_SURVIVOR {
+                    candidates.push(mutate(parent, &mut rng));
+                }
+            }
+        }
+        candidates.sort();
+        candidates.dedup();
+
+        for candidate in &candidates {
+            if scores.contains_key(candidate) {
+                continue;
+            }
+            let kernel = Kernel::new(candidate.chars().collect());
+            render::render_rows(
+                &mut lambdas,
+                THUMBNAIL_SIZE,
+                THUMBNAIL_SIZE,
+                &region,
+                &kernel,
+                0..THUMBNAIL_SIZE,
+            );
+            let score = interestingness(&lambdas, THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+            scores.insert(candidate.clone(), score);
+
+            let buffer: Vec<u32> = lambdas.iter().map(|&l| colorize(l)).collect();
+            let path = out.join(format!("{candidate}.png"));
+            save_png(&path, &buffer, THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+                .map_err(|e| Error::io(&path, e))?;
+        }
+
+        candidates.sort_by(|c1, c2| scores[c2].total_cmp(&scores[c1]));
+        candidates.truncate(args.population as usize);
+        survivors = candidates;
+
+        println!(
+            "generation {generation}: best {} ({:.5})",
+            survivors[0], scores[&survivors[0]]
+        );
+        for (rank, sequence) in survivors.iter().enumerate() {
+            writeln!(
+                log,
+                "{generation}\t{rank}\t{:.6}\t{sequence}",
+                scores[sequence]
+            )
+            .map_err(|e| Error::io(&log_path, e))?;
+        }
+    }
+    Ok(())
+}
+
+// flip, insert or delete a single letter
+fn mutate(sequence: &str, rng: &mut Rng) -> String {
+    let mut letters: Vec<char> = sequence.chars().collect();
+    let random_letter = |rng: &mut Rng| if rng.next_f64() < 0.5 { 'A' } else { 'B' };
+
+    match rng.below(3) {
+        0 if letters.len() < MAX_SEQUENCE_LEN => {
+            let pos = rng.below(letters.len() + 1);
+            letters.insert(pos, random_letter(rng));
+        }
+        1 if letters.len() > 1 => {
+            letters.remove(rng.below(letters.len()));
+        }
+        _ => {
+            let pos = rng.below(letters.len());
+            letters[pos] = if letters[pos] == 'A' { 'B' } else { 'A' };
+        }
+    }
+    letters.into_iter().collect()
+}
@@ -0,0 +1,1179 @@
+// interactive exploration of a diagram in a window.
+// C toggles palette cycling on the already computed λ grid, P switches the palette,
+// . and , raise or lower the iteration depth, M the minimap in the lower right corner,
+// clicking the minimap moves the view there, the mouse wheel zooms around the cursor,
+// dragging with the middle or right button pans, S saves the view named by the output template.
+// every change goes through an Action, so sessions can be recorded and replayed.
+// with --watch the view also follows a config file whenever it is saved.
+// with --refine the order/chaos border is computed again at a higher depth in the background.
+// 3 switches to the terrain view of the same λ grid, dragging with the left button turns and tilts
+// the camera there and the mouse wheel moves it closer or away.
+// with --sonify the orbit under the cursor is played as sound, with --midi dragging with the left button
+// draws a path that is played on a MIDI instrument.
+// L starts a path scan: clicks set the vertices of a polyline and Enter samples λ and the period along it
+// into a CSV file named by the output template and shows λ as a plot in the upper left. L again ends it.
+// B bookmarks the view and N goes to the next bookmark, K shows a colorbar at the right edge, F1 lists
+// all bindings. with --render-scale views are computed smaller than the window and Enter computes the
+// current one at full size. with --workspace the state is written to a file when the window closes, see workspace.rs.
+
+use crate::cache::LambdaCache;
+use crate::color::Palette;
+use crate::config::{ConfigWatch, PaletteDef};
+use crate::contrast::{Clahe, Transfer};
+use crate::display::{Display, Event, Key, MouseButton};
+use crate::error::Error;
+use crate::export;
+use crate::font::{self, draw_text};
+use crate::frame::Frame;
+use crate::gamepad::{Gamepad, Input};
+use crate::help;
+use crate::histogram;
+use crate::i18n;
+use crate::legend;
+use crate::midi::Sequencer;
+use crate::minimap::Minimap;
+use crate::pipe::Job;
+use crate::presets::PRESETS;
+use crate::refine::Refinement;
+use crate::relief::{Camera, Terrain};
+use crate::remote::{Command, Remote};
+use crate::render::{self, Kernel, Region};
+use crate::scaling::{Resampled, Upscale};
+use crate::scan;
+use crate::session::{self, Action, Recorder, Replay};
+use crate::sonify::Sonifier;
+use crate::template;
+use crate::workspace::{Bookmark, Workspace};
+use crate::{print_stats, render_progressive, save_view};
+use crate::{CYCLE_SPEED, DEPTH_STEP, RESIZE_DEBOUNCE, ZOOM_PER_NOTCH};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// distance of the histogram panel to the window border
+const PANEL_MARGIN: usize = 8;
+// camera of the terrain view: radians per pixel dragged, tilt and distance limits
+const ORBIT_SPEED: f64 = 0.01;
+const MIN_PITCH: f64 = 0.05;
+const MAX_PITCH: f64 = 1.4;
+const MIN_DISTANCE: f64 = 0.2;
+const MAX_DISTANCE: f64 = 3.0;
+// samples along a scanned path and the color of the path over the diagram
+const SCAN_SAMPLES: usize = 512;
+const SCAN_PATH: u32 = 0xFFFFFF;
+// f64 steps per pixel below which the view is flagged, at 1 neighbouring pixels become equal
+const PRECISION_WARNING: f64 = 16.0;
+const WARNING: u32 = 0xFFD040;
+const WARNING_SHADOW: u32 = 0x000000;
+// how long a finished view has to stay unchanged before --autosave-dir keeps it
+const AUTOSAVE_AFTER: Duration = Duration::from_secs(3);
+
+pub struct Explorer {
+    frame: Frame,
+    region: Region,
+    kernel: Kernel,
+    palette: Palette,
+    minimap: Minimap,
+    show_minimap: bool,
+    // λ distribution panel in the lower left, drawn again after every render
+    histogram: Option<Vec<u32>>,
+    // colorbar panel at the right edge, drawn again whenever the colors change
+    colorbar: Option<Vec<u32>>,
+    // key bindings over the diagram (F1)
+    help: bool,
+    // view changed and the λ grid has to be computed again
+    rerender: bool,
+    // frame buffer changed and has to be shown again with the overlays on top
+    dirty: bool,
+    // pan in progress, where the button went down and how far the mouse moved since
+    drag: Option<((usize, usize), (i64, i64))>,
+    cycling: bool,
+    phase: f64,
+    // size the window was changed to and when, the diagram is rendered again once it settled
+    resized: Option<((usize, usize), Instant)>,
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
+    watch: Option<ConfigWatch>,
+    remote: Option<Remote>,
+    // with a replay, every state is also written as a numbered PNG into this directory
+    frames: Option<PathBuf>,
+    // a replayed action was applied, write a frame once it is rendered
+    frame_pending: bool,
+    frame_count: usize,
+    // file names of S saves, see template
+    output_template: String,
+    // known λ values and the file they are saved to when the window closes
+    cache: Option<(LambdaCache, PathBuf)>,
+    // depth factor for refining the border after each render, and the refinement running
+    refine: Option<u32>,
+    refinement: Option<Refinement>,
+    // quality of adaptive rendering, a shallow pass first and the border deeper afterwards
+    adaptive_boundary: Option<u32>,
+    // render by quadtree subdivision instead of row by row
+    quadtree: bool,
+    // for scripts: how long the finished diagram is shown before the window closes by itself
+    close_after: Option<Duration>,
+    // when the current view was finished, refinement included
+    finished: Option<Instant>,
+    // the λ grid as landscape instead of the flat diagram, built again after every render
+    terrain: Option<Terrain>,
+    camera: Camera,
+    // left button drag turning the camera, the last mouse position
+    orbit: Option<(usize, usize)>,
+    sonifier: Option<Sonifier>,
+    sequencer: Option<Sequencer>,
+    // --gamepad, and the preset its d-pad went to last
+    gamepad: Option<Gamepad>,
+    preset: Option<usize>,
+    // vertices of the scanned path in (a, b) while L is on, and the plot of the last scan
+    scan: Option<Vec<(f64, f64)>>,
+    scan_plot: Option<Vec<u32>>,
+    bookmarks: Vec<Bookmark>,
+    // the bookmark N goes to
+    next_bookmark: usize,
+    // workspace file written when the window closes, with the custom palettes it defines
+    workspace: Option<(PathBuf, BTreeMap<String, PaletteDef>)>,
+    // f64 steps between neighbouring pixels of the view, see Region::resolution
+    resolution: f64,
+    // journal directory of --autosave-dir and when the view last changed, None once that state is saved
+    autosave: Option<(PathBuf, Option<Instant>)>,
+    // fraction of the window size views are computed at and the filter showing them, Enter computes the
+    // current one at full size until the view changes
+    render_scale: f64,
+    upscale: Upscale,
+    full_resolution: bool,
+}
+
+impl Explorer {
+    pub fn new(
+        (width, height): (usize, usize),
+        region: Region,
+        kernel: Kernel,
+        palette: Palette,
+    ) -> Self {
+        let minimap = Minimap::new(&region, &kernel, palette);
+        Explorer {
+            frame: Frame::new(width, height),
+            region,
+            kernel,
+            palette,
+            minimap,
+            show_minimap: true,
+            histogram: None,
+            colorbar: None,
+            help: false,
+            rerender: true,
+            dirty: true,
+            drag: None,
+            cycling: false,
+            phase: 0.0,
+            resized: None,
+            recorder: None,
+            replay: None,
+            watch: None,
+            remote: None,
+            frames: None,
+            frame_pending: false,
+            frame_count: 0,
+            output_template: template::DEFAULT_TEMPLATE.to_string(),
+            cache: None,
+            refine: None,
+            refinement: None,
+            adaptive_boundary: None,
+            quadtree: false,
+            close_after: None,
+            finished: None,
+            terrain: None,
+            camera: Camera::default(),
+            orbit: None,
+            sonifier: None,
+            sequencer: None,
+            gamepad: None,
+            preset: None,
+            scan: None,
+            scan_plot: None,
+            bookmarks: Vec::new(),
+            next_bookmark: 0,
+            workspace: None,
+            resolution: f64::INFINITY,
+            autosave: None,
+            render_scale: 1.0,
+            upscale: Upscale::Nearest,
+            full_resolution: false,
+        }
+    }
+
+    pub fn record(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+
+    pub fn replay(&mut self, replay: Replay, frames: Option<PathBuf>) {
+        self.replay = Some(replay);
+        self.frames = frames;
+        self.frame_pending = self.frames.is_some();
+    }
+
+    pub fn output_template(&mut self, template: String) {
+        self.output_template = template;
+    }
+
+    pub fn watch(&mut self, watch: ConfigWatch) {
+        self.watch = Some(watch);
+    }
+
+    pub fn remote(&mut self, remote: Remote) {
+        self.remote = Some(remote);
+    }
+
+    pub fn refine(&mut self, factor: u32) {
+        self.refine = Some(factor);
+    }
+
+    pub fn adaptive_boundary(&mut self, quality: u32) {
+        self.adaptive_boundary = Some(quality);
+    }
+
+    pub fn quadtree(&mut self) {
+        self.quadtree = true;
+    }
+
+    pub fn clahe(&mut self, clahe: Clahe) {
+        self.frame.clahe = Some(clahe);
+    }
+
+    pub fn transfer(&mut self, transfer: Transfer) {
+        self.frame.transfer = Some(transfer);
+    }
+
+    pub fn chaos_gradient(&mut self) {
+        self.frame.chaos_gradient = true;
+    }
+
+    pub fn show_histogram(&mut self) {
+        self.histogram = Some(Vec::new());
+    }
+
+    pub fn show_colorbar(&mut self) {
+        self.colorbar = Some(Vec::new());
+    }
+
+    pub fn show_terrain(&mut self) {
+        let frame = &self.frame;
+        let terrain = Terrain::new(&frame.lambdas, &frame.buffer, frame.width, frame.height);
+        self.terrain = Some(terrain);
+        self.dirty = true;
+    }
+
+    pub fn sonify(&mut self, sonifier: Sonifier) {
+        self.sonifier = Some(sonifier);
+    }
+
+    pub fn midi(&mut self, sequencer: Sequencer) {
+        self.sequencer = Some(sequencer);
+    }
+
+    pub fn autosave(&mut self, dir: PathBuf) {
+        self.autosave = Some((dir, Some(Instant::now())));
+    }
+
+    pub fn render_scale(&mut self, scale: f64, upscale: Upscale) {
+        self.render_scale = scale;
+        self.upscale = upscale;
+    }
+
+    pub fn gamepad(&mut self, gamepad: Gamepad) {
+        self.gamepad = Some(gamepad);
+    }
+
+    pub fn workspace(&mut self, path: PathBuf, palettes: BTreeMap<String, PaletteDef>) {
+        self.workspace = Some((path, palettes));
+    }
+
+    // panels and bookmarks of a saved workspace and its λ grid if it still fits, its view is already set
+    pub fn restore(&mut self, workspace: &Workspace, path: &Path) {
+        self.show_minimap = workspace.minimap;
+        self.frame.equalize = workspace.equalize;
+        self.bookmarks = workspace.bookmarks.clone();
+        if workspace.histogram {
+            self.show_histogram();
+        }
+        if workspace.colorbar {
+            self.show_colorbar();
+        }
+        if let Some(lambdas) = workspace.grid(path, self.frame.size(), &self.kernel) {
+            self.frame.lambdas = lambdas;
+            self.frame.colorize_rows(0..self.frame.height, self.palette);
+            self.rerender = false;
+            self.finished = Some(Instant::now());
+            print_stats(&self.frame);
+            self.update_histogram();
+            self.update_colorbar();
+        }
+        if workspace.terrain {
+            self.show_terrain();
+        }
+    }
+
+    pub fn close_after(&mut self, after: Duration) {
+        self.close_after = Some(after);
+    }
+
+    pub fn cache(&mut self, cache: LambdaCache, path: PathBuf) {
+        self.cache = Some((cache, path));
+    }
+
+    // until the window is closed
+    pub fn run(&mut self, display: &mut dyn Display) -> Result<(), Error> {
+        let mut display = Resampled::new(display, self.upscale);
+        while display.is_open() && !self.time_to_close() {
+            self.step(&mut display)?;
+        }
+        if let Some((path, palettes)) = &self.workspace {
+            self.snapshot(palettes.clone()).save(path, &self.frame)?;
+            println!(
+                "{}",
+                i18n::trf("workspace saved to {0}", &[&path.display()])
+            );
+        }
+        if let Some((cache, path)) = &self.cache {
+            cache.save(path).map_err(|e| Error::io(path, e))?;
+            println!(
+                "{}",
+                i18n::trf(
+                    "λ cache: {0} entries saved to {1}, {2} hits, {3} misses",
+                    &[&cache.len(), &path.display(), &cache.hits, &cache.misses]
+                )
+            );
+        }
+        Ok(())
+    }
+
+    // a replay in progress is always played to its end
+    fn time_to_close(&self) -> bool {
+        match (self.close_after, self.finished) {
+            (Some(after), Some(finished)) => self.replay.is_none() && finished.elapsed() >= after,
+            _ => false,
+        }
+    }
+
+    fn step(&mut self, display: &mut Resampled) -> Result<(), Error> {
+        let factor = if self.full_resolution {
+            1.0
+        } else {
+            self.render_scale
+        };
+        if display.factor != factor {
+            display.factor = factor;
+            self.resized = None;
+            let (width, height) = display.size();
+            if width > 0 && height > 0 {
+                self.frame.resize(width, height);
+                self.rerender = true;
+            }
+        }
+
+        let size = display.size();
+        if size != self.frame.size() && size.0 > 0 && size.1 > 0 {
+            match self.resized {
+                Some((pending, since)) if pending == size => {
+                    if since.elapsed() >= RESIZE_DEBOUNCE {
+                        self.resized = None;
+                        self.frame.resize(size.0, size.1);
+                        self.rerender = true;
+                    }
+                }
+                _ => self.resized = Some((size, Instant::now())),
+            }
+        }
+
+        if let Some(action) = self.replay.as_mut().and_then(Replay::next_due) {
+            self.apply(action)?;
+            self.frame_pending = self.frames.is_some();
+        }
+
+        // a command that does not apply, like a letter not in the alphabet, is reported and skipped
+        for command in self
+            .remote
+            .as_ref()
+            .map(Remote::commands)
+            .unwrap_or_default()
+        {
+            let result = match command {
+                Command::Apply(action) => self.apply(action),
+                Command::Save(path) => self.save(path.as_deref()),
+            };
+            if let Err(e) = result {
+                eprintln!("{}", i18n::trf("error: {0}", &[&e]));
+            }
+        }
+
+        for input in self.gamepad.as_mut().map(Gamepad::poll).unwrap_or_default() {
+            self.steer(input)?;
+        }
+
+        // a broken config while editing is reported, the view stays as it was
+        if let Some(config) = self.watch.as_mut().and_then(ConfigWatch::changed) {
+            match config {
+                Ok(config) => {
+                    if let Err(e) = config.register_palettes() {
+                        eprintln!("{}", i18n::trf("error: {0}", &[&e]));
+                    }
+                    if let Some((_, palettes)) = &mut self.workspace {
+                        palettes.extend(config.palettes.clone());
+                    }
+                    for action in config.actions() {
+                        self.apply(action)?;
+                    }
+                    // edited stops of the palette in use show without switching palettes
+                    if config.palette.is_none() && matches!(self.palette, Palette::Custom(_)) {
+                        self.apply(Action::palette(self.palette))?;
+                    }
+                }
+                Err(e) => eprintln!("{}", i18n::trf("error: {0}", &[&e])),
+            }
+        }
+
+        if self.rerender {
+            self.rerender = false;
+            // the old view is not worth refining anymore
+            self.refinement = None;
+            self.finished = None;
+            self.check_precision();
+            let frame = &mut self.frame;
+            let cache = self.cache.as_mut().map(|(cache, _)| cache);
+            let kernel = match self.adaptive_boundary {
+                Some(_) => render::shallow(&self.kernel),
+                None => self.kernel.clone(),
+            };
+            let rendered = if self.quadtree {
+                let (width, height) = frame.size();
+                render::render_quadtree(&mut frame.lambdas, width, height, &self.region, &kernel);
+                frame.colorize_rows(0..height, self.palette);
+                display.is_open()
+            } else {
+                render_progressive(display, frame, &self.region, &kernel, self.palette, cache)
+            };
+            if rendered {
+                if let Some(quality) = self.adaptive_boundary {
+                    let (width, height) = frame.size();
+                    render::refine_border(
+                        &mut frame.lambdas,
+                        width,
+                        height,
+                        &self.region,
+                        &self.kernel,
+                        quality,
+                    );
+                    frame.colorize_rows(0..height, self.palette);
+                }
+                print_stats(frame);
+                self.update_histogram();
+                self.update_colorbar();
+                self.update_terrain();
+                let frame = &self.frame;
+                if let Some(factor) = self.refine {
+                    self.refinement = Refinement::start(frame, self.region, &self.kernel, factor);
+                }
+                if self.refinement.is_none() {
+                    self.finished = Some(Instant::now());
+                }
+            }
+            self.dirty = true;
+        }
+
+        if let Some(refinement) = &mut self.refinement {
+            if refinement.poll(&mut self.frame, self.palette) && !self.cycling {
+                self.dirty = true;
+            }
+            if refinement.is_done() {
+                let total = refinement.total();
+                println!("{}", i18n::trf("refined {0} border pixels", &[&total]));
+                self.refinement = None;
+                self.finished = Some(Instant::now());
+                self.update_histogram();
+                self.update_colorbar();
+                self.update_terrain();
+            }
+        }
+
+        if self.cycling {
+            self.phase = (self.phase + CYCLE_SPEED) % 1.0;
+            self.frame.colorize_cycled(self.palette, self.phase);
+            self.dirty = true;
+        }
+
+        if let Some(sequencer) = &mut self.sequencer {
+            sequencer.tick();
+        }
+
+        if self.dirty {
+            self.dirty = false;
+            self.present(display);
+        }
+
+        if self.frame_pending {
+            self.frame_pending = false;
+            self.save_frame()?;
+        }
+        if let Some((dir, Some(changed))) = &self.autosave {
+            if self.finished.is_some() && changed.elapsed() >= AUTOSAVE_AFTER {
+                let dir = dir.clone();
+                // like a failed S reported, the journal goes on with the next view
+                if let Err(e) = self.save_snapshot(&dir) {
+                    eprintln!("{}", i18n::trf("error: {0}", &[&e]));
+                    if let Some((_, changed)) = &mut self.autosave {
+                        *changed = None;
+                    }
+                }
+            }
+        }
+        if self.replay.as_ref().is_some_and(Replay::is_done) {
+            self.replay = None;
+            println!("{}", i18n::tr("replay finished"));
+        }
+
+        // while a resize is pending the frame keeps its old size, positions are scaled to it
+        let (shown, size) = (display.size(), self.frame.size());
+        for event in display.poll_events() {
+            self.handle(event.scaled(shown, size))?;
+        }
+        Ok(())
+    }
+
+    fn present(&self, display: &mut dyn Display) {
+        let frame = &self.frame;
+        if let Some(terrain) = &self.terrain {
+            let mut buffer = terrain.render(&self.camera, frame.size());
+            if let Some(panel) = &self.histogram {
+                draw_panel(&mut buffer, frame.width, frame.height, panel);
+            }
+            if self.help {
+                help::draw(&mut buffer, frame.size());
+            }
+            display.present(&buffer, frame.width, frame.height);
+            return;
+        }
+        let (mut buffer, shown_region) = match self.drag {
+            Some((_, (dx, dy))) => (
+                frame.shifted(dx, dy).buffer,
+                self.region.dragged_by(dx, dy, frame.width, frame.height),
+            ),
+            None => (frame.buffer.clone(), self.region),
+        };
+        if self.show_minimap {
+            self.minimap
+                .draw(&mut buffer, frame.width, frame.height, &shown_region);
+        }
+        if let Some(sequencer) = &self.sequencer {
+            sequencer.draw(&mut buffer, frame.width, frame.height);
+        }
+        if let Some(vertices) = &self.scan {
+            let points: Vec<(f64, f64)> = vertices
+                .iter()
+                .map(|&(a, b)| shown_region.world_to_pixel(a, b, frame.width, frame.height))
+                .collect();
+            scan::draw_polyline(&mut buffer, frame.width, &points, SCAN_PATH);
+        }
+        if let Some(plot) = &self.scan_plot {
+            draw_plot(&mut buffer, frame.width, frame.height, plot);
+        }
+        if let Some(panel) = &self.histogram {
+            draw_panel(&mut buffer, frame.width, frame.height, panel);
+        }
+        if let Some(strip) = &self.colorbar {
+            draw_colorbar(&mut buffer, frame.width, frame.height, strip);
+        }
+        if self.resolution < PRECISION_WARNING {
+            let text = i18n::trf("f64 limit: {0} steps per pixel", &[&steps(self.resolution)]);
+            let (text_width, _) = font::text_size(&text, 1);
+            let size = frame.size();
+            let x = frame.width as i64 - (text_width + PANEL_MARGIN) as i64;
+            let y = PANEL_MARGIN as i64;
+            draw_text(&mut buffer, size, (x + 1, y + 1), &text, WARNING_SHADOW, 1);
+            draw_text(&mut buffer, size, (x, y), &text, WARNING, 1);
+        }
+        if self.help {
+            help::draw(&mut buffer, frame.size());
+        }
+        display.present(&buffer, frame.width, frame.height);
+    }
+
+    // warns once when a zoom reaches the precision of f64, where parameters of neighbouring pixels
+    // start to coincide and the image turns blocky
+    fn check_precision(&mut self) {
+        let (width, height) = self.frame.size();
+        let resolution = self.region.resolution(width, height);
+        if resolution < PRECISION_WARNING && self.resolution >= PRECISION_WARNING {
+            let pixel = (self.region.x_max - self.region.x_min).abs() / width.max(1) as f64;
+            let center = (self.region.x_min + self.region.x_max) / 2.0;
+            println!(
+                "{}",
+                i18n::trf(
+                    "precision: a pixel is {0} wide and f64 resolves {1} here, {2} steps per pixel. below 1 zooming further only enlarges blocks",
+                    &[
+                        &format!("{pixel:.3e}"),
+                        &format!("{:.3e}", render::ulp(center.abs())),
+                        &steps(resolution),
+                    ],
+                )
+            );
+        }
+        self.resolution = resolution;
+    }
+
+    // everything the workspace file keeps, the λ grid only once the view is completely computed
+    fn snapshot(&self, palettes: BTreeMap<String, PaletteDef>) -> Workspace {
+        let complete = !self.rerender && !self.frame.lambdas.iter().any(|l| l.is_nan());
+        let bookmark = Bookmark::new(&self.kernel, &self.region);
+        Workspace {
+            sequence: bookmark.sequence,
+            region: bookmark.region,
+            palette: self.palette.name().to_string(),
+            depth: self.kernel.depth,
+            minimap: self.show_minimap,
+            histogram: self.histogram.is_some(),
+            terrain: self.terrain.is_some(),
+            equalize: self.frame.equalize,
+            colorbar: self.colorbar.is_some(),
+            bookmarks: self.bookmarks.clone(),
+            palettes,
+            grid_kernel: complete.then(|| self.kernel.key()),
+        }
+    }
+
+    fn update_colorbar(&mut self) {
+        if self.colorbar.is_some() {
+            let size = (legend::STRIP_WIDTH, legend::STRIP_HEIGHT);
+            self.colorbar = Some(legend::strip(&self.frame, size));
+        }
+    }
+
+    fn update_histogram(&mut self) {
+        if self.histogram.is_some() {
+            let size = (histogram::PANEL_WIDTH, histogram::HEIGHT);
+            self.histogram = Some(histogram::draw(&self.frame.lambdas, self.palette, size));
+            self.dirty = true;
+        }
+    }
+
+    fn update_terrain(&mut self) {
+        if self.terrain.is_some() {
+            self.show_terrain();
+        }
+    }
+
+    // λ and the period along the path into a CSV file, shown as plot
+    fn save_scan(&mut self) -> Result<(), Error> {
+        let Some(vertices) = self.scan.as_ref().filter(|vertices| vertices.len() >= 2) else {
+            return Ok(());
+        };
+        let samples = scan::sample(&self.kernel, vertices, SCAN_SAMPLES);
+        let template = Path::new(&self.output_template).with_extension("csv");
+        let path = template::expand(
+            &template.to_string_lossy(),
+            &self.kernel,
+            &self.region,
+            self.palette,
+        );
+        let file = File::create(&path).map_err(|e| Error::io(&path, e))?;
+        scan::write_csv(&samples, &mut BufWriter::new(file)).map_err(|e| Error::io(&path, e))?;
+        println!(
+            "{}",
+            i18n::trf(
+                "path scan: {0} samples saved to {1}",
+                &[&samples.len(), &path.display()]
+            )
+        );
+        let size = (scan::PANEL_WIDTH, scan::HEIGHT);
+        self.scan_plot = Some(scan::plot(&samples, self.palette, size));
+        self.dirty = true;
+        Ok(())
+    }
+
+    // S, named by the output template unless a path is given
+    fn save(&self, path: Option<&Path>) -> Result<(), Error> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => template::expand(
+                &self.output_template,
+                &self.kernel,
+                &self.region,
+                self.palette,
+            ),
+        };
+        save_view(&path, &self.frame, &self.region, None, None, None)
+    }
+
+    // the view as PNG and its parameters as a pipe job, both named by the time in seconds
+    fn save_snapshot(&mut self, dir: &Path) -> Result<(), Error> {
+        std::fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let path = dir.join(format!("ljapunow_{seconds}.png"));
+        save_view(&path, &self.frame, &self.region, None, None, None)?;
+        let job = Job {
+            sequence: Some(self.kernel.sequence_rule.iter().collect()),
+            region: Some(session::region_to_array(&self.region)),
+            palette: Some(self.palette.name().to_string()),
+            depth: Some(self.kernel.depth),
+            size: Some([self.frame.width, self.frame.height]),
+            out: None,
+        };
+        let path = path.with_extension("json");
+        let line = serde_json::to_string(&job).expect("jobs serialize");
+        std::fs::write(&path, line + "\n").map_err(|e| Error::io(&path, e))?;
+        if let Some((_, changed)) = &mut self.autosave {
+            *changed = None;
+        }
+        Ok(())
+    }
+
+    fn save_frame(&mut self) -> Result<(), Error> {
+        let Some(dir) = &self.frames else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+        let path = dir.join(format!("replay_{:04}.png", self.frame_count));
+        export::save_png(
+            &path,
+            &self.frame.buffer,
+            self.frame.width,
+            self.frame.height,
+        )
+        .map_err(|e| Error::io(&path, e))?;
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    // input to actions, the drag preview is the only thing not recorded
+    fn handle(&mut self, event: Event) -> Result<(), Error> {
+        let (width, height) = self.frame.size();
+        let action = match event {
+            Event::KeyPressed(Key::Char('c')) => Action::Cycling { on: !self.cycling },
+            Event::KeyPressed(Key::Char('p')) => Action::palette(self.palette.next()),
+            Event::KeyPressed(Key::Char(key @ ('.' | ','))) => self.depth_step(key == '.'),
+            // a read-only directory or a full disk must not end the session and lose the workspace
+            Event::KeyPressed(Key::Char('s')) => {
+                if let Err(e) = self.save(None) {
+                    eprintln!("{}", i18n::trf("error: {0}", &[&e]));
+                }
+                return Ok(());
+            }
+            Event::KeyPressed(Key::Char('m')) => Action::Minimap {
+                on: !self.show_minimap,
+            },
+            Event::KeyPressed(Key::Char('h')) => Action::Histogram {
+                on: self.histogram.is_none(),
+            },
+            Event::KeyPressed(Key::Char('k')) => Action::Colorbar {
+                on: self.colorbar.is_none(),
+            },
+            Event::KeyPressed(Key::Char('e')) => Action::Equalize {
+                on: !self.frame.equalize,
+            },
+            Event::KeyPressed(Key::Char('3')) => Action::Terrain {
+                on: self.terrain.is_none(),
+            },
+            Event::KeyPressed(Key::Char('b')) => {
+                self.bookmarks
+                    .push(Bookmark::new(&self.kernel, &self.region));
+                let count = self.bookmarks.len();
+                println!("{}", i18n::trf("bookmark {0} saved", &[&count]));
+                return Ok(());
+            }
+            // the jump itself is recorded as the view it leads to
+            Event::KeyPressed(Key::Char('n')) if !self.bookmarks.is_empty() => {
+                let index = self.next_bookmark % self.bookmarks.len();
+                self.next_bookmark = index + 1;
+                let bookmark = self.bookmarks[index].clone();
+                println!(
+                    "{}",
+                    i18n::trf(
+                        "bookmark {0} of {1}",
+                        &[&(index + 1), &self.bookmarks.len()]
+                    )
+                );
+                // a bookmark that does not apply is reported, the window stays open
+                let jumped = self
+                    .apply(Action::Sequence {
+                        sequence: bookmark.sequence,
+                    })
+                    .and_then(|_| {
+                        self.apply(Action::View {
+                            region: bookmark.region,
+                        })
+                    })
+                    .and_then(|_| {
+                        self.apply(Action::Depth {
+                            depth: bookmark.depth,
+                        })
+                    });
+                if let Err(e) = jumped {
+                    eprintln!("{}", i18n::trf("error: {0}", &[&e]));
+                }
+                return Ok(());
+            }
+            // the scan only reads the diagram and is not recorded
+            Event::KeyPressed(Key::Char('l')) => {
+                self.scan = match self.scan {
+                    Some(_) => None,
+                    None => {
+                        println!(
+                            "{}",
+                            i18n::tr("path scan: click the vertices, Enter samples the path")
+                        );
+                        Some(Vec::new())
+                    }
+                };
+                self.scan_plot = None;
+                self.dirty = true;
+                return Ok(());
+            }
+            Event::KeyPressed(Key::Enter) if self.scan.is_some() => return self.save_scan(),
+            // like the window size not part of a session
+            Event::KeyPressed(Key::Enter) if self.render_scale < 1.0 && !self.full_resolution => {
+                println!("{}", i18n::tr("computing at full resolution"));
+                self.full_resolution = true;
+                return Ok(());
+            }
+            // only a look at the bindings, not recorded
+            Event::KeyPressed(Key::Help) => {
+                self.help = !self.help;
+                self.dirty = true;
+                return Ok(());
+            }
+            // the camera is part of the terrain view only and not recorded
+            Event::MousePressed {
+                x,
+                y,
+                button: MouseButton::Left,
+            } if self.terrain.is_some() => {
+                self.orbit = Some((x, y));
+                return Ok(());
+            }
+            Event::MouseMoved { x, y } if self.orbit.is_some() => {
+                if let Some((last_x, last_y)) = self.orbit.replace((x, y)) {
+                    let camera = &mut self.camera;
+                    camera.yaw += (x as f64 - last_x as f64) * ORBIT_SPEED;
+                    camera.pitch = (camera.pitch + (y as f64 - last_y as f64) * ORBIT_SPEED)
+                        .clamp(MIN_PITCH, MAX_PITCH);
+                    self.dirty = true;
+                }
+                return Ok(());
+            }
+            Event::MouseReleased {
+                button: MouseButton::Left,
+            } => {
+                self.orbit = None;
+                if let Some(sequencer) = &mut self.sequencer {
+                    sequencer.end();
+                    self.dirty = true;
+                }
+                return Ok(());
+            }
+            Event::Scroll { delta, .. } if self.terrain.is_some() => {
+                let distance = self.camera.distance * ZOOM_PER_NOTCH.powf(delta);
+                self.camera.distance = distance.clamp(MIN_DISTANCE, MAX_DISTANCE);
+                self.dirty = true;
+                return Ok(());
+            }
+            // a vertex of the scanned path, the first click after a finished scan starts a new one
+            Event::MousePressed {
+                x,
+                y,
+                button: MouseButton::Left,
+            } if self.scan.is_some() => {
+                let finished = self.scan_plot.take().is_some();
+                if let Some(vertices) = &mut self.scan {
+                    if finished {
+                        vertices.clear();
+                    }
+                    vertices.push(self.region.pixel_to_world(x, y, width, height));
+                }
+                self.dirty = true;
+                return Ok(());
+            }
+            // panning the flat diagram makes no sense under the terrain
+            Event::MousePressed {
+                button: MouseButton::Middle | MouseButton::Right,
+                ..
+            } if self.terrain.is_some() => return Ok(()),
+            Event::MousePressed {
+                x,
+                y,
+                button: MouseButton::Middle | MouseButton::Right,
+            } => {
+                self.drag = Some(((x, y), (0, 0)));
+                return Ok(());
+            }
+            Event::MouseMoved { x, y } => {
+                if let Some(((start_x, start_y), offset)) = &mut self.drag {
+                    *offset = (x as i64 - *start_x as i64, y as i64 - *start_y as i64);
+                    self.dirty = true;
+                    return Ok(());
+                }
+                let (a, b) = self.region.pixel_to_world(x, y, width, height);
+                if let Some(sequencer) = &mut self.sequencer {
+                    self.dirty |= sequencer.extend((x, y), (a, b), &self.kernel);
+                }
+                if let Some(sonifier) = &self.sonifier {
+                    // the landscape has no single (a, b) under the cursor
+                    if self.terrain.is_some() {
+                        sonifier.silence();
+                    } else {
+                        sonifier.play(&self.kernel, a, b);
+                    }
+                }
+                return Ok(());
+            }
+            Event::MouseLeft => {
+                if let Some(sonifier) = &self.sonifier {
+                    sonifier.silence();
+                }
+                return Ok(());
+            }
+            Event::MouseReleased {
+                button: MouseButton::Middle | MouseButton::Right,
+            } => match self.drag.take().filter(|&(_, offset)| offset != (0, 0)) {
+                Some((_, (dx, dy))) => {
+                    // keep the moved image while the new view is computed over it
+                    self.frame = self.frame.shifted(dx, dy);
+                    Action::view(&self.region.dragged_by(dx, dy, width, height))
+                }
+                None => return Ok(()),
+            },
+            Event::MousePressed {
+                x,
+                y,
+                button: MouseButton::Left,
+            } => match self.minimap.hit(x, y, width, height) {
+                Some((a, b)) if self.show_minimap => Action::view(&self.region.centered_at(a, b)),
+                _ => {
+                    if let Some(sequencer) = &mut self.sequencer {
+                        sequencer.begin();
+                        let world = self.region.pixel_to_world(x, y, width, height);
+                        sequencer.extend((x, y), world, &self.kernel);
+                        self.dirty = true;
+                    }
+                    return Ok(());
+                }
+            },
+            Event::Scroll { x, y, delta } => {
+                let (a, b) = self.region.pixel_to_world(x, y, width, height);
+                Action::view(&self.region.zoomed_at(a, b, ZOOM_PER_NOTCH.powf(delta)))
+            }
+            _ => return Ok(()),
+        };
+        self.apply(action)
+    }
+
+    fn depth_step(&self, deeper: bool) -> Action {
+        let depth = if deeper {
+            self.kernel.depth.saturating_add(DEPTH_STEP)
+        } else {
+            self.kernel.depth.saturating_sub(DEPTH_STEP).max(DEPTH_STEP)
+        };
+        Action::Depth { depth }
+    }
+
+    // gamepad input to actions, panning and zooming around the middle of the view
+    fn steer(&mut self, input: Input) -> Result<(), Error> {
+        let (width, height) = self.frame.size();
+        let action = match input {
+            Input::Pan(x, y) => {
+                let (dx, dy) = (-x * width as f64, y * height as f64);
+                Action::view(&self.region.dragged_by(dx as i64, dy as i64, width, height))
+            }
+            Input::Zoom(delta) => {
+                let (a, b) = self
+                    .region
+                    .pixel_to_world(width / 2, height / 2, width, height);
+                Action::view(&self.region.zoomed_at(a, b, ZOOM_PER_NOTCH.powf(delta)))
+            }
+            Input::Palette => Action::palette(self.palette.next()),
+            Input::Deeper => self.depth_step(true),
+            Input::Shallower => self.depth_step(false),
+            Input::Preset(step) => {
+                let count = PRESETS.len() as isize;
+                let index = match self.preset {
+                    Some(index) => (index as isize + step).rem_euclid(count) as usize,
+                    None if step > 0 => 0,
+                    None => PRESETS.len() - 1,
+                };
+                self.preset = Some(index);
+                let preset = &PRESETS[index];
+                println!(
+                    "{}",
+                    i18n::trf("preset {0}: {1}", &[&preset.name, &preset.description])
+                );
+                self.apply(Action::Sequence {
+                    sequence: preset.sequence.to_string(),
+                })?;
+                self.apply(Action::view(&preset.region))?;
+                Action::palette(preset.palette)
+            }
+        };
+        self.apply(action)
+    }
+
+    fn apply(&mut self, action: Action) -> Result<(), Error> {
+        // from the remote, a config or a zoom beyond f64, reported and left out of the session
+        if let Err(e) = action.check(&self.kernel.alphabet) {
+            eprintln!("{}", i18n::trf("error: {0}", &[&e]));
+            return Ok(());
+        }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(&action)?;
+        }
+        // overlays are not part of the snapshots
+        let visible = matches!(
+            action,
+            Action::Sequence { .. }
+                | Action::View { .. }
+                | Action::Palette { .. }
+                | Action::Depth { .. }
+                | Action::Equalize { .. }
+        );
+        if let Some((_, changed)) = self.autosave.as_mut().filter(|_| visible) {
+            *changed = Some(Instant::now());
+        }
+        // a new diagram is explored at --render-scale again
+        if matches!(
+            action,
+            Action::Sequence { .. } | Action::View { .. } | Action::Depth { .. }
+        ) {
+            self.full_resolution = false;
+        }
+
+        match action {
+            Action::Sequence { sequence } => {
+                self.kernel.sequence_rule = self.kernel.alphabet.sequence(&sequence)?;
+                self.minimap = Minimap::new(&self.region, &self.kernel, self.palette);
+                self.rerender = true;
+            }
+            Action::View { region } => {
+                self.region = session::array_to_region(region);
+                self.rerender = true;
+            }
+            Action::Palette { name } => {
+                self.palette = session::parse_palette(&name);
+                println!("{}", i18n::trf("palette: {0}", &[&self.palette.name()]));
+                // while cycling the next tick goes on in the new palette
+                if !self.cycling {
+                    self.frame.colorize_rows(0..self.frame.height, self.palette);
+                }
+                self.minimap.recolor(self.palette);
+                self.update_histogram();
+                self.update_colorbar();
+                self.update_terrain();
+                self.dirty = true;
+            }
+            Action::Depth { depth } => {
+                self.kernel.depth = depth;
+                println!("{}", i18n::trf("iteration depth: {0}", &[&depth]));
+                self.rerender = true;
+            }
+            Action::Cycling { on } => {
+                self.cycling = on;
+                // the palette in place of the shifted colors again
+                if !on {
+                    self.frame.colorize_rows(0..self.frame.height, self.palette);
+                    self.dirty = true;
+                }
+            }
+            Action::Minimap { on } => {
+                self.show_minimap = on;
+                self.dirty = true;
+            }
+            Action::Histogram { on } => {
+                self.histogram = on.then(Vec::new);
+                self.update_histogram();
+                self.dirty = true;
+            }
+            Action::Equalize { on } => {
+                self.frame.equalize = on;
+                self.frame.colorize_rows(0..self.frame.height, self.palette);
+                self.update_colorbar();
+                self.update_terrain();
+                self.dirty = true;
+            }
+            Action::Colorbar { on } => {
+                self.colorbar = on.then(Vec::new);
+                self.update_colorbar();
+                self.dirty = true;
+            }
+            Action::Terrain { on } => {
+                self.terrain = None;
+                self.orbit = None;
+                if on {
+                    self.show_terrain();
+                }
+                self.dirty = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+// steps per pixel for messages, fractions only where they matter
+fn steps(resolution: f64) -> String {
+    if resolution < 10.0 {
+        format!("{resolution:.1}")
+    } else {
+        format!("{resolution:.0}")
+    }
+}
+
+// the histogram panel in the lower left corner, left out if the window is too small for it
+fn draw_panel(buffer: &mut [u32], width: usize, height: usize, panel: &[u32]) {
+    let (panel_width, panel_height) = (histogram::PANEL_WIDTH, histogram::HEIGHT);
+    if width < panel_width + 2 * PANEL_MARGIN || height < panel_height + 2 * PANEL_MARGIN {
+        return;
+    }
+    let top = height - panel_height - PANEL_MARGIN;
+    copy_panel(buffer, width, (PANEL_MARGIN, top), panel, panel_width);
+}
+
+// the colorbar at the right edge, vertically centered above the minimap, left out like the histogram
+fn draw_colorbar(buffer: &mut [u32], width: usize, height: usize, strip: &[u32]) {
+    let (strip_width, strip_height) = (legend::STRIP_WIDTH, legend::STRIP_HEIGHT);
+    if width < strip_width + 2 * PANEL_MARGIN || height < strip_height + 2 * PANEL_MARGIN {
+        return;
+    }
+    let (left, top) = (
+        width - strip_width - PANEL_MARGIN,
+        (height - strip_height) / 2,
+    );
+    copy_panel(buffer, width, (left, top), strip, strip_width);
+}
+
+// the plot of a path scan in the upper left corner, left out like the histogram
+fn draw_plot(buffer: &mut [u32], width: usize, height: usize, plot: &[u32]) {
+    let (plot_width, plot_height) = (scan::PANEL_WIDTH, scan::HEIGHT);
+    if width < plot_width + 2 * PANEL_MARGIN || height < plot_height + 2 * PANEL_MARGIN {
+        return;
+    }
+    copy_panel(
+        buffer,
+        width,
+        (PANEL_MARGIN, PANEL_MARGIN),
+        plot,
+        plot_width,
+    );
+}
+
+fn copy_panel(
+    buffer: &mut [u32],
+    width: usize,
+    (left, top): (usize, usize),
+    panel: &[u32],
+    panel_width: usize,
+) {
+    for (y, row) in panel.chunks_exact(panel_width).enumerate() {
+        let start = (top + y) * width + left;
+        buffer[start..start + panel_width].copy_from_slice(row);
+    }
+}
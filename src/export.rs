@@ -0,0 +1,61 @@
+// writing rendered buffers to image files
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+// top byte of pixels left out for compositing, colors never set it
+pub const TRANSPARENT: u32 = 0xFF00_0000;
+
+// save a 0RGB buffer as 8 bit RGB png, as RGBA if some pixels are marked TRANSPARENT
+pub fn save_png(path: &Path, buffer: &[u32], width: usize, height: usize) -> io::Result<()> {
+    write_png(BufWriter::new(File::create(path)?), buffer, width, height)
+}
+
+// save heights as 16 bit grayscale png, the precision terrain tools expect
+pub fn save_png16_gray(path: &Path, levels: &[u16], width: usize, height: usize) -> io::Result<()> {
+    let _span = tracing::info_span!("encode", width, height).entered();
+    let out = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(out, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Sixteen);
+    // png stores 16 bit samples big endian
+    let data: Vec<u8> = levels
+        .iter()
+        .flat_map(|level| level.to_be_bytes())
+        .collect();
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&data).map_err(io::Error::other)
+}
+
+pub fn write_png(out: impl Write, buffer: &[u32], width: usize, height: usize) -> io::Result<()> {
+    let _span = tracing::info_span!("encode", width, height).entered();
+    let mut encoder = png::Encoder::new(out, width as u32, height as u32);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let alpha = buffer.iter().any(|&pixel| pixel & TRANSPARENT != 0);
+    let data: Vec<u8> = if alpha {
+        encoder.set_color(png::ColorType::Rgba);
+        buffer
+            .iter()
+            .flat_map(|&pixel| {
+                let opacity = if pixel & TRANSPARENT != 0 { 0 } else { 0xFF };
+                [
+                    (pixel >> 16) as u8,
+                    (pixel >> 8) as u8,
+                    pixel as u8,
+                    opacity,
+                ]
+            })
+            .collect()
+    } else {
+        encoder.set_color(png::ColorType::Rgb);
+        buffer
+            .iter()
+            .flat_map(|&pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])
+            .collect()
+    };
+
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&data).map_err(io::Error::other)
+}
@@ -0,0 +1,299 @@
+// Linux framebuffer output, for a headless Raspberry Pi driving a screen without X or Wayland.
+// geometry comes from /sys/class/graphics/fbN, pixels are written straight into the device.
+// the screen shows the device memory while it is written, so frames are double buffered where the driver
+// allows it: the virtual screen is made twice as high, every frame goes into the hidden half and the
+// display is panned over once it is complete. other drivers get only the rows that changed written,
+// each in one go, which keeps the half written part of a frame small.
+
+use crate::display::{Display, Event};
+use crate::error::Error;
+use crate::i18n;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const FRAME_TIME: Duration = Duration::from_millis(16); // ~60 fps like the window
+
+pub struct FbDisplay {
+    device: File,
+    width: usize,
+    height: usize,
+    bits_per_pixel: usize,
+    // bytes per line, may be larger than width * bytes per pixel
+    stride: usize,
+    last_poll: Instant,
+    // the page drawn next when double buffered
+    back: Option<usize>,
+    // what the screen shows when not, for writing changed rows only
+    shown: Vec<u32>,
+    // the screen info found on open, put back when done
+    original: Option<paging::Info>,
+    // the last frame could not be written, a failing device is reported once and not every frame
+    failing: bool,
+}
+
+impl FbDisplay {
+    // e.g. /dev/fb0
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let name = path
+            .file_name()
+            .ok_or_else(|| Error::usage(format!("{} is no framebuffer device", path.display())))?;
+        let sysfs = Path::new("/sys/class/graphics").join(name);
+        let read = |attribute: &str| {
+            let file = sysfs.join(attribute);
+            fs::read_to_string(&file)
+                .map(|value| value.trim().to_string())
+                .map_err(|e| Error::io(&file, e))
+        };
+        let invalid = |attribute: &str| {
+            let file = sysfs.join(attribute);
+            Error::io(
+                &file,
+                io::Error::new(io::ErrorKind::InvalidData, "unexpected content"),
+            )
+        };
+
+        // "1920,1080"
+        let size = read("virtual_size")?;
+        let (width, height) = size
+            .split_once(',')
+            .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+            .ok_or_else(|| invalid("virtual_size"))?;
+        let bits_per_pixel: usize = read("bits_per_pixel")?
+            .parse()
+            .map_err(|_| invalid("bits_per_pixel"))?;
+        if bits_per_pixel != 32 && bits_per_pixel != 16 {
+            return Err(Error::io(
+                path,
+                io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("{bits_per_pixel} bits per pixel, only 32 and 16 are supported"),
+                ),
+            ));
+        }
+        let stride = match read("stride") {
+            Ok(stride) => stride.parse().map_err(|_| invalid("stride"))?,
+            Err(_) => width * bits_per_pixel / 8,
+        };
+
+        let device = OpenOptions::new()
+            .write(true)
+            .open(path)
+            .map_err(|e| Error::io(path, e))?;
+        // the visible part of the virtual screen, the whole of it where the driver tells nothing
+        let (width, height, back, original) = match paging::enable(&device) {
+            Some((visible, true, original)) => (visible.0, visible.1, Some(1), Some(original)),
+            Some((visible, false, original)) => (visible.0, visible.1, None, Some(original)),
+            None => (width, height, None, None),
+        };
+
+        Ok(FbDisplay {
+            device,
+            width,
+            height,
+            bits_per_pixel,
+            stride,
+            last_poll: Instant::now(),
+            back,
+            shown: Vec::new(),
+            original,
+            failing: false,
+        })
+    }
+
+    // a frame that could not be written is left out, the next one is tried again
+    fn skip_frame(&mut self, error: io::Error) {
+        if !self.failing {
+            eprintln!("{}", i18n::trf("error: {0}", &[&error]));
+        }
+        self.failing = true;
+    }
+
+    // rows to the page starting at line top, all of them or the ones that differ from the last frame
+    fn write_lines(
+        &mut self,
+        buffer: &[u32],
+        width: usize,
+        height: usize,
+        top: usize,
+        changed_only: bool,
+    ) -> io::Result<()> {
+        let bytes_per_pixel = self.bits_per_pixel / 8;
+        let mut line = vec![0u8; self.stride];
+        let same_size = self.shown.len() == buffer.len();
+        for y in 0..height.min(self.height) {
+            let row = y * width..(y + 1) * width;
+            if changed_only && same_size && self.shown[row.clone()] == buffer[row] {
+                continue;
+            }
+            let pixels = &buffer[y * width..y * width + width.min(self.width)];
+            for (x, &pixel) in pixels.iter().enumerate() {
+                let target = &mut line[x * bytes_per_pixel..(x + 1) * bytes_per_pixel];
+                if bytes_per_pixel == 4 {
+                    // XRGB8888, little endian like the buffer
+                    target.copy_from_slice(&pixel.to_le_bytes());
+                } else {
+                    target.copy_from_slice(&rgb565(pixel).to_le_bytes());
+                }
+            }
+            self.device
+                .seek(SeekFrom::Start(((top + y) * self.stride) as u64))?;
+            self.device.write_all(&line)?;
+        }
+        Ok(())
+    }
+}
+
+fn rgb565(pixel: u32) -> u16 {
+    let r = (pixel >> 19) & 0x1F;
+    let g = (pixel >> 10) & 0x3F;
+    let b = (pixel >> 3) & 0x1F;
+    ((r << 11) | (g << 5) | b) as u16
+}
+
+// page flipping through the fbdev ioctls, struct fb_var_screeninfo as 40 u32 of which the first six are
+// xres, yres, xres_virtual, yres_virtual, xoffset and yoffset
+#[cfg(target_os = "linux")]
+mod paging {
+    use std::fs::File;
+    use std::os::fd::AsRawFd;
+
+    const FBIOGET_VSCREENINFO: u32 = 0x4600;
+    const FBIOPUT_VSCREENINFO: u32 = 0x4601;
+    const FBIOPAN_DISPLAY: u32 = 0x4606;
+    const FBIO_WAITFORVSYNC: u32 = 0x4004_4620;
+    const YRES: usize = 1;
+    const YRES_VIRTUAL: usize = 3;
+    const YOFFSET: usize = 5;
+
+    pub type Info = [u32; 40];
+
+    fn get(device: &File) -> Option<[u32; 40]> {
+        let mut info = [0u32; 40];
+        // SAFETY: the kernel fills exactly the 160 bytes of fb_var_screeninfo
+        let result = unsafe {
+            libc::ioctl(
+                device.as_raw_fd(),
+                FBIOGET_VSCREENINFO as _,
+                info.as_mut_ptr(),
+            )
+        };
+        (result == 0).then_some(info)
+    }
+
+    fn put(device: &File, request: u32, info: &mut [u32; 40]) -> bool {
+        // SAFETY: as above, the kernel reads the struct and may write back what it adjusted
+        unsafe { libc::ioctl(device.as_raw_fd(), request as _, info.as_mut_ptr()) == 0 }
+    }
+
+    // the visible size, whether a second page below it could be had and the info before
+    pub fn enable(device: &File) -> Option<((usize, usize), bool, Info)> {
+        let original = get(device)?;
+        let mut info = original;
+        let visible = (info[0] as usize, info[YRES] as usize);
+        if info[YRES_VIRTUAL] < 2 * info[YRES] {
+            info[YRES_VIRTUAL] = 2 * info[YRES];
+            put(device, FBIOPUT_VSCREENINFO, &mut info);
+        }
+        let info = get(device)?;
+        Some((visible, info[YRES_VIRTUAL] >= 2 * info[YRES], original))
+    }
+
+    // the virtual screen as it was, panned back to the top
+    pub fn restore(device: &File, original: &Info) {
+        let mut info = *original;
+        info[YOFFSET] = 0;
+        put(device, FBIOPUT_VSCREENINFO, &mut info);
+    }
+
+    // pan to the page starting at line top, at the next vertical blank where the driver can wait for it.
+    // false if the driver does not pan
+    pub fn show(device: &File, top: usize) -> bool {
+        let Some(mut info) = get(device) else {
+            return false;
+        };
+        info[YOFFSET] = top as u32;
+        if !put(device, FBIOPAN_DISPLAY, &mut info) {
+            return false;
+        }
+        let screen = 0u32;
+        // SAFETY: FBIO_WAITFORVSYNC reads one u32, the screen number. drivers without it fail harmlessly
+        unsafe {
+            libc::ioctl(device.as_raw_fd(), FBIO_WAITFORVSYNC as _, &screen);
+        }
+        true
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod paging {
+    use std::fs::File;
+
+    pub type Info = ();
+
+    pub fn enable(_device: &File) -> Option<((usize, usize), bool, Info)> {
+        None
+    }
+
+    pub fn restore(_device: &File, _original: &Info) {}
+
+    pub fn show(_device: &File, _top: usize) -> bool {
+        false
+    }
+}
+
+impl Display for FbDisplay {
+    // a kiosk runs until it is killed
+    fn is_open(&self) -> bool {
+        true
+    }
+
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) {
+        let _span = tracing::info_span!("present", width, height).entered();
+        if let Some(page) = self.back {
+            if let Err(e) = self.write_lines(buffer, width, height, page * self.height, false) {
+                return self.skip_frame(e);
+            }
+            self.failing = false;
+            if paging::show(&self.device, page * self.height) {
+                self.back = Some(1 - page);
+                return;
+            }
+            // a driver that takes the taller screen but does not pan, the first page it is
+            self.back = None;
+        }
+        let written = self.write_lines(buffer, width, height, 0, true);
+        // nothing is known to be shown after a failure, the next frame writes every row
+        self.shown.clear();
+        if let Err(e) = written {
+            return self.skip_frame(e);
+        }
+        self.failing = false;
+        self.shown.extend_from_slice(buffer);
+    }
+
+    // no input, just keep the frame rate of the window so loops do not spin
+    fn poll_events(&mut self) -> Vec<Event> {
+        let elapsed = self.last_poll.elapsed();
+        if elapsed < FRAME_TIME {
+            thread::sleep(FRAME_TIME - elapsed);
+        }
+        self.last_poll = Instant::now();
+        Vec::new()
+    }
+}
+
+impl Drop for FbDisplay {
+    // the console or whatever comes next finds the screen as it was, not panned to the second page
+    fn drop(&mut self) {
+        if let Some(original) = &self.original {
+            paging::restore(&self.device, original);
+        }
+    }
+}
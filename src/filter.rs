@@ -0,0 +1,97 @@
+// image space post processing of colored diagrams for print, applied in the given order.
+// both filters add the difference to a gaussian blurred copy: a small radius sharpens edges (unsharp
+// mask), a large one lifts local contrast between neighbouring regions.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    Unsharp { radius: f64, amount: f64 },
+    LocalContrast { radius: f64, amount: f64 },
+}
+
+// box blurs repeated this often approximate a gaussian closely enough
+const BOX_PASSES: usize = 3;
+
+impl Filter {
+    // NAME[:RADIUS[:AMOUNT]], e.g. unsharp, unsharp:1.5 or local-contrast:40:0.3
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut parts = spec.split(':');
+        let name = parts.next()?.trim();
+        let mut number = |default: f64| match parts.next() {
+            Some(value) => value.trim().parse().ok().filter(|&v: &f64| v > 0.0),
+            None => Some(default),
+        };
+        let filter = match name {
+            "unsharp" => Filter::Unsharp {
+                radius: number(1.0)?,
+                amount: number(0.6)?,
+            },
+            "local-contrast" => Filter::LocalContrast {
+                radius: number(30.0)?,
+                amount: number(0.3)?,
+            },
+            _ => return None,
+        };
+        parts.next().is_none().then_some(filter)
+    }
+
+    // on a 0RGB buffer
+    pub fn apply(&self, buffer: &mut [u32], width: usize, height: usize) {
+        let (Filter::Unsharp { radius, amount } | Filter::LocalContrast { radius, amount }) = *self;
+        for shift in [16, 8, 0] {
+            let channel: Vec<f64> = buffer
+                .iter()
+                .map(|&pixel| ((pixel >> shift) & 0xFF) as f64)
+                .collect();
+            let blurred = gaussian_blur(&channel, width, height, radius);
+            for ((pixel, &value), &blur) in buffer.iter_mut().zip(&channel).zip(&blurred) {
+                let sharpened = (value + amount * (value - blur)).round().clamp(0.0, 255.0) as u32;
+                *pixel = (*pixel & !(0xFF << shift)) | (sharpened << shift);
+            }
+        }
+    }
+}
+
+pub fn apply_all(filters: &[Filter], buffer: &mut [u32], width: usize, height: usize) {
+    for filter in filters {
+        filter.apply(buffer, width, height);
+    }
+}
+
+// gaussian of standard deviation sigma from repeated box blurs, rows first then columns
+fn gaussian_blur(values: &[f64], width: usize, height: usize, sigma: f64) -> Vec<f64> {
+    // box width w with BOX_PASSES passes has the variance BOX_PASSES·(w² - 1)/12
+    let half = ((12.0 * sigma * sigma / BOX_PASSES as f64 + 1.0).sqrt() / 2.0).round() as usize;
+    let mut blurred = values.to_vec();
+    for _ in 0..BOX_PASSES {
+        for row in blurred.chunks_exact_mut(width) {
+            box_blur(row, half);
+        }
+        let mut column = vec![0.0; height];
+        for x in 0..width {
+            for (y, value) in column.iter_mut().enumerate() {
+                *value = blurred[y * width + x];
+            }
+            box_blur(&mut column, half);
+            for (y, &value) in column.iter().enumerate() {
+                blurred[y * width + x] = value;
+            }
+        }
+    }
+    blurred
+}
+
+// running mean over 2·half + 1 values, the edge values repeated beyond the ends
+fn box_blur(values: &mut [f64], half: usize) {
+    let len = values.len();
+    if half == 0 || len == 0 {
+        return;
+    }
+    let source = values.to_vec();
+    let at = |i: i64| source[i.clamp(0, len as i64 - 1) as usize];
+    let span = (2 * half + 1) as f64;
+    let mut sum: f64 = (-(half as i64)..=half as i64).map(at).sum();
+    for (i, value) in values.iter_mut().enumerate() {
+        *value = sum / span;
+        sum += at(i as i64 + half as i64 + 1) - at(i as i64 - half as i64);
+    }
+}
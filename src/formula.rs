@@ -0,0 +1,290 @@
+// formulas of a and b for letters of the sequence (--letter C="(a+b)/2"): numbers, a, b, + - * / ^
+// (· for multiplication too), parentheses and the functions sqrt, exp, ln, sin, cos and abs.
+// parsed once on the command line and evaluated once per pixel and letter, outside the iteration.
+
+use crate::cache::StableHasher;
+use crate::i18n::{tr, trf};
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Formula {
+    Number(f64),
+    A,
+    B,
+    Negate(Box<Formula>),
+    Binary(Operator, Box<Formula>, Box<Formula>),
+    Call(Function, Box<Formula>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Sqrt,
+    Exp,
+    Ln,
+    Sin,
+    Cos,
+    Abs,
+}
+
+const FUNCTIONS: [(&str, Function); 6] = [
+    ("sqrt", Function::Sqrt),
+    ("exp", Function::Exp),
+    ("ln", Function::Ln),
+    ("sin", Function::Sin),
+    ("cos", Function::Cos),
+    ("abs", Function::Abs),
+];
+
+impl Formula {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            chars: text.chars().peekable(),
+        };
+        let formula = parser.sum()?;
+        match parser.next_token() {
+            None => Ok(formula),
+            Some(c) => Err(trf("unexpected '{0}' in formula", &[&c])),
+        }
+    }
+
+    pub fn evaluate(&self, a: f64, b: f64) -> f64 {
+        match self {
+            Formula::Number(value) => *value,
+            Formula::A => a,
+            Formula::B => b,
+            Formula::Negate(inner) => -inner.evaluate(a, b),
+            Formula::Binary(operator, left, right) => {
+                let (left, right) = (left.evaluate(a, b), right.evaluate(a, b));
+                match operator {
+                    Operator::Add => left + right,
+                    Operator::Subtract => left - right,
+                    Operator::Multiply => left * right,
+                    Operator::Divide => left / right,
+                    Operator::Power => left.powf(right),
+                }
+            }
+            Formula::Call(function, argument) => {
+                let x = argument.evaluate(a, b);
+                match function {
+                    Function::Sqrt => x.sqrt(),
+                    Function::Exp => x.exp(),
+                    Function::Ln => x.ln(),
+                    Function::Sin => x.sin(),
+                    Function::Cos => x.cos(),
+                    Function::Abs => x.abs(),
+                }
+            }
+        }
+    }
+
+    // whether the value changes along a (with variable Formula::A) or b
+    pub fn uses(&self, variable: &Formula) -> bool {
+        match self {
+            Formula::Number(_) => false,
+            Formula::A | Formula::B => self == variable,
+            Formula::Negate(inner) | Formula::Call(_, inner) => inner.uses(variable),
+            Formula::Binary(_, left, right) => left.uses(variable) || right.uses(variable),
+        }
+    }
+
+    // the tree in prefix order, for Kernel::key
+    pub fn hash<'a>(&self, hash: &'a mut StableHasher) -> &'a mut StableHasher {
+        match self {
+            Formula::Number(value) => hash.u64(0).f64(*value),
+            Formula::A => hash.u64(1),
+            Formula::B => hash.u64(2),
+            Formula::Negate(inner) => inner.hash(hash.u64(3)),
+            Formula::Binary(operator, left, right) => {
+                right.hash(left.hash(hash.u64(4).u64(*operator as u64)))
+            }
+            Formula::Call(function, argument) => argument.hash(hash.u64(5).u64(*function as u64)),
+        }
+    }
+}
+
+// recursive descent, sum > product > unary minus > power > atom. ^ binds to the right
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+
+    fn next_token(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.chars.next();
+        c
+    }
+
+    fn sum(&mut self) -> Result<Formula, String> {
+        let mut formula = self.product()?;
+        while let Some(operator) = match self.peek() {
+            Some('+') => Some(Operator::Add),
+            Some('-') => Some(Operator::Subtract),
+            _ => None,
+        } {
+            self.chars.next();
+            formula = Formula::Binary(operator, Box::new(formula), Box::new(self.product()?));
+        }
+        Ok(formula)
+    }
+
+    fn product(&mut self) -> Result<Formula, String> {
+        let mut formula = self.unary()?;
+        while let Some(operator) = match self.peek() {
+            Some('*' | '·') => Some(Operator::Multiply),
+            Some('/') => Some(Operator::Divide),
+            _ => None,
+        } {
+            self.chars.next();
+            formula = Formula::Binary(operator, Box::new(formula), Box::new(self.unary()?));
+        }
+        Ok(formula)
+    }
+
+    fn unary(&mut self) -> Result<Formula, String> {
+        if self.peek() == Some('-') {
+            self.chars.next();
+            return Ok(Formula::Negate(Box::new(self.unary()?)));
+        }
+        let base = self.atom()?;
+        if self.peek() == Some('^') {
+            self.chars.next();
+            let exponent = self.unary()?;
+            return Ok(Formula::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Formula, String> {
+        match self.peek() {
+            Some('(') => {
+                self.chars.next();
+                let formula = self.sum()?;
+                match self.next_token() {
+                    Some(')') => Ok(formula),
+                    _ => Err(tr("missing ')' in formula").into()),
+                }
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let mut number = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    number.push(c);
+                    // exponent of 1e-3
+                    if let Some(e) = self.chars.next_if(|c| *c == 'e' || *c == 'E') {
+                        number.push(e);
+                        if let Some(sign) = self.chars.next_if(|c| *c == '-' || *c == '+') {
+                            number.push(sign);
+                        }
+                    }
+                }
+                number
+                    .parse()
+                    .map(Formula::Number)
+                    .map_err(|_| trf("'{0}' is not a number", &[&number]))
+            }
+            Some(c) if c.is_alphabetic() => {
+                let mut name = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_alphanumeric()) {
+                    name.push(c);
+                }
+                match name.as_str() {
+                    "a" => Ok(Formula::A),
+                    "b" => Ok(Formula::B),
+                    _ => {
+                        let (_, function) = FUNCTIONS
+                            .iter()
+                            .find(|(known, _)| *known == name)
+                            .ok_or_else(|| trf("unknown name '{0}' in formula", &[&name]))?;
+                        if self.peek() != Some('(') {
+                            return Err(trf("{0} needs an argument in parentheses", &[&name]));
+                        }
+                        Ok(Formula::Call(*function, Box::new(self.atom()?)))
+                    }
+                }
+            }
+            Some(c) => Err(trf("unexpected '{0}' in formula", &[&c])),
+            None => Err(tr("formula ends too early").into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(text: &str) -> f64 {
+        Formula::parse(text).unwrap().evaluate(4.0, 3.0)
+    }
+
+    #[test]
+    fn products_bind_tighter_than_sums() {
+        assert_eq!(value("1+2*3"), 7.0);
+        assert_eq!(value("2*3+1"), 7.0);
+        assert_eq!(value("(1+2)*3"), 9.0);
+        assert_eq!(value("1+6/2"), 4.0);
+    }
+
+    #[test]
+    fn sums_and_products_associate_to_the_left() {
+        assert_eq!(value("8-3-2"), 3.0);
+        assert_eq!(value("8/4/2"), 1.0);
+        assert_eq!(value("8/4*2"), 4.0);
+    }
+
+    #[test]
+    fn powers_associate_to_the_right_below_unary_minus() {
+        assert_eq!(value("2^3^2"), 512.0);
+        assert_eq!(value("-2^2"), -4.0);
+        assert_eq!(value("2^-1"), 0.5);
+        assert_eq!(value("2*3^2"), 18.0);
+    }
+
+    #[test]
+    fn variables_functions_and_numbers() {
+        assert_eq!(value("sqrt(a)*b"), 6.0);
+        assert_eq!(value("2·a"), 8.0);
+        assert_eq!(value(" ( a + b ) / 2 "), 3.5);
+        assert_eq!(value("abs(b-a)"), 1.0);
+        assert_eq!(value("1e-3"), 0.001);
+        assert_eq!(value("2.5E+1"), 25.0);
+    }
+
+    #[test]
+    fn uses_tells_the_variables() {
+        let formula = Formula::parse("(a+b)/2").unwrap();
+        assert!(formula.uses(&Formula::A) && formula.uses(&Formula::B));
+        let formula = Formula::parse("-cos(a)*2").unwrap();
+        assert!(formula.uses(&Formula::A) && !formula.uses(&Formula::B));
+    }
+
+    #[test]
+    fn errors_name_the_problem() {
+        let error = |text| Formula::parse(text).unwrap_err();
+        assert_eq!(error("1+"), "formula ends too early");
+        assert_eq!(error(""), "formula ends too early");
+        assert_eq!(error("(1+2"), "missing ')' in formula");
+        assert_eq!(error("1 2"), "unexpected '2' in formula");
+        assert_eq!(error("1+)"), "unexpected ')' in formula");
+        assert_eq!(error("c*2"), "unknown name 'c' in formula");
+        assert_eq!(error("sqrt 2"), "sqrt needs an argument in parentheses");
+        assert_eq!(error("1..2"), "'1..2' is not a number");
+    }
+}
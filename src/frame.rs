@@ -0,0 +1,195 @@
+// pixel buffer and λ grid of one diagram, sized like the window it is shown in
+
+use crate::analysis;
+use crate::color::Palette;
+use crate::contrast::{Clahe, Transfer};
+use std::ops::Range;
+
+// entries of the ring the stable colors are rotated on while cycling
+const CYCLE_COLORS: usize = 512;
+
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub buffer: Vec<u32>,
+    // raw exponents, NaN where not computed yet
+    pub lambdas: Vec<f64>,
+    // equalize λ before coloring, the raw values stay untouched
+    pub clahe: Option<Clahe>,
+    // equalize over the whole frame instead, E in the window
+    pub equalize: bool,
+    // tone mapping after the equalization, right before the palette
+    pub transfer: Option<Transfer>,
+    // chaotic pixels shaded by the local gradient of λ instead of the flat palette color
+    pub chaos_gradient: bool,
+    // palette and range the buffer was last colored in by a scaled palette
+    scaled: Option<(Palette, (f64, f64))>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Frame {
+            width,
+            height,
+            buffer: vec![0u32; width * height],
+            lambdas: vec![f64::NAN; width * height],
+            clahe: None,
+            equalize: false,
+            transfer: None,
+            chaos_gradient: false,
+            scaled: None,
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    // reallocate for a new size, everything has to be computed again
+    pub fn resize(&mut self, width: usize, height: usize) {
+        let (clahe, equalize, transfer) = (self.clahe, self.equalize, self.transfer);
+        let chaos_gradient = self.chaos_gradient;
+        *self = Frame::new(width, height);
+        self.clahe = clahe;
+        self.equalize = equalize;
+        self.transfer = transfer;
+        self.chaos_gradient = chaos_gradient;
+    }
+
+    // copy with the content moved by (dx, dy) pixels, uncovered pixels are black and not computed
+    pub fn shifted(&self, dx: i64, dy: i64) -> Frame {
+        let mut shifted = Frame::new(self.width, self.height);
+        shifted.clahe = self.clahe;
+        shifted.equalize = self.equalize;
+        shifted.transfer = self.transfer;
+        shifted.chaos_gradient = self.chaos_gradient;
+        for y in 0..self.height {
+            let source_y = y as i64 - dy;
+            if !(0..self.height as i64).contains(&source_y) {
+                continue;
+            }
+            for x in 0..self.width {
+                let source_x = x as i64 - dx;
+                if (0..self.width as i64).contains(&source_x) {
+                    let source = source_y as usize * self.width + source_x as usize;
+                    shifted.buffer[y * self.width + x] = self.buffer[source];
+                    shifted.lambdas[y * self.width + x] = self.lambdas[source];
+                }
+            }
+        }
+        shifted
+    }
+
+    pub fn colorize_rows(&mut self, rows: Range<usize>, palette: Palette) {
+        let _span = tracing::info_span!("colorize", rows = ?rows).entered();
+        let transfer = |lambda: f64| match self.transfer {
+            Some(transfer) => transfer.apply(lambda),
+            None => lambda,
+        };
+        if self.equalize || self.clahe.is_some() {
+            // tiles reach across rows, everything is colored again
+            let equalized = self.palette_values();
+            let range = analysis::lambda_range(&equalized);
+            for (pixel, &lambda) in self.buffer.iter_mut().zip(&equalized) {
+                *pixel = palette.colorize_in(lambda, range);
+            }
+            self.scaled = None;
+            self.shade_chaos();
+            return;
+        }
+        let mut pixels = rows.start * self.width..rows.end * self.width;
+        let mut range = (f64::NAN, f64::NAN);
+        // scaled palettes recolor everything computed so far when the range changed with the new rows.
+        // transfer functions are monotonic, the range maps onto the range of the mapped values
+        if palette.is_scaled() {
+            let (min, max) = analysis::lambda_range(&self.lambdas);
+            range = (transfer(min), transfer(max));
+            if self.scaled != Some((palette, range)) {
+                pixels = 0..self.buffer.len();
+                self.scaled = Some((palette, range));
+            }
+        } else {
+            self.scaled = None;
+        }
+        for (pixel, &lambda) in self.buffer[pixels.clone()]
+            .iter_mut()
+            .zip(&self.lambdas[pixels])
+        {
+            *pixel = palette.colorize_in(transfer(lambda), range);
+        }
+        self.shade_chaos();
+    }
+
+    // color cycling: the colors the palette gives the stable pixels, rotated by phase (0..1) on a ring that
+    // runs through them and back again, so it has no seam. chaos and ±∞ keep their colors
+    pub fn colorize_cycled(&mut self, palette: Palette, phase: f64) {
+        let values = self.palette_values();
+        let range = analysis::lambda_range(&values);
+        let lowest = range.0.min(0.0);
+        let ring: Vec<u32> = (0..CYCLE_COLORS)
+            .map(|i| {
+                let t = i as f64 / (CYCLE_COLORS - 1) as f64;
+                palette.colorize_in(lowest * (1.0 - t), range)
+            })
+            .collect();
+        for (pixel, &value) in self.buffer.iter_mut().zip(&values) {
+            *pixel = if value < 0.0 && value.is_finite() && lowest < 0.0 {
+                let t = (value - lowest) / -lowest;
+                let turn = (t / 2.0 + phase).rem_euclid(1.0);
+                let there_and_back = 1.0 - (2.0 * turn - 1.0).abs();
+                ring[(there_and_back * (CYCLE_COLORS - 1) as f64).round() as usize]
+            } else {
+                palette.colorize_in(value, range)
+            };
+        }
+        self.scaled = None;
+        self.shade_chaos();
+    }
+
+    // what the palette colors: λ equalized and tone mapped as set
+    fn palette_values(&self) -> Vec<f64> {
+        let clahe = if self.equalize {
+            Some(Clahe::global(self.width, self.height))
+        } else {
+            self.clahe
+        };
+        let mut values = match clahe {
+            Some(clahe) => clahe.apply(&self.lambdas, self.width, self.height),
+            None => self.lambdas.clone(),
+        };
+        if let Some(transfer) = self.transfer {
+            values
+                .iter_mut()
+                .for_each(|value| *value = transfer.apply(*value));
+        }
+        values
+    }
+
+    // --chaos-gradient: gray from black where λ is flat to white where it changes fast, the median gradient
+    // of the chaotic pixels computed so far in the middle. the gradient reaches across rows, every chaotic
+    // pixel is shaded again
+    fn shade_chaos(&mut self) {
+        if !self.chaos_gradient {
+            return;
+        }
+        let gradient = analysis::gradient(&self.lambdas, self.width, self.height);
+        let chaotic = |i: &usize| self.lambdas[*i] > 0.0 && self.lambdas[*i].is_finite();
+        let mut magnitudes: Vec<f64> = (0..self.lambdas.len())
+            .filter(chaotic)
+            .map(|i| gradient[i])
+            .collect();
+        if magnitudes.is_empty() {
+            return;
+        }
+        let middle = magnitudes.len() / 2;
+        let median = *magnitudes.select_nth_unstable_by(middle, f64::total_cmp).1;
+        for i in (0..self.lambdas.len()).filter(chaotic) {
+            let shade = if median > 0.0 {
+                gradient[i] / (gradient[i] + median)
+            } else {
+                0.0
+            };
+            self.buffer[i] = 0x010101 * (shade * 255.0) as u32;
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// --gamepad: navigation from the couch or at an exhibition without a keyboard. the left stick pans, the
+// right trigger zooms in and the left one out, both at a speed following how far they are pushed. A (south)
+// switches the palette, the d-pad goes through the presets left and right and changes the depth up and down.
+// any connected pad works, all of them steer together. reading pads needs the gamepad feature (gilrs, on
+// Linux the udev development files), without it --gamepad only reports that.
+
+#[cfg(feature = "gamepad")]
+pub use input::Gamepad;
+#[cfg(not(feature = "gamepad"))]
+pub use silent::Gamepad;
+
+// what the pads asked for since the last poll, only built with the gamepad feature
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(not(feature = "gamepad"), allow(dead_code))]
+pub enum Input {
+    // view widths and heights to move per step, y up
+    Pan(f64, f64),
+    // positive zooms in, 1 is one mouse wheel notch per step
+    Zoom(f64),
+    Palette,
+    // the next (1) or previous (-1) preset
+    Preset(isize),
+    Deeper,
+    Shallower,
+}
+
+#[cfg(feature = "gamepad")]
+mod input {
+    use super::Input;
+    use crate::error::Error;
+    use gilrs::{Axis, Button, EventType, Gilrs};
+
+    // stick deflection ignored around the middle, worn sticks never quite return to 0
+    const DEAD_ZONE: f32 = 0.2;
+    // view fraction per step at full deflection
+    const PAN_SPEED: f64 = 0.05;
+    // triggers report a little pressure at rest
+    const TRIGGER_THRESHOLD: f32 = 0.1;
+
+    pub struct Gamepad {
+        gilrs: Gilrs,
+    }
+
+    impl Gamepad {
+        // pads plugged in later are picked up as well
+        pub fn open() -> Result<Self, Error> {
+            let gilrs = Gilrs::new().map_err(|e| Error::Gamepad(e.to_string()))?;
+            Ok(Gamepad { gilrs })
+        }
+
+        pub fn poll(&mut self) -> Vec<Input> {
+            let mut inputs = Vec::new();
+            while let Some(event) = self.gilrs.next_event() {
+                if let EventType::ButtonPressed(button, _) = event.event {
+                    inputs.extend(match button {
+                        Button::South => Some(Input::Palette),
+                        Button::DPadRight => Some(Input::Preset(1)),
+                        Button::DPadLeft => Some(Input::Preset(-1)),
+                        Button::DPadUp => Some(Input::Deeper),
+                        Button::DPadDown => Some(Input::Shallower),
+                        _ => None,
+                    });
+                }
+            }
+            let (mut pan, mut zoom) = ((0.0, 0.0), 0.0);
+            for (_, pad) in self.gilrs.gamepads() {
+                let stick = |axis| {
+                    let value = pad.value(axis);
+                    if value.abs() > DEAD_ZONE {
+                        value as f64 * PAN_SPEED
+                    } else {
+                        0.0
+                    }
+                };
+                pan.0 += stick(Axis::LeftStickX);
+                pan.1 += stick(Axis::LeftStickY);
+                let trigger = |button| {
+                    let value = pad.button_data(button).map_or(0.0, |data| data.value());
+                    if value > TRIGGER_THRESHOLD {
+                        value as f64
+                    } else {
+                        0.0
+                    }
+                };
+                zoom += trigger(Button::RightTrigger2) - trigger(Button::LeftTrigger2);
+            }
+            if pan != (0.0, 0.0) {
+                inputs.push(Input::Pan(pan.0, pan.1));
+            }
+            if zoom != 0.0 {
+                inputs.push(Input::Zoom(zoom));
+            }
+            inputs
+        }
+    }
+}
+
+#[cfg(not(feature = "gamepad"))]
+mod silent {
+    use super::Input;
+    use crate::error::Error;
+    use crate::i18n;
+
+    pub enum Gamepad {}
+
+    impl Gamepad {
+        pub fn open() -> Result<Self, Error> {
+            let message = i18n::tr("this build reads no gamepads, rebuild with --features gamepad");
+            Err(Error::Gamepad(message.into()))
+        }
+
+        pub fn poll(&mut self) -> Vec<Input> {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,355 @@
+// raw λ grids in a file, written through a memory map so gigapixel grids never have to fit into RAM.
+// layout, all little endian:
+//   0  magic "LJAPGRID"
+//   8  version u32, storage u32 (0 f64, 1 f16, 2 u16), reserved in version 1
+//  16  width u64, height u64
+//  32  x_min, x_max, y_min, y_max f64
+//  64  width * height λ as f64, row by row (version 1 and f64 storage)
+// version 2 stores λ compact for huge grids:
+//  64  lambda_min, lambda_max f64, the range of u16 storage
+//  80  width * height λ as f16 or u16, row by row
+// u16 maps lambda_min..lambda_max linearly onto 2..=65534, 0 is NaN, 1 is -∞ and 65535 is +∞.
+// values outside the range are clamped to it.
+
+use crate::render::Region;
+use half::f16;
+use memmap2::{Mmap, MmapMut};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"LJAPGRID";
+const VERSION: u32 = 1;
+const VERSION_COMPACT: u32 = 2;
+pub const HEADER_SIZE: usize = 64;
+const HEADER_SIZE_COMPACT: usize = 80;
+const U16_NAN: u16 = 0;
+const U16_NEG_INFINITY: u16 = 1;
+const U16_MIN: u16 = 2;
+const U16_MAX: u16 = u16::MAX - 1;
+const U16_INFINITY: u16 = u16::MAX;
+
+// how λ is stored, f16 halves and u16 quarters the size of f64
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Storage {
+    #[default]
+    F64,
+    F16,
+    U16,
+}
+
+impl Storage {
+    fn code(self) -> u32 {
+        match self {
+            Storage::F64 => 0,
+            Storage::F16 => 1,
+            Storage::U16 => 2,
+        }
+    }
+
+    fn from_code(code: u32) -> Option<Self> {
+        match code {
+            0 => Some(Storage::F64),
+            1 => Some(Storage::F16),
+            2 => Some(Storage::U16),
+            _ => None,
+        }
+    }
+
+    pub fn bytes(self) -> usize {
+        match self {
+            Storage::F64 => 8,
+            Storage::F16 | Storage::U16 => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Header {
+    pub width: usize,
+    pub height: usize,
+    pub region: Region,
+    pub storage: Storage,
+    // only used by u16 storage
+    pub range: (f64, f64),
+}
+
+impl Header {
+    // f64 grids keep the version 1 layout, so older readers still open them
+    fn version(&self) -> u32 {
+        match self.storage {
+            Storage::F64 => VERSION,
+            _ => VERSION_COMPACT,
+        }
+    }
+
+    fn data_offset(&self) -> usize {
+        match self.version() {
+            VERSION => HEADER_SIZE,
+            _ => HEADER_SIZE_COMPACT,
+        }
+    }
+
+    fn row_bytes(&self) -> usize {
+        self.width * self.storage.bytes()
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = vec![0u8; self.data_offset()];
+        bytes[0..8].copy_from_slice(MAGIC);
+        bytes[8..12].copy_from_slice(&self.version().to_le_bytes());
+        if self.version() == VERSION_COMPACT {
+            bytes[12..16].copy_from_slice(&self.storage.code().to_le_bytes());
+            bytes[64..72].copy_from_slice(&self.range.0.to_le_bytes());
+            bytes[72..80].copy_from_slice(&self.range.1.to_le_bytes());
+        }
+        bytes[16..24].copy_from_slice(&(self.width as u64).to_le_bytes());
+        bytes[24..32].copy_from_slice(&(self.height as u64).to_le_bytes());
+        let Region {
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+        } = self.region;
+        for (i, v) in [x_min, x_max, y_min, y_max].iter().enumerate() {
+            bytes[32 + i * 8..40 + i * 8].copy_from_slice(&v.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+        if bytes.len() < HEADER_SIZE || &bytes[0..8] != MAGIC {
+            return Err(invalid("not a λ grid file"));
+        }
+        let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let u64_at = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        let f64_at = |at: usize| f64::from_le_bytes(bytes[at..at + 8].try_into().unwrap());
+        let (storage, range) = match u32_at(8) {
+            VERSION => (Storage::F64, (f64::NAN, f64::NAN)),
+            VERSION_COMPACT if bytes.len() >= HEADER_SIZE_COMPACT => {
+                let storage = Storage::from_code(u32_at(12))
+                    .ok_or_else(|| invalid("unknown λ grid storage"))?;
+                (storage, (f64_at(64), f64_at(72)))
+            }
+            _ => return Err(invalid("unsupported λ grid file version")),
+        };
+
+        Ok(Header {
+            width: u64_at(16) as usize,
+            height: u64_at(24) as usize,
+            region: Region::new(f64_at(32), f64_at(40), f64_at(48), f64_at(56)),
+            storage,
+            range,
+        })
+    }
+
+    // width and height of a read header may be anything, sizes beyond u64 are no grid file
+    fn file_size(&self) -> io::Result<u64> {
+        (self.width as u64)
+            .checked_mul(self.storage.bytes() as u64)
+            .and_then(|row_bytes| row_bytes.checked_mul(self.height as u64))
+            .and_then(|data| data.checked_add(self.data_offset() as u64))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "λ grid size out of range"))
+    }
+
+    fn encode(&self, lambda: f64, target: &mut [u8]) {
+        match self.storage {
+            Storage::F64 => target.copy_from_slice(&lambda.to_le_bytes()),
+            Storage::F16 => target.copy_from_slice(&f16::from_f64(lambda).to_le_bytes()),
+            Storage::U16 => target.copy_from_slice(&self.quantize(lambda).to_le_bytes()),
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> f64 {
+        match self.storage {
+            Storage::F64 => f64::from_le_bytes(bytes.try_into().unwrap()),
+            Storage::F16 => f16::from_le_bytes(bytes.try_into().unwrap()).to_f64(),
+            Storage::U16 => self.dequantize(u16::from_le_bytes(bytes.try_into().unwrap())),
+        }
+    }
+
+    fn quantize(&self, lambda: f64) -> u16 {
+        let (min, max) = self.range;
+        if lambda.is_nan() {
+            return U16_NAN;
+        }
+        if lambda == f64::NEG_INFINITY {
+            return U16_NEG_INFINITY;
+        }
+        if lambda == f64::INFINITY {
+            return U16_INFINITY;
+        }
+        let t = ((lambda - min) / (max - min)).clamp(0.0, 1.0);
+        if t.is_nan() {
+            return U16_MIN;
+        }
+        U16_MIN + (t * (U16_MAX - U16_MIN) as f64).round() as u16
+    }
+
+    fn dequantize(&self, value: u16) -> f64 {
+        let (min, max) = self.range;
+        match value {
+            U16_NAN => f64::NAN,
+            U16_NEG_INFINITY => f64::NEG_INFINITY,
+            U16_INFINITY => f64::INFINITY,
+            _ => min + (value - U16_MIN) as f64 / (U16_MAX - U16_MIN) as f64 * (max - min),
+        }
+    }
+}
+
+// grid file opened for writing, rows can be written in any order
+pub struct GridWriter {
+    header: Header,
+    map: MmapMut,
+}
+
+impl GridWriter {
+    // create or truncate, the file gets its full size right away (sparse where supported)
+    pub fn create(path: &Path, header: Header) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(header.file_size()?)?;
+
+        // SAFETY: the file was just created by us, nobody else is expected to change it while mapped
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+        map[..header.data_offset()].copy_from_slice(&header.to_bytes());
+        Ok(GridWriter { header, map })
+    }
+
+    pub fn write_row(&mut self, y: usize, row: &[f64]) {
+        let header = self.header;
+        let start = header.data_offset() + y * header.row_bytes();
+        let bytes = &mut self.map[start..start + header.row_bytes()];
+        for (target, &lambda) in bytes.chunks_exact_mut(header.storage.bytes()).zip(row) {
+            header.encode(lambda, target);
+        }
+    }
+
+    // hand written rows to the OS without waiting for the disk
+    pub fn flush_async(&self) -> io::Result<()> {
+        self.map.flush_async()
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        self.map.flush()
+    }
+}
+
+// grid file opened for reading
+pub struct GridReader {
+    pub header: Header,
+    map: Mmap,
+}
+
+impl GridReader {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: read only mapping, a concurrent writer would only garble values, not memory safety of ours
+        let map = unsafe { Mmap::map(&file)? };
+        let header = Header::from_bytes(&map)?;
+        if (map.len() as u64) < header.file_size()? {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "λ grid file is truncated",
+            ));
+        }
+        Ok(GridReader { header, map })
+    }
+
+    // λ as f64 whatever the storage of the file
+    pub fn row(&self, y: usize) -> Vec<f64> {
+        let header = &self.header;
+        let start = header.data_offset() + y * header.row_bytes();
+        self.map[start..start + header.row_bytes()]
+            .chunks_exact(header.storage.bytes())
+            .map(|bytes| header.decode(bytes))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(storage: Storage) -> Header {
+        Header {
+            width: 3,
+            height: 2,
+            region: Region::new(2.0, 4.0, 2.0, 4.0),
+            storage,
+            range: (-2.0, 1.0),
+        }
+    }
+
+    #[test]
+    fn quantize_keeps_the_special_values() {
+        let header = header(Storage::U16);
+        assert_eq!(header.quantize(f64::NAN), U16_NAN);
+        assert_eq!(header.quantize(f64::NEG_INFINITY), U16_NEG_INFINITY);
+        assert_eq!(header.quantize(f64::INFINITY), U16_INFINITY);
+        assert!(header.dequantize(U16_NAN).is_nan());
+        assert_eq!(header.dequantize(U16_NEG_INFINITY), f64::NEG_INFINITY);
+        assert_eq!(header.dequantize(U16_INFINITY), f64::INFINITY);
+    }
+
+    #[test]
+    fn quantize_maps_the_range_and_clamps_outside() {
+        let header = header(Storage::U16);
+        assert_eq!(header.quantize(-2.0), U16_MIN);
+        assert_eq!(header.quantize(1.0), U16_MAX);
+        assert_eq!(header.quantize(-5.0), U16_MIN);
+        assert_eq!(header.quantize(5.0), U16_MAX);
+        assert_eq!(header.dequantize(U16_MIN), -2.0);
+        assert_eq!(header.dequantize(U16_MAX), 1.0);
+    }
+
+    #[test]
+    fn dequantize_is_within_half_a_step() {
+        let header = header(Storage::U16);
+        let step = 3.0 / (U16_MAX - U16_MIN) as f64;
+        for lambda in [-1.999, -1.0, -0.1234, 0.0, 0.5, 0.99] {
+            let back = header.dequantize(header.quantize(lambda));
+            assert!(
+                (back - lambda).abs() <= step / 2.0,
+                "{lambda} came back as {back}"
+            );
+        }
+    }
+
+    #[test]
+    fn quantize_of_an_empty_range_does_not_fail() {
+        let header = Header {
+            range: (0.5, 0.5),
+            ..header(Storage::U16)
+        };
+        assert_eq!(header.quantize(0.5), U16_MIN);
+    }
+
+    #[test]
+    fn headers_read_back() {
+        for storage in [Storage::F64, Storage::F16, Storage::U16] {
+            let header = header(storage);
+            let read = Header::from_bytes(&header.to_bytes()).unwrap();
+            assert_eq!(read.storage, storage);
+            assert_eq!((read.width, read.height), (3, 2));
+            assert_eq!(read.region, header.region);
+        }
+    }
+
+    #[test]
+    fn file_size_covers_header_and_rows() {
+        assert_eq!(header(Storage::F64).file_size().unwrap(), 64 + 6 * 8);
+        assert_eq!(header(Storage::U16).file_size().unwrap(), 80 + 6 * 2);
+        let huge = Header {
+            width: usize::MAX,
+            ..header(Storage::F64)
+        };
+        let error = huge.file_size().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
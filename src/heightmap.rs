@@ -0,0 +1,55 @@
+// heightmap subcommand: the λ field as 16 bit grayscale heightmap and a matching normal map, for
+// rendering the diagram as terrain in Blender or a game engine
+
+use crate::analysis;
+use crate::cli::HeightmapArgs;
+use crate::error::Error;
+use crate::export::{save_png, save_png16_gray};
+use crate::i18n;
+use crate::relief;
+use crate::render::{self, parse_sequence, Kernel};
+
+pub fn run(args: &HeightmapArgs) -> Result<(), Error> {
+    let (width, height) = args.size;
+    let kernel = Kernel::new(parse_sequence(&args.sequence)?);
+    let mut lambdas = vec![f64::NAN; width * height];
+    render::render_rows(
+        &mut lambdas,
+        width,
+        height,
+        &args.region,
+        &kernel,
+        0..height,
+    );
+
+    let range = args
+        .clamp
+        .unwrap_or_else(|| analysis::lambda_range(&lambdas));
+    let heights = relief::heights(&lambdas, range);
+    let levels: Vec<u16> = heights
+        .iter()
+        .map(|&h| (h * u16::MAX as f64).round() as u16)
+        .collect();
+    let path = &args.out;
+    save_png16_gray(path, &levels, width, height).map_err(|e| Error::io(path, e))?;
+    println!("{}", i18n::trf("saved {0}", &[&path.display()]));
+
+    let normals = relief::normals(&heights, width, height, args.scale);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let normal_path = path.with_file_name(format!("{stem}_normal.png"));
+    save_png(&normal_path, &relief::normal_map(&normals), width, height)
+        .map_err(|e| Error::io(&normal_path, e))?;
+    println!("{}", i18n::trf("saved {0}", &[&normal_path.display()]));
+    println!(
+        "{}",
+        i18n::trf(
+            "λ {0}..{1} mapped to heights 0..65535, {2} pixels of relief in the normal map",
+            &[
+                &format!("{:.4}", range.0),
+                &format!("{:.4}", range.1),
+                &args.scale
+            ],
+        )
+    );
+    Ok(())
+}
@@ -0,0 +1,68 @@
+// F1 in the window: the key and mouse bindings listed in a box over the middle of the diagram, so they
+// can be found without the --help text. F1 again hides it.
+
+use crate::font::{self, draw_text, GLYPH_HEIGHT};
+use crate::i18n;
+
+const TEXT: u32 = 0xFFFFFF;
+const KEY: u32 = 0xFFD040;
+const PADDING: usize = 12;
+const LINE: usize = GLYPH_HEIGHT + 5;
+const GAP: usize = 16;
+
+// key and what it does, the descriptions are translated
+const BINDINGS: &[(&str, &str)] = &[
+    ("F1", "this help"),
+    ("wheel", "zoom at the cursor"),
+    ("middle/right drag", "pan"),
+    ("C", "palette cycling"),
+    ("P", "next palette"),
+    (". ,", "raise or lower the iteration depth"),
+    ("M", "minimap, click it to jump there"),
+    ("H", "λ histogram"),
+    ("K", "colorbar"),
+    ("E", "histogram equalization of the colors"),
+    ("3", "terrain view, left drag turns and tilts"),
+    ("L", "path scan, click vertices, Enter writes CSV"),
+    ("B", "bookmark the view"),
+    ("N", "next bookmark"),
+    ("Enter", "full resolution with --render-scale"),
+    ("S", "save the view"),
+    ("ESC", "exit"),
+];
+
+// the box centered in the buffer, the diagram darkened behind it. left out if the window is too small
+pub fn draw(buffer: &mut [u32], (width, height): (usize, usize)) {
+    let lines: Vec<(&str, &str)> = BINDINGS
+        .iter()
+        .map(|&(key, action)| (key, i18n::tr(action)))
+        .collect();
+    let key_width = lines
+        .iter()
+        .map(|(key, _)| font::text_size(key, 1).0)
+        .max()
+        .unwrap_or(0);
+    let action_width = lines
+        .iter()
+        .map(|(_, action)| font::text_size(action, 1).0)
+        .max()
+        .unwrap_or(0);
+    let box_width = key_width + GAP + action_width + 2 * PADDING;
+    let box_height = lines.len() * LINE + 2 * PADDING;
+    if box_width > width || box_height > height {
+        return;
+    }
+    let (left, top) = ((width - box_width) / 2, (height - box_height) / 2);
+    for row in buffer[top * width..(top + box_height) * width].chunks_exact_mut(width) {
+        for pixel in &mut row[left..left + box_width] {
+            *pixel = (*pixel >> 2) & 0x3F3F3F;
+        }
+    }
+    for (i, (key, action)) in lines.iter().enumerate() {
+        let y = (top + PADDING + i * LINE) as i64;
+        let x = (left + PADDING) as i64;
+        draw_text(buffer, (width, height), (x, y), key, KEY, 1);
+        let x = x + (key_width + GAP) as i64;
+        draw_text(buffer, (width, height), (x, y), action, TEXT, 1);
+    }
+}
@@ -0,0 +1,98 @@
+// distribution of λ in a view as a small bar chart, for tuning palettes and ranges: bins over the finite
+// λ range, each bar in the palette color of its λ, λ = 0 marked by a white line and the range written below
+
+use crate::analysis;
+use crate::color::Palette;
+use crate::font::{self, draw_text, GLYPH_HEIGHT};
+use crate::render::map;
+use crate::svg::tick_label;
+
+// size of the panel in the window, exported strips take the width of the image
+pub const PANEL_WIDTH: usize = 360;
+pub const HEIGHT: usize = 72;
+const BINS: usize = 120;
+const BACKGROUND: u32 = 0x808080;
+const ZERO_MARKER: u32 = 0xFFFFFF;
+const TEXT: u32 = 0xFFFFFF;
+const PADDING: usize = 4;
+
+// λ count per bin and the range they cover, None without a finite λ
+pub fn bins(lambdas: &[f64]) -> Option<(Vec<usize>, (f64, f64))> {
+    let (min, max) = analysis::lambda_range(lambdas);
+    if min > max {
+        return None;
+    }
+    let mut counts = vec![0; BINS];
+    for &lambda in lambdas.iter().filter(|l| l.is_finite()) {
+        let bin = map(lambda, min, max.max(min + f64::EPSILON), 0.0, BINS as f64) as usize;
+        counts[bin.min(BINS - 1)] += 1;
+    }
+    Some((counts, (min, max)))
+}
+
+// the chart as 0RGB buffer of the given size. bar heights grow with log(1 + count),
+// otherwise the wide chaotic plateau would flatten everything else
+pub fn draw(lambdas: &[f64], palette: Palette, (width, height): (usize, usize)) -> Vec<u32> {
+    let mut buffer = vec![BACKGROUND; width * height];
+    let Some((counts, (min, max))) = bins(lambdas) else {
+        return buffer;
+    };
+    let label_height = GLYPH_HEIGHT + PADDING;
+    let (left, right) = (PADDING, width.saturating_sub(PADDING));
+    let (top, bottom) = (PADDING, height.saturating_sub(label_height + PADDING));
+    if right <= left || bottom <= top {
+        return buffer;
+    }
+
+    let tallest = counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+    for x in left..right {
+        let bin = (x - left) * BINS / (right - left);
+        let lambda = map(bin as f64 + 0.5, 0.0, BINS as f64, min, max);
+        let bar = ((counts[bin] as f64).ln_1p() / tallest.ln_1p() * (bottom - top) as f64) as usize;
+        let color = palette.colorize_in(lambda, (min, max));
+        for y in bottom - bar..bottom {
+            buffer[y * width + x] = color;
+        }
+    }
+    if min < 0.0 && 0.0 < max {
+        let x = left + map(0.0, min, max, 0.0, (right - left) as f64) as usize;
+        for y in top..bottom {
+            buffer[y * width + x] = ZERO_MARKER;
+        }
+    }
+
+    let size = (width, height);
+    let y = (height - label_height) as i64;
+    let (min_label, max_label) = (tick_label(min, max - min), tick_label(max, max - min));
+    draw_text(&mut buffer, size, (left as i64, y), &min_label, TEXT, 1);
+    let (w, _) = font::text_size(&max_label, 1);
+    draw_text(
+        &mut buffer,
+        size,
+        ((right - w) as i64, y),
+        &max_label,
+        TEXT,
+        1,
+    );
+    let (w, _) = font::text_size("λ", 1);
+    draw_text(
+        &mut buffer,
+        size,
+        (((width - w) / 2) as i64, y),
+        "λ",
+        TEXT,
+        1,
+    );
+    buffer
+}
+
+// the image with a histogram strip of its width appended below
+pub fn append(
+    (buffer, width, height): (Vec<u32>, usize, usize),
+    lambdas: &[f64],
+    palette: Palette,
+) -> (Vec<u32>, usize, usize) {
+    let mut buffer = buffer;
+    buffer.extend(draw(lambdas, palette, (width, HEIGHT)));
+    (buffer, width, height + HEIGHT)
+}
@@ -0,0 +1,984 @@
+// translated messages: the English text is the key, a catalog maps it to another language.
+// anything missing in a catalog stays English, so untranslated messages still read fine.
+// placeholders are numbered {0}, {1}, ... so a translation may reorder them.
+
+use std::env;
+use std::fmt;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Lang {
+    #[default]
+    En,
+    De,
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+// once at startup, before the first message
+pub fn set(lang: Lang) {
+    let _ = LANG.set(lang);
+}
+
+pub fn lang() -> Lang {
+    LANG.get().copied().unwrap_or_default()
+}
+
+// from the locale variables in the order POSIX gives them precedence, e.g. de_DE.UTF-8
+pub fn detect() -> Lang {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .map_or(Lang::En, |locale| {
+            if locale.starts_with("de") {
+                Lang::De
+            } else {
+                Lang::En
+            }
+        })
+}
+
+pub fn tr(text: &str) -> &str {
+    let catalog = match lang() {
+        Lang::En => return text,
+        Lang::De => DE,
+    };
+    catalog
+        .iter()
+        .find(|(key, _)| *key == text)
+        .map_or(text, |(_, translation)| translation)
+}
+
+// translated and with the placeholders filled in
+pub fn trf(text: &str, args: &[&dyn fmt::Display]) -> String {
+    args.iter()
+        .enumerate()
+        .fold(tr(text).to_string(), |message, (i, arg)| {
+            message.replace(&format!("{{{i}}}"), &arg.to_string())
+        })
+}
+
+// German, help texts first, then messages
+const DE: &[(&str, &str)] = &[
+    (
+        "Ljapunow-Markus diagrams of the logistic map forced by a sequence of A and B",
+        "Ljapunow-Markus-Diagramme der logistischen Gleichung, angetrieben von einer Folge aus A und B",
+    ),
+    (
+        "Ljapunow-Markus diagrams of the logistic map forced by a sequence of A and B.\n\nEvery pixel (a, b) iterates x → r·x·(1−x) with r taken from the sequence (A → a, B → b) and is colored by the Ljapunow exponent λ: order (λ < 0) in colors, chaos (λ > 0) black.",
+        "Ljapunow-Markus-Diagramme der logistischen Gleichung, angetrieben von einer Folge aus A und B.\n\nJeder Bildpunkt (a, b) iteriert x → r·x·(1−x) mit r aus der Folge (A → a, B → b) und wird nach dem Ljapunow-Exponenten λ gefärbt: Ordnung (λ < 0) farbig, Chaos (λ > 0) schwarz.",
+    ),
+    (
+        "Forcing sequence, only the letters A and B unless --values defines others [default: from the preset]",
+        "Folge aus den Buchstaben A und B, sofern --values keine anderen festlegt [Standard: aus der Vorlage]",
+    ),
+    (
+        "Letters of the sequence and their values, a and b follow the axes, e.g. A=a,B=b,C=3.74 [default: A=a,B=b]",
+        "Buchstaben der Folge und ihre Werte, a und b folgen den Achsen, z. B. A=a,B=b,C=3.74 [Standard: A=a,B=b]",
+    ),
+    (
+        "Letter whose value runs along the horizontal axis (a), the letter there takes its old value [default: A]",
+        "Buchstabe, dessen Wert entlang der waagerechten Achse (a) läuft, der Buchstabe dort übernimmt seinen alten Wert [Standard: A]",
+    ),
+    (
+        "Letter whose value runs along the vertical axis (b), the letter there takes its old value [default: B]",
+        "Buchstabe, dessen Wert entlang der senkrechten Achse (b) läuft, der Buchstabe dort übernimmt seinen alten Wert [Standard: B]",
+    ),
+    (
+        "--x-axis and --y-axis need different letters, both are {0}",
+        "--x-axis und --y-axis brauchen verschiedene Buchstaben, beide sind {0}",
+    ),
+    ("expected LETTER=VALUE, not '{0}'", "LETTER=WERT erwartet, nicht '{0}'"),
+    ("'{0}' is not a single letter", "'{0}' ist kein einzelner Buchstabe"),
+    (
+        "Letter whose r is a formula of a and b, e.g. C=(a+b)/2 or D=a*b, repeatable",
+        "Buchstabe, dessen r eine Formel aus a und b ist, z. B. C=(a+b)/2 oder D=a*b, wiederholbar",
+    ),
+    ("value of {0}: {1}", "Wert von {0}: {1}"),
+    ("unexpected '{0}' in formula", "unerwartetes '{0}' in der Formel"),
+    ("missing ')' in formula", "fehlende ')' in der Formel"),
+    ("'{0}' is not a number", "'{0}' ist keine Zahl"),
+    ("unknown name '{0}' in formula", "unbekannter Name '{0}' in der Formel"),
+    (
+        "{0} needs an argument in parentheses",
+        "{0} braucht ein Argument in Klammern",
+    ),
+    ("formula ends too early", "die Formel endet zu früh"),
+    (
+        "Let a and b drift while iterating, linear by RATE over all iterations or sine with amplitude RATE, e.g. linear:0.1 or sine:0.05,0",
+        "a und b während der Iteration wandern lassen, linear um RATE über alle Iterationen oder als Sinus mit Amplitude RATE, z. B. linear:0.1 oder sine:0.05,0",
+    ),
+    ("expected SHAPE:RATE, not '{0}'", "FORM:RATE erwartet, nicht '{0}'"),
+    (
+        "unknown drift '{0}', use linear or sine",
+        "unbekannte Drift '{0}', linear oder sine verwenden",
+    ),
+    ("{0} is given twice", "{0} ist doppelt angegeben"),
+    (
+        "invalid sequence '{0}', {1} has no value in --values",
+        "ungültige Folge '{0}', {1} hat keinen Wert in --values",
+    ),
+    (
+        "invalid sequence '{0}', without a letter for {1} the diagram does not change along {1}",
+        "ungültige Folge '{0}', ohne einen Buchstaben für {1} ändert sich das Diagramm entlang {1} nicht",
+    ),
+    (
+        "Parameter range as a_min,a_max,b_min,b_max [default: from the preset]",
+        "Parameterbereich als a_min,a_max,b_min,b_max [Standard: aus der Vorlage]",
+    ),
+    (
+        "Color scheme for the stable regions [default: from the preset]",
+        "Farbschema der stabilen Bereiche [Standard: aus der Vorlage]",
+    ),
+    (
+        "Named view bundling sequence, ranges and palette, see --list-presets [default: a random one in the window, zircon-city otherwise]",
+        "Benannte Ansicht aus Folge, Bereich und Farbschema, siehe --list-presets [Standard: eine zufällige im Fenster, sonst zircon-city]",
+    ),
+    (
+        "Start with zircon-city instead of a random preset when neither sequence, region nor preset is given",
+        "Mit zircon-city statt einer zufälligen Vorlage starten, wenn weder Folge, Bereich noch Vorlage angegeben sind",
+    ),
+    (
+        "preset {0}: {1}, {2} a {3}..{4} b {5}..{6}, --preset {0} shows it again",
+        "Vorlage {0}: {1}, {2} a {3}..{4} b {5}..{6}, --preset {0} zeigt sie wieder",
+    ),
+    (
+        "Print all presets and exit",
+        "Alle Vorlagen ausgeben und beenden",
+    ),
+    (
+        "Screensaver: endlessly show featured and randomly discovered diagrams",
+        "Bildschirmschoner: zeigt endlos ausgewählte und zufällig gefundene Diagramme",
+    ),
+    (
+        "Seed of everything random (noise, attract mode, evolve), the same seed repeats a run exactly",
+        "Startwert aller Zufallszahlen (Rauschen, Bildschirmschoner, evolve), derselbe Wert wiederholt einen Lauf exakt",
+    ),
+    (
+        "TOML file with [palettes.NAME] color ramps for --palette, the --watch file may define them too",
+        "TOML-Datei mit Farbverläufen [palettes.NAME] für --palette, auch die --watch-Datei kann sie festlegen",
+    ),
+    (
+        "Shared library computing a palette, registered under the name it reports for --palette",
+        "Programmbibliothek, die eine Palette berechnet, für --palette unter dem Namen verfügbar, den sie meldet",
+    ),
+    (
+        "Language of help, messages and window titles [default: from LC_ALL, LC_MESSAGES or LANG]",
+        "Sprache von Hilfe, Meldungen und Fenstertiteln [Standard: aus LC_ALL, LC_MESSAGES oder LANG]",
+    ),
+    (
+        "Print the diagram to the terminal instead of opening a window",
+        "Diagramm im Terminal ausgeben statt ein Fenster zu öffnen",
+    ),
+    (
+        "Draw into a Linux framebuffer device instead of a window, e.g. /dev/fb0 on a Raspberry Pi",
+        "In ein Linux-Framebuffer-Gerät statt in ein Fenster zeichnen, z. B. /dev/fb0 auf einem Raspberry Pi",
+    ),
+    (
+        "Render without a window into FILE, SVG with vector contour and axes if it ends in .svg, PNG otherwise",
+        "Ohne Fenster in FILE rechnen, SVG mit Konturlinie und Achsen bei der Endung .svg, sonst PNG",
+    ),
+    (
+        "Image size for --out as WIDTHxHEIGHT",
+        "Bildgröße für --out als BREITExHÖHE",
+    ),
+    (
+        "Name of views saved with S in the window, {seq} {amin} {amax} {bmin} {bmax} {iters} {palette} {n} are replaced, the same placeholders work in --out",
+        "Dateiname der mit S gespeicherten Ansichten, {seq} {amin} {amax} {bmin} {bmax} {iters} {palette} {n} werden ersetzt, dieselben Platzhalter gelten für --out",
+    ),
+    (
+        "Add a/b axes with ticks and a colorbar for λ to the --out image",
+        "a/b-Achsen mit Skala und eine Farbleiste für λ zum --out-Bild hinzufügen",
+    ),
+    (
+        "Equalize λ within tiles before coloring (CLAHE), STRENGTH limits the contrast gain (1 = off)",
+        "λ vor dem Färben kachelweise angleichen (CLAHE), STRENGTH begrenzt den Kontrastgewinn (1 = aus)",
+    ),
+    (
+        "Tile edge length of --clahe in pixels, smaller tiles bring out more local structure",
+        "Kantenlänge der --clahe-Kacheln in Bildpunkten, kleinere Kacheln zeigen mehr lokale Struktur",
+    ),
+    (
+        "Tone map λ before the palette with log, sqrt (more colors near the border) or sigmoid[:K] (mid range)",
+        "λ vor der Palette abbilden mit log, sqrt (mehr Farben nahe der Grenze) oder sigmoid[:K] (mittlerer Bereich)",
+    ),
+    (
+        "Shade chaotic regions (λ > 0) by how fast λ changes there instead of flat black",
+        "Chaotische Bereiche (λ > 0) nach der Änderung von λ schattieren statt einfach schwarz",
+    ),
+    (
+        "Post process the --out image, NAME[:RADIUS[:AMOUNT]] with unsharp (1, 0.6) or local-contrast (30, 0.3), repeat to chain",
+        "Nachbearbeitung des --out-Bildes, NAME[:RADIUS[:AMOUNT]] mit unsharp (1, 0,6) oder local-contrast (30, 0,3), mehrfach für eine Kette",
+    ),
+    (
+        "Caption below the --out image, placeholders as in --output-template, without TEXT the sequence, ranges and depth",
+        "Bildunterschrift unter dem --out-Bild, Platzhalter wie bei --output-template, ohne TEXT Folge, Bereiche und Tiefe",
+    ),
+    (
+        "Signature written at the lower right below the --out image",
+        "Signatur unten rechts unter dem --out-Bild",
+    ),
+    (
+        "White margin in pixels around the --out image",
+        "Weißer Rand in Pixeln um das --out-Bild",
+    ),
+    (
+        "Start in the terrain view, the λ grid as shaded landscape (3 toggles it, drag to turn and tilt)",
+        "In der Geländeansicht starten, das λ-Raster als schattierte Landschaft (3 schaltet um, Ziehen dreht und neigt)",
+    ),
+    (
+        "Write the --out image as stereo pair of the terrain view instead of the flat diagram",
+        "Das --out-Bild als Stereopaar der Geländeansicht statt des flachen Diagramms schreiben",
+    ),
+    (
+        "Distance of the eyes of --stereo in diagram sizes, more is deeper",
+        "Augenabstand von --stereo in Diagrammgrößen, mehr ist tiefer",
+    ),
+    (
+        "Play the orbit under the mouse cursor as sound, chaos sounds noisy and periodic orbits tonal (needs the audio feature)",
+        "Den Orbit unter dem Mauszeiger als Klang abspielen, Chaos klingt nach Rauschen, periodische Orbits tonal (braucht das Feature audio)",
+    ),
+    (
+        "Restore view, panels, bookmarks and λ grid from this file and save them there when the window closes",
+        "Ansicht, Panels, Lesezeichen und λ-Raster aus dieser Datei wiederherstellen und beim Schließen des Fensters dort speichern",
+    ),
+    ("workspace saved to {0}", "Arbeitsbereich in {0} gespeichert"),
+    (
+        "Keep a journal of the exploration: every view left alone for 3 seconds is written to DIR as PNG and pipe parameters",
+        "Ein Tagebuch der Erkundung führen: jede Ansicht, die 3 Sekunden unverändert bleibt, als PNG und pipe-Parameter in DIR schreiben",
+    ),
+    ("f64 limit: {0} steps per pixel", "f64-Grenze: {0} Schritte pro Pixel"),
+    ("this help", "diese Hilfe"),
+    ("zoom at the cursor", "am Mauszeiger zoomen"),
+    ("pan", "verschieben"),
+    ("palette cycling", "Farbzyklus"),
+    ("next palette", "nächste Palette"),
+    (
+        "raise or lower the iteration depth",
+        "Iterationstiefe erhöhen oder senken",
+    ),
+    (
+        "minimap, click it to jump there",
+        "Übersichtskarte, Klick springt dorthin",
+    ),
+    ("λ histogram", "λ-Histogramm"),
+    ("colorbar", "Farbskala"),
+    (
+        "Show a colorbar mapping the colors to λ at the right edge of the window (K toggles it)",
+        "Farbskala am rechten Fensterrand zeigen, die den Farben λ zuordnet (K schaltet sie um)",
+    ),
+    (
+        "histogram equalization of the colors",
+        "Histogrammausgleich der Farben",
+    ),
+    (
+        "terrain view, left drag turns and tilts",
+        "Geländeansicht, Ziehen mit links dreht und neigt",
+    ),
+    (
+        "path scan, click vertices, Enter writes CSV",
+        "Pfadschnitt, Klicks setzen Punkte, Enter schreibt CSV",
+    ),
+    ("bookmark the view", "Ansicht als Lesezeichen"),
+    ("next bookmark", "nächstes Lesezeichen"),
+    (
+        "full resolution with --render-scale",
+        "volle Auflösung bei --render-scale",
+    ),
+    ("save the view", "Ansicht speichern"),
+    ("exit", "beenden"),
+    (
+        "precision: a pixel is {0} wide and f64 resolves {1} here, {2} steps per pixel. below 1 zooming further only enlarges blocks",
+        "Genauigkeit: ein Pixel ist {0} breit und f64 löst hier {1} auf, {2} Schritte pro Pixel. Unter 1 vergrößert weiteres Zoomen nur die Blöcke",
+    ),
+    ("bookmark {0} saved", "Lesezeichen {0} gespeichert"),
+    ("bookmark {0} of {1}", "Lesezeichen {0} von {1}"),
+    (
+        "Split the window into panes over the same region, SEQUENCE[:PALETTE] each, give it 2 or 4 times",
+        "Das Fenster in Bereiche über derselben Region teilen, je FOLGE[:PALETTE], 2- oder 4-mal angeben",
+    ),
+    (
+        "expected SEQUENCE or SEQUENCE:PALETTE e.g. AB:dual",
+        "erwartet FOLGE oder FOLGE:PALETTE, z. B. AB:dual",
+    ),
+    (
+        "--pane has to be given 2 or 4 times, not {0}",
+        "--pane muss 2- oder 4-mal angegeben werden, nicht {0}",
+    ),
+    (
+        "Draw a path with the left button and play its orbit periods on the MIDI output port containing PORT (needs the midi feature)",
+        "Mit der linken Taste einen Pfad zeichnen und die Perioden seiner Orbits auf dem MIDI-Ausgang abspielen, dessen Name PORT enthält (braucht das Feature midi)",
+    ),
+    (
+        "Leave chaotic pixels (λ > 0) transparent in the --out image, for compositing over other artwork",
+        "Chaotische Pixel (λ > 0) im --out-Bild transparent lassen, zum Überlagern anderer Bilder",
+    ),
+    (
+        "Leave ordered pixels (λ < 0) transparent in the --out image",
+        "Geordnete Pixel (λ < 0) im --out-Bild transparent lassen",
+    ),
+    (
+        "Show the distribution of λ as a histogram panel (H toggles it), below the image with --out",
+        "Verteilung von λ als Histogramm zeigen (H schaltet um), mit --out unter dem Bild",
+    ),
+    (
+        "Follow a TOML file with sequence, region, palette and depth, re-rendering whenever it is saved",
+        "TOML-Datei mit Folge, Bereich, Farbschema und Tiefe beobachten und bei jedem Speichern neu rechnen",
+    ),
+    (
+        "Take commands (sequence, region, palette, depth, save) from other programs, one per line over TCP or as OSC over UDP on this address",
+        "Befehle (sequence, region, palette, depth, save) von anderen Programmen annehmen, zeilenweise über TCP oder als OSC über UDP an dieser Adresse",
+    ),
+    ("remote control on {0} (TCP and OSC)", "Fernsteuerung auf {0} (TCP und OSC)"),
+    ("{0} takes one argument", "{0} erwartet ein Argument"),
+    (
+        "region takes A_MIN A_MAX B_MIN B_MAX with MIN < MAX",
+        "region erwartet A_MIN A_MAX B_MIN B_MAX mit MIN < MAX",
+    ),
+    ("unknown palette '{0}'", "unbekanntes Farbschema '{0}'"),
+    ("depth takes a whole number", "depth erwartet eine ganze Zahl"),
+    ("save takes at most a file name", "save erwartet höchstens einen Dateinamen"),
+    ("unknown command '{0}'", "unbekannter Befehl '{0}'"),
+    (
+        "Log every navigation, palette and parameter change with timestamps to a JSON file",
+        "Jede Navigation, jeden Farb- und Parameterwechsel mit Zeitstempel in eine JSON-Datei schreiben",
+    ),
+    (
+        "Replay a session written by --record, its starting state replaces sequence, region and palette",
+        "Mit --record aufgezeichnete Sitzung abspielen, ihr Anfangszustand ersetzt Folge, Bereich und Farbschema",
+    ),
+    (
+        "Also write every replayed state as a numbered PNG into this directory",
+        "Jeden abgespielten Zustand zusätzlich als nummeriertes PNG in dieses Verzeichnis schreiben",
+    ),
+    (
+        "Compute λ on tile borders and interpolate tiles that are clearly ordered or chaotic all around",
+        "λ auf Kachelrändern berechnen und Kacheln interpolieren, die ringsum eindeutig geordnet oder chaotisch sind",
+    ),
+    (
+        "Render with a quarter of the depth first, then the order/chaos border with QUALITY times the depth and QUALITY² samples",
+        "Zuerst mit einem Viertel der Tiefe rechnen, dann die Grenze zwischen Ordnung und Chaos mit QUALITY-facher Tiefe und QUALITY² Abtastpunkten",
+    ),
+    (
+        "Trace λ = 0 between neighbouring pixels by bisection and write it as lines in a/b, FILE.geojson or FILE.csv",
+        "λ = 0 zwischen benachbarten Bildpunkten per Bisektion verfolgen und als Linien in a/b schreiben, DATEI.geojson oder DATEI.csv",
+    ),
+    (
+        "Bisection steps per crossing of --ridge, each halves the uncertainty along the pixel spacing",
+        "Bisektionsschritte je Übergang für --ridge, jeder halbiert die Unsicherheit über den Bildpunktabstand",
+    ),
+    (
+        "λ = 0: {0} lines with {1} points in {2}",
+        "λ = 0: {0} Linien mit {1} Punkten in {2}",
+    ),
+    (
+        "Label the connected stable regions and report their number and sizes, with FILE.csv their areas and bounds",
+        "Zusammenhängende stabile Gebiete kennzeichnen und ihre Anzahl und Größen melden, mit DATEI.csv ihre Flächen und Grenzen",
+    ),
+    (
+        "Give every stable region of --out a color of its own",
+        "Jedem stabilen Gebiet von --out eine eigene Farbe geben",
+    ),
+    (
+        "Leave stable regions smaller than N pixels out of --components and --color-components, specks along the border",
+        "Stabile Gebiete mit weniger als N Bildpunkten bei --components und --color-components auslassen, Sprenkel entlang der Grenze",
+    ),
+    (
+        "{0} stable regions of at least {1} pixels, the largest {2} pixels",
+        "{0} stabile Gebiete mit mindestens {1} Bildpunkten, das größte mit {2} Bildpunkten",
+    ),
+    (
+        "After each render, compute the order/chaos border again with FACTOR times the depth in the background",
+        "Nach jedem Bild die Grenze zwischen Ordnung und Chaos im Hintergrund mit FACTOR-facher Tiefe neu berechnen",
+    ),
+    (
+        "Keep computed λ in this file across runs, revisited and panned views only compute the new pixels",
+        "Berechnete λ über Programmläufe hinweg in dieser Datei halten, wiederbesuchte und verschobene Ansichten rechnen nur neue Bildpunkte",
+    ),
+    (
+        "Entries of --cache (32 bytes each on disk), the least recently used ones are dropped",
+        "Einträge von --cache (je 32 Byte auf der Platte), die am längsten unbenutzten fallen heraus",
+    ),
+    (
+        "Borderless window covering the whole screen, rendered at its real resolution",
+        "Randloses Fenster über den ganzen Bildschirm, in dessen echter Auflösung gerechnet",
+    ),
+    (
+        "Show every diagram pixel as N×N screen pixels, renders faster. HiDPI screens are detected (GDK_SCALE overrides)",
+        "Jedes Pixel des Diagramms als N×N Bildschirmpixel zeigen, rendert schneller. HiDPI-Bildschirme werden erkannt (GDK_SCALE hat Vorrang)",
+    ),
+    (
+        "Filter bringing the frames of --scale and --render-scale up to the screen",
+        "Filter, der die Bilder von --scale und --render-scale auf Bildschirmgröße bringt",
+    ),
+    (
+        "Compute the views while exploring at this fraction of the window size, Enter computes the current one at full size",
+        "Ansichten beim Erkunden mit diesem Bruchteil der Fenstergröße rechnen, Enter rechnet die aktuelle in voller Größe",
+    ),
+    (
+        "expected a number above 0 and at most 1",
+        "erwartet eine Zahl über 0 und höchstens 1",
+    ),
+    ("computing at full resolution", "Rechnen in voller Auflösung"),
+    (
+        "Screen size for --fullscreen as WIDTHxHEIGHT [default: detected]",
+        "Bildschirmgröße für --fullscreen als BREITExHÖHE [Standard: erkannt]",
+    ),
+    (
+        "Span the diagram over a wall of COLUMNSxROWS equal monitors, one --fullscreen window on each",
+        "Das Diagramm über eine Wand aus SPALTENxZEILEN gleichen Monitoren spannen, auf jedem ein --fullscreen-Fenster",
+    ),
+    (
+        "Close the window as soon as the diagram (and a --refine pass) is finished",
+        "Fenster schließen, sobald das Diagramm (und ein --refine-Durchgang) fertig ist",
+    ),
+    (
+        "Show the finished diagram for SECONDS, then close the window",
+        "Das fertige Diagramm SECONDS Sekunden lang zeigen, dann das Fenster schließen",
+    ),
+    (
+        "Stop summing λ once it leaves ±bailout (divergent or superstable orbits)",
+        "Summation von λ abbrechen, sobald es ±bailout verlässt (divergente oder superstabile Bahnen)",
+    ),
+    (
+        "What to do with pixels that bailed out",
+        "Umgang mit abgebrochenen Bildpunkten",
+    ),
+    (
+        "What to do when the orbit hits the critical point x = 0.5 and log|r(1-2x)| would be -∞",
+        "Umgang mit Bahnen, die den kritischen Punkt x = 0,5 treffen, wo log|r(1-2x)| -∞ wäre",
+    ),
+    (
+        "Highlight superstable orbits, those passing closer than TOLERANCE to x = 0.5, as a white skeleton",
+        "Superstabile Bahnen, die näher als TOLERANCE an x = 0,5 vorbeikommen, als weißes Skelett hervorheben",
+    ),
+    (
+        "Add uniform noise of ±AMPLITUDE to every iteration and average λ over several realizations",
+        "Gleichverteiltes Rauschen von ±AMPLITUDE zu jeder Iteration addieren und λ über mehrere Realisierungen mitteln",
+    ),
+    (
+        "Seed of the noise, the same seed renders the same noisy diagram [default: --seed or 0]",
+        "Startwert des Rauschens, derselbe Wert ergibt dasselbe verrauschte Diagramm [Standard: --seed oder 0]",
+    ),
+    (
+        "Noise realizations averaged per pixel",
+        "Gemittelte Rausch-Realisierungen je Bildpunkt",
+    ),
+    (
+        "Iterate two logistic maps coupled with strength EPSILON (0..1) and show their largest exponent",
+        "Zwei mit der Stärke EPSILON (0..1) gekoppelte logistische Gleichungen iterieren und ihren größten Exponenten zeigen",
+    ),
+    (
+        "Circle map θ → θ + Ω − K/2π·sin(2πθ) with Ω along a and K along b instead of the forced logistic map",
+        "Kreisabbildung θ → θ + Ω − K/2π·sin(2πθ) mit Ω entlang a und K entlang b statt der logistischen Gleichung",
+    ),
+    (
+        "Complex r = a + ib in z → r·z·(1−z), escaping orbits count as bailed out (see --on-bailout)",
+        "Komplexes r = a + ib in z → r·z·(1−z), entweichende Bahnen gelten als abgebrochen (siehe --on-bailout)",
+    ),
+    (
+        "Iterations per pixel, also the orbit length (minus 20 warmup steps) of the measures",
+        "Iterationen je Bildpunkt, auch die Bahnlänge (abzüglich 20 Einschwingschritten) der Maße",
+    ),
+    (
+        "WebAssembly module computing the value of every pixel instead of the built in maps",
+        "WebAssembly-Modul, das den Wert jedes Pixels statt der eingebauten Abbildungen berechnet",
+    ),
+    (
+        "Quantity computed per pixel and colored like λ",
+        "Je Bildpunkt berechnete Größe, gefärbt wie λ",
+    ),
+    (
+        "Which part of the Ljapunow spectrum of the coupled maps to show",
+        "Angezeigter Teil des Ljapunow-Spektrums der gekoppelten Gleichungen",
+    ),
+    (
+        "Write PNG frames morphing one diagram into another",
+        "PNG-Einzelbilder schreiben, die ein Diagramm in ein anderes überblenden",
+    ),
+    (
+        "First view as SEQUENCE:a_min,a_max,b_min,b_max",
+        "Erste Ansicht als FOLGE:a_min,a_max,b_min,b_max",
+    ),
+    (
+        "Last view as SEQUENCE:a_min,a_max,b_min,b_max",
+        "Letzte Ansicht als FOLGE:a_min,a_max,b_min,b_max",
+    ),
+    (
+        "Number of frames including first and last",
+        "Anzahl der Bilder einschließlich des ersten und letzten",
+    ),
+    (
+        "Output directory, created if missing",
+        "Ausgabeverzeichnis, wird bei Bedarf angelegt",
+    ),
+    (
+        "Interpolate the ranges and render every frame instead of blending the two λ grids",
+        "Bereiche interpolieren und jedes Bild rechnen statt die beiden λ-Raster zu überblenden",
+    ),
+    (
+        "Encode all frames into this video (mp4, webm, gif, ...) with ffmpeg instead of writing PNGs",
+        "Alle Bilder mit ffmpeg in dieses Video (mp4, webm, gif, ...) kodieren statt PNGs zu schreiben",
+    ),
+    (
+        "Frames per second of --video",
+        "Bilder pro Sekunde von --video",
+    ),
+    (
+        "Skip frames an earlier run already wrote with the same parameters (see the manifest in the output directory)",
+        "Bilder überspringen, die ein früherer Lauf mit denselben Parametern schon geschrieben hat (siehe Manifest im Ausgabeverzeichnis)",
+    ),
+    (
+        "Experimental genetic search for interesting sequences",
+        "Experimentelle genetische Suche nach interessanten Folgen",
+    ),
+    (
+        "Sequence to start the search from",
+        "Folge, mit der die Suche beginnt",
+    ),
+    (
+        "Parameter range as a_min,a_max,b_min,b_max",
+        "Parameterbereich als a_min,a_max,b_min,b_max",
+    ),
+    (
+        "Number of generations",
+        "Anzahl der Generationen",
+    ),
+    (
+        "Survivors kept per generation",
+        "Überlebende je Generation",
+    ),
+    (
+        "Output directory for thumbnails and log.txt",
+        "Ausgabeverzeichnis für Vorschaubilder und log.txt",
+    ),
+    (
+        "Write the λ field as 16 bit heightmap and normal map for rendering it as terrain",
+        "Das λ-Feld als 16-Bit-Höhenkarte und Normal-Map schreiben, um es als Gelände zu rendern",
+    ),
+    (
+        "Output PNG, 16 bit grayscale with λ growing upwards",
+        "Ausgabe-PNG, 16 Bit Graustufen, λ wächst nach oben",
+    ),
+    (
+        "λ range mapped onto the heights as MIN,MAX, values outside are clamped [default: the range of the grid]",
+        "λ-Bereich, der auf die Höhen abgebildet wird, als MIN,MAX, Werte außerhalb werden begrenzt [Standard: Bereich des Rasters]",
+    ),
+    (
+        "Relief of the normal map, the full height range rises this many pixels",
+        "Relief der Normal-Map, der volle Höhenbereich steigt um so viele Pixel",
+    ),
+    (
+        "Write a tile pyramid (Deep Zoom or z/x/y) for zoomable browser viewers",
+        "Kachelpyramide (Deep Zoom oder z/x/y) für zoombare Browser-Betrachter schreiben",
+    ),
+    (
+        "Size of the full resolution image as WIDTHxHEIGHT",
+        "Größe des Bildes in voller Auflösung als BREITExHÖHE",
+    ),
+    (
+        "Tile layout",
+        "Kachelaufteilung",
+    ),
+    (
+        "Name of the .dzi file and its _files directory",
+        "Name der .dzi-Datei und ihres _files-Verzeichnisses",
+    ),
+    (
+        "Keep tiles already in the output directory, e.g. after an interrupted run with the same parameters",
+        "Kacheln im Ausgabeverzeichnis behalten, z. B. nach einem unterbrochenen Lauf mit denselben Parametern",
+    ),
+    (
+        "Forcing sequence, only the letters A and B",
+        "Folge aus den Buchstaben A und B",
+    ),
+    (
+        "Color scheme",
+        "Farbschema",
+    ),
+    (
+        "Render one diagram per value of a swept parameter into a labeled montage",
+        "Ein Diagramm je Wert eines variierten Parameters rechnen, zusammengesetzt zu einer beschrifteten Montage",
+    ),
+    (
+        "Parameter varied from cell to cell",
+        "Parameter, der sich von Feld zu Feld ändert",
+    ),
+    (
+        "Value in the first cell",
+        "Wert im ersten Feld",
+    ),
+    (
+        "Value in the last cell",
+        "Wert im letzten Feld",
+    ),
+    (
+        "Number of cells including first and last",
+        "Anzahl der Felder einschließlich des ersten und letzten",
+    ),
+    (
+        "Cells of the montage as COLUMNSxROWS [default: as square as possible]",
+        "Felder der Montage als SPALTENxZEILEN [Standard: möglichst quadratisch]",
+    ),
+    (
+        "Size of one cell as WIDTHxHEIGHT",
+        "Größe eines Feldes als BREITExHÖHE",
+    ),
+    (
+        "Output PNG",
+        "Ausgabe-PNG",
+    ),
+    (
+        "Follow the stable regions from cell to cell and write their births, merges, splits and deaths to FILE.csv",
+        "Die stabilen Gebiete von Feld zu Feld verfolgen und ihr Entstehen, Verschmelzen, Aufspalten und Verschwinden in DATEI.csv schreiben",
+    ),
+    (
+        "Leave stable regions smaller than N pixels out of --track",
+        "Stabile Gebiete mit weniger als N Bildpunkten bei --track auslassen",
+    ),
+    (
+        "Serve the progressive render to browsers over WebSocket",
+        "Das schrittweise Rechnen per WebSocket an Browser senden",
+    ),
+    (
+        "Address and port to accept WebSocket clients on",
+        "Adresse und Port für WebSocket-Clients",
+    ),
+    (
+        "Diagram size as WIDTHxHEIGHT",
+        "Diagrammgröße als BREITExHÖHE",
+    ),
+    (
+        "Write the raw λ grid of any size into a memory mapped file",
+        "Rohes λ-Raster beliebiger Größe in eine speichergemappte Datei schreiben",
+    ),
+    (
+        "Output file",
+        "Ausgabedatei",
+    ),
+    (
+        "Grid size as WIDTHxHEIGHT",
+        "Rastergröße als BREITExHÖHE",
+    ),
+    (
+        "How λ is stored: f64, f16 for half or u16 quantized for a quarter of the size",
+        "Speicherformat von λ: f64, f16 für die halbe oder u16 quantisiert für ein Viertel der Größe",
+    ),
+    (
+        "λ range of u16 storage as MIN,MAX, values outside are clamped [default: from a preview]",
+        "λ-Bereich der u16-Speicherung als MIN,MAX, Werte außerhalb werden begrenzt [Standard: aus einer Vorschau]",
+    ),
+    (
+        "Show the signed λ difference of two sequences over the same region",
+        "Vorzeichenbehaftete λ-Differenz zweier Folgen über demselben Bereich zeigen",
+    ),
+    (
+        "Sequence the difference is taken from",
+        "Folge, von der die Differenz gebildet wird",
+    ),
+    (
+        "Sequence compared to the first, shown is λ(second) - λ(first)",
+        "Mit der ersten verglichene Folge, gezeigt wird λ(zweite) - λ(erste)",
+    ),
+    (
+        "Write a PNG instead of opening a window",
+        "PNG schreiben statt ein Fenster zu öffnen",
+    ),
+    (
+        "Show how λ changes when one letter of the sequence is flipped",
+        "Zeigen, wie sich λ ändert, wenn ein Buchstabe der Folge getauscht wird",
+    ),
+    (
+        "Letter to flip A <-> B, counted from 1",
+        "Zu tauschender Buchstabe A <-> B, ab 1 gezählt",
+    ),
+    (
+        "Render frames whose parameters and colors come from a Rhai script",
+        "Bilder rechnen, deren Parameter und Farben aus einem Rhai-Skript stammen",
+    ),
+    (
+        "Rhai script defining on_frame(t) and optionally colorize(lambda)",
+        "Rhai-Skript mit on_frame(t) und optional colorize(lambda)",
+    ),
+    (
+        "Number of frames",
+        "Anzahl der Bilder",
+    ),
+    (
+        "Frame size as WIDTHxHEIGHT",
+        "Bildgröße als BREITExHÖHE",
+    ),
+    (
+        "Render one JSON parameter set per stdin line, to its \"out\" file or as PNG bytes to stdout",
+        "Je Zeile der Standardeingabe einen JSON-Parametersatz rechnen, in seine \"out\"-Datei oder als PNG auf die Standardausgabe",
+    ),
+    (
+        "Print shell completions to stdout",
+        "Shell-Vervollständigungen auf die Standardausgabe schreiben",
+    ),
+    (
+        "Ljapunow-Markus diagram - press ESC to exit",
+        "Ljapunow-Markus-Diagramm - ESC beendet",
+    ),
+    (
+        "press ESC to exit",
+        "ESC beendet",
+    ),
+    (
+        "error: {0}",
+        "Fehler: {0}",
+    ),
+    (
+        "invalid sequence '{0}'",
+        "ungültige Folge '{0}'",
+    ),
+    (
+        "only the letters A and B are allowed unless --values defines others, did you mean '{0}'?",
+        "nur die Buchstaben A und B sind erlaubt, solange --values keine weiteren festlegt, war '{0}' gemeint?",
+    ),
+    (
+        "only the letters A and B are allowed unless --values defines others (e.g. BBBBBBAAAAAA)",
+        "nur die Buchstaben A und B sind erlaubt, solange --values keine weiteren festlegt (z. B. BBBBBBAAAAAA)",
+    ),
+    (
+        "it needs at least one A and one B (e.g. BBBBBBAAAAAA)",
+        "sie braucht mindestens ein A und ein B (z. B. BBBBBBAAAAAA)",
+    ),
+    (
+        "without {0} the diagram does not change along {1}, did you mean '{2}'?",
+        "ohne {0} ändert sich das Diagramm entlang {1} nicht, war '{2}' gemeint?",
+    ),
+    (
+        "unable to create the window: {0}",
+        "Fenster kann nicht geöffnet werden: {0}",
+    ),
+    (
+        "script: {0}",
+        "Skript: {0}",
+    ),
+    (
+        "the ranges have to be finite numbers",
+        "die Bereichsgrenzen müssen endliche Zahlen sein",
+    ),
+    (
+        "the {0} range is empty ({0}_min = {0}_max = {1}), widen it e.g. to {2},{3}",
+        "der Bereich von {0} ist leer ({0}_min = {0}_max = {1}), z. B. auf {2},{3} erweitern",
+    ),
+    (
+        "the {0} range is reversed (min > max), did you mean {1}?",
+        "der Bereich von {0} ist vertauscht (min > max), war {1} gemeint?",
+    ),
+    (
+        "expected a_min,a_max,b_min,b_max e.g. 3.4,4.0,2.5,3.4",
+        "erwartet a_min,a_max,b_min,b_max z. B. 3.4,4.0,2.5,3.4",
+    ),
+    (
+        "expected MIN,MAX with MIN < MAX e.g. -2,1",
+        "erwartet MIN,MAX mit MIN < MAX z. B. -2,1",
+    ),
+    (
+        "expected WIDTHxHEIGHT e.g. 1920x1080",
+        "erwartet BREITExHÖHE z. B. 1920x1080",
+    ),
+    (
+        "expected COLUMNSxROWS up to {0}x{0} e.g. 3x2",
+        "erwartet SPALTENxZEILEN bis {0}x{0} z. B. 3x2",
+    ),
+    (
+        "expected a number between 0 and 1",
+        "erwartet eine Zahl zwischen 0 und 1",
+    ),
+    (
+        "expected log, sqrt or sigmoid[:K] e.g. sigmoid:8",
+        "erwartet log, sqrt oder sigmoid[:K] z. B. sigmoid:8",
+    ),
+    (
+        "expected unsharp or local-contrast, optionally with :RADIUS and :AMOUNT e.g. unsharp:1.5:0.8",
+        "erwartet unsharp oder local-contrast, optional mit :RADIUS und :AMOUNT z. B. unsharp:1.5:0.8",
+    ),
+    (
+        "expected a positive number",
+        "erwartet eine positive Zahl",
+    ),
+    (
+        "unknown preset, known are:",
+        "unbekannte Vorlage, bekannt sind:",
+    ),
+    (
+        "boundary dimension (box counting): {0}",
+        "Dimension der Grenze (Box-Counting): {0}",
+    ),
+    (
+        "no chaos boundary in view",
+        "keine Chaosgrenze im Bild",
+    ),
+    (
+        "quadtree: {0} of {1} pixels computed",
+        "Quadtree: {0} von {1} Bildpunkten berechnet",
+    ),
+    (
+        "{0} border pixels refined",
+        "{0} Randpunkte verfeinert",
+    ),
+    (
+        "refined {0} border pixels",
+        "{0} Randpunkte verfeinert",
+    ),
+    (
+        "refining {0} border pixels at depth {1}",
+        "verfeinere {0} Randpunkte mit Tiefe {1}",
+    ),
+    (
+        "λ cache: {0} entries from {1}",
+        "λ-Cache: {0} Einträge aus {1}",
+    ),
+    (
+        "λ cache: {0} entries saved to {1}, {2} hits, {3} misses",
+        "λ-Cache: {0} Einträge in {1} gespeichert, {2} Treffer, {3} Fehlgriffe",
+    ),
+    (
+        "interrupted, {0}",
+        "unterbrochen, {0}",
+    ),
+    ("audio: {0}", "Audio: {0}"),
+    ("no output device", "kein Ausgabegerät"),
+    (
+        "this build has no sound output, rebuild with --features audio",
+        "dieser Build hat keine Tonausgabe, mit --features audio neu bauen",
+    ),
+    ("MIDI: {0}", "MIDI: {0}"),
+    ("gamepad: {0}", "Gamepad: {0}"),
+    (
+        "Open the window through winit for touchscreens: drag pans, pinch zooms, tap clicks (needs the touch feature)",
+        "Das Fenster für Touchscreens über winit öffnen: Ziehen verschiebt, Spreizen zoomt, Tippen klickt (braucht das Feature touch)",
+    ),
+    (
+        "this build has no touch window, rebuild with --features touch",
+        "dieser Build hat kein Touch-Fenster, mit --features touch neu bauen",
+    ),
+    ("no window was created", "es wurde kein Fenster erzeugt"),
+    (
+        "this build reads no gamepads, rebuild with --features gamepad",
+        "dieser Build liest keine Gamepads, mit --features gamepad neu bauen",
+    ),
+    (
+        "Steer the window with a game controller: stick pans, triggers zoom, A palette, d-pad presets and depth (needs the gamepad feature)",
+        "Das Fenster mit einem Gamecontroller steuern: Stick verschiebt, Schultertasten zoomen, A Farbschema, Steuerkreuz Vorlagen und Tiefe (braucht das Feature gamepad)",
+    ),
+    ("preset {0}: {1}", "Vorlage {0}: {1}"),
+    (
+        "path scan: click the vertices, Enter samples the path",
+        "Pfad-Scan: Eckpunkte anklicken, Enter tastet den Pfad ab",
+    ),
+    (
+        "path scan: {0} samples saved to {1}",
+        "Pfad-Scan: {0} Stützstellen in {1} gespeichert",
+    ),
+    ("MIDI output: {0}", "MIDI-Ausgang: {0}"),
+    (
+        "no MIDI output port matching '{0}'",
+        "kein MIDI-Ausgang passt zu '{0}'",
+    ),
+    (
+        "this build has no MIDI output, rebuild with --features midi",
+        "dieser Build hat keinen MIDI-Ausgang, mit --features midi neu bauen",
+    ),
+    ("plugin: {0}", "Plugin: {0}"),
+    (
+        "interface version {0}, this build supports {1}",
+        "Schnittstellenversion {0}, dieser Build unterstützt {1}",
+    ),
+    ("the palette has no name", "die Palette hat keinen Namen"),
+    (
+        "this build cannot load plugins, rebuild with --features plugins",
+        "dieser Build kann keine Plugins laden, mit --features plugins neu bauen",
+    ),
+    (
+        "Threads computing rows of the diagram, e.g. 1 for reproducible benchmarks [default: all cores]",
+        "Threads, die Zeilen des Diagramms berechnen, z. B. 1 für reproduzierbare Benchmarks [Standard: alle Kerne]",
+    ),
+    (
+        "Compute at the lowest OS priority, so long renders do not make the desktop sluggish",
+        "Mit der niedrigsten Priorität des Systems rechnen, damit lange Berechnungen den Desktop nicht ausbremsen",
+    ),
+    (
+        "this build cannot write traces, rebuild with --features trace",
+        "dieser Build kann keine Traces schreiben, mit --features trace neu bauen",
+    ),
+    (
+        "Write the time spent per pipeline stage, FILE.folded as folded stacks for flamegraphs, else Chrome trace JSON for perfetto",
+        "Die Zeit je Stufe der Verarbeitung schreiben, FILE.folded als gefaltete Stacks für Flamegraphs, sonst Chrome-Trace-JSON für Perfetto",
+    ),
+    (
+        "sequence is exported without memory",
+        "sequence wird ohne memory exportiert",
+    ),
+    (
+        "this build cannot run WebAssembly, rebuild with --features wasm",
+        "dieser Build kann kein WebAssembly ausführen, mit --features wasm neu bauen",
+    ),
+    (
+        "interrupted, finishing the current step (again to quit right away)",
+        "unterbrochen, der aktuelle Schritt wird beendet (nochmal für sofortiges Ende)",
+    ),
+    (
+        "no signal handling, interrupting loses the partial output: {0}",
+        "keine Signalbehandlung, bei einer Unterbrechung geht die bisherige Ausgabe verloren: {0}",
+    ),
+    (
+        "replay finished",
+        "Wiedergabe beendet",
+    ),
+    (
+        "palette: {0}",
+        "Farbschema: {0}",
+    ),
+    (
+        "iteration depth: {0}",
+        "Iterationstiefe: {0}",
+    ),
+    (
+        "saved {0}",
+        "{0} gespeichert",
+    ),
+    (
+        "random seed {0}, repeat with --seed {0}",
+        "Zufallsstartwert {0}, wiederholbar mit --seed {0}",
+    ),
+    (
+        "λ {0}..{1} mapped to heights 0..65535, {2} pixels of relief in the normal map",
+        "λ {0}..{1} auf Höhen 0..65535 abgebildet, {2} Pixel Relief in der Normal-Map",
+    ),
+    ("cell {0} of {1}", "Zelle {0} von {1}"),
+    (
+        "{0}: bookmark {1} left out: {2}",
+        "{0}: Lesezeichen {1} ausgelassen: {2}",
+    ),
+    ("{0} tracks, saved {1}", "{0} Spuren, {1} gespeichert"),
+    ("{0}x{1} λ written to {2}", "{0}x{1} λ nach {2} geschrieben"),
+    ("λ quantized over {0}..{1}", "λ über {0}..{1} quantisiert"),
+    ("level {0}: {1}x{2}, {3}x{4} tiles", "Stufe {0}: {1}x{2}, {3}x{4} Kacheln"),
+    ("{0} tiles written to {1}", "{0} Kacheln nach {1} geschrieben"),
+    ("mean |Δλ|: {0}", "mittleres |Δλ|: {0}"),
+    ("order/chaos flipped: {0}% of the pixels", "Ordnung/Chaos gewechselt: {0} % der Pixel"),
+    ("frame {0}/{1}: {2} (done)", "Bild {0}/{1}: {2} (schon fertig)"),
+    ("frame {0}/{1}: {2}", "Bild {0}/{1}: {2}"),
+    ("invalid view '{0}', expected e.g. BBBBBBAAAAAA:3.4,4.0,2.5,3.4", "ungültige Ansicht '{0}', erwartet z. B. BBBBBBAAAAAA:3.4,4.0,2.5,3.4"),
+    ("invalid view '{0}': {1}", "ungültige Ansicht '{0}': {1}"),
+    ("streaming on ws://{0}", "Stream auf ws://{0}"),
+    ("position {0} is outside of the sequence, it has {1} letters", "Position {0} liegt außerhalb der Folge, sie hat {1} Buchstaben"),
+    ("line {0}: {1}", "Zeile {0}: {1}"),
+    ("depth has to be above {0}", "die Tiefe muss über {0} liegen"),
+    ("size has to be between 1 and {0} pixels each way", "die Größe muss in jeder Richtung zwischen 1 und {0} Pixeln liegen"),
+    ("a {0}x{1} montage has no room for {2} steps", "eine {0}x{1}-Montage hat keinen Platz für {2} Schritte"),
+    ("--param {0} {1} is out of range, it has to be {2}", "--param {0} {1} liegt außerhalb des Bereichs, erlaubt ist {2}"),
+    ("between 0 and 1", "zwischen 0 und 1"),
+    ("more than {0}", "mehr als {0}"),
+    ("0 or more", "0 oder mehr"),
+    ("{0} is no color, expected #RRGGBB", "{0} ist keine Farbe, erwartet #RRGGBB"),
+    ("palette {0}: {1}", "Farbschema {0}: {1}"),
+    ("{0}: not a session file: {1}", "{0}: keine Sitzungsdatei: {1}"),
+    ("palette {0} is built in", "Farbschema {0} ist eingebaut"),
+    ("palette {0} is new, restart to use it", "Farbschema {0} ist neu, zum Verwenden neu starten"),
+];
@@ -0,0 +1,34 @@
+// SIGINT/SIGTERM during long headless renders: the work stops after the current row block, tile or frame,
+// writes what it has and the process exits with Error::Interrupted. a second signal exits right away.
+
+use crate::error::Error;
+use crate::i18n;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+// only for headless work, a window or a server keeps the default handling
+pub fn install() {
+    let installed = ctrlc::set_handler(|| {
+        if REQUESTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(Error::Interrupted(String::new()).exit_code());
+        }
+        eprintln!(
+            "{}",
+            i18n::tr("interrupted, finishing the current step (again to quit right away)")
+        );
+    });
+    if let Err(e) = installed {
+        eprintln!(
+            "{}",
+            i18n::trf(
+                "no signal handling, interrupting loses the partial output: {0}",
+                &[&e]
+            )
+        );
+    }
+}
+
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,190 @@
+// publication ready raster: the diagram framed by numeric a and b axes and a colorbar mapping color to λ
+
+use crate::analysis;
+use crate::color::Palette;
+use crate::font::{self, draw_text};
+use crate::frame::Frame;
+use crate::render::{map, Region};
+use crate::svg::{tick_label, ticks};
+
+const BACKGROUND: u32 = 0xFFFFFF;
+const INK: u32 = 0x000000;
+const TEXT_SCALE: usize = 2;
+const TICK_LENGTH: usize = 6;
+const TICK_COUNT: usize = 6;
+// room left of and below the diagram for tick labels and axis names
+const MARGIN_LEFT: usize = 90;
+const MARGIN_BOTTOM: usize = 60;
+const MARGIN: usize = 20;
+const BAR_GAP: usize = 24;
+const BAR_WIDTH: usize = 24;
+// room right of the colorbar for its labels
+const BAR_LABELS: usize = 110;
+// colorbar panel of the window
+pub const STRIP_WIDTH: usize = 64;
+pub const STRIP_HEIGHT: usize = 240;
+const STRIP_BACKGROUND: u32 = 0x808080;
+const STRIP_TEXT: u32 = 0xFFFFFF;
+const STRIP_PADDING: usize = 4;
+const STRIP_BAR: usize = 12;
+
+// the composed image as 0RGB buffer with its size
+pub fn compose(frame: &Frame, region: &Region, palette: Palette) -> (Vec<u32>, usize, usize) {
+    let (width, height) = frame.size();
+    let total_width = MARGIN_LEFT + width + BAR_GAP + BAR_WIDTH + BAR_LABELS;
+    let total_height = MARGIN + height + MARGIN_BOTTOM;
+    let size = (total_width, total_height);
+    let mut buffer = vec![BACKGROUND; total_width * total_height];
+    let (left, top) = (MARGIN_LEFT, MARGIN);
+
+    for y in 0..height {
+        let start = (top + y) * total_width + left;
+        buffer[start..start + width].copy_from_slice(&frame.buffer[y * width..(y + 1) * width]);
+    }
+    let mut line = |x0: usize, y0: usize, x1: usize, y1: usize| {
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                buffer[y * total_width + x] = INK;
+            }
+        }
+    };
+    line(left - 1, top - 1, left + width, top - 1);
+    line(left - 1, top + height, left + width, top + height);
+    line(left - 1, top - 1, left - 1, top + height);
+    line(left + width, top - 1, left + width, top + height);
+
+    // a along the bottom
+    let mut labels = Vec::new();
+    for a in ticks(region.x_min, region.x_max, TICK_COUNT) {
+        let x = left + map(a, region.x_min, region.x_max, 0.0, width as f64).round() as usize;
+        line(
+            x.min(left + width),
+            top + height,
+            x.min(left + width),
+            top + height + TICK_LENGTH,
+        );
+        let label = tick_label(a, region.x_max - region.x_min);
+        let (w, _) = font::text_size(&label, TEXT_SCALE);
+        labels.push((
+            x as i64 - w as i64 / 2,
+            (top + height + TICK_LENGTH + 4) as i64,
+            label,
+        ));
+    }
+    // b along the left side
+    for b in ticks(region.y_min, region.y_max, TICK_COUNT) {
+        let y = top + map(b, region.y_min, region.y_max, 0.0, height as f64).round() as usize;
+        line(
+            left - 1 - TICK_LENGTH,
+            y.min(top + height),
+            left - 1,
+            y.min(top + height),
+        );
+        let label = tick_label(b, region.y_max - region.y_min);
+        let (w, h) = font::text_size(&label, TEXT_SCALE);
+        labels.push((
+            left as i64 - (TICK_LENGTH + 6 + w) as i64,
+            y as i64 - h as i64 / 2,
+            label,
+        ));
+    }
+
+    // colorbar, λ_max on top
+    let (lambda_min, lambda_max) = analysis::lambda_range(&frame.lambdas);
+    let bar_left = left + width + BAR_GAP;
+    if lambda_min < lambda_max {
+        for y in 0..height {
+            let lambda = map(
+                y as f64,
+                0.0,
+                (height - 1).max(1) as f64,
+                lambda_max,
+                lambda_min,
+            );
+            let color = palette.colorize_in(lambda, (lambda_min, lambda_max));
+            let start = (top + y) * total_width + bar_left;
+            buffer[start..start + BAR_WIDTH].fill(color);
+        }
+        for lambda in ticks(lambda_min, lambda_max, TICK_COUNT) {
+            let y = top
+                + map(lambda, lambda_max, lambda_min, 0.0, (height - 1) as f64).round() as usize;
+            let x = bar_left + BAR_WIDTH;
+            for px in x..x + TICK_LENGTH {
+                buffer[y * total_width + px] = INK;
+            }
+            let label = tick_label(lambda, lambda_max - lambda_min);
+            let (_, h) = font::text_size(&label, TEXT_SCALE);
+            labels.push(((x + TICK_LENGTH + 4) as i64, y as i64 - h as i64 / 2, label));
+        }
+    }
+
+    // axis names
+    let (_, h) = font::text_size("a", TEXT_SCALE);
+    labels.push((
+        (left + width / 2) as i64,
+        (total_height - MARGIN_BOTTOM / 4 - h) as i64,
+        "a".into(),
+    ));
+    labels.push((MARGIN as i64 / 2, (top + height / 2) as i64, "b".into()));
+    let (w, _) = font::text_size("λ", TEXT_SCALE);
+    labels.push((
+        (bar_left + (BAR_WIDTH - w) / 2) as i64,
+        (top + height + TICK_LENGTH + 4) as i64,
+        "λ".into(),
+    ));
+
+    for (x, y, label) in labels {
+        draw_text(&mut buffer, size, (x, y), &label, INK, TEXT_SCALE);
+    }
+    (buffer, total_width, total_height)
+}
+
+// the colorbar alone as panel for the window, λ_max on top. the colors are looked up in the colored
+// frame itself, so transfer functions and equalization show up in the bar as they do in the diagram
+pub fn strip(frame: &Frame, (width, height): (usize, usize)) -> Vec<u32> {
+    let mut buffer = vec![STRIP_BACKGROUND; width * height];
+    let mut colors: Vec<(f64, u32)> = frame
+        .lambdas
+        .iter()
+        .zip(&frame.buffer)
+        .filter(|(lambda, _)| lambda.is_finite())
+        .map(|(&lambda, &color)| (lambda, color))
+        .collect();
+    colors.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+    let (Some(&(min, _)), Some(&(max, _))) = (colors.first(), colors.last()) else {
+        return buffer;
+    };
+    let (top, bottom) = (STRIP_PADDING, height.saturating_sub(STRIP_PADDING));
+    if min >= max || bottom <= top + 1 {
+        return buffer;
+    }
+    let row = |lambda: f64| map(lambda, max, min, top as f64, (bottom - 1) as f64).round() as usize;
+    for y in top..bottom {
+        let lambda = map(y as f64, top as f64, (bottom - 1) as f64, max, min);
+        // the color of the pixel with the closest λ
+        let i = colors.partition_point(|&(l, _)| l < lambda);
+        let (_, color) = match (colors.get(i.wrapping_sub(1)), colors.get(i)) {
+            (Some(below), Some(above)) if lambda - below.0 < above.0 - lambda => *below,
+            (_, Some(above)) => *above,
+            (below, None) => *below.unwrap_or(&colors[0]),
+        };
+        buffer[y * width + STRIP_PADDING..y * width + STRIP_PADDING + STRIP_BAR].fill(color);
+    }
+    let x = STRIP_PADDING + STRIP_BAR;
+    for lambda in ticks(min, max, TICK_COUNT) {
+        let y = row(lambda);
+        buffer[y * width + x..y * width + x + STRIP_PADDING].fill(STRIP_TEXT);
+        let label = tick_label(lambda, max - min);
+        let (_, h) = font::text_size(&label, 1);
+        let position = ((x + 2 * STRIP_PADDING) as i64, y as i64 - h as i64 / 2);
+        draw_text(
+            &mut buffer,
+            (width, height),
+            position,
+            &label,
+            STRIP_TEXT,
+            1,
+        );
+    }
+    buffer
+}
@@ -0,0 +1,27 @@
+// render core of the Ljapunow-Markus diagrams, usable without any window.
+// the ljapunow binary (main.rs) adds the command line, displays and interactive modes on top.
+
+pub mod analysis;
+pub mod cache;
+pub mod caption;
+pub mod color;
+pub mod components;
+pub mod contrast;
+pub mod error;
+pub mod export;
+pub mod filter;
+pub mod font;
+pub mod formula;
+pub mod frame;
+pub mod grid_file;
+pub mod histogram;
+pub mod i18n;
+pub mod legend;
+pub mod measure;
+pub mod presets;
+pub mod relief;
+pub mod render;
+pub mod ridge;
+pub mod rng;
+pub mod scan;
+pub mod svg;
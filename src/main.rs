@@ -8,171 +8,650 @@
 // Das Ergebnis ist das Ljapunow-Diagramm, das häufig fraktaler Natur ist.
 // Ein Beispiel ist das Diagramm Zircon Zity, gebildet mit 3,4 ≤ a ≤ 4,0  und 2,5 ≤ b ≤ 3,4 und der Sequenz „BBBBBBAAAAAA“.
 
-use minifb::{Key, Scale, ScaleMode, Window, WindowOptions};
-use std::env;
+mod attract;
+mod blend;
+mod cli;
+mod config;
+mod diff;
+mod display;
+mod dump;
+mod evolve;
+mod explore;
+mod fbdev;
+mod gamepad;
+mod heightmap;
+mod help;
+mod interrupt;
+mod midi;
+mod minimap;
+mod pipe;
+mod plugin;
+mod refine;
+mod remote;
+mod scaling;
+mod screen;
+mod script;
+mod sensitivity;
+mod session;
+mod sonify;
+mod span;
+mod split;
+mod stream;
+mod sweep;
+mod template;
+mod term;
+mod tiles;
+mod touch;
+mod trace;
+mod video;
+mod wasm;
+mod workspace;
+
+use cache::LambdaCache;
+use caption::Caption;
+use clap::CommandFactory;
+use cli::{Cli, Command, Stereo};
+use color::Palette;
+use components::Components;
+use config::{Config, ConfigWatch};
+use contrast::Clahe;
+use display::{Display, MinifbDisplay};
+use error::Error;
+use explore::Explorer;
+use fbdev::FbDisplay;
+use frame::Frame;
+use gamepad::Gamepad;
+use ljapunow::{
+    analysis, cache, caption, color, components, contrast, error, export, filter, font, frame,
+    grid_file, histogram, i18n, legend, measure, presets, relief, render, ridge, rng, scan, svg,
+};
+use midi::Sequencer;
+use relief::{Camera, Terrain};
+use remote::Remote;
+use render::{Forcing, Kernel, Noise, Region, StateMap};
+use rng::Rng;
+use session::{Recorder, Replay, Session};
+use sonify::Sonifier;
+use span::SpanDisplay;
+use split::SplitView;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use term::TermDisplay;
+use touch::TouchDisplay;
+use workspace::Workspace;
 
 const WIDTH: usize = 800;
 const HEIGHT: usize = 800;
-const ITERATION_DEPTH: u32 = 300; // everything from 100+ seems to be fine
-const WARMUP: u32 = 20;
+const ROWS_PER_UPDATE: usize = 16; // rows rendered between two window updates
+const CYCLE_SPEED: f64 = 0.004; // palette rotation per frame, a full cycle takes ~4s at 60 fps
+const RESIZE_DEBOUNCE: Duration = Duration::from_millis(300); // wait for the size to settle before re-rendering
+const DEPTH_STEP: u32 = 100; // iterations added or removed by the . and , keys
+const ZOOM_PER_NOTCH: f64 = 0.8; // view size factor per mouse wheel notch, zooming out divides by it
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let (mut buffer, mut window) = init_window();
-
-    let sequence_rule = args
-        .get(1)
-        .unwrap_or(&"BBBBBBAAAAAA".to_string())
-        .chars()
-        .collect::<Vec<_>>();
-    let seq_len = sequence_rule.len();
-
-    // todo: read ranges from args,
-    let x_min = 3.4;
-    let x_max = 4.0;
-    let y_min = 2.5;
-    let y_max = 3.4;
-
-    let mut lambda_min = 5.0e5;
-    let mut lambda_max = 0.0;
-
-    // while window.is_open() && !window.is_key_down(Key::Escape) {
-    for (i, pixel) in buffer.iter_mut().enumerate() {
-        if !window.is_open() || window.is_key_down(Key::Escape) {
-            break;
-        }
-
-        // map pixel to world coordinates
-        let a = map((i % WIDTH) as f64, 0., WIDTH as f64, x_min, x_max);
-        let b = map((i / HEIGHT) as f64, 0., HEIGHT as f64, y_min, y_max);
-
-        // map sequence rules to actual values outside of inner loop
-        let sequence = sequence_rule
-            .iter()
-            .map(|r| match r {
-                'A' => a,
-                'B' => b,
-                _ => panic!("Invalid sequence"),
-            })
-            .collect::<Vec<_>>();
-        let r = |n| sequence[n as usize % seq_len];
-
-        let mut x_n = 0.5; // X_0 as start of iteration
-        let mut lambda = 0.0;
-
-        for n in 0..ITERATION_DEPTH {
-            // ignore the first iterations or we always have -inf as first value as log(1-2*0.5) = log(0) = -inf
-            if n > WARMUP || x_n != 0.5 {
-                // sum for ljapunow exponent
-                lambda += (r(n) * (1.0 - 2.0 * x_n)).abs().ln();
-            }
+    if let Err(error) = run() {
+        eprintln!("{}", i18n::trf("error: {0}", &[&error]));
+        std::process::exit(error.exit_code());
+    }
+}
 
-            // iterate x to next value
-            x_n = r(n) * x_n * (1.0 - x_n);
+fn run() -> Result<(), Error> {
+    let cli = Cli::parse_localized()?;
+    let _trace = cli.trace.as_deref().map(trace::start).transpose()?;
+    if let Some(threads) = cli.threads {
+        render::set_threads(threads as usize);
+    }
+    render::set_nice(cli.nice);
 
-            // shortcut if we are already out of bounds
-            if lambda > 1e12 || lambda < -1e12 {
-                break;
-            }
+    // headless subcommands
+    match &cli.command {
+        Some(Command::Blend(args)) => return blend::run(args),
+        Some(Command::Evolve(args)) => return evolve::run(args, cli.rng_seed),
+        Some(Command::Dump(args)) => return dump::run(args),
+        Some(Command::Heightmap(args)) => return heightmap::run(args),
+        Some(Command::Tiles(args)) => return tiles::run(args),
+        Some(Command::Sweep(args)) => return sweep::run(args),
+        Some(Command::Stream(args)) => return stream::run(args),
+        Some(Command::Script(args)) => return script::run(args),
+        Some(Command::Pipe) => return pipe::run(),
+        Some(Command::Diff(args)) => return diff::run(args),
+        Some(Command::Sensitivity(args)) => return sensitivity::run(args),
+        Some(Command::Completions { shell }) => {
+            clap_complete::generate(*shell, &mut Cli::command(), "ljapunow", &mut io::stdout());
+            return Ok(());
         }
-        lambda /= (ITERATION_DEPTH - WARMUP) as f64;
+        None => {}
+    }
 
-        if lambda < lambda_min {
-            lambda_min = lambda;
+    if cli.list_presets {
+        for preset in &presets::PRESETS {
+            let Region {
+                x_min,
+                x_max,
+                y_min,
+                y_max,
+            } = preset.region;
+            let ranges = format!("a {x_min}..{x_max} b {y_min}..{y_max}");
+            println!(
+                "{:<12} {:<14} {ranges:<28} {:<9} {}",
+                preset.name,
+                preset.sequence,
+                preset.palette.name(),
+                preset.description
+            );
         }
-        if lambda > lambda_max {
-            lambda_max = lambda;
+        return Ok(());
+    }
+
+    // explicit arguments win over the preset
+    let setup = tracing::info_span!("map setup").entered();
+    // nothing chooses the view: the window starts at a random preset to discover. --out, --term and
+    // scripts keep the default, replays and workspaces bring their own view
+    let featured = cli.preset.is_none()
+        && cli.sequence.is_none()
+        && cli.region.is_none()
+        && !(cli.default_preset || cli.circle || cli.complex)
+        && cli.out.is_none()
+        && cli.term.is_none()
+        && cli.replay.is_none()
+        && cli.workspace.is_none();
+    let preset = match cli.preset {
+        Some(preset) => preset,
+        None if featured => {
+            let mut rng = Rng::new(cli.rng_seed.unwrap_or_else(rng::time_seed));
+            let preset = &presets::PRESETS[rng.below(presets::PRESETS.len())];
+            let Region {
+                x_min,
+                x_max,
+                y_min,
+                y_max,
+            } = preset.region;
+            println!(
+                "{}",
+                i18n::trf(
+                    "preset {0}: {1}, {2} a {3}..{4} b {5}..{6}, --preset {0} shows it again",
+                    &[
+                        &preset.name,
+                        &preset.description,
+                        &preset.sequence,
+                        &x_min,
+                        &x_max,
+                        &y_min,
+                        &y_max
+                    ],
+                )
+            );
+            preset
         }
-        // println!("lambda {lambda} a {a} b {b}");
+        None => presets::find(presets::DEFAULT_PRESET).expect("the default preset exists"),
+    };
+    let sequence = cli.sequence.as_deref().unwrap_or(preset.sequence);
+    let mut alphabet = cli.values.clone().unwrap_or_default();
+    for (letter, forcing) in &cli.letters {
+        alphabet.define(*letter, forcing.clone());
+    }
+    if let (Some(x), Some(y)) = (cli.x_axis, cli.y_axis) {
+        if x == y {
+            return Err(Error::usage(i18n::trf(
+                "--x-axis and --y-axis need different letters, both are {0}",
+                &[&x],
+            )));
+        }
+        alphabet.bind(x, Forcing::A);
+        alphabet.bind(y, Forcing::B);
+    }
+    let mut kernel = Kernel::new(alphabet.sequence(sequence)?);
+    kernel.alphabet = alphabet;
+    kernel.bailout = cli.bailout;
+    kernel.on_bailout = cli.on_bailout;
+    kernel.on_log_zero = cli.on_log_zero;
+    kernel.superstable = cli.superstable;
+    kernel.noise = cli.noise.map(|amplitude| Noise {
+        amplitude,
+        seed: cli.noise_seed.or(cli.rng_seed).unwrap_or(0),
+        realizations: cli.realizations,
+    });
+    kernel.measure = cli.measure;
+    kernel.drift = cli.drift;
+    kernel.external = cli.wasm.as_deref().map(wasm::load).transpose()?;
+    if let Some(depth) = cli.depth {
+        kernel.depth = depth;
+    }
+    if let Some(coupling) = cli.coupling {
+        kernel.map = StateMap::Coupled(coupling, cli.spectrum);
+    }
+    let mut region = cli.region.unwrap_or(preset.region);
+    if cli.circle {
+        kernel.map = StateMap::Circle;
+        region = cli.region.unwrap_or(Region::ARNOLD_TONGUES);
+    }
+    if cli.complex {
+        kernel.map = StateMap::Complex;
+        region = cli.region.unwrap_or(Region::LOGISTIC_MANDELBROT);
+    }
+    let mut palette = cli.palette.unwrap_or(preset.palette);
+    drop(setup);
+
+    // a replayed session brings its own starting state
+    let replay = cli
+        .replay
+        .as_deref()
+        .map(|path| Session::load(path, &kernel.alphabet))
+        .transpose()?;
+    if let Some(session) = &replay {
+        kernel.sequence_rule = kernel.alphabet.sequence(&session.sequence)?;
+        kernel.depth = session.depth;
+        region = session.start_region();
+        palette = session::parse_palette(&session.palette);
+    }
+    // and so does a saved workspace
+    let workspace = cli
+        .workspace
+        .as_deref()
+        .map(|path| Workspace::load(path, &kernel.alphabet))
+        .transpose()?
+        .flatten();
+    if let Some(workspace) = &workspace {
+        workspace.register_palettes()?;
+        kernel.sequence_rule = kernel.alphabet.sequence(&workspace.sequence)?;
+        kernel.depth = workspace.depth;
+        region = workspace.start_region();
+        palette = session::parse_palette(&workspace.palette);
+    }
 
-        // map to color
-        *pixel = if lambda > 0.0 {
-            0x00
+    let clahe = cli.clahe.map(|strength| Clahe {
+        strength,
+        tile: cli.clahe_tile as usize,
+    });
+
+    // headless render into a file
+    if let Some(out) = &cli.out {
+        interrupt::install();
+        let path = &template::expand(&out.to_string_lossy(), &kernel, &region, palette);
+        let (width, height) = cli.out_size;
+        let mut frame = Frame::new(width, height);
+        frame.clahe = clahe;
+        frame.transfer = cli.transfer;
+        frame.chaos_gradient = cli.chaos_gradient;
+        let first = match cli.adaptive_boundary {
+            Some(_) => render::shallow(&kernel),
+            None => kernel.clone(),
+        };
+        let lambdas = &mut frame.lambdas;
+        if cli.quadtree {
+            let computed = render::render_quadtree(lambdas, width, height, &region, &first);
+            let total = width * height;
+            println!(
+                "{}",
+                i18n::trf("quadtree: {0} of {1} pixels computed", &[&computed, &total])
+            );
         } else {
-            color_ramp(lambda)
-            //color_gradient(lambda)
-            //0xFF
+            // in blocks of rows, so an interrupt still leaves the rows done so far
+            let mut done = 0;
+            while done < height && !interrupt::requested() {
+                let rows = done..(done + ROWS_PER_UPDATE).min(height);
+                render::render_rows(lambdas, width, height, &region, &first, rows.clone());
+                done = rows.end;
+            }
+            if done < height {
+                frame.colorize_rows(0..height, palette);
+                save_view(path, &frame, &region, None, None, None)?;
+                return Err(Error::Interrupted(format!(
+                    "{done} of {height} rows in {}",
+                    path.display()
+                )));
+            }
+        }
+        let lambdas = &mut frame.lambdas;
+        if let Some(quality) = cli.adaptive_boundary {
+            let refined = render::refine_border(lambdas, width, height, &region, &kernel, quality);
+            println!("{}", i18n::trf("{0} border pixels refined", &[&refined]));
+        }
+        if let Some(ridge_path) = &cli.ridge {
+            let polylines = ridge::trace(lambdas, width, height, &region, &kernel, cli.ridge_steps);
+            ridge::save(ridge_path, &polylines).map_err(|e| Error::io(ridge_path, e))?;
+            let points: usize = polylines.iter().map(|line| line.points.len()).sum();
+            println!(
+                "{}",
+                i18n::trf(
+                    "λ = 0: {0} lines with {1} points in {2}",
+                    &[&polylines.len(), &points, &ridge_path.display()]
+                )
+            );
+        }
+        let min_area = cli.min_area as usize;
+        let components = (cli.components.is_some() || cli.color_components)
+            .then(|| Components::label(&frame.lambdas, width, height));
+        if let (Some(components), Some(csv)) = (&components, &cli.components) {
+            let largest = components.largest(min_area);
+            let area = largest.first().map_or(0, |component| component.area);
+            println!(
+                "{}",
+                i18n::trf(
+                    "{0} stable regions of at least {1} pixels, the largest {2} pixels",
+                    &[&largest.len(), &min_area, &area]
+                )
+            );
+            if let Some(csv) = csv {
+                components
+                    .save(csv, &region, min_area)
+                    .map_err(|e| Error::io(csv, e))?;
+                println!("{}", i18n::trf("saved {0}", &[&csv.display()]));
+            }
+        }
+        frame.colorize_rows(0..height, palette);
+        if let (Some(components), true) = (&components, cli.color_components) {
+            components.colorize(&mut frame.buffer, min_area);
+        }
+        filter::apply_all(&cli.filters, &mut frame.buffer, width, height);
+        let transparent =
+            |lambda: f64| (cli.alpha_chaos && lambda > 0.0) || (cli.alpha_stable && lambda < 0.0);
+        for (pixel, &lambda) in frame.buffer.iter_mut().zip(&frame.lambdas) {
+            if transparent(lambda) {
+                *pixel |= export::TRANSPARENT;
+            }
+        }
+        // the stereo pair is saved in place of the diagram, the statistics stay those of the diagram
+        let stereo = cli.stereo.map(|stereo| {
+            let terrain = Terrain::new(&frame.lambdas, &frame.buffer, width, height);
+            let (camera, separation) = (Camera::default(), cli.eye_separation);
+            let mut pair = match stereo {
+                Stereo::Anaglyph => Frame::new(width, height),
+                Stereo::SideBySide => Frame::new(2 * width, height),
+            };
+            pair.buffer = match stereo {
+                Stereo::Anaglyph => terrain.anaglyph(&camera, (width, height), separation),
+                Stereo::SideBySide => terrain.side_by_side(&camera, (width, height), separation),
+            };
+            pair
+        });
+        let legend = cli.legend.then_some(palette);
+        let histogram = cli.histogram.then_some(palette);
+        let caption = Caption {
+            margin: cli.margin as usize,
+            text: cli
+                .caption
+                .as_deref()
+                .map(|text| template::expand_text(text, &kernel, &region, palette, 0)),
+            signature: cli.signature.clone(),
         };
+        let caption = (caption != Caption::default()).then_some(&caption);
+        let saved = stereo.as_ref().unwrap_or(&frame);
+        save_view(path, saved, &region, legend, histogram, caption)?;
+        print_stats(&frame);
+        return Ok(());
+    }
 
-        // how to update window while buffer is borrowed mutable?
+    if let Some(mode) = cli.term {
+        let mut display = TermDisplay::new(mode);
+        let (width, height) = display.size();
+        let mut frame = Frame::new(width, height);
+        frame.clahe = clahe;
+        frame.transfer = cli.transfer;
+        frame.chaos_gradient = cli.chaos_gradient;
+        render::render_rows(
+            &mut frame.lambdas,
+            width,
+            height,
+            &region,
+            &kernel,
+            0..height,
+        );
+        frame.colorize_rows(0..height, palette);
+        display.present(&frame.buffer, width, height);
+        print_stats(&frame);
+        return Ok(());
     }
 
-    println!("λ: ({lambda_min}..{lambda_max})");
+    // checked before a window opens
+    let mut split = (!cli.panes.is_empty())
+        .then(|| {
+            SplitView::new(
+                &cli.panes,
+                region,
+                &kernel,
+                palette,
+                (clahe, cli.transfer, cli.chaos_gradient),
+            )
+        })
+        .transpose()?;
+    // winit finds the screen size of a --touch window itself
+    let fullscreen = if cli.fullscreen && !cli.touch {
+        let size = cli
+            .screen_size
+            .or_else(screen::desktop_size)
+            .ok_or_else(|| {
+                Error::usage(
+                    "unable to detect the screen size, pass it with --screen-size WIDTHxHEIGHT",
+                )
+            })?;
+        Some(size)
+    } else {
+        None
+    };
+    let scale = cli.scale as usize;
+    let mut display: Box<dyn Display> = match (&cli.fbdev, cli.span, fullscreen) {
+        _ if cli.touch => Box::new(TouchDisplay::open(cli.fullscreen, scale, cli.upscale)?),
+        (Some(device), _, _) => Box::new(FbDisplay::open(device)?),
+        (None, Some(grid), Some(screen)) => {
+            Box::new(SpanDisplay::open(grid, screen, scale, cli.upscale)?)
+        }
+        (None, _, _) => Box::new(MinifbDisplay::new(fullscreen, scale, cli.upscale)?),
+    };
+    let display = display.as_mut();
+    if featured && replay.is_none() && workspace.is_none() {
+        display.set_title(&format!(
+            "{} - {} - {}",
+            preset.name,
+            preset.description,
+            i18n::tr("press ESC to exit")
+        ));
+    }
 
-    // We unwrap here as we want this code to exit if it fails
-    window.update_with_buffer(&buffer, WIDTH, HEIGHT).unwrap();
+    if cli.attract {
+        attract::run(display, cli.rng_seed);
+        return Ok(());
+    }
 
-    // wait for window close
-    while window.is_open() && !window.is_key_down(Key::Escape) {
-        window.update();
+    if let Some(split) = &mut split {
+        return split.run(display);
     }
-}
-// }
-
-fn init_window() -> (Vec<u32>, Window) {
-    let buffer = vec![0u32; WIDTH * HEIGHT];
-
-    let mut window = Window::new(
-        "Ljapunow-Markus-Diagramm - press ESC to exit",
-        WIDTH,
-        HEIGHT,
-        WindowOptions {
-            resize: true,
-            scale: Scale::X1, // scale: Scale::X2,
-            scale_mode: ScaleMode::AspectRatioStretch,
-            ..WindowOptions::default()
-        },
-    )
-    .expect("Unable to create the window");
-    window.set_target_fps(60);
-    window.set_background_color(0, 0, 20);
-
-    (buffer, window)
+
+    let recorder = match &cli.record {
+        Some(path) => {
+            let session = Session::new(&kernel.sequence_rule, &region, palette, kernel.depth);
+            Some(Recorder::create(path, session)?)
+        }
+        None => None,
+    };
+    let watch = cli
+        .watch
+        .as_deref()
+        .map(|path| ConfigWatch::new(path, &kernel.alphabet))
+        .transpose()?;
+    let remote = cli.remote.as_deref().map(Remote::listen).transpose()?;
+    let mut explorer = Explorer::new(display.size(), region, kernel, palette);
+    explorer.output_template(cli.output_template.clone());
+    if let Some(watch) = watch {
+        explorer.watch(watch);
+    }
+    if let Some(remote) = remote {
+        explorer.remote(remote);
+    }
+    if let Some(recorder) = recorder {
+        explorer.record(recorder);
+    }
+    if let Some(path) = &cli.workspace {
+        let mut palettes = workspace
+            .as_ref()
+            .map(|w| w.palettes.clone())
+            .unwrap_or_default();
+        if let Some(file) = &cli.palettes {
+            palettes.extend(Config::load(file)?.palettes);
+        }
+        explorer.workspace(path.clone(), palettes);
+    }
+    if let Some(factor) = cli.refine {
+        explorer.refine(factor);
+    }
+    if let Some(quality) = cli.adaptive_boundary {
+        explorer.adaptive_boundary(quality);
+    }
+    if cli.quadtree {
+        explorer.quadtree();
+    }
+    if cli.histogram {
+        explorer.show_histogram();
+    }
+    if cli.colorbar {
+        explorer.show_colorbar();
+    }
+    if cli.terrain {
+        explorer.show_terrain();
+    }
+    if let Some(mode) = cli.sonify {
+        explorer.sonify(Sonifier::start(mode)?);
+    }
+    if let Some(port) = &cli.midi {
+        explorer.midi(Sequencer::connect(port)?);
+    }
+    if cli.gamepad {
+        explorer.gamepad(Gamepad::open()?);
+    }
+    if let Some(dir) = &cli.autosave_dir {
+        explorer.autosave(dir.clone());
+    }
+    if let Some(clahe) = clahe {
+        explorer.clahe(clahe);
+    }
+    if let Some(transfer) = cli.transfer {
+        explorer.transfer(transfer);
+    }
+    if cli.chaos_gradient {
+        explorer.chaos_gradient();
+    }
+    if let Some(scale) = cli.render_scale {
+        explorer.render_scale(scale, cli.upscale);
+    }
+    if let Some(path) = &cli.cache {
+        let cache = LambdaCache::load(path, cli.cache_size).map_err(|e| Error::io(path, e))?;
+        println!(
+            "{}",
+            i18n::trf(
+                "λ cache: {0} entries from {1}",
+                &[&cache.len(), &path.display()]
+            )
+        );
+        explorer.cache(cache, path.clone());
+    }
+    let close_after = match cli.display_seconds {
+        Some(seconds) => Some(Duration::from_secs_f64(seconds)),
+        None => cli.exit_after_render.then_some(Duration::ZERO),
+    };
+    if let Some(after) = close_after {
+        explorer.close_after(after);
+    }
+    if let Some(session) = &replay {
+        explorer.replay(Replay::new(session), cli.replay_frames.clone());
+    }
+    if let (Some(workspace), Some(path)) = (&workspace, &cli.workspace) {
+        explorer.restore(workspace, path);
+    }
+    explorer.run(display)
 }
 
-// map / lerp between to ranges
-fn map(val: f64, start1: f64, stop1: f64, start2: f64, stop2: f64) -> f64 {
-    start2 + (stop2 - start2) * ((val - start1) / (stop1 - start1))
+fn print_stats(frame: &Frame) {
+    let (lambda_min, lambda_max) = analysis::lambda_range(&frame.lambdas);
+    println!("λ: ({lambda_min}..{lambda_max})");
+    let dimension =
+        match analysis::box_counting_dimension(&frame.lambdas, frame.width, frame.height) {
+            Some(dimension) => format!("{dimension:.4}"),
+            None => i18n::tr("no chaos boundary in view").into(),
+        };
+    println!(
+        "{}",
+        i18n::trf("boundary dimension (box counting): {0}", &[&dimension])
+    );
 }
 
-// map to a byte range and shift in target range. 0 for values outside of range.
-fn map_byte(val: f64, start1: f64, stop1: f64, start2: f64, stop2: f64, shift: u32) -> u32 {
-    if val < start1 || val > stop1 {
-        return 0;
+// PNG, or SVG with the λ = 0 border as vector paths and labeled axes if the name ends in .svg.
+// with a legend palette, axes and a colorbar for it are added, with a histogram palette a PNG gets
+// the λ distribution as a strip below and with a caption a margin and text lines around everything
+fn save_view(
+    path: &Path,
+    frame: &Frame,
+    region: &Region,
+    legend: Option<Palette>,
+    histogram: Option<Palette>,
+    caption: Option<&Caption>,
+) -> Result<(), Error> {
+    let is_svg = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        svg::save_svg(path, frame, region, legend)
+    } else {
+        let mut image = match legend {
+            Some(palette) => legend::compose(frame, region, palette),
+            None => (frame.buffer.clone(), frame.width, frame.height),
+        };
+        if let Some(palette) = histogram {
+            image = histogram::append(image, &frame.lambdas, palette);
+        }
+        if let Some(caption) = caption {
+            image = caption.compose(image);
+        }
+        let (buffer, width, height) = image;
+        export::save_png(path, &buffer, width, height)
     }
-
-    (map(val, start1, stop1, start2, stop2)
-        .round()
-        .clamp(0.0, 255.0) as u32)
-        << shift
+    .map_err(|e| Error::io(path, e))?;
+    println!("{}", i18n::trf("saved {0}", &[&path.display()]));
+    Ok(())
 }
 
-const RED_SHIFT: u32 = 16;
-const GREEN_SHIFT: u32 = 8;
-const BLUE_SHIFT: u32 = 0;
-// simple RGB ramp
-#[allow(dead_code)]
-fn color_ramp(lambda: f64) -> u32 {
-    map_byte(lambda, -2.0, 0.5, 196.0, 255.0, RED_SHIFT)
-        + map_byte(lambda, -0.5, 0.0, 0.0, 255.0, GREEN_SHIFT)
-        + map_byte(lambda, -2.5, 0.5, 10.0, 55.0, BLUE_SHIFT)
-}
+// render the λ grid a few rows at a time and show every chunk as soon as it is done.
+// false if the window was closed or ESC pressed before the diagram was complete.
+pub fn render_progressive(
+    display: &mut dyn Display,
+    frame: &mut Frame,
+    region: &Region,
+    kernel: &Kernel,
+    palette: Palette,
+    mut cache: Option<&mut LambdaCache>,
+) -> bool {
+    let (width, height) = frame.size();
+    if !LambdaCache::covers(region, width, height) {
+        cache = None;
+    }
+    for start in (0..height).step_by(ROWS_PER_UPDATE) {
+        if !display.is_open() {
+            return false;
+        }
 
-// interpolate along a color gradient
-#[allow(dead_code)]
-fn color_gradient(lambda: f64) -> u32 {
-    let gradient = [0x161c31, 0x613c62, 0xb75f74, 0xf29a6b, 0xfaec70];
-    let ranges = [-2.5, -1.5, -0.8, -0.2, 0.0, 4.0];
+        let rows = start..(start + ROWS_PER_UPDATE).min(height);
+        match cache.as_deref_mut() {
+            Some(cache) => cache.render_rows(
+                &mut frame.lambdas,
+                width,
+                height,
+                region,
+                kernel,
+                rows.clone(),
+            ),
+            None => render::render_rows(
+                &mut frame.lambdas,
+                width,
+                height,
+                region,
+                kernel,
+                rows.clone(),
+            ),
+        }
+        frame.colorize_rows(rows, palette);
 
-    // find the range via simple search, no need for binary
-    let mut pos = 1;
-    while pos < ranges.len() && ranges[pos] < lambda {
-        pos += 1;
+        display.present(&frame.buffer, width, height);
+        display.poll_events();
     }
-
-    // -0.3 -> pos 3 -> gradient[2]..gradient[3]
-    let g1 = gradient[pos - 1] as f64;
-    let g2 = gradient[pos] as f64;
-    // todo: interpolate in hsl or lab space, rgb is not good for linear interpolation
-    map(lambda, ranges[pos - 1], ranges[pos], g1, g2) as u32
+    true
 }
@@ -0,0 +1,247 @@
+// per pixel quantities besides the λ loop in render.rs. every measure sees the orbit after the warmup
+// and reduces it to one value that is colored like λ. a new quantity is a PixelMeasure plus a Measure variant
+
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+use std::cell::RefCell;
+
+// one iteration: the state before the step and the derivative of the map there
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub x: f64,
+    pub derivative: f64,
+}
+
+pub trait PixelMeasure {
+    fn measure(&self, orbit: &mut dyn Iterator<Item = Step>) -> f64;
+}
+
+// the quantity shown, selected on the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Measure {
+    /// Ljapunow exponent λ, with bailout and log(0) handling
+    #[default]
+    Lyapunov,
+    /// correlation of consecutive states, -1 (period 2) to 1 (fixed point)
+    Autocorrelation,
+    /// mean advance of the state per step, the rotation number of the circle map
+    Winding,
+    /// permutation entropy of the orbit, 0 (periodic) to 1 (all orderings equally likely)
+    Entropy,
+    /// dominant frequency of the orbit in cycles per step, 0.5 for period 2, 0.25 for period 4
+    Frequency,
+    /// spectral flatness of the orbit, 0 (periodic) to 1 (white noise)
+    Flatness,
+}
+
+impl Measure {
+    pub fn plugin(self) -> &'static dyn PixelMeasure {
+        match self {
+            Measure::Lyapunov => &Lyapunov,
+            Measure::Autocorrelation => &Autocorrelation,
+            Measure::Winding => &Winding,
+            Measure::Entropy => &PermutationEntropy,
+            Measure::Frequency => &Spectral::DominantFrequency,
+            Measure::Flatness => &Spectral::Flatness,
+        }
+    }
+}
+
+// plain mean of log|derivative|, the kernel uses its own loop with all the policies for λ instead
+pub struct Lyapunov;
+
+impl PixelMeasure for Lyapunov {
+    fn measure(&self, orbit: &mut dyn Iterator<Item = Step>) -> f64 {
+        let (sum, terms) = orbit
+            .map(|step| step.derivative.abs())
+            .filter(|&derivative| derivative != 0.0)
+            .fold((0.0, 0), |(sum, terms), derivative| {
+                (sum + derivative.ln(), terms + 1)
+            });
+        sum / terms.max(1) as f64
+    }
+}
+
+// lag 1 autocorrelation of x, orbits without any variance sit on a fixed point and count as 1
+pub struct Autocorrelation;
+
+impl PixelMeasure for Autocorrelation {
+    fn measure(&self, orbit: &mut dyn Iterator<Item = Step>) -> f64 {
+        let x: Vec<f64> = orbit.map(|step| step.x).collect();
+        if x.len() < 2 {
+            return f64::NAN;
+        }
+        let mean = x.iter().sum::<f64>() / x.len() as f64;
+        let variance: f64 = x.iter().map(|x| (x - mean).powi(2)).sum();
+        if variance <= f64::EPSILON * x.len() as f64 {
+            return 1.0;
+        }
+        let covariance: f64 = x.windows(2).map(|w| (w[0] - mean) * (w[1] - mean)).sum();
+        covariance / variance
+    }
+}
+
+// (x_last - x_first) / steps, needs an unwrapped state like the lifted θ of the circle map
+pub struct Winding;
+
+impl PixelMeasure for Winding {
+    fn measure(&self, orbit: &mut dyn Iterator<Item = Step>) -> f64 {
+        let Some(first) = orbit.next() else {
+            return f64::NAN;
+        };
+        let (last, steps) = orbit.fold((first.x, 0), |(_, steps), step| (step.x, steps + 1));
+        (last - first.x) / steps.max(1) as f64
+    }
+}
+
+// consecutive states compared by permutation entropy, 4! orderings are plenty for a few hundred steps
+const ENTROPY_ORDER: usize = 4;
+
+// Bandt-Pompe permutation entropy: how evenly the orderings of ENTROPY_ORDER consecutive states are spread,
+// normalized by log(ENTROPY_ORDER!). tells apart chaotic regimes λ alone colors the same
+pub struct PermutationEntropy;
+
+impl PixelMeasure for PermutationEntropy {
+    fn measure(&self, orbit: &mut dyn Iterator<Item = Step>) -> f64 {
+        let x: Vec<f64> = orbit.map(|step| step.x).collect();
+        let patterns: usize = (1..=ENTROPY_ORDER).product();
+        let mut counts = vec![0usize; patterns];
+        for window in x.windows(ENTROPY_ORDER) {
+            // lehmer code of the ordering, ties count as the earlier state being smaller
+            let index = (0..ENTROPY_ORDER).fold(0, |index, i| {
+                let smaller_after = window[i + 1..].iter().filter(|&&v| v < window[i]).count();
+                index * (ENTROPY_ORDER - i) + smaller_after
+            });
+            counts[index] += 1;
+        }
+        let total: usize = counts.iter().sum();
+        if total == 0 {
+            return f64::NAN;
+        }
+        let entropy: f64 = counts
+            .iter()
+            .filter(|&&count| count > 0)
+            .map(|&count| count as f64 / total as f64)
+            .fold(0.0, |entropy, p| entropy - p * p.ln());
+        entropy / (patterns as f64).ln()
+    }
+}
+
+thread_local! {
+    // plans are cached by length, every pixel of a diagram has the same orbit length
+    static PLANNER: RefCell<FftPlanner<f64>> = RefCell::new(FftPlanner::new());
+}
+
+// power spectrum of the orbit after the warmup, its length is set with the iteration depth
+pub enum Spectral {
+    DominantFrequency,
+    Flatness,
+}
+
+impl PixelMeasure for Spectral {
+    fn measure(&self, orbit: &mut dyn Iterator<Item = Step>) -> f64 {
+        let x: Vec<f64> = orbit.map(|step| step.x).collect();
+        if x.len() < 2 || x.iter().any(|x| !x.is_finite()) {
+            return f64::NAN;
+        }
+        let mean = x.iter().sum::<f64>() / x.len() as f64;
+        let mut spectrum: Vec<Complex<f64>> =
+            x.iter().map(|&x| Complex::new(x - mean, 0.0)).collect();
+        PLANNER.with(|planner| {
+            planner
+                .borrow_mut()
+                .plan_fft_forward(spectrum.len())
+                .process(&mut spectrum)
+        });
+        // the mean is gone, so the DC bin is skipped. up to Nyquist, the rest mirrors it
+        let power: Vec<f64> = spectrum[1..=x.len() / 2]
+            .iter()
+            .map(|c| c.norm_sqr())
+            .collect();
+        let total: f64 = power.iter().sum();
+        // a fixed point has no frequency at all and is perfectly ordered
+        if total <= f64::EPSILON {
+            return 0.0;
+        }
+        match self {
+            Spectral::DominantFrequency => {
+                let peak = (0..power.len())
+                    .max_by(|&i, &j| power[i].total_cmp(&power[j]))
+                    .unwrap_or(0);
+                (peak + 1) as f64 / x.len() as f64
+            }
+            Spectral::Flatness => {
+                // geometric over arithmetic mean, empty bins make it 0
+                let log_mean = power
+                    .iter()
+                    .map(|p| p.max(f64::MIN_POSITIVE).ln())
+                    .sum::<f64>()
+                    / power.len() as f64;
+                log_mean.exp() / (total / power.len() as f64)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn measure(measure: Measure, states: &[f64]) -> f64 {
+        let mut orbit = states.iter().map(|&x| Step { x, derivative: 1.0 });
+        measure.plugin().measure(&mut orbit)
+    }
+
+    fn period(states: &[f64], steps: usize) -> Vec<f64> {
+        states.iter().copied().cycle().take(steps).collect()
+    }
+
+    fn noise(steps: usize) -> Vec<f64> {
+        let mut rng = Rng::new(7);
+        (0..steps).map(|_| rng.next_f64()).collect()
+    }
+
+    #[test]
+    fn entropy_of_ordered_and_random_orbits() {
+        let rising: Vec<f64> = (0..100).map(|i| i as f64).collect();
+        assert_eq!(measure(Measure::Entropy, &rising), 0.0);
+
+        // two orderings equally often
+        let alternating = measure(Measure::Entropy, &period(&[0.2, 0.8], 100));
+        assert!(
+            (alternating - 2f64.ln() / 24f64.ln()).abs() < 1e-3,
+            "{alternating}"
+        );
+
+        let random = measure(Measure::Entropy, &noise(5000));
+        assert!(random > 0.98, "{random}");
+    }
+
+    #[test]
+    fn entropy_needs_a_full_window() {
+        assert!(measure(Measure::Entropy, &[0.1, 0.2, 0.3]).is_nan());
+    }
+
+    #[test]
+    fn dominant_frequency_of_periodic_orbits() {
+        let frequency = |states: &[f64]| measure(Measure::Frequency, &period(states, 256));
+        assert_eq!(frequency(&[0.2, 0.8]), 0.5);
+        assert_eq!(frequency(&[0.1, 0.5, 0.9, 0.5]), 0.25);
+        assert_eq!(frequency(&[0.5]), 0.0);
+    }
+
+    #[test]
+    fn flatness_tells_periodic_orbits_from_noise() {
+        let periodic = measure(Measure::Flatness, &period(&[0.1, 0.5, 0.9, 0.5], 256));
+        assert!(periodic < 1e-3, "{periodic}");
+        let random = measure(Measure::Flatness, &noise(256));
+        assert!(random > 0.4, "{random}");
+    }
+
+    #[test]
+    fn spectra_of_diverged_orbits_are_missing() {
+        assert!(measure(Measure::Frequency, &[0.5, f64::INFINITY, 0.5]).is_nan());
+        assert!(measure(Measure::Flatness, &[0.5]).is_nan());
+    }
+}
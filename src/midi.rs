@@ -0,0 +1,225 @@
+// experimental: playing the diagram on a MIDI instrument. with --midi, dragging with the left button
+// draws a path through the flat diagram and every few pixels along it the orbit there becomes a note:
+// a periodic orbit plays the note of its period on channel 1, a chaotic one a note following its last
+// state on channel 2, so the two can go to different instruments. once the button is released the path
+// plays in a loop until a new one is drawn, a click without dragging clears it.
+// MIDI output needs the midi feature (midir, on Linux the ALSA development files), without it --midi
+// only reports that.
+
+#[cfg(feature = "midi")]
+pub use output::Sequencer;
+#[cfg(not(feature = "midi"))]
+pub use silent::Sequencer;
+
+#[cfg(feature = "midi")]
+mod output {
+    use crate::analysis;
+    use crate::error::Error;
+    use crate::i18n;
+    use crate::render::Kernel;
+    use midir::{MidiOutput, MidiOutputConnection};
+    use std::time::{Duration, Instant};
+
+    // distance of the notes along the path in pixels and their length when the path loops
+    const SPACING: f64 = 12.0;
+    const NOTE_LENGTH: Duration = Duration::from_millis(150);
+    // period 1 is C3, longer periods climb a pentatonic scale
+    const BASE_NOTE: u8 = 48;
+    const PENTATONIC: [u8; 5] = [0, 2, 4, 7, 9];
+    // chaotic orbits spread their last state over these notes
+    const CHAOS_NOTES: (u8, u8) = (60, 84);
+    const VELOCITY: u8 = 96;
+    const NOTE_ON: u8 = 0x90;
+    const NOTE_OFF: u8 = 0x80;
+    const PERIODIC_CHANNEL: u8 = 0;
+    const CHAOTIC_CHANNEL: u8 = 1;
+    // white, the points of the path drawn over the diagram
+    const MARK: u32 = 0xFFFFFF;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Note {
+        channel: u8,
+        key: u8,
+    }
+
+    pub struct Sequencer {
+        connection: MidiOutputConnection,
+        // pixel and note of every point of the path
+        path: Vec<((usize, usize), Note)>,
+        drawing: bool,
+        // the note sounding, the point it belongs to and since when
+        playing: Option<(Note, usize, Instant)>,
+    }
+
+    impl Sequencer {
+        // on the first output port whose name contains port, any port for an empty name
+        pub fn connect(port: &str) -> Result<Self, Error> {
+            let midi = |e: &dyn std::fmt::Display| Error::Midi(e.to_string());
+            let output = MidiOutput::new("ljapunow").map_err(|e| midi(&e))?;
+            let ports = output.ports();
+            let found = ports
+                .iter()
+                .find(|p| output.port_name(p).is_ok_and(|name| name.contains(port)))
+                .ok_or_else(|| {
+                    Error::Midi(i18n::trf("no MIDI output port matching '{0}'", &[&port]))
+                })?;
+            let name = output.port_name(found).unwrap_or_default();
+            let connection = output.connect(found, "ljapunow").map_err(|e| midi(&e))?;
+            println!("{}", i18n::trf("MIDI output: {0}", &[&name]));
+            Ok(Sequencer {
+                connection,
+                path: Vec::new(),
+                drawing: false,
+                playing: None,
+            })
+        }
+
+        // left button down, a new path replaces the old one
+        pub fn begin(&mut self) {
+            self.stop();
+            self.path.clear();
+            self.drawing = true;
+        }
+
+        // the mouse moved with the button down, a note for every SPACING pixels, played right away
+        // true if a point was added
+        pub fn extend(
+            &mut self,
+            (x, y): (usize, usize),
+            (a, b): (f64, f64),
+            kernel: &Kernel,
+        ) -> bool {
+            if !self.drawing {
+                return false;
+            }
+            let far_enough = self.path.last().is_none_or(|&((last_x, last_y), _)| {
+                (x as f64 - last_x as f64).hypot(y as f64 - last_y as f64) >= SPACING
+            });
+            if far_enough {
+                let note = note(&kernel.states(a, b));
+                self.path.push(((x, y), note));
+                self.sound(self.path.len() - 1);
+            }
+            far_enough
+        }
+
+        // button released, the path loops from its start. a click without a path silences it
+        pub fn end(&mut self) {
+            self.drawing = false;
+            if self.path.len() < 2 {
+                self.path.clear();
+                self.stop();
+            }
+        }
+
+        // called every frame, moves the loop on to the next note once the current one is over
+        pub fn tick(&mut self) {
+            if self.drawing || self.path.is_empty() {
+                return;
+            }
+            match self.playing {
+                Some((_, point, since)) if since.elapsed() >= NOTE_LENGTH => {
+                    self.sound((point + 1) % self.path.len())
+                }
+                Some(_) => {}
+                None => self.sound(0),
+            }
+        }
+
+        // the path as dots over the diagram
+        pub fn draw(&self, buffer: &mut [u32], width: usize, height: usize) {
+            for &((x, y), _) in &self.path {
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let (x, y) = (x + dx, y + dy);
+                    if x < width && y < height {
+                        buffer[y * width + x] = MARK;
+                    }
+                }
+            }
+        }
+
+        // the connection may go away with the device, playing just stops then
+        fn sound(&mut self, point: usize) {
+            self.stop();
+            let note = self.path[point].1;
+            let _ = self
+                .connection
+                .send(&[NOTE_ON | note.channel, note.key, VELOCITY]);
+            self.playing = Some((note, point, Instant::now()));
+        }
+
+        fn stop(&mut self) {
+            if let Some((note, _, _)) = self.playing.take() {
+                let _ = self
+                    .connection
+                    .send(&[NOTE_OFF | note.channel, note.key, 0]);
+            }
+        }
+    }
+
+    impl Drop for Sequencer {
+        fn drop(&mut self) {
+            self.stop();
+        }
+    }
+
+    fn note(states: &[f64]) -> Note {
+        match analysis::period(states) {
+            Some(period) => {
+                let (octave, step) = (
+                    (period - 1) / PENTATONIC.len(),
+                    (period - 1) % PENTATONIC.len(),
+                );
+                Note {
+                    channel: PERIODIC_CHANNEL,
+                    key: BASE_NOTE + 12 * octave as u8 + PENTATONIC[step],
+                }
+            }
+            None => {
+                let (low, high) = CHAOS_NOTES;
+                let x = states.last().copied().unwrap_or(0.0);
+                Note {
+                    channel: CHAOTIC_CHANNEL,
+                    key: low + (x * (high - low) as f64).round() as u8,
+                }
+            }
+        }
+    }
+}
+
+// without the midi feature a Sequencer cannot be connected and so never exists
+#[cfg(not(feature = "midi"))]
+mod silent {
+    use crate::error::Error;
+    use crate::i18n;
+    use crate::render::Kernel;
+
+    pub enum Sequencer {}
+
+    impl Sequencer {
+        pub fn connect(_: &str) -> Result<Self, Error> {
+            let message = i18n::tr("this build has no MIDI output, rebuild with --features midi");
+            Err(Error::Midi(message.into()))
+        }
+
+        pub fn begin(&mut self) {
+            match *self {}
+        }
+
+        pub fn extend(&mut self, _: (usize, usize), _: (f64, f64), _: &Kernel) -> bool {
+            match *self {}
+        }
+
+        pub fn end(&mut self) {
+            match *self {}
+        }
+
+        pub fn tick(&mut self) {
+            match *self {}
+        }
+
+        pub fn draw(&self, _: &mut [u32], _: usize, _: usize) {
+            match *self {}
+        }
+    }
+}
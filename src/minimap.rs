@@ -0,0 +1,111 @@
+// small overview of the whole parameter space in a corner of the window, the current view marked by
+// a rectangle. clicking it moves the view there.
+
+use crate::color::Palette;
+use crate::frame::Frame;
+use crate::render::{self, Kernel, Region};
+
+const SIZE: usize = 160;
+const MARGIN: usize = 8;
+const BORDER_COLOR: u32 = 0x808080;
+const VIEW_COLOR: u32 = 0xFFFFFF;
+// smallest view rectangle, so it does not vanish when zoomed in deep
+const MIN_VIEW_SIZE: i64 = 3;
+
+pub struct Minimap {
+    frame: Frame,
+    region: Region,
+}
+
+impl Minimap {
+    // 2..4 × 2..4 where most diagrams live, 0..4 × 0..4 if the view reaches outside of it
+    pub fn new(view: &Region, kernel: &Kernel, palette: Palette) -> Self {
+        let inside = |v: f64| (2.0..=4.0).contains(&v);
+        let region = if [view.x_min, view.x_max, view.y_min, view.y_max]
+            .into_iter()
+            .all(inside)
+        {
+            Region::new(2.0, 4.0, 2.0, 4.0)
+        } else {
+            Region::new(0.0, 4.0, 0.0, 4.0)
+        };
+
+        let mut frame = Frame::new(SIZE, SIZE);
+        render::render_rows(&mut frame.lambdas, SIZE, SIZE, &region, kernel, 0..SIZE);
+        frame.colorize_rows(0..SIZE, palette);
+        Minimap { frame, region }
+    }
+
+    pub fn recolor(&mut self, palette: Palette) {
+        self.frame.colorize_rows(0..SIZE, palette);
+    }
+
+    // top left corner in the lower right of the window, None if the window is too small for it
+    fn origin(width: usize, height: usize) -> Option<(usize, usize)> {
+        let needed = SIZE + 2 * MARGIN;
+        (width >= needed && height >= needed)
+            .then(|| (width - SIZE - MARGIN, height - SIZE - MARGIN))
+    }
+
+    pub fn draw(&self, buffer: &mut [u32], width: usize, height: usize, view: &Region) {
+        let Some((left, top)) = Minimap::origin(width, height) else {
+            return;
+        };
+        for y in 0..SIZE {
+            let start = (top + y) * width + left;
+            buffer[start..start + SIZE]
+                .copy_from_slice(&self.frame.buffer[y * SIZE..(y + 1) * SIZE]);
+        }
+
+        let (left, top, size) = (left as i64, top as i64, SIZE as i64);
+        let window = (0, 0, width as i64 - 1, height as i64 - 1);
+        let inset = (left, top, left + size - 1, top + size - 1);
+        let border = (left - 1, top - 1, left + size, top + size);
+        draw_rect(buffer, width, border, window, BORDER_COLOR);
+
+        let to_inset = |a: f64, b: f64| {
+            let x = render::map(a, self.region.x_min, self.region.x_max, 0.0, SIZE as f64);
+            let y = render::map(b, self.region.y_min, self.region.y_max, 0.0, SIZE as f64);
+            (left + x.round() as i64, top + y.round() as i64)
+        };
+        let (x0, y0) = to_inset(view.x_min, view.y_min);
+        let (x1, y1) = to_inset(view.x_max, view.y_max);
+        let rect = (
+            x0,
+            y0,
+            x1.max(x0 + MIN_VIEW_SIZE - 1),
+            y1.max(y0 + MIN_VIEW_SIZE - 1),
+        );
+        draw_rect(buffer, width, rect, inset, VIEW_COLOR);
+    }
+
+    // parameters (a, b) under a click, None if the click missed the minimap
+    pub fn hit(&self, x: usize, y: usize, width: usize, height: usize) -> Option<(f64, f64)> {
+        let (left, top) = Minimap::origin(width, height)?;
+        let inside = (left..left + SIZE).contains(&x) && (top..top + SIZE).contains(&y);
+        inside.then(|| self.region.pixel_to_world(x - left, y - top, SIZE, SIZE))
+    }
+}
+
+// outline of (x0, y0, x1, y1), both corners inclusive, only the parts inside the clip rectangle
+fn draw_rect(
+    buffer: &mut [u32],
+    width: usize,
+    (x0, y0, x1, y1): (i64, i64, i64, i64),
+    (clip_x0, clip_y0, clip_x1, clip_y1): (i64, i64, i64, i64),
+    color: u32,
+) {
+    let mut plot = |x: i64, y: i64| {
+        if (clip_x0..=clip_x1).contains(&x) && (clip_y0..=clip_y1).contains(&y) {
+            buffer[y as usize * width + x as usize] = color;
+        }
+    };
+    for x in x0..=x1 {
+        plot(x, y0);
+        plot(x, y1);
+    }
+    for y in y0..=y1 {
+        plot(x0, y);
+        plot(x1, y);
+    }
+}
@@ -0,0 +1,113 @@
+// pipe subcommand: one JSON parameter set per line on stdin, one render per line.
+// each line may set sequence, region, palette, depth, size ([width, height]) and out (with the
+// placeholders of --output-template), the rest comes from the default preset. without out the PNG bytes go to stdout, so
+// messages are written to stderr only.
+
+use crate::color::Palette;
+use crate::error::Error;
+use crate::export;
+use crate::frame::Frame;
+use crate::i18n;
+use crate::presets;
+use crate::render::{self, parse_sequence, Kernel, Region};
+use crate::session;
+use crate::template;
+use crate::{HEIGHT, WIDTH};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+// pixels along either side of a job, 8 GiB of λ at most
+const MAX_SIDE: usize = 32768;
+
+// also what --autosave-dir writes next to every snapshot, so a journal entry renders again through pipe
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Job {
+    pub sequence: Option<String>,
+    pub region: Option<[f64; 4]>,
+    pub palette: Option<String>,
+    pub depth: Option<u32>,
+    pub size: Option<[usize; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub out: Option<PathBuf>,
+}
+
+pub fn run() -> Result<(), Error> {
+    let stdin = io::stdin();
+    let stdout_path = Path::new("<stdout>");
+    for (index, line) in stdin.lock().lines().enumerate() {
+        let line = line.map_err(|e| Error::io(Path::new("<stdin>"), e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let job: Job = serde_json::from_str(&line)
+            .map_err(|e| Error::usage(i18n::trf("line {0}: {1}", &[&(index + 1), &e])))?;
+        let (frame, kernel, region, palette) = render(&job)?;
+
+        match &job.out {
+            Some(out) => {
+                let path = &template::expand(&out.to_string_lossy(), &kernel, &region, palette);
+                export::save_png(path, &frame.buffer, frame.width, frame.height)
+                    .map_err(|e| Error::io(path, e))?;
+                eprintln!("{}", path.display());
+            }
+            None => {
+                let mut stdout = io::stdout().lock();
+                export::write_png(&mut stdout, &frame.buffer, frame.width, frame.height)
+                    .and_then(|_| stdout.flush())
+                    .map_err(|e| Error::io(stdout_path, e))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn render(job: &Job) -> Result<(Frame, Kernel, Region, Palette), Error> {
+    let preset = presets::find(presets::DEFAULT_PRESET).expect("default preset exists");
+    let sequence = job.sequence.as_deref().unwrap_or(preset.sequence);
+    let mut kernel = Kernel::new(parse_sequence(sequence)?);
+    if let Some(depth) = job.depth {
+        if depth <= render::WARMUP {
+            return Err(Error::usage(i18n::trf(
+                "depth has to be above {0}",
+                &[&render::WARMUP],
+            )));
+        }
+        kernel.depth = depth;
+    }
+    let region = job
+        .region
+        .map(session::array_to_region)
+        .unwrap_or(preset.region);
+    region.check().map_err(Error::usage)?;
+    let palette = match &job.palette {
+        Some(name) => Palette::from_str(name, true)
+            .map_err(|_| Error::usage(i18n::trf("unknown palette '{0}'", &[&name])))?,
+        None => preset.palette,
+    };
+    let [width, height] = job.size.unwrap_or([WIDTH, HEIGHT]);
+    // like --out-size no empty images, and none whose λ grid alone would not fit into memory
+    if !(1..=MAX_SIDE).contains(&width) || !(1..=MAX_SIDE).contains(&height) {
+        return Err(Error::usage(i18n::trf(
+            "size has to be between 1 and {0} pixels each way",
+            &[&MAX_SIDE],
+        )));
+    }
+    if let Some(out) = &job.out {
+        template::validate(&out.to_string_lossy()).map_err(Error::usage)?;
+    }
+
+    let mut frame = Frame::new(width, height);
+    render::render_rows(
+        &mut frame.lambdas,
+        width,
+        height,
+        &region,
+        &kernel,
+        0..height,
+    );
+    frame.colorize_rows(0..height, palette);
+    Ok((frame, kernel, region, palette))
+}
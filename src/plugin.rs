@@ -0,0 +1,95 @@
+// --colorizer: palettes computed by shared libraries, so exotic colorings can be shipped without
+// changing ljapunow. a plugin exports three C functions (version 1 of the interface):
+//
+//     uint32_t ljapunow_colorizer_abi(void);        returns 1
+//     const char *ljapunow_colorizer_name(void);    palette name, UTF-8, valid while loaded
+//     uint32_t ljapunow_colorize(double lambda, double min, double max);
+//
+// ljapunow_colorize returns the color of λ as 0x00RRGGBB, min and max are the λ range of the diagram.
+// it is called from several threads at once and never for bailed out pixels, λ = -∞ or NaN, those
+// keep their usual colors. the palette is known by its name like a custom ramp.
+// loading libraries needs the plugins feature (libloading), without it --colorizer only reports that.
+
+#[cfg(feature = "plugins")]
+pub use dynamic::load;
+#[cfg(not(feature = "plugins"))]
+pub use silent::load;
+
+#[cfg(feature = "plugins")]
+mod dynamic {
+    use crate::color;
+    use crate::error::Error;
+    use crate::i18n;
+    use libloading::Library;
+    use std::ffi::{c_char, CStr};
+    use std::path::Path;
+    use std::sync::Arc;
+
+    const ABI_VERSION: u32 = 1;
+
+    type AbiFn = unsafe extern "C" fn() -> u32;
+    type NameFn = unsafe extern "C" fn() -> *const c_char;
+    type ColorizeFn = unsafe extern "C" fn(f64, f64, f64) -> u32;
+
+    // registers the palette of the library at path
+    pub fn load(path: &Path) -> Result<(), Error> {
+        let plugin = |message: &dyn std::fmt::Display| {
+            Error::Plugin(format!("{}: {message}", path.display()))
+        };
+        // SAFETY: loading runs the initializers of the library, it is trusted like the program itself.
+        // the error names the file already
+        let library = unsafe { Library::new(path) }.map_err(|e| Error::Plugin(e.to_string()))?;
+        // SAFETY: the signatures are the ones the interface above defines
+        let (abi, name, colorize) = unsafe {
+            let abi = *library
+                .get::<AbiFn>(b"ljapunow_colorizer_abi\0")
+                .map_err(|e| plugin(&e))?;
+            let name = *library
+                .get::<NameFn>(b"ljapunow_colorizer_name\0")
+                .map_err(|e| plugin(&e))?;
+            let colorize = *library
+                .get::<ColorizeFn>(b"ljapunow_colorize\0")
+                .map_err(|e| plugin(&e))?;
+            (abi, name, colorize)
+        };
+
+        // SAFETY: as above
+        let version = unsafe { abi() };
+        if version != ABI_VERSION {
+            let message = i18n::trf(
+                "interface version {0}, this build supports {1}",
+                &[&version, &ABI_VERSION],
+            );
+            return Err(plugin(&message));
+        }
+        // SAFETY: as above, null is checked and the string stays valid while the library is loaded
+        let name = unsafe {
+            let name = name();
+            if name.is_null() {
+                return Err(plugin(&i18n::tr("the palette has no name")));
+            }
+            CStr::from_ptr(name).to_string_lossy().into_owned()
+        };
+
+        // the function points into the library, so it stays loaded until the program ends
+        std::mem::forget(library);
+        let colorizer: color::Colorizer = Arc::new(move |lambda, (min, max)| {
+            // SAFETY: the library is never unloaded
+            unsafe { colorize(lambda, min, max) & 0xFFFFFF }
+        });
+        color::register_colorizer(&name, colorizer).map_err(|e| plugin(&e))
+    }
+}
+
+// without the plugins feature no library can be loaded
+#[cfg(not(feature = "plugins"))]
+mod silent {
+    use crate::error::Error;
+    use crate::i18n;
+    use std::path::Path;
+
+    pub fn load(_: &Path) -> Result<(), Error> {
+        let message = i18n::tr("this build cannot load plugins, rebuild with --features plugins");
+        Err(Error::Plugin(message.into()))
+    }
+}
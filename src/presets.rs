@@ -0,0 +1,54 @@
+// named views worth a visit, each bundling sequence, ranges and palette
+
+use crate::color::Palette;
+use crate::render::Region;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub sequence: &'static str,
+    pub region: Region,
+    pub palette: Palette,
+}
+
+pub const DEFAULT_PRESET: &str = "zircon-city";
+
+pub const PRESETS: [Preset; 4] = [
+    Preset {
+        name: "zircon-city",
+        description: "Zircon Zity, the classic from Markus",
+        sequence: "BBBBBBAAAAAA",
+        region: Region::ZIRCON_CITY,
+        palette: Palette::Ramp,
+    },
+    Preset {
+        name: "ab",
+        description: "standard AB space, the whole interesting square",
+        sequence: "AB",
+        region: Region::new(2.0, 4.0, 2.0, 4.0),
+        palette: Palette::Gradient,
+    },
+    Preset {
+        name: "swallow",
+        description: "fanned out wings of the AABAB sequence",
+        sequence: "AABAB",
+        region: Region::new(2.0, 4.0, 2.0, 4.0),
+        palette: Palette::Ramp,
+    },
+    Preset {
+        name: "jellyfish",
+        description: "lonely stable island deep inside the chaos of Zircon Zity",
+        sequence: "BBBBBBAAAAAA",
+        region: Region::new(3.81, 3.87, 3.81, 3.87),
+        palette: Palette::Gradient,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|preset| preset.name == name)
+}
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    PRESETS.iter().map(|preset| preset.name)
+}
@@ -0,0 +1,107 @@
+// background refinement: once a view is rendered, the pixels along the order/chaos border are computed
+// again with a much higher iteration depth in a separate thread, the window shows them as they come in.
+// navigating away drops the refinement, the thread notices and stops.
+
+use crate::analysis;
+use crate::color::Palette;
+use crate::frame::Frame;
+use crate::i18n;
+use crate::render::{self, Kernel, Region};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+
+// pixels computed between two messages to the window
+const BATCH: usize = 256;
+// |λ| below this counts as border even without a sign change next to it
+const NEAR_ZERO: f64 = 0.05;
+
+pub struct Refinement {
+    results: Receiver<Vec<(usize, f64)>>,
+    cancel: Arc<AtomicBool>,
+    total: usize,
+    done: usize,
+}
+
+impl Refinement {
+    // None if there is no border in view
+    pub fn start(frame: &Frame, region: Region, kernel: &Kernel, factor: u32) -> Option<Self> {
+        let (width, height) = frame.size();
+        let pixels = analysis::border_pixels(&frame.lambdas, width, height, NEAR_ZERO);
+        if pixels.is_empty() {
+            return None;
+        }
+        let mut deep = kernel.clone();
+        deep.depth = kernel.depth.saturating_mul(factor);
+        let (sender, results) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let total = pixels.len();
+        let depth = deep.depth;
+        let stop = Arc::clone(&cancel);
+        thread::spawn(move || {
+            render::lower_priority();
+            for batch in pixels.chunks(BATCH) {
+                if stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                let refined = batch
+                    .iter()
+                    .map(|&index| {
+                        let (a, b) =
+                            region.pixel_to_world(index % width, index / width, width, height);
+                        (index, deep.value(a, b))
+                    })
+                    .collect();
+                if sender.send(refined).is_err() {
+                    return;
+                }
+                // leave the window thread some room
+                thread::yield_now();
+            }
+        });
+        println!(
+            "{}",
+            i18n::trf("refining {0} border pixels at depth {1}", &[&total, &depth])
+        );
+        Some(Refinement {
+            results,
+            cancel,
+            total,
+            done: 0,
+        })
+    }
+
+    // takes over what arrived so far, true if any pixel changed
+    pub fn poll(&mut self, frame: &mut Frame, palette: Palette) -> bool {
+        let mut changed = false;
+        while let Ok(batch) = self.results.try_recv() {
+            self.done += batch.len();
+            for (index, lambda) in batch {
+                frame.lambdas[index] = lambda;
+                if !palette.is_scaled() {
+                    frame.buffer[index] = palette.colorize_in(lambda, (f64::NAN, f64::NAN));
+                }
+            }
+            changed = true;
+        }
+        if changed && palette.is_scaled() {
+            frame.colorize_rows(0..frame.height, palette);
+        }
+        changed
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done >= self.total
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+}
+
+impl Drop for Refinement {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,201 @@
+// the λ field as terrain: heights over a clamped λ range and the normals of that heightfield, for
+// heightmap exports and everything that shades or tilts the diagram in 3D
+
+use crate::analysis;
+use crate::color::lerp_rgb;
+
+// heights in 0..=1, λ = min at the bottom and max at the top. ±∞ and NaN end up at the nearer end
+pub fn heights(lambdas: &[f64], (min, max): (f64, f64)) -> Vec<f64> {
+    let span = (max - min).max(f64::EPSILON);
+    lambdas
+        .iter()
+        .map(|&lambda| {
+            if lambda.is_nan() {
+                0.0
+            } else {
+                ((lambda - min) / span).clamp(0.0, 1.0)
+            }
+        })
+        .collect()
+}
+
+// unit normals with central differences, edge pixels repeated. a height step from 0 to 1 rises
+// scale pixels, so larger scales give steeper slopes. x points right, y up the image, z out of it
+pub fn normals(heights: &[f64], width: usize, height: usize, scale: f64) -> Vec<[f64; 3]> {
+    let at = |x: usize, y: usize| heights[y * width + x];
+    let mut normals = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let (up, down) = (y.saturating_sub(1), (y + 1).min(height - 1));
+        for x in 0..width {
+            let (left, right) = (x.saturating_sub(1), (x + 1).min(width - 1));
+            let dx = (at(right, y) - at(left, y)) * scale / (right - left).max(1) as f64;
+            // image rows grow downwards
+            let dy = (at(x, up) - at(x, down)) * scale / (down - up).max(1) as f64;
+            let length = (dx * dx + dy * dy + 1.0).sqrt();
+            normals.push([-dx / length, -dy / length, 1.0 / length]);
+        }
+    }
+    normals
+}
+
+// tangent space normal map as 0RGB, each component mapped from -1..1 to 0..255 (OpenGL convention, green up)
+pub fn normal_map(normals: &[[f64; 3]]) -> Vec<u32> {
+    let byte = |v: f64| ((v + 1.0) / 2.0 * 255.0).round().clamp(0.0, 255.0) as u32;
+    normals
+        .iter()
+        .map(|&[x, y, z]| byte(x) << 16 | byte(y) << 8 | byte(z))
+        .collect()
+}
+
+// the diagram as a landscape for the terrain view: order rises from the chaotic plain (λ > 0 low,
+// superstable ridges highest), colored by the palette and lit from the upper left
+pub struct Terrain {
+    width: usize,
+    height: usize,
+    heights: Vec<f64>,
+    colors: Vec<u32>,
+}
+
+// orbit around the center of the diagram
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    // direction the camera looks in, radians
+    pub yaw: f64,
+    // downwards tilt, radians
+    pub pitch: f64,
+    // from the center in diagram sizes
+    pub distance: f64,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera {
+            yaw: std::f64::consts::FRAC_PI_2,
+            pitch: 0.5,
+            distance: 0.9,
+        }
+    }
+}
+
+// highest point relative to the diagram size
+const RELIEF: f64 = 0.15;
+// horizontal field of view, radians
+const FIELD_OF_VIEW: f64 = 1.2;
+// march step growth per sample, far terrain is sampled coarser
+const STEP_GROWTH: f64 = 1.01;
+const SKY: u32 = 0x1A1E2A;
+// light from the upper left and a bit from the front
+const LIGHT: [f64; 3] = [-0.5, 0.5, 0.7];
+const AMBIENT: f64 = 0.35;
+
+impl Terrain {
+    pub fn new(lambdas: &[f64], buffer: &[u32], width: usize, height: usize) -> Self {
+        let (min, max) = analysis::lambda_range(lambdas);
+        let heights: Vec<f64> = heights(lambdas, (min, max))
+            .iter()
+            .zip(lambdas)
+            // not computed yet is flat ground, superstable orbits the top
+            .map(|(&h, &lambda)| match lambda {
+                l if l.is_nan() => 0.0,
+                f64::NEG_INFINITY => 1.0,
+                _ => 1.0 - h,
+            })
+            .collect();
+        let size = width.max(height) as f64;
+        let length = LIGHT.iter().map(|v| v * v).sum::<f64>().sqrt();
+        let colors = normals(&heights, width, height, RELIEF * size)
+            .iter()
+            .zip(buffer)
+            .map(|(normal, &color)| {
+                let lit = (0..3).map(|i| normal[i] * LIGHT[i] / length).sum::<f64>();
+                let shade = AMBIENT + (1.0 - AMBIENT) * lit.max(0.0);
+                lerp_rgb(0, color, shade.min(1.0))
+            })
+            .collect();
+        Terrain {
+            width,
+            height,
+            heights,
+            colors,
+        }
+    }
+
+    // perspective view as 0RGB buffer: rays per column marched front to back over the heightfield,
+    // every sample drawn as a vertical span above what is already drawn (voxel space)
+    pub fn render(&self, camera: &Camera, size: (usize, usize)) -> Vec<u32> {
+        self.render_eye(camera, size, 0.0)
+    }
+
+    // the view of a red-cyan anaglyph: red from the left eye, green and blue from the right one
+    pub fn anaglyph(&self, camera: &Camera, size: (usize, usize), separation: f64) -> Vec<u32> {
+        let left = self.render_eye(camera, size, -separation / 2.0);
+        let right = self.render_eye(camera, size, separation / 2.0);
+        left.iter()
+            .zip(&right)
+            .map(|(&l, &r)| (l & 0xFF0000) | (r & 0x00FFFF))
+            .collect()
+    }
+
+    // left and right view next to each other for parallel viewing, twice as wide as size
+    pub fn side_by_side(&self, camera: &Camera, size: (usize, usize), separation: f64) -> Vec<u32> {
+        let (width, _) = size;
+        let left = self.render_eye(camera, size, -separation / 2.0);
+        let right = self.render_eye(camera, size, separation / 2.0);
+        left.chunks_exact(width)
+            .zip(right.chunks_exact(width))
+            .flat_map(|(l, r)| l.iter().chain(r).copied())
+            .collect()
+    }
+
+    // eye moved sideways by offset diagram sizes (positive to the right), looking parallel to the
+    // camera. the images are shifted so the center of the diagram lies in the screen plane
+    fn render_eye(
+        &self,
+        camera: &Camera,
+        (out_width, out_height): (usize, usize),
+        offset: f64,
+    ) -> Vec<u32> {
+        let mut buffer = vec![SKY; out_width * out_height];
+        if self.width == 0 || self.height == 0 {
+            return buffer;
+        }
+        let size = self.width.max(self.height) as f64;
+        let relief = RELIEF * size;
+        let (center_x, center_y) = (self.width as f64 / 2.0, self.height as f64 / 2.0);
+        let orbit = camera.distance * size;
+        let (eye_x, eye_y) = (
+            center_x - orbit * camera.yaw.cos() - offset * size * camera.yaw.sin(),
+            center_y - orbit * camera.yaw.sin() + offset * size * camera.yaw.cos(),
+        );
+        let convergence = offset * size / orbit;
+        let eye_z = relief / 2.0 + orbit * camera.pitch.tan();
+        let focal = out_width as f64 / 2.0 / (FIELD_OF_VIEW / 2.0).tan();
+        let horizon = out_height as f64 / 2.0 - focal * camera.pitch.tan();
+        let far = orbit + size;
+
+        for column in 0..out_width {
+            let angle = ((column as f64 - out_width as f64 / 2.0) / focal - convergence).atan();
+            let (dx, dy) = ((camera.yaw + angle).cos(), (camera.yaw + angle).sin());
+            // distance along the view direction, against the fisheye bulge
+            let perpendicular = angle.cos();
+            let mut lowest = out_height as f64;
+            let mut t = 1.0;
+            while t < far && lowest > 0.0 {
+                let (x, y) = (eye_x + t * dx, eye_y + t * dy);
+                if (0.0..self.width as f64).contains(&x) && (0.0..self.height as f64).contains(&y) {
+                    let i = y as usize * self.width + x as usize;
+                    let z = self.heights[i] * relief;
+                    let top = horizon - (z - eye_z) / (t * perpendicular) * focal;
+                    if top < lowest {
+                        for row in top.max(0.0) as usize..lowest as usize {
+                            buffer[row * out_width + column] = self.colors[i];
+                        }
+                        lowest = top;
+                    }
+                }
+                t = (t * STEP_GROWTH).max(t + 0.5);
+            }
+        }
+        buffer
+    }
+}
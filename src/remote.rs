@@ -0,0 +1,221 @@
+// --remote ADDRESS: the window driven live by other programs. a TCP socket takes one command per line and
+// answers each with "ok" or "error: ...", the same port takes OSC messages over UDP (TouchOSC and
+// installations) with the command as the last part of the address, e.g. /ljapunow/palette "dual".
+//   sequence AABAB
+//   region A_MIN A_MAX B_MIN B_MAX
+//   palette NAME
+//   depth N
+//   save [FILE]
+// changes go through the same actions as the keys, so they are recorded with --record as well.
+
+use crate::color::Palette;
+use crate::error::Error;
+use crate::i18n::{tr, trf};
+use crate::session::{self, Action};
+use clap::ValueEnum;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+// largest OSC packet read, TouchOSC sends far less
+const DATAGRAM: usize = 4096;
+
+pub enum Command {
+    Apply(Action),
+    // under the output template without a file name
+    Save(Option<PathBuf>),
+}
+
+impl Command {
+    pub fn parse(name: &str, arguments: &[String]) -> Result<Command, String> {
+        let numbers = || -> Result<Vec<f64>, String> {
+            arguments
+                .iter()
+                .map(|a| a.parse().map_err(|_| trf("'{0}' is not a number", &[a])))
+                .collect()
+        };
+        let single = || match arguments {
+            [argument] => Ok(argument.clone()),
+            _ => Err(trf("{0} takes one argument", &[&name])),
+        };
+        match name {
+            "sequence" => Ok(Command::Apply(Action::Sequence {
+                sequence: single()?,
+            })),
+            "region" => match numbers()?[..] {
+                [a_min, a_max, b_min, b_max] if a_min < a_max && b_min < b_max => {
+                    Ok(Command::Apply(Action::View {
+                        region: [a_min, a_max, b_min, b_max],
+                    }))
+                }
+                _ => Err(tr("region takes A_MIN A_MAX B_MIN B_MAX with MIN < MAX").into()),
+            },
+            "palette" => {
+                let name = single()?;
+                Palette::from_str(&name, true)
+                    .map_err(|_| trf("unknown palette '{0}'", &[&name]))?;
+                Ok(Command::Apply(Action::Palette { name }))
+            }
+            "depth" => match single()?.parse() {
+                Ok(depth) => {
+                    session::check_depth(depth)?;
+                    Ok(Command::Apply(Action::Depth { depth }))
+                }
+                _ => Err(tr("depth takes a whole number").into()),
+            },
+            "save" => match arguments {
+                [] => Ok(Command::Save(None)),
+                [path] => Ok(Command::Save(Some(PathBuf::from(path)))),
+                _ => Err(tr("save takes at most a file name").into()),
+            },
+            _ => Err(trf("unknown command '{0}'", &[&name])),
+        }
+    }
+}
+
+pub struct Remote {
+    commands: Receiver<Command>,
+}
+
+impl Remote {
+    pub fn listen(address: &str) -> Result<Self, Error> {
+        let path = Path::new(address);
+        let listener = TcpListener::bind(address).map_err(|e| Error::io(path, e))?;
+        let socket = UdpSocket::bind(address).map_err(|e| Error::io(path, e))?;
+        println!(
+            "{}",
+            trf("remote control on {0} (TCP and OSC)", &[&address])
+        );
+        let (sender, commands) = mpsc::channel();
+        let tcp = sender.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = tcp.clone();
+                thread::spawn(move || serve(stream, sender));
+            }
+        });
+        thread::spawn(move || receive_osc(socket, sender));
+        Ok(Remote { commands })
+    }
+
+    // what arrived since the last call
+    pub fn commands(&self) -> Vec<Command> {
+        self.commands.try_iter().collect()
+    }
+}
+
+// a line per command, the connection stays open until the client closes it
+fn serve(stream: TcpStream, sender: Sender<Command>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            continue;
+        };
+        let arguments: Vec<String> = words.map(String::from).collect();
+        let reply = match Command::parse(name, &arguments) {
+            Ok(command) => match sender.send(command) {
+                Ok(()) => "ok".to_string(),
+                // the window is closed
+                Err(_) => return,
+            },
+            Err(message) => trf("error: {0}", &[&message]),
+        };
+        if writeln!(writer, "{reply}").is_err() {
+            return;
+        }
+    }
+}
+
+fn receive_osc(socket: UdpSocket, sender: Sender<Command>) {
+    let mut datagram = [0u8; DATAGRAM];
+    while let Ok((length, peer)) = socket.recv_from(&mut datagram) {
+        for (address, arguments) in osc::decode(&datagram[..length]) {
+            let name = address.rsplit('/').next().unwrap_or_default();
+            match Command::parse(name, &arguments) {
+                Ok(command) => {
+                    if sender.send(command).is_err() {
+                        return;
+                    }
+                }
+                Err(message) => eprintln!("{peer}: {address}: {message}"),
+            }
+        }
+    }
+}
+
+// the part of OSC 1.0 controllers send: messages with int32, float32, float64, string and symbol
+// arguments, also inside bundles. everything is turned into text for Command::parse
+mod osc {
+    pub fn decode(packet: &[u8]) -> Vec<(String, Vec<String>)> {
+        let mut messages = Vec::new();
+        decode_into(packet, &mut messages);
+        messages
+    }
+
+    fn decode_into(packet: &[u8], messages: &mut Vec<(String, Vec<String>)>) {
+        if let Some(mut rest) = packet.strip_prefix(b"#bundle\0") {
+            // time tag, then elements with a big endian size each
+            rest = rest.get(8..).unwrap_or_default();
+            while let Some((size, element)) = rest.split_first_chunk::<4>() {
+                let size = u32::from_be_bytes(*size) as usize;
+                let Some(element) = element.get(..size) else {
+                    return;
+                };
+                decode_into(element, messages);
+                rest = &rest[4 + size..];
+            }
+            return;
+        }
+        if let Some(message) = message(packet) {
+            messages.push(message);
+        }
+    }
+
+    fn message(packet: &[u8]) -> Option<(String, Vec<String>)> {
+        let (address, mut rest) = string(packet)?;
+        let tags = match string(rest) {
+            Some((tags, after)) if tags.starts_with(',') => {
+                rest = after;
+                tags
+            }
+            // very old senders leave out the type tags, there are no arguments then
+            _ => String::from(","),
+        };
+        let mut arguments = Vec::new();
+        for tag in tags.chars().skip(1) {
+            let argument = match tag {
+                'i' => i32::from_be_bytes(*rest.first_chunk()?).to_string(),
+                'f' => f32::from_be_bytes(*rest.first_chunk()?).to_string(),
+                'd' => f64::from_be_bytes(*rest.first_chunk()?).to_string(),
+                's' | 'S' => {
+                    let (text, after) = string(rest)?;
+                    rest = after;
+                    arguments.push(text);
+                    continue;
+                }
+                // no data of their own
+                'T' | 'F' | 'N' | 'I' => continue,
+                _ => return None,
+            };
+            rest = &rest[if tag == 'd' { 8 } else { 4 }..];
+            arguments.push(argument);
+        }
+        Some((address, arguments))
+    }
+
+    // zero terminated and padded to a multiple of 4 bytes
+    fn string(bytes: &[u8]) -> Option<(String, &[u8])> {
+        let end = bytes.iter().position(|&b| b == 0)?;
+        let text = String::from_utf8(bytes[..end].to_vec()).ok()?;
+        let padded = (end / 4 + 1) * 4;
+        Some((text, bytes.get(padded..).unwrap_or_default()))
+    }
+}
@@ -0,0 +1,1509 @@
+// the actual Ljapunow computation, independent of any window
+
+use crate::analysis;
+use crate::cache::StableHasher;
+use crate::error::Error;
+use crate::formula::Formula;
+use crate::i18n::{tr, trf};
+use crate::measure::{Measure, Step};
+use crate::rng::Rng;
+use std::f64::consts::TAU;
+use std::fmt;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub const ITERATION_DEPTH: u32 = 300; // default, everything from 100+ seems to be fine
+pub const WARMUP: u32 = 20;
+
+// threads render_rows computes rows on (--threads), 0 for as many as the machine has
+static THREADS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn set_threads(threads: usize) {
+    THREADS.store(threads, Ordering::Relaxed);
+}
+
+pub fn threads() -> usize {
+    match THREADS.load(Ordering::Relaxed) {
+        0 => thread::available_parallelism().map_or(1, |threads| threads.get()),
+        threads => threads,
+    }
+}
+
+// computing threads at the lowest OS priority (--nice), so long renders leave the desktop usable
+static NICE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_nice(nice: bool) {
+    NICE.store(nice, Ordering::Relaxed);
+}
+
+// called first thing in a computing thread. linux keeps the nice value per thread, the window thread
+// stays responsive. other unixes have it per process and lower everything, elsewhere it does nothing
+pub fn lower_priority() {
+    if !NICE.load(Ordering::Relaxed) {
+        return;
+    }
+    #[cfg(unix)]
+    // SAFETY: plain system call on the calling thread, a failure leaves the priority as it was
+    unsafe {
+        libc::setpriority(libc::PRIO_PROCESS, 0, 19);
+    }
+}
+
+// parameter window of the diagram, a along x, b along y
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Region {
+    pub x_min: f64,
+    pub x_max: f64,
+    pub y_min: f64,
+    pub y_max: f64,
+}
+
+impl Region {
+    // Zircon Zity, 3,4 ≤ a ≤ 4,0 und 2,5 ≤ b ≤ 3,4
+    pub const ZIRCON_CITY: Region = Region::new(3.4, 4.0, 2.5, 3.4);
+    // one period of Ω and the circle map up to twice the critical K = 1
+    pub const ARNOLD_TONGUES: Region = Region::new(0.0, 1.0, 0.0, 2.0);
+    // the whole Mandelbrot set of the logistic map, real r along a, imaginary along b
+    pub const LOGISTIC_MANDELBROT: Region = Region::new(-2.0, 4.0, -2.5, 2.5);
+
+    pub const fn new(x_min: f64, x_max: f64, y_min: f64, y_max: f64) -> Self {
+        Region {
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+        }
+    }
+
+    // x_min,x_max,y_min,y_max
+    pub fn parse(ranges: &str) -> Option<Self> {
+        let ranges = ranges
+            .split(',')
+            .map(|v| v.trim().parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        let [x_min, x_max, y_min, y_max] = <[f64; 4]>::try_from(ranges).ok()?;
+        Some(Region::new(x_min, x_max, y_min, y_max))
+    }
+
+    // reversed, empty or infinite ranges render nothing useful, the message suggests a fix
+    pub fn check(&self) -> Result<(), String> {
+        let ranges = [self.x_min, self.x_max, self.y_min, self.y_max];
+        if ranges.iter().any(|v| !v.is_finite()) {
+            return Err(tr("the ranges have to be finite numbers").into());
+        }
+        for (name, min, max) in [("a", self.x_min, self.x_max), ("b", self.y_min, self.y_max)] {
+            if min == max {
+                return Err(trf(
+                    "the {0} range is empty ({0}_min = {0}_max = {1}), widen it e.g. to {2},{3}",
+                    &[&name, &min, &(min - 0.1), &(max + 0.1)],
+                ));
+            }
+        }
+        if self.x_min > self.x_max || self.y_min > self.y_max {
+            let reversed = if self.x_min > self.x_max { "a" } else { "b" };
+            let [a_min, a_max] = sorted(self.x_min, self.x_max);
+            let [b_min, b_max] = sorted(self.y_min, self.y_max);
+            return Err(trf(
+                "the {0} range is reversed (min > max), did you mean {1}?",
+                &[&reversed, &format!("{a_min},{a_max},{b_min},{b_max}")],
+            ));
+        }
+        Ok(())
+    }
+
+    // same size, moved so (a, b) is in the middle
+    pub fn centered_at(&self, a: f64, b: f64) -> Region {
+        let half_width = (self.x_max - self.x_min) / 2.0;
+        let half_height = (self.y_max - self.y_min) / 2.0;
+        Region::new(
+            a - half_width,
+            a + half_width,
+            b - half_height,
+            b + half_height,
+        )
+    }
+
+    // moved by whole pixels of a diagram with the given size, the content follows the mouse
+    pub fn dragged_by(&self, dx: i64, dy: i64, width: usize, height: usize) -> Region {
+        let da = -dx as f64 * (self.x_max - self.x_min) / width as f64;
+        let db = -dy as f64 * (self.y_max - self.y_min) / height as f64;
+        Region::new(
+            self.x_min + da,
+            self.x_max + da,
+            self.y_min + db,
+            self.y_max + db,
+        )
+    }
+
+    // distinct f64 values between neighbouring pixels on the coarser axis. below 1 neighbouring pixels
+    // get the same parameters and the image turns blocky, zooming further only enlarges the blocks
+    pub fn resolution(&self, width: usize, height: usize) -> f64 {
+        let steps = |min: f64, max: f64, pixels: usize| {
+            (max - min).abs() / pixels.max(1) as f64 / ulp(min.abs().max(max.abs()))
+        };
+        steps(self.x_min, self.x_max, width).min(steps(self.y_min, self.y_max, height))
+    }
+
+    // scaled by factor around (a, b), which stays at the same place on screen. factor < 1 zooms in
+    pub fn zoomed_at(&self, a: f64, b: f64, factor: f64) -> Region {
+        Region::new(
+            a - (a - self.x_min) * factor,
+            a + (self.x_max - a) * factor,
+            b - (b - self.y_min) * factor,
+            b + (self.y_max - b) * factor,
+        )
+    }
+
+    // map pixel to world coordinates
+    pub fn pixel_to_world(&self, x: usize, y: usize, width: usize, height: usize) -> (f64, f64) {
+        (
+            map(x as f64, 0., width as f64, self.x_min, self.x_max),
+            map(y as f64, 0., height as f64, self.y_min, self.y_max),
+        )
+    }
+
+    // and back, in fractional pixels that may lie outside the diagram
+    pub fn world_to_pixel(&self, a: f64, b: f64, width: usize, height: usize) -> (f64, f64) {
+        (
+            map(a, self.x_min, self.x_max, 0., width as f64),
+            map(b, self.y_min, self.y_max, 0., height as f64),
+        )
+    }
+}
+
+// both values in ascending order, for the suggestion of Region::check
+fn sorted(v1: f64, v2: f64) -> [f64; 2] {
+    [v1.min(v2), v1.max(v2)]
+}
+
+pub const DEFAULT_BAILOUT: f64 = 1e12;
+// |z| beyond which a complex orbit has escaped for sure
+const ESCAPE_RADIUS: f64 = 1e6;
+// x₀ of every orbit unless a sweep varies it
+pub const START: f64 = 0.5;
+// start values tried one after another with Bailout::Retry, after the kernel's own
+const RETRY_X0: [f64; 4] = [0.5, 0.25, 0.75, 0.1];
+
+// what to do with pixels whose sum leaves ±bailout (divergent orbits, log(0) of superstable orbits)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Bailout {
+    /// stop summing and divide by the whole depth as if the orbit had gone on
+    Stop,
+    /// mark as f64::INFINITY, colored with a dedicated sentinel color
+    Sentinel,
+    /// clamp the sum to ±bailout and average over the terms summed so far
+    Clamp,
+    /// start again with other x₀, sentinel if all of them bail out
+    Retry,
+}
+
+// what to do with a derivative of exactly 0, the orbit hit the critical point x = 0.5 (superstable orbit).
+// log(0) = -∞ drags the whole sum to -∞ and triggers the bailout unless it is handled here.
+// the start x₀ = 0.5 always sits on the critical point and is never counted as a hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum LogZero {
+    /// add log(0) = -∞ like any other term after the warmup, the pixel bails out
+    #[default]
+    Keep,
+    /// leave the term out of the sum
+    Skip,
+    /// add log(f64::MIN_POSITIVE) ≈ -708 instead, superstable pixels get very negative but finite λ
+    Clamp,
+    /// leave the term out and mark pixels whose orbit hit the critical point with λ = -∞
+    Count,
+}
+
+// the map iterated at each pixel, r always comes from the forcing sequence
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum StateMap {
+    // x → r·x·(1−x)
+    #[default]
+    Logistic,
+    // two logistic maps with diffusive coupling ε, the pixel value is taken from both exponents:
+    // x → (1−ε)·f(x) + ε·f(y), y → (1−ε)·f(y) + ε·f(x)
+    Coupled(f64, Spectrum),
+    // standard circle map θ → θ + Ω − K/2π·sin(2πθ) with Ω along a and K along b, no forcing.
+    // mode locked Arnold tongues have λ < 0, quasiperiodic orbits λ = 0, K > 1 allows chaos
+    Circle,
+    // z → r·z·(1−z) with complex r = a + ib, z₀ = 0.5. bounded orbits get their λ, escaping ones count as
+    // bailed out: a sentinel with --on-bailout sentinel (the Mandelbrot set of the logistic map),
+    // their growth rate up to the escape with --on-bailout clamp (smooth escape time)
+    Complex,
+}
+
+// what of the Ljapunow spectrum λ₁ ≥ λ₂ of a two dimensional map is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Spectrum {
+    /// the largest exponent λ₁
+    #[default]
+    Largest,
+    /// the second exponent λ₂
+    Second,
+    /// λ₁ + λ₂, the mean log contraction of areas (dissipation)
+    Sum,
+    /// Kaplan-Yorke dimension of the attractor, 0 (fixed point) to 2
+    KaplanYorke,
+}
+
+impl Spectrum {
+    // the exponents come in unordered, the QR columns may swap roles along the orbit
+    pub fn value(self, l1: f64, l2: f64) -> f64 {
+        let (l1, l2) = (l1.max(l2), l1.min(l2));
+        match self {
+            Spectrum::Largest => l1,
+            Spectrum::Second => l2,
+            Spectrum::Sum => l1 + l2,
+            Spectrum::KaplanYorke if l1 < 0.0 => 0.0,
+            Spectrum::KaplanYorke if l1 + l2 >= 0.0 => 2.0,
+            Spectrum::KaplanYorke => 1.0 + l1 / l2.abs(),
+        }
+    }
+}
+
+// additive dynamical noise x_{n+1} = r x_n (1 - x_n) + ξ_n, ξ_n uniform in ±amplitude.
+// λ is averaged over several realizations, each pixel draws its own reproducible noise from the seed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Noise {
+    pub amplitude: f64,
+    pub seed: u64,
+    pub realizations: u32,
+}
+
+impl Noise {
+    // generator for one realization at (a, b), independent of the order pixels are rendered in
+    fn rng(&self, a: f64, b: f64, realization: u32) -> Rng {
+        let mut z = self.seed
+            ^ a.to_bits().rotate_left(21)
+            ^ b.to_bits()
+            ^ (realization as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        // splitmix64 finalizer, neighboring pixels must not get correlated xorshift streams
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Rng::new(z ^ (z >> 31))
+    }
+}
+
+// a and b swept while the orbit is iterated, a + a_rate·s(n) and b + b_rate·s(n): linear runs s from 0
+// at the first step to 1 at the last, sine once around sin(2πn/depth). a diagram of a swept parameter
+// shows where the orbit lags behind the attractor of the moment, like hysteresis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drift {
+    pub shape: DriftShape,
+    pub rates: (f64, f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftShape {
+    Linear,
+    Sine,
+}
+
+impl Drift {
+    // linear|sine:RATE[,B_RATE], the same rate for a and b unless B_RATE is given
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (shape, rates) = spec
+            .split_once(':')
+            .ok_or_else(|| trf("expected SHAPE:RATE, not '{0}'", &[&spec]))?;
+        let shape = match shape.trim() {
+            "linear" => DriftShape::Linear,
+            "sine" => DriftShape::Sine,
+            shape => return Err(trf("unknown drift '{0}', use linear or sine", &[&shape])),
+        };
+        let rate = |rate: &str| match rate.trim().parse::<f64>() {
+            Ok(rate) if rate.is_finite() => Ok(rate),
+            _ => Err(trf("'{0}' is not a number", &[&rate.trim()])),
+        };
+        let rates = match rates.split_once(',') {
+            Some((a, b)) => (rate(a)?, rate(b)?),
+            None => (rate(rates)?, rate(rates)?),
+        };
+        Ok(Drift { shape, rates })
+    }
+
+    // how far a and b have moved at step n of depth
+    fn offset(&self, n: u32, depth: u32) -> (f64, f64) {
+        let t = n as f64 / (depth.max(2) - 1) as f64;
+        let s = match self.shape {
+            DriftShape::Linear => t,
+            DriftShape::Sine => (TAU * n as f64 / depth.max(1) as f64).sin(),
+        };
+        (self.rates.0 * s, self.rates.1 * s)
+    }
+}
+
+// distance of x ≥ 0 to the next larger f64, the smallest parameter change near x
+pub fn ulp(x: f64) -> f64 {
+    let x = x.max(f64::MIN_POSITIVE);
+    f64::from_bits(x.to_bits() + 1) - x
+}
+
+// sequence of A and B letters as given on the command line. a sequence without A or without B leaves
+// one axis without influence, the diagram would only be stripes
+pub fn parse_sequence(sequence: &str) -> Result<Vec<char>, Error> {
+    let letters_only = sequence.chars().all(|c| c == 'A' || c == 'B');
+    if !letters_only || !sequence.contains('A') || !sequence.contains('B') {
+        return Err(Error::InvalidSequence(sequence.to_string()));
+    }
+    Ok(sequence.chars().collect())
+}
+
+// what a letter of the sequence stands for: the parameter along one of the axes, a fixed r or a
+// formula of both
+#[derive(Debug, Clone, PartialEq)]
+pub enum Forcing {
+    A,
+    B,
+    Fixed(f64),
+    Formula(Formula),
+}
+
+impl Forcing {
+    // a, b, a number or a formula of a and b
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "a" => Ok(Forcing::A),
+            "b" => Ok(Forcing::B),
+            value => match value.parse::<f64>() {
+                Ok(r) if r.is_finite() => Ok(Forcing::Fixed(r)),
+                _ => Formula::parse(value).map(Forcing::Formula),
+            },
+        }
+    }
+
+    fn varies_along(&self, axis: &Forcing) -> bool {
+        match (self, axis) {
+            (Forcing::Formula(formula), Forcing::A) => formula.uses(&Formula::A),
+            (Forcing::Formula(formula), Forcing::B) => formula.uses(&Formula::B),
+            _ => self == axis,
+        }
+    }
+
+    fn value(&self, a: f64, b: f64) -> f64 {
+        match self {
+            Forcing::A => a,
+            Forcing::B => b,
+            Forcing::Fixed(r) => *r,
+            Forcing::Formula(formula) => formula.evaluate(a, b),
+        }
+    }
+}
+
+// the letters a sequence may use and their values (--values), A = a and B = b unless given otherwise
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alphabet(Vec<(char, Forcing)>);
+
+impl Default for Alphabet {
+    fn default() -> Self {
+        Alphabet(vec![('A', Forcing::A), ('B', Forcing::B)])
+    }
+}
+
+impl Alphabet {
+    // LETTER=VALUE,... with a, b, a number or a formula as value, e.g. A=a,B=b,C=3.74
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut alphabet = Alphabet(Vec::new());
+        for entry in spec.split(',') {
+            let (letter, forcing) = parse_letter(entry)?;
+            if alphabet.forcing(letter).is_some() {
+                return Err(trf("{0} is given twice", &[&letter]));
+            }
+            alphabet.define(letter, forcing);
+        }
+        Ok(alphabet)
+    }
+
+    // like parse_sequence: only known letters, and at least one of them follows a and one b
+    pub fn sequence(&self, sequence: &str) -> Result<Vec<char>, Error> {
+        if *self == Alphabet::default() {
+            return parse_sequence(sequence);
+        }
+        let mut forcings = Vec::new();
+        for letter in sequence.chars() {
+            let forcing = self.forcing(letter).ok_or_else(|| {
+                Error::usage(trf(
+                    "invalid sequence '{0}', {1} has no value in --values",
+                    &[&sequence, &letter],
+                ))
+            })?;
+            forcings.push(forcing);
+        }
+        for (axis, name) in [(Forcing::A, 'a'), (Forcing::B, 'b')] {
+            if !forcings.iter().any(|forcing| forcing.varies_along(&axis)) {
+                return Err(Error::usage(trf(
+                    "invalid sequence '{0}', without a letter for {1} the diagram does not change along {1}",
+                    &[&sequence, &name],
+                )));
+            }
+        }
+        Ok(sequence.chars().collect())
+    }
+
+    // letter stands for forcing from now on, the letters that did take over its old value. binding B to
+    // a swaps the axes of A and B
+    pub fn bind(&mut self, letter: char, forcing: Forcing) {
+        let old = self.forcing(letter).cloned();
+        for (known, value) in &mut self.0 {
+            if *known == letter {
+                *value = forcing.clone();
+            } else if let Some(old) = old.as_ref().filter(|_| *value == forcing) {
+                *value = old.clone();
+            }
+        }
+        if old.is_none() {
+            self.0.push((letter, forcing));
+        }
+    }
+
+    // letter stands for forcing from now on, whatever it was before
+    pub fn define(&mut self, letter: char, forcing: Forcing) {
+        match self.0.iter_mut().find(|(known, _)| *known == letter) {
+            Some((_, known)) => *known = forcing,
+            None => self.0.push((letter, forcing)),
+        }
+    }
+
+    // the letter after this one, the last one wraps around to the first
+    fn next(&self, letter: char) -> Option<char> {
+        let index = self.0.iter().position(|(known, _)| *known == letter)?;
+        Some(self.0[(index + 1) % self.0.len()].0)
+    }
+
+    fn forcing(&self, letter: char) -> Option<&Forcing> {
+        self.0
+            .iter()
+            .find(|(known, _)| *known == letter)
+            .map(|(_, forcing)| forcing)
+    }
+
+    // r for a letter at (a, b), letters checked by sequence
+    fn value(&self, letter: char, a: f64, b: f64) -> f64 {
+        self.forcing(letter)
+            .map_or(f64::NAN, |forcing| forcing.value(a, b))
+    }
+
+    // r for every letter at (a, b), formulas evaluated once per letter
+    fn values(&self, a: f64, b: f64) -> Vec<(char, f64)> {
+        self.0
+            .iter()
+            .map(|(letter, forcing)| (*letter, forcing.value(a, b)))
+            .collect()
+    }
+}
+
+// LETTER=VALUE, e.g. C=(a+b)/2
+pub fn parse_letter(entry: &str) -> Result<(char, Forcing), String> {
+    let (letter, value) = entry
+        .split_once('=')
+        .ok_or_else(|| trf("expected LETTER=VALUE, not '{0}'", &[&entry]))?;
+    let mut chars = letter.trim().chars();
+    let (Some(letter), None) = (chars.next(), chars.next()) else {
+        return Err(trf("'{0}' is not a single letter", &[&letter.trim()]));
+    };
+    let forcing = Forcing::parse(value).map_err(|e| trf("value of {0}: {1}", &[&letter, &e]))?;
+    Ok((letter, forcing))
+}
+
+// everything needed to compute λ for a single point
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kernel {
+    pub sequence_rule: Vec<char>,
+    pub bailout: f64,
+    pub on_bailout: Bailout,
+    pub on_log_zero: LogZero,
+    // iterations per pixel, more than WARMUP
+    pub depth: u32,
+    // orbits passing closer than this to the critical point after the warmup are superstable, λ = -∞
+    pub superstable: Option<f64>,
+    // x₀, the first state of the orbits
+    pub start: f64,
+    pub noise: Option<Noise>,
+    pub map: StateMap,
+    pub measure: Measure,
+    pub alphabet: Alphabet,
+    // replaces map and measure when set (--wasm)
+    pub external: Option<External>,
+    // a and b moving during the iteration (--drift)
+    pub drift: Option<Drift>,
+}
+
+// the value of a pixel computed outside the crate from a, b, the sequence and the depth. the key tells
+// different functions apart in comparisons and in the keys of the λ cache
+#[derive(Clone)]
+pub struct External {
+    pub key: u64,
+    pub value: ExternalFn,
+}
+
+// (a, b, sequence, depth) -> value, called from several render threads at once
+pub type ExternalFn = Arc<dyn Fn(f64, f64, &[char], u32) -> f64 + Send + Sync>;
+
+impl fmt::Debug for External {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "External({:016x})", self.key)
+    }
+}
+
+impl PartialEq for External {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Kernel {
+    pub fn new(sequence_rule: Vec<char>) -> Self {
+        Kernel {
+            sequence_rule,
+            bailout: DEFAULT_BAILOUT,
+            on_bailout: Bailout::Stop,
+            on_log_zero: LogZero::default(),
+            superstable: None,
+            start: START,
+            depth: ITERATION_DEPTH,
+            noise: None,
+            map: StateMap::Logistic,
+            measure: Measure::Lyapunov,
+            alphabet: Alphabet::default(),
+            external: None,
+            drift: None,
+        }
+    }
+
+    // the same for every kernel that computes the same values and on every build, the λ cache and saved
+    // workspaces keep it to know what their values were computed with
+    pub fn key(&self) -> u64 {
+        let mut hash = StableHasher::default();
+        hash.u64(self.sequence_rule.len() as u64);
+        for &letter in &self.sequence_rule {
+            hash.u64(letter as u64);
+        }
+        hash.f64(self.bailout)
+            .u64(self.on_bailout as u64)
+            .u64(self.on_log_zero as u64)
+            .u64(self.depth as u64)
+            .f64(self.start)
+            .u64(self.measure as u64);
+        match self.superstable {
+            Some(tolerance) => hash.u64(1).f64(tolerance),
+            None => hash.u64(0),
+        };
+        match self.noise {
+            Some(noise) => hash
+                .u64(1)
+                .f64(noise.amplitude)
+                .u64(noise.seed)
+                .u64(noise.realizations as u64),
+            None => hash.u64(0),
+        };
+        match self.map {
+            StateMap::Logistic => hash.u64(0),
+            StateMap::Coupled(coupling, spectrum) => hash.u64(1).f64(coupling).u64(spectrum as u64),
+            StateMap::Circle => hash.u64(2),
+            StateMap::Complex => hash.u64(3),
+        };
+        hash.u64(self.alphabet.0.len() as u64);
+        for (letter, forcing) in &self.alphabet.0 {
+            hash.u64(*letter as u64);
+            match forcing {
+                Forcing::A => hash.u64(0),
+                Forcing::B => hash.u64(1),
+                Forcing::Fixed(r) => hash.u64(2).f64(*r),
+                Forcing::Formula(formula) => formula.hash(hash.u64(3)),
+            };
+        }
+        match &self.external {
+            Some(external) => hash.u64(1).u64(external.key),
+            None => hash.u64(0),
+        };
+        match self.drift {
+            Some(drift) => hash
+                .u64(1)
+                .u64(drift.shape as u64)
+                .f64(drift.rates.0)
+                .f64(drift.rates.1),
+            None => hash.u64(0),
+        };
+        hash.finish()
+    }
+
+    // same kernel with the letter at position (0 based) turned into the next letter of the alphabet,
+    // A <-> B for the default one. None past the end or for a letter the alphabet does not know
+    pub fn flipped(&self, position: usize) -> Option<Kernel> {
+        let mut flipped = self.clone();
+        let letter = flipped.sequence_rule.get_mut(position)?;
+        *letter = self.alphabet.next(*letter)?;
+        Some(flipped)
+    }
+
+    // the selected measure at (a, b)
+    pub fn value(&self, a: f64, b: f64) -> f64 {
+        if let Some(external) = &self.external {
+            return (external.value)(a, b, &self.sequence_rule, self.depth);
+        }
+        match self.measure {
+            Measure::Lyapunov => self.lyapunov(a, b),
+            measure => self.averaged(a, b, |rng| {
+                measure
+                    .plugin()
+                    .measure(&mut self.orbit(a, b, rng).skip(WARMUP as usize))
+            }),
+        }
+    }
+
+    // ljapunow exponent of the logistic map forced by the sequence at (a, b)
+    pub fn lyapunov(&self, a: f64, b: f64) -> f64 {
+        let schedule = self.schedule(a, b);
+        self.averaged(a, b, |mut rng| self.realization(&schedule, &mut rng))
+    }
+
+    // map sequence rules to actual values outside of inner loop, letters are checked by parse_sequence.
+    // the circle map and complex r are not forced, they get their two parameters [Ω, K] or [Re, Im] instead
+    fn schedule(&self, a: f64, b: f64) -> Schedule<'_> {
+        let sequence = match self.map {
+            StateMap::Circle | StateMap::Complex => vec![a, b],
+            _ => {
+                let values = self.alphabet.values(a, b);
+                let value = |letter: char| {
+                    values
+                        .iter()
+                        .find(|&&(known, _)| known == letter)
+                        .map_or(f64::NAN, |&(_, r)| r)
+                };
+                self.sequence_rule
+                    .iter()
+                    .map(|&letter| value(letter))
+                    .collect()
+            }
+        };
+        Schedule {
+            kernel: self,
+            sequence,
+            at: (a, b),
+        }
+    }
+
+    // without noise a single run, with noise the mean over the realizations.
+    // a bailed out or superstable realization decides the pixel
+    fn averaged(&self, a: f64, b: f64, run: impl Fn(Option<Rng>) -> f64) -> f64 {
+        let Some(noise) = self.noise else {
+            return run(None);
+        };
+        let mut sum = 0.0;
+        for realization in 0..noise.realizations.max(1) {
+            let value = run(Some(noise.rng(a, b, realization)));
+            if !value.is_finite() {
+                return value;
+            }
+            sum += value;
+        }
+        sum / noise.realizations.max(1) as f64
+    }
+
+    // the states from x₀ on, depth steps in all, for the measures besides λ.
+    // coupled maps report their first map, complex r the modulus |z|
+    pub fn orbit(&self, a: f64, b: f64, mut noise: Option<Rng>) -> impl Iterator<Item = Step> + '_ {
+        let schedule = self.schedule(a, b);
+        let amplitude = self.noise.map_or(0.0, |noise| noise.amplitude);
+        let mut kick = move || {
+            noise
+                .as_mut()
+                .map_or(0.0, |rng| rng.range(-amplitude, amplitude))
+        };
+        // the second map starts elsewhere like in iterate_coupled, z starts on the real axis
+        let (mut x, mut y) = (
+            self.start,
+            if self.map == StateMap::Complex {
+                0.0
+            } else {
+                0.25
+            },
+        );
+        (0..self.depth).map(move |n| {
+            let r = schedule.r(n);
+            match self.map {
+                StateMap::Logistic => {
+                    let step = Step {
+                        x,
+                        derivative: r * (1.0 - 2.0 * x),
+                    };
+                    x = (r * x * (1.0 - x) + kick()).clamp(0.0, 1.0);
+                    step
+                }
+                StateMap::Coupled(coupling, _) => {
+                    let step = Step {
+                        x,
+                        derivative: (1.0 - coupling) * r * (1.0 - 2.0 * x),
+                    };
+                    let (fx, fy) = (r * x * (1.0 - x), r * y * (1.0 - y));
+                    x = ((1.0 - coupling) * fx + coupling * fy + kick()).clamp(0.0, 1.0);
+                    y = ((1.0 - coupling) * fy + coupling * fx + kick()).clamp(0.0, 1.0);
+                    step
+                }
+                StateMap::Circle => {
+                    let (omega, k) = (schedule.sequence[0], schedule.sequence[1]);
+                    let step = Step {
+                        x,
+                        derivative: 1.0 - k * (TAU * x).cos(),
+                    };
+                    x += omega - k / TAU * (TAU * x).sin() + kick();
+                    step
+                }
+                StateMap::Complex => {
+                    let (re, im) = (schedule.sequence[0], schedule.sequence[1]);
+                    let step = Step {
+                        x: x.hypot(y),
+                        derivative: re.hypot(im) * (1.0 - 2.0 * x).hypot(2.0 * y),
+                    };
+                    let (zr, zi) = (x * (1.0 - x) + y * y, y * (1.0 - x) - x * y);
+                    (x, y) = (re * zr - im * zi + kick(), re * zi + im * zr + kick());
+                    step
+                }
+            }
+        })
+    }
+
+    // the clean orbit after the warmup with every state in 0..=1, the circle map modulo 1.
+    // what --sonify and --midi play
+    pub fn states(&self, a: f64, b: f64) -> Vec<f64> {
+        self.orbit(a, b, None)
+            .skip(WARMUP as usize)
+            .map(|step| match self.map {
+                StateMap::Circle => step.x.rem_euclid(1.0),
+                _ if step.x.is_finite() => step.x.clamp(0.0, 1.0),
+                _ => 0.0,
+            })
+            .collect()
+    }
+
+    fn realization(&self, schedule: &Schedule, noise: &mut Option<Rng>) -> f64 {
+        match self.on_bailout {
+            Bailout::Sentinel => self
+                .iterate(schedule, self.start, noise)
+                .unwrap_or(f64::INFINITY),
+            Bailout::Stop | Bailout::Clamp => self
+                .iterate(schedule, self.start, noise)
+                .unwrap_or_else(|clamped| clamped),
+            Bailout::Retry => std::iter::once(self.start)
+                .chain(RETRY_X0.into_iter().filter(|&x_0| x_0 != self.start))
+                .find_map(|x_0| self.iterate(schedule, x_0, noise).ok())
+                .unwrap_or(f64::INFINITY),
+        }
+    }
+
+    // what is left of a sum that went beyond ±bailout after terms terms, Sentinel and Retry drop it
+    fn bailed_out(&self, lambda: f64, terms: usize) -> f64 {
+        if self.on_bailout == Bailout::Stop {
+            return lambda / (self.depth - WARMUP) as f64;
+        }
+        let clamped = if lambda.is_nan() {
+            self.bailout
+        } else {
+            lambda.clamp(-self.bailout, self.bailout)
+        };
+        clamped / terms.max(1) as f64
+    }
+
+    // λ, or the bailed out estimate as error if the sum left ±bailout
+    fn iterate(&self, schedule: &Schedule, x_0: f64, noise: &mut Option<Rng>) -> Result<f64, f64> {
+        match self.map {
+            StateMap::Logistic => {}
+            StateMap::Coupled(coupling, spectrum) => {
+                return self.iterate_coupled(schedule, x_0, (coupling, spectrum), noise)
+            }
+            StateMap::Circle | StateMap::Complex => {
+                let (a, b) = (schedule.sequence[0], schedule.sequence[1]);
+                return match self.map {
+                    StateMap::Circle => self.iterate_circle(a, b, x_0, noise),
+                    _ => self.iterate_complex((a, b), x_0, noise),
+                };
+            }
+        }
+        let r = |n| schedule.r(n);
+
+        let mut x_n = x_0; // X_0 as start of iteration
+        let mut lambda = 0.0;
+        let mut terms = 0;
+        let mut critical_hits = 0;
+
+        for n in 0..self.depth {
+            let critical = (1.0 - 2.0 * x_n).abs();
+            let derivative = (r(n) * critical).abs();
+            // only the critical point is a hit, log(0) of r = 0 is summed like any other term
+            if critical != 0.0 || self.on_log_zero == LogZero::Keep && n > WARMUP {
+                // sum for ljapunow exponent
+                lambda += derivative.ln();
+                terms += 1;
+            } else if n > 0 {
+                match self.on_log_zero {
+                    LogZero::Keep | LogZero::Skip => {}
+                    LogZero::Clamp => {
+                        lambda += f64::MIN_POSITIVE.ln();
+                        terms += 1;
+                    }
+                    LogZero::Count => critical_hits += 1,
+                }
+            }
+            // superstable skeleton, the periodic orbit runs through (or very close to) x = 0.5
+            if n > WARMUP
+                && self
+                    .superstable
+                    .is_some_and(|tolerance| critical < tolerance)
+            {
+                critical_hits += 1;
+            }
+
+            // iterate x to next value
+            x_n = r(n) * x_n * (1.0 - x_n);
+            // noise is kept inside the unit interval, otherwise every kick across 0 or 1 diverges
+            if let (Some(rng), Some(noise)) = (noise.as_mut(), self.noise) {
+                x_n = (x_n + rng.range(-noise.amplitude, noise.amplitude)).clamp(0.0, 1.0);
+            }
+
+            // shortcut if we are already out of bounds
+            if !(-self.bailout..=self.bailout).contains(&lambda) {
+                return Err(self.bailed_out(lambda, terms));
+            }
+        }
+        if critical_hits > 0 {
+            return Ok(f64::NEG_INFINITY);
+        }
+        Ok(lambda / (self.depth - WARMUP) as f64)
+    }
+
+    // like iterate with the circle map, θ is not reduced mod 1 since only sin and cos of it are needed
+    fn iterate_circle(
+        &self,
+        omega: f64,
+        k: f64,
+        theta_0: f64,
+        noise: &mut Option<Rng>,
+    ) -> Result<f64, f64> {
+        let mut theta = theta_0;
+        let mut lambda = 0.0;
+        let mut terms = 0;
+        let mut critical_hits = 0;
+
+        for n in 0..self.depth {
+            let derivative = (1.0 - k * (TAU * theta).cos()).abs();
+            if derivative != 0.0 || self.on_log_zero == LogZero::Keep && n > WARMUP {
+                lambda += derivative.ln();
+                terms += 1;
+            } else if n > 0 {
+                match self.on_log_zero {
+                    LogZero::Keep | LogZero::Skip => {}
+                    LogZero::Clamp => {
+                        lambda += f64::MIN_POSITIVE.ln();
+                        terms += 1;
+                    }
+                    LogZero::Count => critical_hits += 1,
+                }
+            }
+            if n > WARMUP
+                && self
+                    .superstable
+                    .is_some_and(|tolerance| derivative < tolerance)
+            {
+                critical_hits += 1;
+            }
+
+            theta += omega - k / TAU * (TAU * theta).sin();
+            if let (Some(rng), Some(noise)) = (noise.as_mut(), self.noise) {
+                theta += rng.range(-noise.amplitude, noise.amplitude);
+            }
+
+            if !(-self.bailout..=self.bailout).contains(&lambda) {
+                return Err(self.bailed_out(lambda, terms));
+            }
+        }
+        if critical_hits > 0 {
+            return Ok(f64::NEG_INFINITY);
+        }
+        Ok(lambda / (self.depth - WARMUP) as f64)
+    }
+
+    // like iterate in the complex plane, λ from |r·(1−2z)|
+    fn iterate_complex(
+        &self,
+        (re, im): (f64, f64),
+        x_0: f64,
+        noise: &mut Option<Rng>,
+    ) -> Result<f64, f64> {
+        let mul = |(a, b): (f64, f64), (c, d): (f64, f64)| (a * c - b * d, a * d + b * c);
+
+        let mut z = (x_0, 0.0);
+        let mut lambda = 0.0;
+        let mut terms = 0;
+        let mut critical_hits = 0;
+
+        for n in 0..self.depth {
+            let critical = (1.0 - 2.0 * z.0).hypot(-2.0 * z.1);
+            let derivative = re.hypot(im) * critical;
+            if critical != 0.0 || self.on_log_zero == LogZero::Keep && n > WARMUP {
+                lambda += derivative.ln();
+                terms += 1;
+            } else if n > 0 {
+                match self.on_log_zero {
+                    LogZero::Keep | LogZero::Skip => {}
+                    LogZero::Clamp => {
+                        lambda += f64::MIN_POSITIVE.ln();
+                        terms += 1;
+                    }
+                    LogZero::Count => critical_hits += 1,
+                }
+            }
+            if n > WARMUP
+                && self
+                    .superstable
+                    .is_some_and(|tolerance| critical < tolerance)
+            {
+                critical_hits += 1;
+            }
+
+            z = mul((re, im), mul(z, (1.0 - z.0, -z.1)));
+            if let (Some(rng), Some(noise)) = (noise.as_mut(), self.noise) {
+                z.0 += rng.range(-noise.amplitude, noise.amplitude);
+                z.1 += rng.range(-noise.amplitude, noise.amplitude);
+            }
+
+            // escaped orbits are handled like any other bailout, NaN has escaped as well
+            let escaped = z.0.hypot(z.1) >= ESCAPE_RADIUS || z.0.is_nan() || z.1.is_nan();
+            if !(-self.bailout..=self.bailout).contains(&lambda) || escaped {
+                return Err(self.bailed_out(lambda, terms));
+            }
+        }
+        if critical_hits > 0 {
+            return Ok(f64::NEG_INFINITY);
+        }
+        Ok(lambda / (self.depth - WARMUP) as f64)
+    }
+
+    // both exponents of the coupled pair by QR decomposition: two tangent vectors are pushed through
+    // the jacobian every step and orthonormalized again (Gram-Schmidt), the logs of the diagonal of R
+    // sum up to λ₁ and λ₂. which of them, or what combination, becomes the pixel value is up to spectrum
+    fn iterate_coupled(
+        &self,
+        schedule: &Schedule,
+        x_0: f64,
+        (coupling, spectrum): (f64, Spectrum),
+        noise: &mut Option<Rng>,
+    ) -> Result<f64, f64> {
+        let r = |n| schedule.r(n);
+
+        // different start values, a synchronized pair would never leave the diagonal
+        let (mut x, mut y) = (x_0, x_0 / 2.0);
+        let (mut q1, mut q2) = ((1.0, 0.0), (0.0, 1.0));
+        let mut lambdas = [0.0, 0.0];
+        let mut terms = 0;
+        let mut critical_hits = 0;
+
+        for n in 0..self.depth {
+            let (dx, dy) = (r(n) * (1.0 - 2.0 * x), r(n) * (1.0 - 2.0 * y));
+            let jacobian = |(u, v): (f64, f64)| {
+                (
+                    (1.0 - coupling) * dx * u + coupling * dy * v,
+                    coupling * dx * u + (1.0 - coupling) * dy * v,
+                )
+            };
+            let (u, v) = (jacobian(q1), jacobian(q2));
+            let r11 = u.0.hypot(u.1);
+            let collapsed = !(r11 > 0.0 && r11.is_finite());
+            if !collapsed {
+                q1 = (u.0 / r11, u.1 / r11);
+            }
+            // whatever is left of v perpendicular to q1
+            let along = q1.0 * v.0 + q1.1 * v.1;
+            let w = if collapsed {
+                v
+            } else {
+                (v.0 - along * q1.0, v.1 - along * q1.1)
+            };
+            let r22 = w.0.hypot(w.1);
+            q2 = if r22 > 0.0 && r22.is_finite() {
+                (w.0 / r22, w.1 / r22)
+            } else {
+                (-q1.1, q1.0)
+            };
+
+            let mut any_term = false;
+            for (lambda, growth) in lambdas.iter_mut().zip([r11, r22]) {
+                if growth > 0.0 && growth.is_finite()
+                    || self.on_log_zero == LogZero::Keep && n > WARMUP
+                {
+                    *lambda += growth.ln();
+                    any_term = true;
+                } else if n > 0 {
+                    match self.on_log_zero {
+                        LogZero::Keep | LogZero::Skip => {}
+                        LogZero::Clamp => {
+                            *lambda += f64::MIN_POSITIVE.ln();
+                            any_term = true;
+                        }
+                        LogZero::Count => critical_hits += 1,
+                    }
+                }
+            }
+            if any_term {
+                terms += 1;
+            }
+            if collapsed {
+                // start over with fresh directions, the old ones collapsed
+                (q1, q2) = ((1.0, 0.0), (0.0, 1.0));
+            }
+            if n > WARMUP
+                && self
+                    .superstable
+                    .is_some_and(|tolerance| (1.0 - 2.0 * x).abs() < tolerance)
+            {
+                critical_hits += 1;
+            }
+
+            let (fx, fy) = (r(n) * x * (1.0 - x), r(n) * y * (1.0 - y));
+            (x, y) = (
+                (1.0 - coupling) * fx + coupling * fy,
+                (1.0 - coupling) * fy + coupling * fx,
+            );
+            if let (Some(rng), Some(noise)) = (noise.as_mut(), self.noise) {
+                x = (x + rng.range(-noise.amplitude, noise.amplitude)).clamp(0.0, 1.0);
+                y = (y + rng.range(-noise.amplitude, noise.amplitude)).clamp(0.0, 1.0);
+            }
+
+            let bailout = -self.bailout..=self.bailout;
+            if !lambdas.iter().all(|lambda| bailout.contains(lambda)) {
+                let [l1, l2] = lambdas.map(|lambda| self.bailed_out(lambda, terms));
+                return Err(spectrum.value(l1, l2));
+            }
+        }
+        if critical_hits > 0 {
+            return Ok(f64::NEG_INFINITY);
+        }
+        let [l1, l2] = lambdas.map(|lambda| lambda / (self.depth - WARMUP) as f64);
+        Ok(spectrum.value(l1, l2))
+    }
+}
+
+// r(n) along one orbit at (a, b). without drift the values of the letters are computed once per pixel,
+// with drift a and b move every step and the letter of the step is evaluated at where they are
+struct Schedule<'a> {
+    kernel: &'a Kernel,
+    sequence: Vec<f64>,
+    at: (f64, f64),
+}
+
+impl Schedule<'_> {
+    fn r(&self, n: u32) -> f64 {
+        let i = n as usize % self.sequence.len();
+        let Some(drift) = self.kernel.drift else {
+            return self.sequence[i];
+        };
+        let (da, db) = drift.offset(n, self.kernel.depth);
+        let (a, b) = self.at;
+        self.kernel
+            .alphabet
+            .value(self.kernel.sequence_rule[i], a + da, b + db)
+    }
+}
+
+// compute some rows of the λ grid, so callers can render progressively and update the window in between
+// todo: splitting the image over several devices (two GPUs, or GPU + CPU) needs a GPU backend first.
+// the row ranges here are the natural unit for that: one queue of row ranges per device, each writing
+// into its own slice of the λ grid, so the merge step is just waiting for all queues to drain.
+pub fn render_rows(
+    lambdas: &mut [f64],
+    width: usize,
+    height: usize,
+    region: &Region,
+    kernel: &Kernel,
+    rows: Range<usize>,
+) {
+    let _span = tracing::info_span!("kernel", rows = ?rows).entered();
+    // nothing to do, and rows of no pixels cannot be split off the grid
+    if width == 0 || rows.is_empty() {
+        return;
+    }
+    let count = rows.len();
+    let band = &mut lambdas[rows.start * width..rows.end * width];
+    on_threads(band.chunks_exact_mut(width).zip(rows), count, |(row, y)| {
+        render_row(row, y, height, region, kernel)
+    });
+}
+
+// λ (or the selected measure) at single points, e.g. the pixels a cache does not know yet
+pub fn render_points(lambdas: &mut [f64], points: &[(f64, f64)], kernel: &Kernel) {
+    const CHUNK: usize = 256;
+    let chunks = lambdas.chunks_mut(CHUNK).zip(points.chunks(CHUNK));
+    on_threads(chunks, points.len().div_ceil(CHUNK), |(lambdas, points)| {
+        for (lambda, &(a, b)) in lambdas.iter_mut().zip(points) {
+            *lambda = kernel.value(a, b);
+        }
+    });
+}
+
+// work on count items spread over the --threads computing threads
+fn on_threads<I: Iterator + Send>(items: I, count: usize, work: impl Fn(I::Item) + Sync) {
+    let threads = threads().min(count);
+    // with --nice even a single thread is spawned, the caller may be the window thread
+    if threads <= 1 && !NICE.load(Ordering::Relaxed) {
+        items.for_each(work);
+        return;
+    }
+    // every thread takes the next item when done with its last, slow rows (chaos at full depth)
+    // do not hold up the others
+    let queue = Mutex::new(items);
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                lower_priority();
+                loop {
+                    let next = queue.lock().unwrap().next();
+                    let Some(item) = next else {
+                        break;
+                    };
+                    work(item);
+                }
+            });
+        }
+    });
+}
+
+// one row of λ (or the selected measure), the row slice defines the width
+fn render_row(row: &mut [f64], y: usize, height: usize, region: &Region, kernel: &Kernel) {
+    let width = row.len();
+    for (x, lambda) in row.iter_mut().enumerate() {
+        let (a, b) = region.pixel_to_world(x, y, width, height);
+        *lambda = kernel.value(a, b);
+    }
+}
+
+// a diagram of fixed size for library users that want to stream it,
+// e.g. into an encoder or over the network, without allocating the whole grid
+#[derive(Debug, Clone, PartialEq)]
+pub struct Renderer {
+    pub width: usize,
+    pub height: usize,
+    pub region: Region,
+    pub kernel: Kernel,
+}
+
+impl Renderer {
+    pub fn new(width: usize, height: usize, region: Region, kernel: Kernel) -> Self {
+        Renderer {
+            width,
+            height,
+            region,
+            kernel,
+        }
+    }
+
+    // λ of row y
+    pub fn row(&self, y: usize) -> Vec<f64> {
+        let mut row = vec![f64::NAN; self.width];
+        render_row(&mut row, y, self.height, &self.region, &self.kernel);
+        row
+    }
+
+    // rows from top to bottom, computed a few per thread at a time when the iterator gets there
+    pub fn rows(&self) -> impl Iterator<Item = Vec<f64>> + '_ {
+        let band = threads() * 4;
+        (0..self.height).step_by(band).flat_map(move |start| {
+            let rows = start..(start + band).min(self.height);
+            let mut lambdas = vec![f64::NAN; rows.len() * self.width];
+            on_threads(
+                lambdas
+                    .chunks_exact_mut(self.width.max(1))
+                    .zip(rows.clone()),
+                rows.len(),
+                |(row, y)| render_row(row, y, self.height, &self.region, &self.kernel),
+            );
+            let width = self.width;
+            rows.map(move |y| lambdas[(y - start) * width..(y - start + 1) * width].to_vec())
+        })
+    }
+}
+
+// tiles whose border is deeper than this on one side of λ = 0 get their interior interpolated,
+// as long as λ along the border varies less than QUADTREE_SPREAD. a superstable curve crossing
+// the tile shows up as a dip on its border and keeps it from being filled over
+const QUADTREE_MARGIN: f64 = 0.1;
+const QUADTREE_SPREAD: f64 = 0.5;
+// tiles this small are computed completely instead of subdivided further
+const QUADTREE_MIN: usize = 8;
+
+// Mariani-Silver rendering: λ is computed on the border of a tile. if the whole border is clearly and evenly
+// ordered or chaotic, the interior is interpolated from it, otherwise the tile is split in four.
+// large stable areas cost little more than their outline. returns the number of pixels actually computed
+pub fn render_quadtree(
+    lambdas: &mut [f64],
+    width: usize,
+    height: usize,
+    region: &Region,
+    kernel: &Kernel,
+) -> usize {
+    let _span = tracing::info_span!("kernel", quadtree = true).entered();
+    lambdas.fill(f64::NAN);
+    let mut computed = 0;
+    let mut compute = |lambdas: &mut [f64], x: usize, y: usize| {
+        let index = y * width + x;
+        if lambdas[index].is_nan() {
+            let (a, b) = region.pixel_to_world(x, y, width, height);
+            lambdas[index] = kernel.value(a, b);
+            computed += 1;
+        }
+        lambdas[index]
+    };
+
+    // inclusive pixel ranges
+    let mut tiles = vec![(0, 0, width.saturating_sub(1), height.saturating_sub(1))];
+    while let Some((x0, y0, x1, y1)) = tiles.pop() {
+        if x1 - x0 < QUADTREE_MIN || y1 - y0 < QUADTREE_MIN {
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    compute(lambdas, x, y);
+                }
+            }
+            continue;
+        }
+        let border: Vec<f64> = (x0..=x1)
+            .flat_map(|x| [(x, y0), (x, y1)])
+            .chain((y0 + 1..y1).flat_map(|y| [(x0, y), (x1, y)]))
+            .map(|(x, y)| compute(lambdas, x, y))
+            .collect();
+        let (low, high) = analysis::lambda_range(&border);
+        let uniform = border.iter().all(|l| l.is_finite())
+            && (high < -QUADTREE_MARGIN || low > QUADTREE_MARGIN)
+            && high - low < QUADTREE_SPREAD;
+        if uniform {
+            fill_interior(lambdas, width, (x0, y0, x1, y1));
+        } else {
+            let (xm, ym) = ((x0 + x1) / 2, (y0 + y1) / 2);
+            tiles.extend([
+                (x0, y0, xm, ym),
+                (xm, y0, x1, ym),
+                (x0, ym, xm, y1),
+                (xm, ym, x1, y1),
+            ]);
+        }
+    }
+    computed
+}
+
+// interior of a tile from its computed border, the mean of the horizontal and vertical linear interpolation
+fn fill_interior(
+    lambdas: &mut [f64],
+    width: usize,
+    (x0, y0, x1, y1): (usize, usize, usize, usize),
+) {
+    let at = |lambdas: &[f64], x: usize, y: usize| lambdas[y * width + x];
+    for y in y0 + 1..y1 {
+        let v = (y - y0) as f64 / (y1 - y0) as f64;
+        for x in x0 + 1..x1 {
+            let u = (x - x0) as f64 / (x1 - x0) as f64;
+            let horizontal = at(lambdas, x0, y) * (1.0 - u) + at(lambdas, x1, y) * u;
+            let vertical = at(lambdas, x, y0) * (1.0 - v) + at(lambdas, x, y1) * v;
+            lambdas[y * width + x] = (horizontal + vertical) / 2.0;
+        }
+    }
+}
+
+// first pass of adaptive rendering: a quarter of the depth is enough to tell order from chaos away from the border
+pub fn shallow(kernel: &Kernel) -> Kernel {
+    let mut shallow = kernel.clone();
+    shallow.depth = (kernel.depth / 4).max(WARMUP + 20);
+    shallow
+}
+
+// second pass of adaptive rendering: pixels whose neighbors straddle λ = 0 are computed again with
+// quality times the depth of kernel, averaged over quality × quality samples spread over the pixel.
+// returns the number of pixels refined
+pub fn refine_border(
+    lambdas: &mut [f64],
+    width: usize,
+    height: usize,
+    region: &Region,
+    kernel: &Kernel,
+    quality: u32,
+) -> usize {
+    let border = analysis::border_pixels(lambdas, width, height, 0.0);
+    let mut deep = kernel.clone();
+    deep.depth = kernel.depth.saturating_mul(quality);
+    let samples = quality.max(1) as usize;
+    let (pixel_width, pixel_height) = (
+        (region.x_max - region.x_min) / width as f64,
+        (region.y_max - region.y_min) / height as f64,
+    );
+    let offset = |i: usize| (i as f64 + 0.5) / samples as f64 - 0.5;
+    for &index in &border {
+        let (a, b) = region.pixel_to_world(index % width, index / width, width, height);
+        let (sum, count) = (0..samples * samples)
+            .map(|s| {
+                deep.value(
+                    a + offset(s % samples) * pixel_width,
+                    b + offset(s / samples) * pixel_height,
+                )
+            })
+            .filter(|lambda| lambda.is_finite())
+            .fold((0.0, 0), |(sum, count), lambda| (sum + lambda, count + 1));
+        if count > 0 {
+            lambdas[index] = sum / count as f64;
+        }
+    }
+    border.len()
+}
+
+// map / lerp between to ranges
+pub fn map(val: f64, start1: f64, stop1: f64, start2: f64, stop2: f64) -> f64 {
+    start2 + (stop2 - start2) * ((val - start1) / (stop1 - start1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kernel(on_bailout: Bailout) -> Kernel {
+        Kernel {
+            bailout: 1000.0,
+            on_bailout,
+            ..Kernel::new("AB".chars().collect())
+        }
+    }
+
+    // r = 5 throws x out of the unit interval, the orbit runs off to -∞
+    const DIVERGENT: (f64, f64) = (5.0, 5.0);
+    // r = 3.2 settles on a period 2 orbit
+    const PERIODIC: (f64, f64) = (3.2, 3.2);
+
+    #[test]
+    fn bailed_out_pixels_follow_the_policy() {
+        let (a, b) = DIVERGENT;
+        assert_eq!(kernel(Bailout::Sentinel).value(a, b), f64::INFINITY);
+        assert_eq!(kernel(Bailout::Retry).value(a, b), f64::INFINITY);
+
+        let clamped = kernel(Bailout::Clamp).value(a, b);
+        assert!(clamped > 0.0 && clamped <= 1000.0, "{clamped}");
+        let stopped = kernel(Bailout::Stop).value(a, b);
+        assert!(stopped > 0.0 && stopped < clamped, "{stopped}");
+    }
+
+    #[test]
+    fn bailout_leaves_bounded_orbits_alone() {
+        let (a, b) = PERIODIC;
+        let lambda = kernel(Bailout::Stop).value(a, b);
+        assert!(lambda < 0.0, "{lambda}");
+        for on_bailout in [Bailout::Sentinel, Bailout::Clamp, Bailout::Retry] {
+            assert_eq!(kernel(on_bailout).value(a, b), lambda);
+        }
+    }
+
+    // r = 2 keeps x₀ = 0.5 on the critical point for good, every derivative is 0
+    fn superstable(on_log_zero: LogZero) -> f64 {
+        let kernel = Kernel {
+            on_log_zero,
+            ..Kernel::new("AB".chars().collect())
+        };
+        kernel.value(2.0, 2.0)
+    }
+
+    #[test]
+    fn log_zero_follows_the_policy() {
+        assert_eq!(superstable(LogZero::Keep), f64::NEG_INFINITY);
+        assert_eq!(superstable(LogZero::Skip), 0.0);
+        assert_eq!(superstable(LogZero::Count), f64::NEG_INFINITY);
+        let clamped = superstable(LogZero::Clamp);
+        assert!(clamped.is_finite() && clamped < -700.0, "{clamped}");
+    }
+
+    #[test]
+    fn log_zero_leaves_other_orbits_alone() {
+        let (a, b) = PERIODIC;
+        let lambda = Kernel::new("AB".chars().collect()).value(a, b);
+        for on_log_zero in [LogZero::Skip, LogZero::Clamp, LogZero::Count] {
+            let kernel = Kernel {
+                on_log_zero,
+                ..Kernel::new("AB".chars().collect())
+            };
+            assert_eq!(kernel.value(a, b), lambda);
+        }
+    }
+
+    fn coupled(coupling: f64, spectrum: Spectrum) -> Kernel {
+        Kernel {
+            map: StateMap::Coupled(coupling, spectrum),
+            ..Kernel::new("AB".chars().collect())
+        }
+    }
+
+    #[test]
+    fn spectrum_orders_and_combines_the_exponents() {
+        assert_eq!(Spectrum::Largest.value(-1.0, 0.5), 0.5);
+        assert_eq!(Spectrum::Second.value(-1.0, 0.5), -1.0);
+        assert_eq!(Spectrum::Sum.value(-1.0, 0.5), -0.5);
+        assert_eq!(Spectrum::KaplanYorke.value(-0.5, -1.0), 0.0);
+        assert_eq!(Spectrum::KaplanYorke.value(0.5, 0.2), 2.0);
+        assert_eq!(Spectrum::KaplanYorke.value(0.5, -1.0), 1.5);
+    }
+
+    #[test]
+    fn uncoupled_maps_have_the_exponent_of_one_map_twice() {
+        let (a, b) = PERIODIC;
+        let single = Kernel::new("AB".chars().collect()).value(a, b);
+        for spectrum in [Spectrum::Largest, Spectrum::Second] {
+            let lambda = coupled(0.0, spectrum).value(a, b);
+            assert!((lambda - single).abs() < 0.05, "{lambda} against {single}");
+        }
+    }
+
+    #[test]
+    fn sum_of_the_spectrum_is_both_exponents() {
+        let (a, b) = (3.8, 3.6);
+        let exponents = [Spectrum::Largest, Spectrum::Second, Spectrum::Sum]
+            .map(|spectrum| coupled(0.1, spectrum).value(a, b));
+        assert!(exponents[0] >= exponents[1]);
+        assert!((exponents[0] + exponents[1] - exponents[2]).abs() < 1e-12);
+    }
+
+    fn full_and_quadtree(region: Region) -> (Vec<f64>, Vec<f64>, usize) {
+        const SIZE: usize = 64;
+        let kernel = Kernel::new("AB".chars().collect());
+        let mut full = vec![f64::NAN; SIZE * SIZE];
+        render_rows(&mut full, SIZE, SIZE, &region, &kernel, 0..SIZE);
+        let mut quadtree = vec![0.0; SIZE * SIZE];
+        let computed = render_quadtree(&mut quadtree, SIZE, SIZE, &region, &kernel);
+        (full, quadtree, computed)
+    }
+
+    #[test]
+    fn quadtree_fills_ordered_areas_from_their_outline() {
+        // a fixed point all over, λ changes smoothly
+        let (full, quadtree, computed) = full_and_quadtree(Region::new(2.2, 2.6, 2.2, 2.6));
+        assert!(computed < full.len() / 2, "{computed} of {}", full.len());
+        for (full, quadtree) in full.iter().zip(&quadtree) {
+            assert!(
+                (full - quadtree).abs() < 0.05,
+                "{quadtree} instead of {full}"
+            );
+        }
+    }
+
+    #[test]
+    fn quadtree_computes_the_chaos_border_exactly() {
+        let (full, quadtree, _) = full_and_quadtree(Region::new(2.0, 4.0, 2.0, 4.0));
+        assert!(quadtree.iter().all(|lambda| !lambda.is_nan()));
+        let chaotic = full.iter().zip(&quadtree).filter(|(full, _)| **full > 0.0);
+        for (full, quadtree) in chaotic {
+            assert_eq!(full.to_bits(), quadtree.to_bits());
+        }
+    }
+
+    #[test]
+    fn the_start_of_a_circle_orbit_is_no_critical_hit() {
+        // K = 1 puts θ₀ = 0 on the critical point of the circle map
+        let kernel = Kernel {
+            map: StateMap::Circle,
+            start: 0.0,
+            on_log_zero: LogZero::Count,
+            ..Kernel::new("AB".chars().collect())
+        };
+        assert!(kernel.value(0.3, 1.0).is_finite());
+    }
+}
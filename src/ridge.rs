@@ -0,0 +1,324 @@
+// the λ = 0 curve between order and chaos to sub-pixel precision, for measuring the geometry of the border.
+// wherever two neighbouring pixels lie on different sides of 0 the crossing between their sample points is
+// found by bisection with the kernel, the crossings are linked through the grid cells like marching squares
+// and exported in (a, b) as GeoJSON line strings or CSV.
+
+use crate::render::{self, Kernel, Region};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+
+// edges bisected per thread at a time
+const EDGES_PER_TASK: usize = 256;
+
+pub struct Polyline {
+    pub points: Vec<(f64, f64)>,
+    // the last point connects to the first
+    pub closed: bool,
+}
+
+impl Polyline {
+    pub fn length(&self) -> f64 {
+        let mut length: f64 = self
+            .points
+            .windows(2)
+            .map(|pair| (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1))
+            .sum();
+        if let (true, Some(first), Some(last)) =
+            (self.closed, self.points.first(), self.points.last())
+        {
+            length += (first.0 - last.0).hypot(first.1 - last.1);
+        }
+        length
+    }
+}
+
+// polylines of the curve through the computed part of the grid, steps bisections per crossing.
+// pixels that are not computed (NaN) end a line
+pub fn trace(
+    lambdas: &[f64],
+    width: usize,
+    height: usize,
+    region: &Region,
+    kernel: &Kernel,
+    steps: u32,
+) -> Vec<Polyline> {
+    let _span = tracing::info_span!("ridge", width, height).entered();
+    let grid = Grid {
+        lambdas,
+        width,
+        height,
+    };
+    let edges: Vec<usize> = (0..2 * width * height)
+        .filter(|&edge| grid.crossed(edge))
+        .collect();
+    let mut points = vec![(0.0, 0.0); edges.len()];
+    let queue = Mutex::new(
+        edges
+            .chunks(EDGES_PER_TASK)
+            .zip(points.chunks_mut(EDGES_PER_TASK)),
+    );
+    thread::scope(|scope| {
+        for _ in 0..render::threads() {
+            scope.spawn(|| {
+                render::lower_priority();
+                loop {
+                    let next = queue.lock().unwrap().next();
+                    let Some((edges, points)) = next else {
+                        break;
+                    };
+                    for (&edge, point) in edges.iter().zip(points) {
+                        *point = grid.bisect(edge, region, kernel, steps);
+                    }
+                }
+            });
+        }
+    });
+    let crossings: HashMap<usize, (f64, f64)> = edges.into_iter().zip(points).collect();
+    link(&grid.segments(), &crossings)
+}
+
+// edge e is the one from pixel e % (width × height) to its right neighbour for e < width × height,
+// to the one below it after that
+struct Grid<'a> {
+    lambdas: &'a [f64],
+    width: usize,
+    height: usize,
+}
+
+impl Grid<'_> {
+    fn ends(&self, edge: usize) -> Option<(usize, usize)> {
+        let pixels = self.width * self.height;
+        let (pixel, down) = (edge % pixels, edge >= pixels);
+        let (x, y) = (pixel % self.width, pixel / self.width);
+        match down {
+            false if x + 1 < self.width => Some((pixel, pixel + 1)),
+            true if y + 1 < self.height => Some((pixel, pixel + self.width)),
+            _ => None,
+        }
+    }
+
+    fn crossed(&self, edge: usize) -> bool {
+        self.ends(edge).is_some_and(|(from, to)| {
+            let (from, to) = (self.lambdas[from], self.lambdas[to]);
+            !from.is_nan() && !to.is_nan() && (from > 0.0) != (to > 0.0)
+        })
+    }
+
+    // the crossing in (a, b), narrowed down between the two sample points
+    fn bisect(&self, edge: usize, region: &Region, kernel: &Kernel, steps: u32) -> (f64, f64) {
+        let (from, to) = self.ends(edge).expect("crossed edges have two ends");
+        let world = |pixel: usize| {
+            region.pixel_to_world(
+                pixel % self.width,
+                pixel / self.width,
+                self.width,
+                self.height,
+            )
+        };
+        let ((a0, b0), (a1, b1)) = (world(from), world(to));
+        let at = |t: f64| (a0 + (a1 - a0) * t, b0 + (b1 - b0) * t);
+        let chaotic = self.lambdas[from] > 0.0;
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..steps {
+            let middle = (low + high) / 2.0;
+            let (a, b) = at(middle);
+            let lambda = kernel.value(a, b);
+            // an orbit without an answer ends the search, the crossing stays within the bounds so far
+            if lambda.is_nan() {
+                break;
+            }
+            if (lambda > 0.0) == chaotic {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        at((low + high) / 2.0)
+    }
+
+    // pairs of crossed edges joined within a cell of four sample points. saddles are split by the mean
+    // of the corners like analysis::zero_contour does
+    fn segments(&self) -> Vec<(usize, usize)> {
+        let (width, height) = (self.width, self.height);
+        let pixels = width * height;
+        let mut segments = Vec::new();
+        for y in 0..height.saturating_sub(1) {
+            for x in 0..width.saturating_sub(1) {
+                let pixel = y * width + x;
+                let corners = [pixel, pixel + 1, pixel + width + 1, pixel + width]
+                    .map(|i| self.lambdas[i].clamp(-5.0, 5.0));
+                if corners.iter().any(|v| v.is_nan()) {
+                    continue;
+                }
+                // top, right, bottom, left
+                let edges = [pixel, pixels + pixel + 1, pixel + width, pixels + pixel];
+                let crossed = edges.map(|edge| self.crossed(edge));
+                match crossed.iter().filter(|&&c| c).count() {
+                    2 => {
+                        let mut ends = (0..4).filter(|&i| crossed[i]).map(|i| edges[i]);
+                        segments.extend(ends.next().zip(ends.next()));
+                    }
+                    4 => {
+                        let [top, right, bottom, left] = edges;
+                        let center_chaotic = corners.iter().sum::<f64>() > 0.0;
+                        if center_chaotic == (corners[0] > 0.0) {
+                            segments.extend([(top, right), (bottom, left)]);
+                        } else {
+                            segments.extend([(left, top), (right, bottom)]);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        segments
+    }
+}
+
+// segments sharing a crossing joined into lines, open ones from their ends first, closed loops after
+fn link(segments: &[(usize, usize)], crossings: &HashMap<usize, (f64, f64)>) -> Vec<Polyline> {
+    let mut neighbours: HashMap<usize, Vec<usize>> = HashMap::new();
+    for &(from, to) in segments {
+        neighbours.entry(from).or_default().push(to);
+        neighbours.entry(to).or_default().push(from);
+    }
+    let mut starts: Vec<usize> = neighbours.keys().copied().collect();
+    starts.sort_unstable();
+    // ends before the rest, every edge sits on at most two segments
+    starts.sort_by_key(|edge| neighbours[edge].len() != 1);
+
+    let mut polylines = Vec::new();
+    for start in starts {
+        if neighbours[&start].is_empty() {
+            continue;
+        }
+        let mut line = vec![start];
+        let mut current = start;
+        while let Some(next) = neighbours.get_mut(&current).and_then(Vec::pop) {
+            let back = neighbours.get_mut(&next).expect("both ends are known");
+            if let Some(i) = back.iter().position(|&edge| edge == current) {
+                back.swap_remove(i);
+            }
+            line.push(next);
+            current = next;
+        }
+        let closed = line.len() > 2 && line.first() == line.last();
+        if closed {
+            line.pop();
+        }
+        polylines.push(Polyline {
+            points: line.iter().map(|edge| crossings[edge]).collect(),
+            closed,
+        });
+    }
+    polylines
+}
+
+// FILE.csv gets one row per point, anything else a GeoJSON feature collection
+pub fn save(path: &Path, polylines: &[Polyline]) -> io::Result<()> {
+    let is_csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    let mut out = BufWriter::new(File::create(path)?);
+    if is_csv {
+        write_csv(&mut out, polylines)?;
+    } else {
+        write_geojson(&mut out, polylines)?;
+    }
+    out.flush()
+}
+
+pub fn write_csv(out: &mut impl Write, polylines: &[Polyline]) -> io::Result<()> {
+    writeln!(out, "line,point,a,b,closed")?;
+    for (line, polyline) in polylines.iter().enumerate() {
+        for (point, (a, b)) in polyline.points.iter().enumerate() {
+            writeln!(out, "{line},{point},{a},{b},{}", polyline.closed)?;
+        }
+    }
+    Ok(())
+}
+
+// a along x and b along y, closed lines repeat their first point as GeoJSON rings do
+pub fn write_geojson(out: &mut impl Write, polylines: &[Polyline]) -> io::Result<()> {
+    let features: Vec<_> = polylines
+        .iter()
+        .map(|polyline| {
+            let mut coordinates: Vec<[f64; 2]> =
+                polyline.points.iter().map(|&(a, b)| [a, b]).collect();
+            if polyline.closed {
+                coordinates.push(coordinates[0]);
+            }
+            json!({
+                "type": "Feature",
+                "properties": {"closed": polyline.closed, "length": polyline.length()},
+                "geometry": {"type": "LineString", "coordinates": coordinates},
+            })
+        })
+        .collect();
+    let collection = json!({"type": "FeatureCollection", "features": features});
+    serde_json::to_writer(&mut *out, &collection)?;
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::External;
+    use std::sync::Arc;
+
+    const SIZE: usize = 32;
+
+    // traces the zero line of lambda over 3..4 × 3..4
+    fn trace_of(lambda: fn(f64, f64) -> f64) -> Vec<Polyline> {
+        let kernel = Kernel {
+            external: Some(External {
+                key: 0,
+                value: Arc::new(move |a, b, _, _| lambda(a, b)),
+            }),
+            ..Kernel::new("AB".chars().collect())
+        };
+        let region = Region::new(3.0, 4.0, 3.0, 4.0);
+        let mut lambdas = vec![f64::NAN; SIZE * SIZE];
+        render::render_rows(&mut lambdas, SIZE, SIZE, &region, &kernel, 0..SIZE);
+        trace(&lambdas, SIZE, SIZE, &region, &kernel, 30)
+    }
+
+    #[test]
+    fn a_straight_border_is_one_open_line() {
+        let lines = trace_of(|a, _| a - 3.3);
+        assert_eq!(lines.len(), 1);
+        let line = &lines[0];
+        assert!(!line.closed);
+        assert_eq!(line.points.len(), SIZE);
+        assert!(line.points.iter().all(|(a, _)| (a - 3.3).abs() < 1e-6));
+        assert!((line.length() - 1.0).abs() < 0.05, "{}", line.length());
+    }
+
+    #[test]
+    fn a_round_window_is_one_closed_line() {
+        let lines = trace_of(|a, b| (a - 3.5).hypot(b - 3.5) - 0.25);
+        assert_eq!(lines.len(), 1);
+        let line = &lines[0];
+        assert!(line.closed);
+        for (a, b) in &line.points {
+            assert!(((a - 3.5).hypot(b - 3.5) - 0.25).abs() < 1e-6);
+        }
+        let circumference = 2.0 * std::f64::consts::PI * 0.25;
+        assert!((line.length() - circumference).abs() < 0.02 * circumference);
+    }
+
+    #[test]
+    fn csv_has_a_row_per_point() {
+        let lines = trace_of(|a, _| a - 3.3);
+        let mut out = Vec::new();
+        write_csv(&mut out, &lines).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().count(), 1 + SIZE);
+        assert_eq!(text.lines().next(), Some("line,point,a,b,closed"));
+    }
+}
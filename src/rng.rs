@@ -0,0 +1,53 @@
+// tiny xorshift generator, good enough to pick regions and sequences
+
+use crate::i18n::trf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift must not start at 0
+        Rng(seed.max(1))
+    }
+
+    // the given seed, or one from the clock that is printed so the run can be repeated with --seed
+    pub fn seeded(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            let seed = time_seed();
+            println!(
+                "{}",
+                trf("random seed {0}, repeat with --seed {0}", &[&seed])
+            );
+            seed
+        });
+        Rng::new(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // uniform in 0..1
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn range(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.next_f64()
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+pub fn time_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0x2545_f491_4f6c_dd1d)
+}
@@ -0,0 +1,257 @@
+// --upscale: how frames rendered at fewer pixels than the screen has (--scale, --render-scale) are brought
+// up to it before they are shown, instead of leaving it to the stretching of the window. resampling is
+// separable, rows first and columns after, in 8 bit sRGB like the palettes blend.
+
+use crate::display::{Display, Event};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Upscale {
+    /// every diagram pixel a sharp block
+    #[default]
+    Nearest,
+    /// linear blend of the four nearest pixels, soft
+    Bilinear,
+    /// Lanczos with three lobes, sharper than bilinear with slight ringing at hard edges
+    Lanczos,
+}
+
+impl Upscale {
+    // reach of the filter in source pixels
+    fn support(self) -> f64 {
+        match self {
+            Upscale::Nearest => 0.5,
+            Upscale::Bilinear => 1.0,
+            Upscale::Lanczos => 3.0,
+        }
+    }
+
+    fn weight(self, x: f64) -> f64 {
+        let x = x.abs();
+        match self {
+            Upscale::Nearest => f64::from(x < 0.5),
+            Upscale::Bilinear => (1.0 - x).max(0.0),
+            Upscale::Lanczos if x < 3.0 => sinc(x) * sinc(x / 3.0),
+            Upscale::Lanczos => 0.0,
+        }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let x = x * std::f64::consts::PI;
+        x.sin() / x
+    }
+}
+
+// the frame at to_width × to_height. pixel centers line up, so a whole factor with nearest gives blocks
+pub fn resize(
+    buffer: &[u32],
+    (width, height): (usize, usize),
+    (to_width, to_height): (usize, usize),
+    filter: Upscale,
+) -> Vec<u32> {
+    let _span = tracing::info_span!("resize", to_width, to_height).entered();
+    if width == 0 || height == 0 {
+        return vec![0; to_width * to_height];
+    }
+    let factor = to_width / width;
+    if filter == Upscale::Nearest && (to_width, to_height) == (width * factor, height * factor) {
+        return blocks(buffer, width, factor);
+    }
+    let columns = taps(width, to_width, filter);
+    let rows = taps(height, to_height, filter);
+    let mut wide = vec![[0.0f32; 3]; to_width * height];
+    for (row, wide) in buffer
+        .chunks_exact(width)
+        .zip(wide.chunks_exact_mut(to_width))
+    {
+        for (pixel, taps) in wide.iter_mut().zip(&columns) {
+            *pixel = blend(taps.iter().map(|&(x, weight)| (channels(row[x]), weight)));
+        }
+    }
+    let mut scaled = Vec::with_capacity(to_width * to_height);
+    for taps in &rows {
+        for x in 0..to_width {
+            let column = taps
+                .iter()
+                .map(|&(y, weight)| (wide[y * to_width + x], weight));
+            scaled.push(pack(blend(column)));
+        }
+    }
+    scaled
+}
+
+// every pixel as a block of scale × scale
+fn blocks(buffer: &[u32], width: usize, scale: usize) -> Vec<u32> {
+    let mut scaled = Vec::with_capacity(buffer.len() * scale * scale);
+    for row in buffer.chunks_exact(width.max(1)) {
+        let start = scaled.len();
+        scaled.extend(
+            row.iter()
+                .flat_map(|&pixel| std::iter::repeat_n(pixel, scale)),
+        );
+        for _ in 1..scale {
+            scaled.extend_from_within(start..start + width * scale);
+        }
+    }
+    scaled
+}
+
+// source pixels and their weights for every target pixel along one axis. shrinking widens the filter
+// so that every source pixel still counts
+fn taps(from: usize, to: usize, filter: Upscale) -> Vec<Vec<(usize, f32)>> {
+    let ratio = from as f64 / to as f64;
+    let stretch = ratio.max(1.0);
+    let support = filter.support() * stretch;
+    (0..to)
+        .map(|i| {
+            let center = (i as f64 + 0.5) * ratio - 0.5;
+            let first = (center - support).ceil().max(0.0) as usize;
+            let last = ((center + support).floor().max(0.0) as usize).min(from - 1);
+            let mut taps: Vec<(usize, f64)> = (first..=last)
+                .map(|j| (j, filter.weight((j as f64 - center) / stretch)))
+                .filter(|&(_, weight)| weight != 0.0)
+                .collect();
+            if taps.is_empty() {
+                taps = vec![(center.round().clamp(0.0, (from - 1) as f64) as usize, 1.0)];
+            }
+            let total: f64 = taps.iter().map(|&(_, weight)| weight).sum();
+            taps.into_iter()
+                .map(|(j, weight)| (j, (weight / total) as f32))
+                .collect()
+        })
+        .collect()
+}
+
+fn channels(pixel: u32) -> [f32; 3] {
+    [16, 8, 0].map(|shift| ((pixel >> shift) & 0xFF) as f32)
+}
+
+fn blend(samples: impl Iterator<Item = ([f32; 3], f32)>) -> [f32; 3] {
+    samples.fold([0.0; 3], |sum, (channels, weight)| {
+        [0, 1, 2].map(|i| sum[i] + channels[i] * weight)
+    })
+}
+
+fn pack(channels: [f32; 3]) -> u32 {
+    channels
+        .iter()
+        .zip([16, 8, 0])
+        .map(|(&value, shift)| (value.round().clamp(0.0, 255.0) as u32) << shift)
+        .sum()
+}
+
+// size at a fraction of another, at least a pixel each way
+pub fn scaled_size((width, height): (usize, usize), factor: f64) -> (usize, usize) {
+    let scale = |length: usize| ((length as f64 * factor).round() as usize).max(1);
+    (scale(width), scale(height))
+}
+
+// a display taking frames of factor times its size (--render-scale), resampled to it by filter. frames of
+// any other size, e.g. while the window is being resized, are resampled too. the mouse positions are
+// scaled to the frame
+pub struct Resampled<'a> {
+    display: &'a mut dyn Display,
+    pub factor: f64,
+    filter: Upscale,
+}
+
+impl<'a> Resampled<'a> {
+    pub fn new(display: &'a mut dyn Display, filter: Upscale) -> Self {
+        Resampled {
+            display,
+            factor: 1.0,
+            filter,
+        }
+    }
+}
+
+impl Display for Resampled<'_> {
+    fn is_open(&self) -> bool {
+        self.display.is_open()
+    }
+
+    fn size(&self) -> (usize, usize) {
+        let size = self.display.size();
+        if self.factor == 1.0 || size.0 == 0 || size.1 == 0 {
+            return size;
+        }
+        scaled_size(size, self.factor)
+    }
+
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) {
+        let size = self.display.size();
+        if (width, height) == size || size.0 == 0 || size.1 == 0 {
+            self.display.present(buffer, width, height);
+        } else {
+            let scaled = resize(buffer, (width, height), size, self.filter);
+            self.display.present(&scaled, size.0, size.1);
+        }
+    }
+
+    fn poll_events(&mut self) -> Vec<Event> {
+        let (inner, outer) = (self.display.size(), self.size());
+        self.display
+            .poll_events()
+            .into_iter()
+            .map(|event| event.scaled(inner, outer))
+            .collect()
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.display.set_title(title);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILTERS: [Upscale; 3] = [Upscale::Nearest, Upscale::Bilinear, Upscale::Lanczos];
+
+    #[test]
+    fn taps_are_normalized_and_inside() {
+        for filter in FILTERS {
+            for (from, to) in [(4, 9), (9, 4), (5, 5), (1, 3), (3, 1)] {
+                for taps in taps(from, to, filter) {
+                    let total: f32 = taps.iter().map(|&(_, weight)| weight).sum();
+                    assert!((total - 1.0).abs() < 1e-5, "{filter:?} {from} → {to}");
+                    assert!(taps.iter().all(|&(j, _)| j < from));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_doubles_every_pixel() {
+        let taps = taps(2, 4, Upscale::Nearest);
+        assert_eq!(taps, [[(0, 1.0)], [(0, 1.0)], [(1, 1.0)], [(1, 1.0)]]);
+    }
+
+    // Lanczos keeps its neighbours with weights of float noise, sin(kπ) is not exactly 0
+    #[test]
+    fn same_size_is_the_identity() {
+        for filter in FILTERS {
+            for (i, taps) in taps(5, 5, filter).iter().enumerate() {
+                for &(j, weight) in taps {
+                    let expected = if i == j { 1.0 } else { 0.0 };
+                    assert!(
+                        (weight - expected).abs() < 1e-6,
+                        "{filter:?} {i} {j} {weight}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn resize_keeps_a_flat_image() {
+        let buffer = vec![0x336699; 3 * 3];
+        for filter in FILTERS {
+            let scaled = resize(&buffer, (3, 3), (7, 5), filter);
+            assert_eq!(scaled, vec![0x336699; 7 * 5], "{filter:?}");
+        }
+    }
+}
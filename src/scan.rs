@@ -0,0 +1,171 @@
+// transects: λ and the orbit period sampled at evenly spaced points along a polyline through the
+// diagram, written as CSV and drawn as a small line plot for quantitative looks across structures
+
+use crate::analysis;
+use crate::color::Palette;
+use crate::font::{self, draw_text, GLYPH_HEIGHT};
+use crate::render::{map, Kernel};
+use crate::svg::tick_label;
+use std::io::{self, Write};
+
+// size of the plot panel in the window
+pub const PANEL_WIDTH: usize = 360;
+pub const HEIGHT: usize = 96;
+const BACKGROUND: u32 = 0x808080;
+const ZERO_LINE: u32 = 0xFFFFFF;
+const TEXT: u32 = 0xFFFFFF;
+const PADDING: usize = 4;
+// λ = -∞ and bailed out pixels are plotted at the edge of this range
+const CLAMP: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    // along the path from its first vertex, in units of a and b
+    pub distance: f64,
+    pub a: f64,
+    pub b: f64,
+    pub lambda: f64,
+    pub period: Option<usize>,
+}
+
+// count samples evenly spaced along the polyline through the (a, b) vertices, both ends included
+pub fn sample(kernel: &Kernel, vertices: &[(f64, f64)], count: usize) -> Vec<Sample> {
+    if vertices.is_empty() || count == 0 {
+        return Vec::new();
+    }
+    let lengths: Vec<f64> = vertices
+        .windows(2)
+        .map(|pair| (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1))
+        .collect();
+    let total: f64 = lengths.iter().sum();
+    (0..count)
+        .map(|i| {
+            let distance = match count {
+                1 => 0.0,
+                _ => total * i as f64 / (count - 1) as f64,
+            };
+            let (a, b) = point_at(vertices, &lengths, distance);
+            Sample {
+                distance,
+                a,
+                b,
+                lambda: kernel.lyapunov(a, b),
+                period: analysis::period(&kernel.states(a, b)),
+            }
+        })
+        .collect()
+}
+
+// the point the given distance along the polyline, the last vertex beyond its end
+fn point_at(vertices: &[(f64, f64)], lengths: &[f64], mut distance: f64) -> (f64, f64) {
+    for (pair, &length) in vertices.windows(2).zip(lengths) {
+        if distance <= length && length > 0.0 {
+            let t = distance / length;
+            return (
+                pair[0].0 + (pair[1].0 - pair[0].0) * t,
+                pair[0].1 + (pair[1].1 - pair[0].1) * t,
+            );
+        }
+        distance -= length;
+    }
+    vertices[vertices.len() - 1]
+}
+
+// distance,a,b,lambda,period with an empty period for chaos
+pub fn write_csv(samples: &[Sample], out: &mut impl Write) -> io::Result<()> {
+    writeln!(out, "distance,a,b,lambda,period")?;
+    for sample in samples {
+        let period = sample.period.map(|p| p.to_string()).unwrap_or_default();
+        writeln!(
+            out,
+            "{},{},{},{},{period}",
+            sample.distance, sample.a, sample.b, sample.lambda
+        )?;
+    }
+    Ok(())
+}
+
+// λ over the distance as 0RGB buffer of the given size, every point in the palette color of its λ,
+// λ = 0 as white line and the λ range written at the left
+pub fn plot(samples: &[Sample], palette: Palette, (width, height): (usize, usize)) -> Vec<u32> {
+    let mut buffer = vec![BACKGROUND; width * height];
+    let lambdas: Vec<f64> = samples
+        .iter()
+        .map(|sample| sample.lambda.clamp(-CLAMP, CLAMP))
+        .collect();
+    let (min, max) = analysis::lambda_range(&lambdas);
+    let (left, right) = (PADDING, width.saturating_sub(PADDING));
+    let (top, bottom) = (PADDING, height.saturating_sub(PADDING));
+    if min > max || right <= left + 1 || bottom <= top + 1 {
+        return buffer;
+    }
+    let max = max.max(min + f64::EPSILON);
+    let row = |lambda: f64| map(lambda, min, max, (bottom - 1) as f64, top as f64).round() as usize;
+
+    if min < 0.0 && 0.0 < max {
+        let y = row(0.0);
+        buffer[y * width + left..y * width + right].fill(ZERO_LINE);
+    }
+    // a column per pixel, filled between neighbouring samples so steep jumps stay connected
+    let mut last: Option<usize> = None;
+    for x in left..right {
+        let i = (x - left) * (lambdas.len() - 1) / (right - left - 1).max(1);
+        let y = row(lambdas[i].clamp(min, max));
+        let color = palette.colorize_in(samples[i].lambda, (min, max));
+        let (from, to) = match last {
+            Some(previous) => (previous.min(y), previous.max(y)),
+            None => (y, y),
+        };
+        for y in from..=to {
+            buffer[y * width + x] = color;
+        }
+        last = Some(y);
+    }
+
+    let size = (width, height);
+    let (max_label, min_label) = (tick_label(max, max - min), tick_label(min, max - min));
+    draw_text(
+        &mut buffer,
+        size,
+        (left as i64, top as i64),
+        &max_label,
+        TEXT,
+        1,
+    );
+    let y = (bottom - GLYPH_HEIGHT) as i64;
+    draw_text(&mut buffer, size, (left as i64, y), &min_label, TEXT, 1);
+    let (w, _) = font::text_size("λ", 1);
+    draw_text(
+        &mut buffer,
+        size,
+        ((right - w) as i64, top as i64),
+        "λ",
+        TEXT,
+        1,
+    );
+    buffer
+}
+
+// straight lines between the pixel positions, for drawing the path over the diagram. segments far
+// longer than the buffer (deep zooms) are dotted instead of drawn pixel by pixel
+pub fn draw_polyline(buffer: &mut [u32], width: usize, points: &[(f64, f64)], color: u32) {
+    let height = buffer.len() / width.max(1);
+    let longest = 4 * (width + height);
+    let mut put = |x: f64, y: f64| {
+        if x >= 0.0 && y >= 0.0 && (x as usize) < width && (y as usize) < height {
+            buffer[y as usize * width + x as usize] = color;
+        }
+    };
+    for pair in points.windows(2) {
+        let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+        let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0) as usize;
+        let steps = steps.min(longest);
+        for step in 0..=steps {
+            let t = step as f64 / steps as f64;
+            put(x0 + (x1 - x0) * t, y0 + (y1 - y0) * t);
+        }
+    }
+    if let [(x, y)] = points {
+        put(*x, *y);
+    }
+}
@@ -0,0 +1,74 @@
+// desktop resolution for fullscreen mode and pixel density for HiDPI screens. minifb can not tell, so
+// look at the connected DRM outputs (Linux, also Raspberry Pi kiosks), everything else needs
+// --screen-size and GDK_SCALE.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// desktops scale everything by 2 from about this density on
+const HIDPI_DPI: f64 = 192.0;
+
+// preferred mode of the first connected output
+pub fn desktop_size() -> Option<(usize, usize)> {
+    first_output(|path| {
+        // first line is the preferred mode, e.g. 1920x1080
+        let modes = fs::read_to_string(path.join("modes")).ok()?;
+        parse_size(modes.lines().next()?)
+    })
+}
+
+// screen pixels per window pixel, what the desktop scales windows by. taken from GDK_SCALE or
+// QT_SCALE_FACTOR like toolkits do, otherwise guessed from the physical size in the EDID of the first
+// output. 1 if nothing is known
+pub fn density() -> usize {
+    let configured = ["GDK_SCALE", "QT_SCALE_FACTOR"]
+        .into_iter()
+        .find_map(|name| env::var(name).ok()?.trim().parse::<f64>().ok());
+    let density = configured.or_else(|| {
+        let dpi = first_output(edid_dpi)?;
+        Some(if dpi >= HIDPI_DPI {
+            (dpi / 96.0).floor()
+        } else {
+            1.0
+        })
+    });
+    // only whole factors stay crisp
+    density.map_or(1, |density| density.round().max(1.0) as usize)
+}
+
+fn first_output<T>(read: impl Fn(&Path) -> Option<T>) -> Option<T> {
+    let mut outputs: Vec<_> = fs::read_dir("/sys/class/drm").ok()?.flatten().collect();
+    outputs.sort_by_key(|output| output.file_name());
+
+    outputs.iter().find_map(|output| {
+        let path = output.path();
+        let status = fs::read_to_string(path.join("status")).ok()?;
+        if status.trim() != "connected" {
+            return None;
+        }
+        read(&path)
+    })
+}
+
+// horizontal pixels per inch of the preferred mode, the EDID holds the screen width in cm at byte 21
+fn edid_dpi(path: &Path) -> Option<f64> {
+    let edid = fs::read(path.join("edid")).ok()?;
+    let centimeters = *edid.get(21).filter(|&&cm| cm > 0)? as f64;
+    let modes = fs::read_to_string(path.join("modes")).ok()?;
+    let (width, _) = parse_size(modes.lines().next()?)?;
+    Some(width as f64 / (centimeters / 2.54))
+}
+
+// WIDTHxHEIGHT, e.g. 1920x1080
+pub fn parse_size(size: &str) -> Option<(usize, usize)> {
+    let (width, height) = size.trim().split_once('x')?;
+    let width = width.parse().ok().filter(|&w| w > 0)?;
+    // modes may carry a suffix like 1920x1080i
+    let height = height
+        .trim_end_matches(|c: char| !c.is_ascii_digit())
+        .parse()
+        .ok()
+        .filter(|&h| h > 0)?;
+    Some((width, height))
+}
@@ -0,0 +1,175 @@
+// script subcommand: frames driven by a Rhai script instead of fixed parameters.
+// on_frame(t) is called for every frame with t going from 0 to 1 and returns a map with any of
+// region ([a_min, a_max, b_min, b_max]), sequence, palette and depth, missing ones keep their value.
+// an optional colorize(lambda) returning 0xRRGGBB replaces the palette.
+
+use crate::cache::stable_hash;
+use crate::cli::ScriptArgs;
+use crate::color::Palette;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::i18n;
+use crate::interrupt;
+use crate::presets;
+use crate::render::{self, parse_sequence, Kernel, Region};
+use crate::video::Frames;
+use clap::ValueEnum;
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use std::fs;
+
+pub fn run(args: &ScriptArgs) -> Result<(), Error> {
+    interrupt::install();
+    let engine = Engine::new();
+    let source = fs::read_to_string(&args.script).map_err(|e| Error::io(&args.script, e))?;
+    let source_hash = stable_hash(&source);
+    let ast = engine
+        .compile(source)
+        .map_err(|e| Error::Script(format!("{}: {e}", args.script.display())))?;
+    let defines = |name: &str| {
+        ast.iter_functions()
+            .any(|f| f.name == name && f.params.len() == 1)
+    };
+    if !defines("on_frame") {
+        return Err(Error::Script(format!(
+            "{}: no on_frame(t) function",
+            args.script.display()
+        )));
+    }
+    let custom_colors = defines("colorize");
+
+    // start from the default preset, the script changes what it needs
+    let preset = presets::find(presets::DEFAULT_PRESET).expect("default preset exists");
+    let mut kernel = Kernel::new(preset.sequence.chars().collect());
+    let mut region = preset.region;
+    let mut palette = preset.palette;
+
+    let (width, height) = args.size;
+    let frames = args.frames as usize;
+    let mut sink = Frames::new(
+        &args.out,
+        "script",
+        args.video.as_deref(),
+        args.fps,
+        args.size,
+        args.resume_animation,
+    )?;
+
+    let mut scope = Scope::new();
+    for index in 0..frames {
+        let t = index as f64 / (frames - 1).max(1) as f64;
+        let state: Map = engine
+            .call_fn(&mut scope, &ast, "on_frame", (t,))
+            .map_err(|e| Error::Script(format!("on_frame({t}): {e}")))?;
+        apply(&state, &mut kernel, &mut region, &mut palette)?;
+        // the source covers colorize, the state after on_frame everything else
+        let hash = stable_hash(&format!(
+            "script {source_hash:016x} {kernel:?} {region:?} {palette:?} {width}x{height}"
+        ));
+        if let Some(path) = sink.is_done(index, hash) {
+            println!(
+                "{}",
+                i18n::trf(
+                    "frame {0}/{1}: {2} (done)",
+                    &[&(index + 1), &frames, &path.display()]
+                )
+            );
+            continue;
+        }
+
+        let mut frame = Frame::new(width, height);
+        render::render_rows(
+            &mut frame.lambdas,
+            width,
+            height,
+            &region,
+            &kernel,
+            0..height,
+        );
+        if custom_colors {
+            colorize(&engine, &mut scope, &ast, &mut frame)?;
+        } else {
+            frame.colorize_rows(0..height, palette);
+        }
+
+        let path = sink.write(index, &frame.buffer, hash)?;
+        println!(
+            "{}",
+            i18n::trf(
+                "frame {0}/{1}: {2}",
+                &[&(index + 1), &frames, &path.display()]
+            )
+        );
+        if interrupt::requested() {
+            sink.finish()?;
+            return Err(Error::Interrupted(format!(
+                "{} of {frames} frames, --resume-animation continues",
+                index + 1
+            )));
+        }
+    }
+    sink.finish()
+}
+
+// take over whatever the script returned
+fn apply(
+    state: &Map,
+    kernel: &mut Kernel,
+    region: &mut Region,
+    palette: &mut Palette,
+) -> Result<(), Error> {
+    if let Some(value) = state.get("region") {
+        let ranges = value
+            .as_array_ref()
+            .ok()
+            .and_then(|ranges| ranges.iter().map(number).collect::<Option<Vec<_>>>())
+            .and_then(|ranges| <[f64; 4]>::try_from(ranges).ok())
+            .ok_or_else(|| Error::Script("region has to be [a_min, a_max, b_min, b_max]".into()))?;
+        let [x_min, x_max, y_min, y_max] = ranges;
+        *region = Region::new(x_min, x_max, y_min, y_max);
+        region.check().map_err(Error::Script)?;
+    }
+    if let Some(value) = state.get("sequence") {
+        let sequence = value
+            .clone()
+            .into_string()
+            .map_err(|_| Error::Script("sequence has to be a string".into()))?;
+        kernel.sequence_rule = parse_sequence(&sequence)?;
+    }
+    if let Some(value) = state.get("palette") {
+        let name = value
+            .clone()
+            .into_string()
+            .map_err(|_| Error::Script("palette has to be a string".into()))?;
+        *palette = Palette::from_str(&name, true)
+            .map_err(|_| Error::Script(i18n::trf("unknown palette '{0}'", &[&name])))?;
+    }
+    if let Some(value) = state.get("depth") {
+        kernel.depth = value
+            .as_int()
+            .ok()
+            .and_then(|depth| u32::try_from(depth).ok())
+            .filter(|&depth| depth > render::WARMUP)
+            .ok_or_else(|| {
+                Error::Script(format!("depth has to be a number above {}", render::WARMUP))
+            })?;
+    }
+    Ok(())
+}
+
+// scripts write 2 as well as 2.0
+fn number(value: &Dynamic) -> Option<f64> {
+    value
+        .as_float()
+        .ok()
+        .or_else(|| value.as_int().ok().map(|i| i as f64))
+}
+
+fn colorize(engine: &Engine, scope: &mut Scope, ast: &AST, frame: &mut Frame) -> Result<(), Error> {
+    for (pixel, &lambda) in frame.buffer.iter_mut().zip(&frame.lambdas) {
+        let color: i64 = engine
+            .call_fn(scope, ast, "colorize", (lambda,))
+            .map_err(|e| Error::Script(format!("colorize({lambda}): {e}")))?;
+        *pixel = (color & 0xFFFFFF) as u32;
+    }
+    Ok(())
+}
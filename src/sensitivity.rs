@@ -0,0 +1,37 @@
+// sensitivity subcommand: how much λ changes when a single letter of the sequence is flipped,
+// shown like diff as λ(flipped) - λ(original)
+
+use crate::cli::SensitivityArgs;
+use crate::diff;
+use crate::error::Error;
+use crate::i18n;
+use crate::render::{parse_sequence, Kernel};
+
+pub fn run(args: &SensitivityArgs) -> Result<(), Error> {
+    let kernel = Kernel::new(parse_sequence(&args.sequence)?);
+    let position = args.position as usize;
+    let flipped = position
+        .checked_sub(1)
+        .and_then(|index| kernel.flipped(index))
+        .ok_or_else(|| {
+            Error::usage(i18n::trf(
+                "position {0} is outside of the sequence, it has {1} letters",
+                &[&position, &kernel.sequence_rule.len()],
+            ))
+        })?;
+
+    let flipped_sequence: String = flipped.sequence_rule.iter().collect();
+    println!("{} -> {flipped_sequence}", args.sequence);
+    let title = format!(
+        "Δλ flipping letter {position}: {flipped_sequence} - {}",
+        args.sequence
+    );
+    diff::compare(
+        &kernel,
+        &flipped,
+        &args.region,
+        args.out.as_deref(),
+        args.size,
+        &title,
+    )
+}
@@ -0,0 +1,255 @@
+// recording of an exploration session and its replay. the file holds the starting state and every
+// navigation, palette or parameter change with the seconds since the start, as JSON lines: the
+// starting state first, then one change per line.
+
+use crate::color::Palette;
+use crate::error::Error;
+use crate::i18n;
+use crate::render::{self, Alphabet, Region};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+// one change of the explored diagram, independent of the input that caused it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    Sequence { sequence: String },
+    View { region: [f64; 4] },
+    Palette { name: String },
+    Depth { depth: u32 },
+    Cycling { on: bool },
+    Minimap { on: bool },
+    Histogram { on: bool },
+    Terrain { on: bool },
+    Equalize { on: bool },
+    Colorbar { on: bool },
+}
+
+impl Action {
+    pub fn view(region: &Region) -> Action {
+        Action::View {
+            region: region_to_array(region),
+        }
+    }
+
+    pub fn palette(palette: Palette) -> Action {
+        Action::Palette {
+            name: palette.name().to_string(),
+        }
+    }
+
+    // sequences, depths and views the kernel cannot render are refused, wherever the action comes from.
+    // sequences are made of the letters of the alphabet of the window
+    pub fn check(&self, alphabet: &Alphabet) -> Result<(), String> {
+        match self {
+            Action::Sequence { sequence } => alphabet
+                .sequence(sequence)
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            Action::Depth { depth } => check_depth(*depth),
+            Action::View { region } => array_to_region(*region).check(),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub fn check_depth(depth: u32) -> Result<(), String> {
+    if depth <= render::WARMUP {
+        return Err(i18n::trf("depth has to be above {0}", &[&render::WARMUP]));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    // seconds since the start of the session
+    pub t: f64,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub sequence: String,
+    pub region: [f64; 4],
+    pub palette: String,
+    pub depth: u32,
+    #[serde(default)]
+    pub entries: Vec<Entry>,
+}
+
+impl Session {
+    pub fn new(sequence: &[char], region: &Region, palette: Palette, depth: u32) -> Self {
+        Session {
+            sequence: sequence.iter().collect(),
+            region: region_to_array(region),
+            palette: palette.name().to_string(),
+            depth,
+            entries: Vec::new(),
+        }
+    }
+
+    // the starting state and every recorded change are checked before anything is replayed
+    pub fn load(path: &Path, alphabet: &Alphabet) -> Result<Self, Error> {
+        let text = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        let not_a_session = |e| {
+            Error::usage(i18n::trf(
+                "{0}: not a session file: {1}",
+                &[&path.display(), &e],
+            ))
+        };
+        // earlier versions wrote everything as one pretty printed document
+        let session = match serde_json::from_str::<Session>(&text) {
+            Ok(session) => session,
+            Err(_) => {
+                let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+                let mut session: Session = serde_json::from_str(lines.next().unwrap_or_default())
+                    .map_err(not_a_session)?;
+                for line in lines {
+                    session
+                        .entries
+                        .push(serde_json::from_str(line).map_err(not_a_session)?);
+                }
+                session
+            }
+        };
+        let invalid = |e: String| Error::usage(format!("{}: {e}", path.display()));
+        check_depth(session.depth).map_err(invalid)?;
+        session.start_region().check().map_err(invalid)?;
+        alphabet
+            .sequence(&session.sequence)
+            .map_err(|e| invalid(e.to_string()))?;
+        for entry in &session.entries {
+            entry.action.check(alphabet).map_err(invalid)?;
+        }
+        Ok(session)
+    }
+
+    pub fn start_region(&self) -> Region {
+        array_to_region(self.region)
+    }
+}
+
+pub fn region_to_array(region: &Region) -> [f64; 4] {
+    [region.x_min, region.x_max, region.y_min, region.y_max]
+}
+
+pub fn array_to_region([x_min, x_max, y_min, y_max]: [f64; 4]) -> Region {
+    Region::new(x_min, x_max, y_min, y_max)
+}
+
+// palette names of a session file, unknown ones fall back to the default
+pub fn parse_palette(name: &str) -> Palette {
+    Palette::from_str(name, true).unwrap_or_default()
+}
+
+// appends every action as a line of its own right away, so nothing is lost when the program is killed
+pub struct Recorder {
+    path: PathBuf,
+    file: File,
+    start: Instant,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, session: Session) -> Result<Self, Error> {
+        let file = File::create(path).map_err(|e| Error::io(path, e))?;
+        let mut recorder = Recorder {
+            path: path.to_path_buf(),
+            file,
+            start: Instant::now(),
+        };
+        recorder.write_line(&session)?;
+        Ok(recorder)
+    }
+
+    pub fn record(&mut self, action: &Action) -> Result<(), Error> {
+        let entry = Entry {
+            t: self.start.elapsed().as_secs_f64(),
+            action: action.clone(),
+        };
+        self.write_line(&entry)
+    }
+
+    fn write_line(&mut self, value: &impl Serialize) -> Result<(), Error> {
+        let line = serde_json::to_string(value).expect("sessions serialize") + "\n";
+        self.file
+            .write_all(line.as_bytes())
+            .map_err(|e| Error::io(&self.path, e))
+    }
+}
+
+// hands out the recorded actions keeping the pauses between them. a pause starts when the
+// previous action was handed out, so slow renders do not make the following actions pile up.
+pub struct Replay {
+    entries: VecDeque<Entry>,
+    last_t: f64,
+    last_at: Instant,
+}
+
+impl Replay {
+    pub fn new(session: &Session) -> Self {
+        Replay {
+            entries: session.entries.iter().cloned().collect(),
+            last_t: 0.0,
+            last_at: Instant::now(),
+        }
+    }
+
+    pub fn next_due(&mut self) -> Option<Action> {
+        let entry = self.entries.front()?;
+        if self.last_at.elapsed().as_secs_f64() < entry.t - self.last_t {
+            return None;
+        }
+        let entry = self.entries.pop_front()?;
+        self.last_t = entry.t;
+        self.last_at = Instant::now();
+        Some(entry.action)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_lines_load_again() {
+        let path =
+            std::env::temp_dir().join(format!("ljapunow_session_{}.json", std::process::id()));
+        let region = Region::new(2.0, 4.0, 2.0, 4.0);
+        let session = Session::new(&['A', 'B'], &region, Palette::default(), 300);
+        let mut recorder = Recorder::create(&path, session).unwrap();
+        let actions = [
+            Action::Depth { depth: 400 },
+            Action::Sequence {
+                sequence: "AAB".into(),
+            },
+        ];
+        for action in &actions {
+            recorder.record(action).unwrap();
+        }
+        let loaded = Session::load(&path, &Alphabet::default()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.sequence, "AB");
+        let replayed: Vec<Action> = loaded.entries.into_iter().map(|e| e.action).collect();
+        assert_eq!(replayed, actions);
+    }
+
+    #[test]
+    fn sequences_are_checked_against_the_alphabet() {
+        let action = Action::Sequence {
+            sequence: "ABC".into(),
+        };
+        assert!(action.check(&Alphabet::default()).is_err());
+        let alphabet = Alphabet::parse("A=a,B=b,C=3.7").unwrap();
+        assert!(action.check(&alphabet).is_ok());
+    }
+}
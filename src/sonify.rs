@@ -0,0 +1,179 @@
+// the orbit under the mouse cursor as sound. the states after the warmup are played in a loop, either
+// as the waveform itself or as a melody with one short note per state. a periodic orbit repeats and
+// sounds tonal, a chaotic one never does and sounds like noise, a fixed point is silent in wave mode.
+// sound output needs the audio feature (cpal, on Linux the ALSA development files), without it
+// --sonify only reports that.
+
+#[cfg(feature = "audio")]
+pub use output::Sonifier;
+#[cfg(not(feature = "audio"))]
+pub use silent::Sonifier;
+
+#[cfg(feature = "audio")]
+mod output {
+    use crate::cli::Sonify;
+    use crate::error::Error;
+    use crate::i18n;
+    use crate::render::Kernel;
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use cpal::{FromSample, SampleFormat, SizedSample, Stream, StreamConfig};
+    use std::sync::{Arc, Mutex};
+
+    // wave mode: states per second, period 2 comes out at half of it
+    const WAVE_RATE: f32 = 1500.0;
+    // pitch mode: length of a note and the range the states are spread over
+    const NOTE_SECONDS: f32 = 0.08;
+    const LOWEST_NOTE: f32 = 220.0;
+    const OCTAVES: f32 = 2.0;
+    const VOLUME: f32 = 0.2;
+    // share of the way to the target volume per sample, fades in and out without clicks
+    const FADE: f32 = 0.002;
+
+    // what the audio thread plays, shared with the explorer
+    #[derive(Debug, Default)]
+    struct Voice {
+        mode: Option<Sonify>,
+        // states in a loop, without their mean in wave mode
+        orbit: Vec<f32>,
+        playing: bool,
+        // current state, samples played of it and the phase of the note in 0..1
+        step: usize,
+        elapsed: usize,
+        phase: f32,
+        gain: f32,
+    }
+
+    impl Voice {
+        fn set(&mut self, mut orbit: Vec<f32>) {
+            if self.mode == Some(Sonify::Wave) && !orbit.is_empty() {
+                let mean = orbit.iter().sum::<f32>() / orbit.len() as f32;
+                orbit.iter_mut().for_each(|x| *x -= mean);
+            }
+            self.orbit = orbit;
+            self.playing = !self.orbit.is_empty();
+            self.step = 0;
+            self.elapsed = 0;
+        }
+
+        // one sample at the given rate, -1..=1
+        fn next(&mut self, rate: f32) -> f32 {
+            let target = if self.playing { VOLUME } else { 0.0 };
+            self.gain += (target - self.gain) * FADE;
+            if self.orbit.is_empty() || (!self.playing && self.gain < 1e-4) {
+                return 0.0;
+            }
+            let x = self.orbit[self.step];
+            let (value, seconds) = match self.mode {
+                Some(Sonify::Pitch) => {
+                    let frequency = LOWEST_NOTE * (OCTAVES * x).exp2();
+                    self.phase = (self.phase + frequency / rate).fract();
+                    ((std::f32::consts::TAU * self.phase).sin(), NOTE_SECONDS)
+                }
+                _ => (2.0 * x, 1.0 / WAVE_RATE),
+            };
+            self.elapsed += 1;
+            if self.elapsed as f32 >= seconds * rate {
+                self.elapsed = 0;
+                self.step = (self.step + 1) % self.orbit.len();
+            }
+            value * self.gain
+        }
+    }
+
+    pub struct Sonifier {
+        voice: Arc<Mutex<Voice>>,
+        // sound stops when the stream is dropped
+        _stream: Stream,
+    }
+
+    impl Sonifier {
+        // on the default output device
+        pub fn start(mode: Sonify) -> Result<Self, Error> {
+            let audio = |e: &dyn std::fmt::Display| Error::Audio(e.to_string());
+            let device = cpal::default_host()
+                .default_output_device()
+                .ok_or_else(|| Error::Audio(i18n::tr("no output device").into()))?;
+            let supported = device.default_output_config().map_err(|e| audio(&e))?;
+            let voice = Arc::new(Mutex::new(Voice {
+                mode: Some(mode),
+                ..Voice::default()
+            }));
+            let config = supported.config();
+            let stream = match supported.sample_format() {
+                SampleFormat::I16 => build::<i16>(&device, &config, &voice),
+                SampleFormat::U16 => build::<u16>(&device, &config, &voice),
+                SampleFormat::I32 => build::<i32>(&device, &config, &voice),
+                _ => build::<f32>(&device, &config, &voice),
+            }
+            .map_err(|e| audio(&e))?;
+            stream.play().map_err(|e| audio(&e))?;
+            Ok(Sonifier {
+                voice,
+                _stream: stream,
+            })
+        }
+
+        pub fn play(&self, kernel: &Kernel, a: f64, b: f64) {
+            let orbit = kernel.states(a, b).into_iter().map(|x| x as f32).collect();
+            if let Ok(mut voice) = self.voice.lock() {
+                voice.set(orbit);
+            }
+        }
+
+        pub fn silence(&self) {
+            if let Ok(mut voice) = self.voice.lock() {
+                voice.playing = false;
+            }
+        }
+    }
+
+    // the same sample on every channel
+    fn build<T: SizedSample + FromSample<f32>>(
+        device: &cpal::Device,
+        config: &StreamConfig,
+        voice: &Arc<Mutex<Voice>>,
+    ) -> Result<Stream, cpal::BuildStreamError> {
+        let voice = Arc::clone(voice);
+        let (rate, channels) = (config.sample_rate.0 as f32, config.channels as usize);
+        device.build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                let Ok(mut voice) = voice.lock() else {
+                    return;
+                };
+                for frame in data.chunks_mut(channels) {
+                    let sample = T::from_sample(voice.next(rate));
+                    frame.fill(sample);
+                }
+            },
+            |e| eprintln!("{}", i18n::trf("audio: {0}", &[&e])),
+            None,
+        )
+    }
+}
+
+// without the audio feature a Sonifier cannot be started and so never exists
+#[cfg(not(feature = "audio"))]
+mod silent {
+    use crate::cli::Sonify;
+    use crate::error::Error;
+    use crate::i18n;
+    use crate::render::Kernel;
+
+    pub enum Sonifier {}
+
+    impl Sonifier {
+        pub fn start(_: Sonify) -> Result<Self, Error> {
+            let message = i18n::tr("this build has no sound output, rebuild with --features audio");
+            Err(Error::Audio(message.into()))
+        }
+
+        pub fn play(&self, _: &Kernel, _: f64, _: f64) {
+            match *self {}
+        }
+
+        pub fn silence(&self) {
+            match *self {}
+        }
+    }
+}
@@ -0,0 +1,127 @@
+// --span COLUMNSxROWS: one diagram across a wall of equal monitors, a borderless window on each. to the
+// render loop it is a single display as large as the whole wall, so world coordinates run on seamlessly
+// from one monitor to the next. frames are cut into tiles on present and the input of every window is
+// moved by the position of its tile.
+
+use crate::display::{Display, Event, MinifbDisplay};
+use crate::error::Error;
+use crate::scaling::Upscale;
+
+pub struct SpanDisplay {
+    // row by row, columns × rows of them
+    tiles: Vec<MinifbDisplay>,
+    columns: usize,
+    // the tile the cursor was last seen in
+    hover: Option<usize>,
+}
+
+impl SpanDisplay {
+    // monitors of screen pixels each, side by side from the upper left corner of the desktop
+    pub fn open(
+        (columns, rows): (usize, usize),
+        screen: (usize, usize),
+        scale: usize,
+        filter: Upscale,
+    ) -> Result<Self, Error> {
+        let (width, height) = screen;
+        let tiles = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column * width, row * height)))
+            .map(|position| MinifbDisplay::tile(screen, position, scale, filter))
+            .collect::<Result<_, _>>()?;
+        Ok(SpanDisplay {
+            tiles,
+            columns,
+            hover: None,
+        })
+    }
+
+    fn tile_size(&self) -> (usize, usize) {
+        self.tiles
+            .iter()
+            .map(|tile| tile.size())
+            .reduce(|(w, h), (width, height)| (w.min(width), h.min(height)))
+            .unwrap_or((0, 0))
+    }
+
+    // upper left corner of tile i in the wall
+    fn origin(&self, i: usize) -> (usize, usize) {
+        let (width, height) = self.tile_size();
+        (i % self.columns * width, i / self.columns * height)
+    }
+}
+
+impl Display for SpanDisplay {
+    // closing one window ends all
+    fn is_open(&self) -> bool {
+        self.tiles.iter().all(|tile| tile.is_open())
+    }
+
+    fn size(&self) -> (usize, usize) {
+        let (width, height) = self.tile_size();
+        let rows = self.tiles.len() / self.columns;
+        (width * self.columns, height * rows)
+    }
+
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) {
+        let rows = self.tiles.len() / self.columns;
+        let (tile_width, tile_height) = (width / self.columns, height / rows);
+        for i in 0..self.tiles.len() {
+            let (left, top) = (
+                i % self.columns * tile_width,
+                i / self.columns * tile_height,
+            );
+            let tile: Vec<u32> = buffer[top * width..(top + tile_height) * width]
+                .chunks_exact(width)
+                .flat_map(|row| &row[left..left + tile_width])
+                .copied()
+                .collect();
+            self.tiles[i].present(&tile, tile_width, tile_height);
+        }
+    }
+
+    fn poll_events(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+        let mut left = false;
+        for i in 0..self.tiles.len() {
+            let (dx, dy) = self.origin(i);
+            for event in self.tiles[i].poll_events() {
+                events.push(match event {
+                    Event::MousePressed { x, y, button } => Event::MousePressed {
+                        x: x + dx,
+                        y: y + dy,
+                        button,
+                    },
+                    Event::MouseMoved { x, y } => {
+                        self.hover = Some(i);
+                        Event::MouseMoved {
+                            x: x + dx,
+                            y: y + dy,
+                        }
+                    }
+                    Event::Scroll { x, y, delta } => Event::Scroll {
+                        x: x + dx,
+                        y: y + dy,
+                        delta,
+                    },
+                    // moving on to the next monitor is no leaving
+                    Event::MouseLeft => {
+                        left |= self.hover == Some(i);
+                        continue;
+                    }
+                    event => event,
+                });
+            }
+        }
+        if left && !events.iter().any(|e| matches!(e, Event::MouseMoved { .. })) {
+            self.hover = None;
+            events.push(Event::MouseLeft);
+        }
+        events
+    }
+
+    fn set_title(&mut self, title: &str) {
+        for tile in &mut self.tiles {
+            tile.set_title(title);
+        }
+    }
+}
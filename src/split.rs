@@ -0,0 +1,308 @@
+// split screen for comparative studies: two panes side by side or four in a 2x2 grid, each with its own
+// sequence and palette over the same region. navigation is linked, zooming or panning in one pane moves
+// all of them. P switches the palette of the pane under the cursor, . and , change the depth of all.
+
+use crate::color::Palette;
+use crate::contrast::{Clahe, Transfer};
+use crate::display::{Display, Event, Key, MouseButton};
+use crate::error::Error;
+use crate::font::{self, draw_text};
+use crate::frame::Frame;
+use crate::i18n;
+use crate::render::{self, Kernel, Region};
+use crate::{DEPTH_STEP, RESIZE_DEBOUNCE, ROWS_PER_UPDATE, ZOOM_PER_NOTCH};
+use clap::ValueEnum;
+use std::time::Instant;
+
+// between the panes
+const SEPARATOR: usize = 2;
+const SEPARATOR_COLOR: u32 = 0x000000;
+const LABEL: u32 = 0xFFFFFF;
+const LABEL_SHADOW: u32 = 0x000000;
+const LABEL_MARGIN: i64 = 6;
+
+// what --pane asks for, SEQUENCE[:PALETTE]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaneSpec {
+    pub sequence: String,
+    pub palette: Option<Palette>,
+}
+
+impl PaneSpec {
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (sequence, palette) = match spec.split_once(':') {
+            Some((sequence, name)) => (sequence, Some(Palette::from_str(name.trim(), true).ok()?)),
+            None => (spec, None),
+        };
+        Some(PaneSpec {
+            sequence: sequence.trim().to_string(),
+            palette,
+        })
+    }
+}
+
+struct Pane {
+    kernel: Kernel,
+    palette: Palette,
+    frame: Frame,
+}
+
+pub struct SplitView {
+    panes: Vec<Pane>,
+    region: Region,
+    // panes per row and column, and the size of the window they are laid out in
+    layout: (usize, usize),
+    size: (usize, usize),
+    rerender: bool,
+    dirty: bool,
+    // pan in progress in pane pixels, where the button went down and how far the mouse moved since
+    drag: Option<((usize, usize), (i64, i64))>,
+    resized: Option<((usize, usize), Instant)>,
+    mouse: Option<(usize, usize)>,
+}
+
+impl SplitView {
+    // every pane is the base kernel with its own sequence, without a palette of its own the given one
+    pub fn new(
+        specs: &[PaneSpec],
+        region: Region,
+        kernel: &Kernel,
+        palette: Palette,
+        (clahe, transfer, chaos_gradient): (Option<Clahe>, Option<Transfer>, bool),
+    ) -> Result<Self, Error> {
+        let layout = match specs.len() {
+            2 => (2, 1),
+            4 => (2, 2),
+            count => {
+                return Err(Error::usage(i18n::trf(
+                    "--pane has to be given 2 or 4 times, not {0}",
+                    &[&count],
+                )))
+            }
+        };
+        let panes = specs
+            .iter()
+            .map(|spec| {
+                let mut kernel = kernel.clone();
+                kernel.sequence_rule = kernel.alphabet.sequence(&spec.sequence)?;
+                let mut frame = Frame::new(0, 0);
+                frame.clahe = clahe;
+                frame.transfer = transfer;
+                frame.chaos_gradient = chaos_gradient;
+                Ok(Pane {
+                    kernel,
+                    palette: spec.palette.unwrap_or(palette),
+                    frame,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(SplitView {
+            panes,
+            region,
+            layout,
+            size: (0, 0),
+            rerender: true,
+            dirty: true,
+            drag: None,
+            resized: None,
+            mouse: None,
+        })
+    }
+
+    // until the window is closed
+    pub fn run(&mut self, display: &mut dyn Display) -> Result<(), Error> {
+        let size = display.size();
+        self.resize(size);
+        while display.is_open() {
+            self.step(display);
+        }
+        Ok(())
+    }
+
+    fn step(&mut self, display: &mut dyn Display) {
+        let size = display.size();
+        if size != self.size && size.0 > 0 && size.1 > 0 {
+            match self.resized {
+                Some((pending, since)) if pending == size => {
+                    if since.elapsed() >= RESIZE_DEBOUNCE {
+                        self.resized = None;
+                        self.resize(size);
+                    }
+                }
+                _ => self.resized = Some((size, Instant::now())),
+            }
+        }
+
+        if self.rerender {
+            self.rerender = false;
+            self.render(display);
+            self.dirty = true;
+        }
+        if self.dirty {
+            self.dirty = false;
+            let (buffer, width, height) = self.compose();
+            display.present(&buffer, width, height);
+        }
+        for event in display.poll_events() {
+            self.handle(event);
+        }
+    }
+
+    // the window split evenly, what does not divide stays black at the right and bottom
+    fn resize(&mut self, (width, height): (usize, usize)) {
+        self.size = (width, height);
+        let (columns, rows) = self.layout;
+        let pane_width = width.saturating_sub((columns - 1) * SEPARATOR) / columns;
+        let pane_height = height.saturating_sub((rows - 1) * SEPARATOR) / rows;
+        for pane in &mut self.panes {
+            pane.frame.resize(pane_width.max(1), pane_height.max(1));
+        }
+        self.rerender = true;
+    }
+
+    fn pane_size(&self) -> (usize, usize) {
+        self.panes[0].frame.size()
+    }
+
+    // all panes a few rows at a time, so they fill in side by side
+    fn render(&mut self, display: &mut dyn Display) {
+        let (width, height) = self.pane_size();
+        for start in (0..height).step_by(ROWS_PER_UPDATE) {
+            if !display.is_open() {
+                return;
+            }
+            let rows = start..(start + ROWS_PER_UPDATE).min(height);
+            for pane in &mut self.panes {
+                let frame = &mut pane.frame;
+                render::render_rows(
+                    &mut frame.lambdas,
+                    width,
+                    height,
+                    &self.region,
+                    &pane.kernel,
+                    rows.clone(),
+                );
+                frame.colorize_rows(rows.clone(), pane.palette);
+            }
+            let (buffer, window_width, window_height) = self.compose();
+            display.present(&buffer, window_width, window_height);
+            display.poll_events();
+        }
+    }
+
+    // the panes with separators and a label with sequence and palette in their upper left corner,
+    // shifted along while a pan is in progress
+    fn compose(&self) -> (Vec<u32>, usize, usize) {
+        let (window_width, window_height) = self.size;
+        let (pane_width, pane_height) = self.pane_size();
+        let mut buffer = vec![SEPARATOR_COLOR; window_width * window_height];
+        let size = (window_width, window_height);
+        for (i, pane) in self.panes.iter().enumerate() {
+            let (left, top) = self.origin(i);
+            if left + pane_width > window_width || top + pane_height > window_height {
+                continue;
+            }
+            let shifted;
+            let frame = match self.drag {
+                Some((_, (dx, dy))) => {
+                    shifted = pane.frame.shifted(dx, dy);
+                    &shifted
+                }
+                None => &pane.frame,
+            };
+            for (y, row) in frame.buffer.chunks_exact(pane_width).enumerate() {
+                let start = (top + y) * window_width + left;
+                buffer[start..start + pane_width].copy_from_slice(row);
+            }
+            let sequence: String = pane.kernel.sequence_rule.iter().collect();
+            let label = format!("{sequence}  {}", pane.palette.name());
+            let (label_width, _) = font::text_size(&label, 1);
+            if label_width + 2 * LABEL_MARGIN as usize <= pane_width && pane_height > 20 {
+                let (x, y) = (left as i64 + LABEL_MARGIN, top as i64 + LABEL_MARGIN);
+                draw_text(&mut buffer, size, (x + 1, y + 1), &label, LABEL_SHADOW, 1);
+                draw_text(&mut buffer, size, (x, y), &label, LABEL, 1);
+            }
+        }
+        (buffer, window_width, window_height)
+    }
+
+    // upper left corner of pane i in the window
+    fn origin(&self, i: usize) -> (usize, usize) {
+        let (columns, _) = self.layout;
+        let (width, height) = self.pane_size();
+        (
+            i % columns * (width + SEPARATOR),
+            i / columns * (height + SEPARATOR),
+        )
+    }
+
+    // the pane under a window position and the position within it, None on a separator
+    fn hit(&self, x: usize, y: usize) -> Option<(usize, (usize, usize))> {
+        let (columns, rows) = self.layout;
+        let (width, height) = self.pane_size();
+        let (column, row) = (x / (width + SEPARATOR), y / (height + SEPARATOR));
+        let (local_x, local_y) = (x % (width + SEPARATOR), y % (height + SEPARATOR));
+        (column < columns && row < rows && local_x < width && local_y < height)
+            .then_some((row * columns + column, (local_x, local_y)))
+    }
+
+    fn handle(&mut self, event: Event) {
+        let (width, height) = self.pane_size();
+        match event {
+            Event::KeyPressed(Key::Char('p')) => {
+                if let Some((i, _)) = self.mouse.and_then(|(x, y)| self.hit(x, y)) {
+                    let pane = &mut self.panes[i];
+                    pane.palette = pane.palette.next();
+                    pane.frame.colorize_rows(0..height, pane.palette);
+                    self.dirty = true;
+                }
+            }
+            Event::KeyPressed(Key::Char(key @ ('.' | ','))) => {
+                for pane in &mut self.panes {
+                    let depth = pane.kernel.depth;
+                    pane.kernel.depth = if key == '.' {
+                        depth.saturating_add(DEPTH_STEP)
+                    } else {
+                        depth.saturating_sub(DEPTH_STEP).max(DEPTH_STEP)
+                    };
+                }
+                let depth = self.panes[0].kernel.depth;
+                println!("{}", i18n::trf("iteration depth: {0}", &[&depth]));
+                self.rerender = true;
+            }
+            Event::Scroll { x, y, delta } => {
+                if let Some((_, (x, y))) = self.hit(x, y) {
+                    let (a, b) = self.region.pixel_to_world(x, y, width, height);
+                    self.region = self.region.zoomed_at(a, b, ZOOM_PER_NOTCH.powf(delta));
+                    self.rerender = true;
+                }
+            }
+            Event::MousePressed {
+                x,
+                y,
+                button: MouseButton::Middle | MouseButton::Right,
+            } => self.drag = Some(((x, y), (0, 0))),
+            Event::MouseMoved { x, y } => {
+                self.mouse = Some((x, y));
+                if let Some(((start_x, start_y), offset)) = &mut self.drag {
+                    *offset = (x as i64 - *start_x as i64, y as i64 - *start_y as i64);
+                    self.dirty = true;
+                }
+            }
+            Event::MouseLeft => self.mouse = None,
+            Event::MouseReleased {
+                button: MouseButton::Middle | MouseButton::Right,
+            } => {
+                if let Some((_, (dx, dy))) = self.drag.take().filter(|&(_, o)| o != (0, 0)) {
+                    // keep the moved images while the new view is computed over them
+                    for pane in &mut self.panes {
+                        pane.frame = pane.frame.shifted(dx, dy);
+                    }
+                    self.region = self.region.dragged_by(dx, dy, width, height);
+                    self.rerender = true;
+                }
+            }
+            _ => {}
+        }
+    }
+}
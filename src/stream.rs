@@ -0,0 +1,92 @@
+// stream subcommand: a WebSocket server that sends the diagram to every client row by row as it is rendered,
+// so a browser can watch a render on a headless machine. scripts/stream.html is such a client.
+// each client first gets a JSON text message {width, height, sequence, region}, then binary messages
+// of two little endian u32 (first row, row count) followed by RGBA pixels of those rows.
+
+use crate::cli::StreamArgs;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::i18n;
+use crate::render::{self, parse_sequence, Kernel};
+use crate::ROWS_PER_UPDATE;
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::thread;
+use tungstenite::{Message, WebSocket};
+
+pub fn run(args: &StreamArgs) -> Result<(), Error> {
+    let kernel = Kernel::new(parse_sequence(&args.sequence)?);
+    let listener =
+        TcpListener::bind(&args.listen).map_err(|e| Error::io(Path::new(&args.listen), e))?;
+    println!("{}", i18n::trf("streaming on ws://{0}", &[&args.listen]));
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let peer = stream
+            .peer_addr()
+            .map_or_else(|_| "client".into(), |addr| addr.to_string());
+        let (kernel, args) = (kernel.clone(), args.clone());
+        // every client gets its own render, a slow one does not hold up the others
+        thread::spawn(move || match serve(stream, &kernel, &args) {
+            Ok(()) => println!("{peer}: done"),
+            Err(e) => eprintln!("{peer}: {e}"),
+        });
+    }
+    Ok(())
+}
+
+fn serve(stream: TcpStream, kernel: &Kernel, args: &StreamArgs) -> Result<(), tungstenite::Error> {
+    let mut socket = tungstenite::accept(stream).map_err(|e| match e {
+        tungstenite::HandshakeError::Failure(e) => e,
+        tungstenite::HandshakeError::Interrupted(_) => tungstenite::Error::ConnectionClosed,
+    })?;
+    let (width, height) = args.size;
+    let region = args.region;
+    let header = format!(
+        r#"{{"width": {width}, "height": {height}, "sequence": "{}", "region": [{}, {}, {}, {}]}}"#,
+        args.sequence, region.x_min, region.x_max, region.y_min, region.y_max
+    );
+    socket.send(Message::text(header))?;
+
+    let mut frame = Frame::new(width, height);
+    for start in (0..height).step_by(ROWS_PER_UPDATE) {
+        let rows = start..(start + ROWS_PER_UPDATE).min(height);
+        render::render_rows(
+            &mut frame.lambdas,
+            width,
+            height,
+            &region,
+            kernel,
+            rows.clone(),
+        );
+        frame.colorize_rows(rows.clone(), args.palette);
+        // scaled palettes recolored everything, so everything is sent again
+        let rows = if args.palette.is_scaled() {
+            0..rows.end
+        } else {
+            rows
+        };
+        send_rows(&mut socket, &frame, rows)?;
+    }
+    socket.close(None)?;
+    // wait for the close handshake, the client may still be drawing
+    while socket.read().is_ok() {}
+    Ok(())
+}
+
+fn send_rows(
+    socket: &mut WebSocket<TcpStream>,
+    frame: &Frame,
+    rows: std::ops::Range<usize>,
+) -> Result<(), tungstenite::Error> {
+    let width = frame.width;
+    let mut message = Vec::with_capacity(8 + rows.len() * width * 4);
+    message.extend_from_slice(&(rows.start as u32).to_le_bytes());
+    message.extend_from_slice(&(rows.len() as u32).to_le_bytes());
+    for &pixel in &frame.buffer[rows.start * width..rows.end * width] {
+        message.extend_from_slice(&[(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8, 0xff]);
+    }
+    socket.send(Message::binary(message))
+}
@@ -0,0 +1,222 @@
+// publication export: the diagram as embedded PNG, the λ = 0 border as vector paths and labeled axes,
+// optionally a colorbar mapping color to λ
+
+use crate::analysis;
+use crate::color::Palette;
+use crate::export;
+use crate::frame::Frame;
+use crate::render::{map, Region};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+// room around the diagram for tick labels and axis names
+const MARGIN_LEFT: usize = 70;
+const MARGIN_RIGHT: usize = 20;
+const MARGIN_TOP: usize = 20;
+const MARGIN_BOTTOM: usize = 50;
+const TICK_LENGTH: usize = 6;
+const TICK_COUNT: usize = 6;
+const BAR_GAP: usize = 20;
+const BAR_WIDTH: usize = 20;
+const BAR_LABELS: usize = 60;
+
+pub fn save_svg(
+    path: &Path,
+    frame: &Frame,
+    region: &Region,
+    colorbar: Option<Palette>,
+) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write_svg(&mut out, frame, region, colorbar)?;
+    out.flush()
+}
+
+pub fn write_svg(
+    out: &mut impl Write,
+    frame: &Frame,
+    region: &Region,
+    colorbar: Option<Palette>,
+) -> io::Result<()> {
+    let (width, height) = frame.size();
+    let mut png = Vec::new();
+    export::write_png(&mut png, &frame.buffer, width, height)?;
+
+    let bar_space = match colorbar {
+        Some(_) => BAR_GAP + BAR_WIDTH + BAR_LABELS,
+        None => 0,
+    };
+    let total_width = MARGIN_LEFT + width + bar_space + MARGIN_RIGHT;
+    let total_height = MARGIN_TOP + height + MARGIN_BOTTOM;
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{total_width}" height="{total_height}" viewBox="0 0 {total_width} {total_height}" font-family="sans-serif" font-size="12">"#
+    )?;
+    writeln!(
+        out,
+        r#"<rect width="{total_width}" height="{total_height}" fill="white"/>"#
+    )?;
+    writeln!(
+        out,
+        r#"<g transform="translate({MARGIN_LEFT} {MARGIN_TOP})">"#
+    )?;
+    writeln!(
+        out,
+        r#"<image width="{width}" height="{height}" image-rendering="pixelated" href="data:image/png;base64,{}"/>"#,
+        base64(&png)
+    )?;
+
+    // λ = 0 border, one path with a move for every segment
+    let mut path = String::new();
+    for [(x0, y0), (x1, y1)] in analysis::zero_contour(&frame.lambdas, width, height) {
+        // writing to a String can not fail
+        let _ = write!(path, "M{x0:.2} {y0:.2}L{x1:.2} {y1:.2}");
+    }
+    writeln!(
+        out,
+        r#"<path d="{path}" fill="none" stroke="white" stroke-width="0.6" stroke-opacity="0.8"/>"#
+    )?;
+
+    writeln!(
+        out,
+        r#"<rect width="{width}" height="{height}" fill="none" stroke="black"/>"#
+    )?;
+    for a in ticks(region.x_min, region.x_max, TICK_COUNT) {
+        let x = map(a, region.x_min, region.x_max, 0.0, width as f64);
+        writeln!(
+            out,
+            r#"<line x1="{x:.2}" y1="{height}" x2="{x:.2}" y2="{}" stroke="black"/><text x="{x:.2}" y="{}" text-anchor="middle">{}</text>"#,
+            height + TICK_LENGTH,
+            height + TICK_LENGTH + 14,
+            tick_label(a, region.x_max - region.x_min)
+        )?;
+    }
+    for b in ticks(region.y_min, region.y_max, TICK_COUNT) {
+        let y = map(b, region.y_min, region.y_max, 0.0, height as f64);
+        writeln!(
+            out,
+            r#"<line x1="0" y1="{y:.2}" x2="-{TICK_LENGTH}" y2="{y:.2}" stroke="black"/><text x="-{}" y="{y:.2}" text-anchor="end" dominant-baseline="middle">{}</text>"#,
+            TICK_LENGTH + 3,
+            tick_label(b, region.y_max - region.y_min)
+        )?;
+    }
+    writeln!(
+        out,
+        r#"<text x="{}" y="{}" text-anchor="middle" font-style="italic">a</text>"#,
+        width / 2,
+        height + MARGIN_BOTTOM - 10
+    )?;
+    writeln!(
+        out,
+        r#"<text x="-{}" y="{}" text-anchor="middle" font-style="italic">b</text>"#,
+        MARGIN_LEFT - 12,
+        height / 2
+    )?;
+    if let Some(palette) = colorbar {
+        write_colorbar(out, frame, palette)?;
+    }
+    writeln!(out, "</g>\n</svg>")
+}
+
+// vertical bar right of the diagram, λ_max on top
+fn write_colorbar(out: &mut impl Write, frame: &Frame, palette: Palette) -> io::Result<()> {
+    let (width, height) = frame.size();
+    let (lambda_min, lambda_max) = analysis::lambda_range(&frame.lambdas);
+    if lambda_min >= lambda_max {
+        return Ok(());
+    }
+
+    let colors: Vec<u32> = (0..height)
+        .map(|y| {
+            let lambda = map(
+                y as f64,
+                0.0,
+                (height - 1).max(1) as f64,
+                lambda_max,
+                lambda_min,
+            );
+            palette.colorize_in(lambda, (lambda_min, lambda_max))
+        })
+        .collect();
+    let mut png = Vec::new();
+    export::write_png(&mut png, &colors, 1, height)?;
+
+    let left = width + BAR_GAP;
+    writeln!(
+        out,
+        r#"<image x="{left}" width="{BAR_WIDTH}" height="{height}" preserveAspectRatio="none" image-rendering="pixelated" href="data:image/png;base64,{}"/>"#,
+        base64(&png)
+    )?;
+    writeln!(
+        out,
+        r#"<rect x="{left}" width="{BAR_WIDTH}" height="{height}" fill="none" stroke="black"/>"#
+    )?;
+    let right = left + BAR_WIDTH;
+    for lambda in ticks(lambda_min, lambda_max, TICK_COUNT) {
+        let y = map(lambda, lambda_max, lambda_min, 0.0, height as f64);
+        writeln!(
+            out,
+            r#"<line x1="{right}" y1="{y:.2}" x2="{}" y2="{y:.2}" stroke="black"/><text x="{}" y="{y:.2}" dominant-baseline="middle">{}</text>"#,
+            right + TICK_LENGTH,
+            right + TICK_LENGTH + 3,
+            tick_label(lambda, lambda_max - lambda_min)
+        )?;
+    }
+    writeln!(
+        out,
+        r#"<text x="{}" y="{}" text-anchor="middle" font-style="italic">λ</text>"#,
+        left + BAR_WIDTH / 2,
+        height + MARGIN_BOTTOM - 10
+    )
+}
+
+// round values inside min..max, about count of them, spaced 1, 2 or 5 times a power of ten
+pub fn ticks(min: f64, max: f64, count: usize) -> Vec<f64> {
+    let (low, high) = (min.min(max), min.max(max));
+    if high <= low || high.is_nan() || low.is_nan() || count == 0 {
+        return Vec::new();
+    }
+    let step = tick_step(high - low, count);
+    let first = (low / step).ceil() as i64;
+    let last = (high / step).floor() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+fn tick_step(range: f64, count: usize) -> f64 {
+    let rough = range / count as f64;
+    let magnitude = 10f64.powf(rough.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|f| f * magnitude)
+        .find(|&step| step >= rough)
+        .unwrap_or(10.0 * magnitude)
+}
+
+// as many decimals as the tick spacing needs
+pub fn tick_label(value: f64, range: f64) -> String {
+    let step = tick_step(range.abs(), TICK_COUNT);
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    format!("{value:.decimals$}")
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - 6 * i)) as usize & 0x3F] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
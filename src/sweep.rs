@@ -0,0 +1,231 @@
+// sweep subcommand: the same view rendered for evenly spaced values of one kernel parameter, laid out
+// row by row as a montage with the value written below every cell. all cells are colored over the
+// common λ range, so the montage shows how the diagram depends on the parameter at a glance.
+
+use crate::analysis;
+use crate::cli::{SweepArgs, SweepParam};
+use crate::components::{Components, Event, Tracker};
+use crate::error::Error;
+use crate::export::save_png;
+use crate::font::{self, draw_text};
+use crate::i18n;
+use crate::interrupt;
+use crate::render::{self, parse_sequence, Kernel, Noise, Spectrum, StateMap, WARMUP};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const BACKGROUND: u32 = 0xFFFFFF;
+const INK: u32 = 0x000000;
+// around and between the cells
+const GAP: usize = 8;
+const TEXT_SCALE: usize = 2;
+// noise realizations averaged per pixel, as --realizations
+const REALIZATIONS: u32 = 8;
+
+pub fn run(args: &SweepArgs) -> Result<(), Error> {
+    interrupt::install();
+    let steps = args.steps as usize;
+    let (columns, rows) = match args.montage {
+        Some((columns, rows)) if columns * rows < steps => {
+            return Err(Error::usage(i18n::trf(
+                "a {0}x{1} montage has no room for {2} steps",
+                &[&columns, &rows, &steps],
+            )))
+        }
+        Some(montage) => montage,
+        None => {
+            let columns = (steps as f64).sqrt().ceil() as usize;
+            (columns, steps.div_ceil(columns))
+        }
+    };
+    let base = Kernel::new(parse_sequence(&args.sequence)?);
+    let values: Vec<f64> = (0..steps)
+        .map(|i| args.from + (args.to - args.from) * i as f64 / (steps - 1) as f64)
+        .collect();
+    let kernels = values
+        .iter()
+        .map(|&value| kernel_with(&base, args.param, value))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (width, height) = args.cell_size;
+    let mut cells = Vec::with_capacity(steps);
+    for kernel in &kernels {
+        let mut lambdas = vec![f64::NAN; width * height];
+        render::render_rows(&mut lambdas, width, height, &args.region, kernel, 0..height);
+        cells.push(lambdas);
+        println!("{}", i18n::trf("cell {0} of {1}", &[&cells.len(), &steps]));
+        if interrupt::requested() {
+            break;
+        }
+    }
+
+    let all: Vec<f64> = cells.iter().flatten().copied().collect();
+    let range = analysis::lambda_range(&all);
+    let labels: Vec<String> = values
+        .iter()
+        .map(|&value| label(args.param, value, args.to - args.from, steps))
+        .collect();
+    let (buffer, total_width, total_height) = montage(
+        &cells,
+        &labels,
+        (columns, rows),
+        (width, height),
+        |lambda| args.palette.colorize_in(lambda, range),
+    );
+    let path = &args.out;
+    save_png(path, &buffer, total_width, total_height).map_err(|e| Error::io(path, e))?;
+    if let Some(track) = &args.track {
+        let tracks = save_tracks(track, args, &cells, &values).map_err(|e| Error::io(track, e))?;
+        println!(
+            "{}",
+            i18n::trf("{0} tracks, saved {1}", &[&tracks, &track.display()])
+        );
+    }
+    if cells.len() < steps {
+        return Err(Error::Interrupted(format!(
+            "{} of {steps} cells in {}",
+            cells.len(),
+            path.display()
+        )));
+    }
+    println!("{}", i18n::trf("saved {0}", &[&path.display()]));
+    Ok(())
+}
+
+// the base kernel with the swept parameter set, values outside its domain are rejected
+fn kernel_with(base: &Kernel, param: SweepParam, value: f64) -> Result<Kernel, Error> {
+    let mut kernel = base.clone();
+    let out_of_range = |domain: &str| {
+        Error::usage(i18n::trf(
+            "--param {0} {1} is out of range, it has to be {2}",
+            &[&param.label(), &value, &domain],
+        ))
+    };
+    match param {
+        SweepParam::X0 if (0.0..=1.0).contains(&value) => kernel.start = value,
+        SweepParam::X0 => return Err(out_of_range(i18n::tr("between 0 and 1"))),
+        SweepParam::Coupling if (0.0..=1.0).contains(&value) => {
+            kernel.map = StateMap::Coupled(value, Spectrum::Largest)
+        }
+        SweepParam::Coupling => return Err(out_of_range(i18n::tr("between 0 and 1"))),
+        SweepParam::Depth if value.round() > WARMUP as f64 && value <= u32::MAX as f64 => {
+            kernel.depth = value.round() as u32
+        }
+        SweepParam::Depth => return Err(out_of_range(&i18n::trf("more than {0}", &[&WARMUP]))),
+        SweepParam::Noise if value > 0.0 => {
+            kernel.noise = Some(Noise {
+                amplitude: value,
+                seed: 0,
+                realizations: REALIZATIONS,
+            })
+        }
+        // no noise at all, the clean diagram for comparison
+        SweepParam::Noise if value == 0.0 => kernel.noise = None,
+        SweepParam::Noise => return Err(out_of_range(i18n::tr("0 or more"))),
+    }
+    Ok(kernel)
+}
+
+// one row per window and cell: its track, what happened to it since the cell before, the tracks it
+// split off from or that merged into it, its area and centroid. a death row has no area and centroid.
+// returns the number of tracks
+fn save_tracks(
+    path: &Path,
+    args: &SweepArgs,
+    cells: &[Vec<f64>],
+    values: &[f64],
+) -> io::Result<usize> {
+    let (width, height) = args.cell_size;
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "cell,value,track,event,from,area,a,b")?;
+    let mut tracker = Tracker::new(args.min_area as usize);
+    let mut tracks = 0;
+    for (cell, (lambdas, value)) in cells.iter().zip(values).enumerate() {
+        for step in tracker.step(Components::label(lambdas, width, height)) {
+            tracks = tracks.max(step.track);
+            let (event, from) = match &step.event {
+                Event::Birth => ("birth", String::new()),
+                Event::Continue => ("continue", String::new()),
+                Event::Merge(merged) => {
+                    let merged: Vec<String> = merged.iter().map(usize::to_string).collect();
+                    ("merge", merged.join(" "))
+                }
+                Event::Split(track) => ("split", track.to_string()),
+                Event::Death => ("death", String::new()),
+            };
+            let measures = match &step.component {
+                Some(component) => {
+                    let (a, b) = component.center(&args.region, width, height);
+                    format!("{},{a},{b}", component.area)
+                }
+                None => ",,".to_string(),
+            };
+            writeln!(
+                out,
+                "{cell},{value},{},{event},{from},{measures}",
+                step.track
+            )?;
+        }
+    }
+    out.flush()?;
+    Ok(tracks)
+}
+
+// "x0 0.15", with as many decimals as the step between cells needs to tell them apart
+fn label(param: SweepParam, value: f64, span: f64, steps: usize) -> String {
+    if param == SweepParam::Depth {
+        return format!("{} {}", param.label(), value.round());
+    }
+    let step = (span / (steps - 1) as f64).abs();
+    let decimals = if step > 0.0 {
+        (-step.log10().floor()).max(0.0) as usize + 1
+    } else {
+        1
+    };
+    format!("{} {value:.decimals$}", param.label())
+}
+
+// cells row by row on a white sheet, every one with its label below it
+fn montage(
+    cells: &[Vec<f64>],
+    labels: &[String],
+    (columns, rows): (usize, usize),
+    (width, height): (usize, usize),
+    colorize: impl Fn(f64) -> u32,
+) -> (Vec<u32>, usize, usize) {
+    let widest = labels
+        .iter()
+        .map(|label| font::text_size(label, TEXT_SCALE).0)
+        .max()
+        .unwrap_or(0);
+    let scale = if widest <= width { TEXT_SCALE } else { 1 };
+    let label_height = font::GLYPH_HEIGHT * scale + GAP;
+    let (cell_width, cell_height) = (width + GAP, height + label_height + GAP);
+    let total_width = GAP + columns * cell_width;
+    let total_height = GAP + rows * cell_height;
+    let size = (total_width, total_height);
+    let mut buffer = vec![BACKGROUND; total_width * total_height];
+
+    for (i, lambdas) in cells.iter().enumerate() {
+        let (left, top) = (
+            GAP + i % columns * cell_width,
+            GAP + i / columns * cell_height,
+        );
+        for y in 0..height {
+            let start = (top + y) * total_width + left;
+            for (pixel, &lambda) in buffer[start..start + width]
+                .iter_mut()
+                .zip(&lambdas[y * width..(y + 1) * width])
+            {
+                *pixel = colorize(lambda);
+            }
+        }
+        let label = &labels[i];
+        let (w, _) = font::text_size(label, scale);
+        let x = left as i64 + (width as i64 - w as i64) / 2;
+        let y = (top + height + GAP / 2) as i64;
+        draw_text(&mut buffer, size, (x, y), label, INK, scale);
+    }
+    (buffer, total_width, total_height)
+}
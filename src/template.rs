@@ -0,0 +1,132 @@
+// self-describing file names: placeholders in braces are replaced by the parameters of the saved view.
+//   {seq} sequence, {amin} {amax} {bmin} {bmax} ranges, {iters} iteration depth, {palette} palette name,
+//   {n} the first number giving a file that does not exist yet
+
+use crate::color::Palette;
+use crate::render::{Kernel, Region};
+use std::path::PathBuf;
+
+// caption of --caption without a text
+pub const DEFAULT_CAPTION: &str = "{seq}  a {amin}..{amax}  b {bmin}..{bmax}  {iters} iterations";
+
+// names of S saves in the window unless --output-template is given
+pub const DEFAULT_TEMPLATE: &str = "ljapunow_{n}.svg";
+
+const PLACEHOLDERS: [&str; 8] = [
+    "seq", "amin", "amax", "bmin", "bmax", "iters", "palette", "n",
+];
+
+// value parser for the command line, rejects unknown placeholders and unbalanced braces
+pub fn validate(template: &str) -> Result<String, String> {
+    fill(template, |name| {
+        PLACEHOLDERS
+            .contains(&name)
+            .then(String::new)
+            .ok_or_else(|| {
+                format!(
+                    "unknown placeholder {{{name}}}, known are {{{}}}",
+                    PLACEHOLDERS.join("} {")
+                )
+            })
+    })?;
+    Ok(template.to_string())
+}
+
+pub fn expand(template: &str, kernel: &Kernel, region: &Region, palette: Palette) -> PathBuf {
+    let path = |n| PathBuf::from(expand_text(template, kernel, region, palette, n));
+    if !template.contains("{n}") {
+        return path(0);
+    }
+    (0..)
+        .map(path)
+        .find(|path| !path.exists())
+        .unwrap_or_default()
+}
+
+// the placeholders filled in with {n} replaced by n, also for captions
+pub fn expand_text(
+    template: &str,
+    kernel: &Kernel,
+    region: &Region,
+    palette: Palette,
+    n: usize,
+) -> String {
+    let value = |name: &str| -> Result<String, String> {
+        Ok(match name {
+            "seq" => kernel.sequence_rule.iter().collect(),
+            "amin" => region.x_min.to_string(),
+            "amax" => region.x_max.to_string(),
+            "bmin" => region.y_min.to_string(),
+            "bmax" => region.y_max.to_string(),
+            "iters" => kernel.depth.to_string(),
+            "palette" => palette.name().to_string(),
+            "n" => format!("{n:04}"),
+            // validated on the command line, anything else is kept as written
+            _ => format!("{{{name}}}"),
+        })
+    };
+    // unbalanced braces, also validated on the command line, leave the text as it is
+    fill(template, value).unwrap_or_else(|_| template.to_string())
+}
+
+fn fill(
+    template: &str,
+    mut value: impl FnMut(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| "'{' without closing '}'".to_string())?;
+        filled.push_str(&rest[..start]);
+        filled.push_str(&value(&rest[start + 1..start + end])?);
+        rest = &rest[start + end + 1..];
+    }
+    if rest.contains('}') {
+        return Err("'}' without opening '{'".into());
+    }
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_replaces_every_placeholder() {
+        let filled = fill("a{x}b{y}", |name| Ok(name.to_uppercase()));
+        assert_eq!(filled.unwrap(), "aXbY");
+        assert_eq!(fill("plain", |_| Ok(String::new())).unwrap(), "plain");
+    }
+
+    #[test]
+    fn fill_rejects_unbalanced_braces() {
+        let filled = |template| fill(template, |_| Ok(String::new()));
+        assert_eq!(filled("{seq").unwrap_err(), "'{' without closing '}'");
+        assert_eq!(filled("seq}").unwrap_err(), "'}' without opening '{'");
+        assert_eq!(filled("{a}}").unwrap_err(), "'}' without opening '{'");
+    }
+
+    #[test]
+    fn validate_knows_the_placeholders() {
+        assert!(validate("ljapunow_{seq}_{n}.png").is_ok());
+        assert!(validate(DEFAULT_CAPTION).is_ok());
+        let error = validate("{bogus}.png").unwrap_err();
+        assert!(error.starts_with("unknown placeholder {bogus}"), "{error}");
+    }
+
+    #[test]
+    fn expand_text_keeps_what_it_can_not_fill() {
+        let kernel = Kernel::new(vec!['A', 'B']);
+        let region = Region::new(2.0, 4.0, 2.5, 3.5);
+        let expand = |template| expand_text(template, &kernel, &region, Palette::default(), 7);
+        assert_eq!(
+            expand("{seq}_{amin}_{bmax}_{iters}_{n}"),
+            format!("AB_2_3.5_{}_0007", kernel.depth)
+        );
+        assert_eq!(expand("{seq"), "{seq");
+        assert_eq!(expand("{bogus}"), "{bogus}");
+    }
+}
@@ -0,0 +1,204 @@
+// preview in the terminal, e.g. over SSH without X forwarding.
+// ansi prints two pixels per cell with 24 bit colors and the upper half block, sixel sends real pixels.
+
+use crate::display::{Display, Event};
+use std::io::{self, Write};
+use terminal_size::{terminal_size, Height, Width};
+
+// pixels computed per terminal pixel and axis, averaged down for a smoother preview
+const SUPERSAMPLE: usize = 2;
+// rough size of a terminal cell for sixel output
+const CELL_WIDTH: usize = 8;
+const CELL_HEIGHT: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum TermMode {
+    /// 24 bit ANSI colors with half blocks, works almost everywhere
+    Ansi,
+    /// sixel graphics (xterm -ti vt340, mlterm, foot, WezTerm, ...)
+    Sixel,
+}
+
+pub struct TermDisplay {
+    mode: TermMode,
+    // size of the printed image in terminal pixels
+    width: usize,
+    height: usize,
+}
+
+impl TermDisplay {
+    // a square image as large as the terminal allows, one line left for the prompt
+    pub fn new(mode: TermMode) -> Self {
+        let (columns, rows) = terminal_size()
+            .map(|(Width(w), Height(h))| (w as usize, h as usize))
+            .unwrap_or((80, 24));
+        let rows = rows.saturating_sub(1).max(1);
+
+        let (width, height) = match mode {
+            TermMode::Ansi => (columns, rows * 2),
+            TermMode::Sixel => (columns * CELL_WIDTH, rows * CELL_HEIGHT),
+        };
+        let side = width.min(height);
+        TermDisplay {
+            mode,
+            width: side,
+            height: side,
+        }
+    }
+}
+
+impl Display for TermDisplay {
+    fn is_open(&self) -> bool {
+        true
+    }
+
+    fn size(&self) -> (usize, usize) {
+        match self.mode {
+            TermMode::Ansi => (self.width * SUPERSAMPLE, self.height * SUPERSAMPLE),
+            TermMode::Sixel => (self.width, self.height),
+        }
+    }
+
+    fn present(&mut self, buffer: &[u32], width: usize, height: usize) {
+        let _span = tracing::info_span!("present", width, height).entered();
+        let pixels = downsample(buffer, width, height, self.width, self.height);
+        let out = match self.mode {
+            TermMode::Ansi => ansi(&pixels, self.width, self.height),
+            TermMode::Sixel => sixel(&pixels, self.width, self.height),
+        };
+
+        let mut stdout = io::stdout().lock();
+        // a closed pipe just ends the preview
+        let _ = stdout
+            .write_all(out.as_bytes())
+            .and_then(|_| stdout.flush());
+    }
+
+    fn poll_events(&mut self) -> Vec<Event> {
+        Vec::new()
+    }
+}
+
+fn channels(pixel: u32) -> [u32; 3] {
+    [(pixel >> 16) & 0xFF, (pixel >> 8) & 0xFF, pixel & 0xFF]
+}
+
+// box filter, every target pixel averages the source pixels it covers
+fn downsample(
+    buffer: &[u32],
+    width: usize,
+    height: usize,
+    to_width: usize,
+    to_height: usize,
+) -> Vec<u32> {
+    // source pixels covered by target pixel t, at least one
+    let span = |t: usize, from: usize, to: usize| {
+        let start = t * from / to;
+        start..((t + 1) * from / to).clamp(start + 1, from)
+    };
+
+    let mut pixels = Vec::with_capacity(to_width * to_height);
+    for ty in 0..to_height {
+        let ys = span(ty, height, to_height);
+        for tx in 0..to_width {
+            let xs = span(tx, width, to_width);
+            let mut sum = [0u32; 3];
+            for y in ys.clone() {
+                for x in xs.clone() {
+                    for (s, c) in sum.iter_mut().zip(channels(buffer[y * width + x])) {
+                        *s += c;
+                    }
+                }
+            }
+            let count = (ys.len() * xs.len()) as u32;
+            pixels.push(((sum[0] / count) << 16) | ((sum[1] / count) << 8) | (sum[2] / count));
+        }
+    }
+    pixels
+}
+
+// upper half block: foreground is the upper pixel, background the lower one
+fn ansi(pixels: &[u32], width: usize, height: usize) -> String {
+    let mut out = String::new();
+    for y in (0..height).step_by(2) {
+        for x in 0..width {
+            let [r1, g1, b1] = channels(pixels[y * width + x]);
+            let [r2, g2, b2] = channels(if y + 1 < height {
+                pixels[(y + 1) * width + x]
+            } else {
+                0
+            });
+            out += &format!("\x1b[38;2;{r1};{g1};{b1}m\x1b[48;2;{r2};{g2};{b2}m▀");
+        }
+        out += "\x1b[0m\n";
+    }
+    out
+}
+
+// sixel with a 6x6x6 color cube, each band of six rows is drawn color by color
+fn sixel(pixels: &[u32], width: usize, height: usize) -> String {
+    let level = |c: u32| (c * 5 + 127) / 255;
+    let index = |pixel: u32| {
+        let [r, g, b] = channels(pixel);
+        (level(r) * 36 + level(g) * 6 + level(b)) as usize
+    };
+
+    let mut out = String::from("\x1bPq");
+    out += &format!("\"1;1;{width};{height}");
+    for i in 0..216 {
+        let percent = |l: usize| l * 100 / 5;
+        out += &format!(
+            "#{i};2;{};{};{}",
+            percent(i / 36),
+            percent(i / 6 % 6),
+            percent(i % 6)
+        );
+    }
+
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+        let mut used = [false; 216];
+        for y in rows.clone() {
+            for x in 0..width {
+                used[index(pixels[y * width + x])] = true;
+            }
+        }
+
+        for color in (0..216).filter(|&c| used[c]) {
+            out += &format!("#{color}");
+            let mut run: Option<(char, usize)> = None;
+            for x in 0..width {
+                let bits = rows
+                    .clone()
+                    .filter(|&y| index(pixels[y * width + x]) == color)
+                    .fold(0u8, |bits, y| bits | 1 << (y - band));
+                let sixel = (63 + bits) as char;
+                run = match run {
+                    Some((c, n)) if c == sixel => Some((c, n + 1)),
+                    Some((c, n)) => {
+                        push_run(&mut out, c, n);
+                        Some((sixel, 1))
+                    }
+                    None => Some((sixel, 1)),
+                };
+            }
+            if let Some((c, n)) = run {
+                push_run(&mut out, c, n);
+            }
+            // back to the start of the band for the next color
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out += "\x1b\\\n";
+    out
+}
+
+// run length encoding: !<count><char>
+fn push_run(out: &mut String, sixel: char, count: usize) {
+    if count > 3 {
+        out.push_str(&format!("!{count}{sixel}"));
+    } else {
+        out.extend(std::iter::repeat_n(sixel, count));
+    }
+}
@@ -0,0 +1,153 @@
+// tiles subcommand: a multi resolution tile pyramid for browser viewers.
+// dzi writes <name>.dzi and <name>_files/<level>/<col>_<row>.png for OpenSeadragon,
+// xyz writes <z>/<x>/<y>.png for Leaflet and the like. every tile is rendered from its own
+// part of the region at its own resolution, so deep levels are sharp and nothing has to fit into RAM.
+
+use crate::analysis;
+use crate::cli::{TileFormat, TilesArgs};
+use crate::error::Error;
+use crate::export::save_png;
+use crate::i18n;
+use crate::interrupt;
+use crate::render::{self, parse_sequence, Kernel, Region};
+use std::fs;
+use std::path::Path;
+
+pub const TILE_SIZE: usize = 256;
+// the λ range for scaled palettes comes from a preview of the whole region, tiles alone would disagree
+const PREVIEW_SIZE: usize = 256;
+
+pub fn run(args: &TilesArgs) -> Result<(), Error> {
+    interrupt::install();
+    let kernel = Kernel::new(parse_sequence(&args.sequence)?);
+    let region = args.region;
+    let (width, height) = args.size;
+
+    let mut preview = vec![f64::NAN; PREVIEW_SIZE * PREVIEW_SIZE];
+    render::render_rows(
+        &mut preview,
+        PREVIEW_SIZE,
+        PREVIEW_SIZE,
+        &region,
+        &kernel,
+        0..PREVIEW_SIZE,
+    );
+    let range = analysis::lambda_range(&preview);
+
+    let out = &args.out;
+    let levels = match args.format {
+        // level 0 is a single pixel, the last one the full size
+        TileFormat::Dzi => {
+            let max_level = width.max(height).next_power_of_two().trailing_zeros() as usize;
+            (0..=max_level)
+                .map(|level| {
+                    let scale = 1 << (max_level - level);
+                    (level, width.div_ceil(scale), height.div_ceil(scale))
+                })
+                .collect::<Vec<_>>()
+        }
+        // square levels, zoom z has 2^z × 2^z tiles
+        TileFormat::Xyz => {
+            let max_zoom = width
+                .max(height)
+                .div_ceil(TILE_SIZE)
+                .next_power_of_two()
+                .trailing_zeros() as usize;
+            (0..=max_zoom)
+                .map(|zoom| (zoom, TILE_SIZE << zoom, TILE_SIZE << zoom))
+                .collect()
+        }
+    };
+
+    let mut written = 0;
+    for &(level, level_width, level_height) in &levels {
+        let (columns, rows) = (
+            level_width.div_ceil(TILE_SIZE),
+            level_height.div_ceil(TILE_SIZE),
+        );
+        for column in 0..columns {
+            let dir = match args.format {
+                TileFormat::Dzi => out
+                    .join(format!("{}_files", args.name))
+                    .join(level.to_string()),
+                TileFormat::Xyz => out.join(level.to_string()).join(column.to_string()),
+            };
+            fs::create_dir_all(&dir).map_err(|e| Error::io(&dir, e))?;
+            for row in 0..rows {
+                let path = match args.format {
+                    TileFormat::Dzi => dir.join(format!("{column}_{row}.png")),
+                    TileFormat::Xyz => dir.join(format!("{row}.png")),
+                };
+                if args.resume && path.exists() {
+                    continue;
+                }
+                let x = column * TILE_SIZE..((column + 1) * TILE_SIZE).min(level_width);
+                let y = row * TILE_SIZE..((row + 1) * TILE_SIZE).min(level_height);
+                let tile = tile_region(&region, (level_width, level_height), &x, &y);
+                let (tile_width, tile_height) = (x.len(), y.len());
+
+                let mut lambdas = vec![f64::NAN; tile_width * tile_height];
+                render::render_rows(
+                    &mut lambdas,
+                    tile_width,
+                    tile_height,
+                    &tile,
+                    &kernel,
+                    0..tile_height,
+                );
+                let buffer: Vec<u32> = lambdas
+                    .iter()
+                    .map(|&lambda| args.palette.colorize_in(lambda, range))
+                    .collect();
+                save_png(&path, &buffer, tile_width, tile_height)
+                    .map_err(|e| Error::io(&path, e))?;
+                written += 1;
+                if interrupt::requested() {
+                    return Err(Error::Interrupted(format!(
+                        "{written} tiles in {}, --resume continues",
+                        out.display()
+                    )));
+                }
+            }
+        }
+        println!(
+            "{}",
+            i18n::trf(
+                "level {0}: {1}x{2}, {3}x{4} tiles",
+                &[&level, &level_width, &level_height, &columns, &rows]
+            )
+        );
+    }
+
+    if args.format == TileFormat::Dzi {
+        let path = out.join(format!("{}.dzi", args.name));
+        write_dzi(&path, width, height)?;
+    }
+    println!(
+        "{}",
+        i18n::trf("{0} tiles written to {1}", &[&written, &out.display()])
+    );
+    Ok(())
+}
+
+// the part of the region covered by pixels x, y of an image of the given size
+fn tile_region(
+    region: &Region,
+    (width, height): (usize, usize),
+    x: &std::ops::Range<usize>,
+    y: &std::ops::Range<usize>,
+) -> Region {
+    let (a_min, b_min) = region.pixel_to_world(x.start, y.start, width, height);
+    let (a_max, b_max) = region.pixel_to_world(x.end, y.end, width, height);
+    Region::new(a_min, a_max, b_min, b_max)
+}
+
+fn write_dzi(path: &Path, width: usize, height: usize) -> Result<(), Error> {
+    let xml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <Image xmlns=\"http://schemas.microsoft.com/deepzoom/2008\" TileSize=\"{TILE_SIZE}\" Overlap=\"0\" Format=\"png\">\n  \
+         <Size Width=\"{width}\" Height=\"{height}\"/>\n\
+         </Image>\n"
+    );
+    fs::write(path, xml).map_err(|e| Error::io(path, e))
+}
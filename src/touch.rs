@@ -0,0 +1,419 @@
+// --touch: the window through winit and softbuffer instead of minifb, for touchscreen kiosks. minifb sees
+// no touches, winit does. one finger dragging pans like the right mouse button, a tap clicks like the left
+// one (the minimap), two fingers pinching zoom around their middle. mouse and keys work as in the minifb
+// window. needs the touch feature, without it --touch only reports that.
+
+#[cfg(not(feature = "touch"))]
+pub use silent::TouchDisplay;
+#[cfg(feature = "touch")]
+pub use window::TouchDisplay;
+
+#[cfg(feature = "touch")]
+mod window {
+    use crate::display::{Display, Event, Key, MouseButton};
+    use crate::error::Error;
+    use crate::i18n;
+    use crate::scaling::{self, Upscale};
+    use crate::{HEIGHT, WIDTH, ZOOM_PER_NOTCH};
+    use softbuffer::{Context, Surface};
+    use std::collections::BTreeMap;
+    use std::num::NonZeroU32;
+    use std::rc::Rc;
+    use std::time::Duration;
+    use winit::application::ApplicationHandler;
+    use winit::dpi::LogicalSize;
+    use winit::event::{ElementState, MouseScrollDelta, TouchPhase, WindowEvent};
+    use winit::event_loop::{ActiveEventLoop, EventLoop};
+    use winit::keyboard::{Key as WinitKey, NamedKey};
+    use winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
+    use winit::window::{Fullscreen, Window, WindowId};
+
+    // frame pixels a finger may wander and still tap
+    const TAP_RADIUS: f64 = 12.0;
+
+    pub struct TouchDisplay {
+        event_loop: EventLoop<()>,
+        state: State,
+    }
+
+    // what one or two fingers are doing
+    #[derive(Clone, Copy)]
+    enum Gesture {
+        Idle,
+        // one finger down that has not moved far yet, where it went down
+        Tap(u64, (f64, f64)),
+        // one finger dragging, reported as the right button
+        Pan(u64),
+        // distance of the two fingers at the last event
+        Pinch(f64),
+        // a pinch lost a finger, the rest is ignored until all are lifted
+        Done,
+    }
+
+    struct State {
+        fullscreen: bool,
+        // frame pixels per screen pixel edge, --scale, and the filter bringing frames up to them
+        scale: usize,
+        filter: Upscale,
+        window: Option<Rc<Window>>,
+        surface: Option<Surface<Rc<Window>, Rc<Window>>>,
+        failure: Option<String>,
+        open: bool,
+        events: Vec<Event>,
+        // fingers on the screen in frame pixels
+        touches: BTreeMap<u64, (f64, f64)>,
+        gesture: Gesture,
+        // mouse wheel notches pinched since the last poll
+        zoom: f64,
+        cursor: Option<(usize, usize)>,
+    }
+
+    impl TouchDisplay {
+        pub fn open(fullscreen: bool, scale: usize, filter: Upscale) -> Result<Self, Error> {
+            let failed = |message: String| Error::WindowInit(minifb::Error::WindowCreate(message));
+            let mut event_loop = EventLoop::new().map_err(|e| failed(e.to_string()))?;
+            let mut state = State {
+                fullscreen,
+                scale: scale.max(1),
+                filter,
+                window: None,
+                surface: None,
+                failure: None,
+                open: true,
+                events: Vec::new(),
+                touches: BTreeMap::new(),
+                gesture: Gesture::Idle,
+                zoom: 0.0,
+                cursor: None,
+            };
+            // the window is created when the loop first resumes
+            event_loop.pump_app_events(Some(Duration::ZERO), &mut state);
+            if let Some(failure) = state.failure.take() {
+                return Err(failed(failure));
+            }
+            if state.surface.is_none() {
+                return Err(failed(i18n::tr("no window was created").into()));
+            }
+            Ok(TouchDisplay { event_loop, state })
+        }
+    }
+
+    impl Display for TouchDisplay {
+        fn is_open(&self) -> bool {
+            self.state.open
+        }
+
+        fn size(&self) -> (usize, usize) {
+            let Some(window) = &self.state.window else {
+                return (0, 0);
+            };
+            let size = window.inner_size();
+            let scale = self.state.scale;
+            (size.width as usize / scale, size.height as usize / scale)
+        }
+
+        fn present(&mut self, buffer: &[u32], width: usize, height: usize) {
+            let _span = tracing::info_span!("present", width, height).entered();
+            let (Some(window), Some(surface)) = (&self.state.window, &mut self.state.surface)
+            else {
+                return;
+            };
+            let scale = self.state.scale;
+            let scaled;
+            let (buffer, width, height) = if scale > 1 {
+                let size = (width * scale, height * scale);
+                scaled = scaling::resize(buffer, (width, height), size, self.state.filter);
+                (scaled.as_slice(), width * scale, height * scale)
+            } else {
+                (buffer, width, height)
+            };
+            let size = window.inner_size();
+            let (Some(surface_width), Some(surface_height)) =
+                (NonZeroU32::new(size.width), NonZeroU32::new(size.height))
+            else {
+                return;
+            };
+            if surface.resize(surface_width, surface_height).is_err() {
+                return;
+            }
+            let Ok(mut target) = surface.buffer_mut() else {
+                return;
+            };
+            // while a resize settles the frame has the old size, the rest stays black
+            target.fill(0);
+            let target_width = size.width as usize;
+            let (columns, rows) = (width.min(target_width), height.min(size.height as usize));
+            for (y, row) in buffer.chunks_exact(width.max(1)).take(rows).enumerate() {
+                let start = y * target_width;
+                target[start..start + columns].copy_from_slice(&row[..columns]);
+            }
+            let _ = target.present();
+        }
+
+        fn poll_events(&mut self) -> Vec<Event> {
+            let state = &mut self.state;
+            if let PumpStatus::Exit(_) =
+                self.event_loop.pump_app_events(Some(Duration::ZERO), state)
+            {
+                state.open = false;
+            }
+            if state.zoom != 0.0 {
+                if let Some((x, y)) = state.middle() {
+                    state.events.push(Event::Scroll {
+                        x: x as usize,
+                        y: y as usize,
+                        delta: state.zoom,
+                    });
+                }
+                state.zoom = 0.0;
+            }
+            std::mem::take(&mut state.events)
+        }
+
+        fn set_title(&mut self, title: &str) {
+            if let Some(window) = &self.state.window {
+                window.set_title(title);
+            }
+        }
+    }
+
+    impl ApplicationHandler for State {
+        fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+            if self.window.is_some() {
+                return;
+            }
+            let mut attributes = Window::default_attributes()
+                .with_title(i18n::tr("Ljapunow-Markus diagram - press ESC to exit"))
+                .with_inner_size(LogicalSize::new(WIDTH as f64, HEIGHT as f64));
+            if self.fullscreen {
+                attributes = attributes.with_fullscreen(Some(Fullscreen::Borderless(None)));
+            }
+            let created = event_loop
+                .create_window(attributes)
+                .map_err(|e| e.to_string())
+                .and_then(|window| {
+                    let window = Rc::new(window);
+                    let context = Context::new(window.clone()).map_err(|e| e.to_string())?;
+                    let surface =
+                        Surface::new(&context, window.clone()).map_err(|e| e.to_string())?;
+                    Ok((window, surface))
+                });
+            match created {
+                Ok((window, surface)) => {
+                    if self.fullscreen {
+                        window.set_cursor_visible(false);
+                    }
+                    self.window = Some(window);
+                    self.surface = Some(surface);
+                }
+                Err(failure) => self.failure = Some(failure),
+            }
+        }
+
+        fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+            let scale = self.scale as f64;
+            match event {
+                WindowEvent::CloseRequested => self.open = false,
+                WindowEvent::KeyboardInput { event, .. }
+                    if event.state == ElementState::Pressed && !event.repeat =>
+                {
+                    match event.logical_key {
+                        WinitKey::Named(NamedKey::Escape) => self.open = false,
+                        key => self.events.extend(map_key(key).map(Event::KeyPressed)),
+                    }
+                }
+                WindowEvent::CursorMoved { position, .. } => {
+                    let pixel = ((position.x / scale) as usize, (position.y / scale) as usize);
+                    if self.cursor != Some(pixel) {
+                        self.cursor = Some(pixel);
+                        let (x, y) = pixel;
+                        self.events.push(Event::MouseMoved { x, y });
+                    }
+                }
+                WindowEvent::CursorLeft { .. } => {
+                    self.cursor = None;
+                    self.events.push(Event::MouseLeft);
+                }
+                WindowEvent::MouseInput { state, button, .. } => {
+                    let button = match button {
+                        winit::event::MouseButton::Left => MouseButton::Left,
+                        winit::event::MouseButton::Middle => MouseButton::Middle,
+                        winit::event::MouseButton::Right => MouseButton::Right,
+                        _ => return,
+                    };
+                    match (state, self.cursor) {
+                        (ElementState::Pressed, Some((x, y))) => {
+                            self.events.push(Event::MousePressed { x, y, button })
+                        }
+                        (ElementState::Released, _) => {
+                            self.events.push(Event::MouseReleased { button })
+                        }
+                        _ => {}
+                    }
+                }
+                // only the direction, like the minifb window
+                WindowEvent::MouseWheel { delta, .. } => {
+                    let delta = match delta {
+                        MouseScrollDelta::LineDelta(_, y) => y as f64,
+                        MouseScrollDelta::PixelDelta(position) => position.y,
+                    };
+                    if let (Some((x, y)), true) = (self.cursor, delta != 0.0) {
+                        let delta = delta.signum();
+                        self.events.push(Event::Scroll { x, y, delta });
+                    }
+                }
+                WindowEvent::Touch(touch) => {
+                    let position = (touch.location.x / scale, touch.location.y / scale);
+                    match touch.phase {
+                        TouchPhase::Started => self.finger_down(touch.id, position),
+                        TouchPhase::Moved => self.finger_moved(touch.id, position),
+                        TouchPhase::Ended | TouchPhase::Cancelled => self.finger_up(touch.id),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    impl State {
+        fn finger_down(&mut self, id: u64, position: (f64, f64)) {
+            self.touches.insert(id, position);
+            self.gesture = match (self.gesture, self.touches.len()) {
+                (Gesture::Idle, 1) => Gesture::Tap(id, position),
+                (Gesture::Tap(..), 2) => Gesture::Pinch(self.spread()),
+                (Gesture::Pan(_), 2) => {
+                    self.release(MouseButton::Right);
+                    Gesture::Pinch(self.spread())
+                }
+                _ => Gesture::Done,
+            };
+        }
+
+        fn finger_moved(&mut self, id: u64, position: (f64, f64)) {
+            let Some(finger) = self.touches.get_mut(&id) else {
+                return;
+            };
+            *finger = position;
+            let (x, y) = (position.0 as usize, position.1 as usize);
+            match self.gesture {
+                Gesture::Tap(finger, start) if finger == id => {
+                    let moved = (position.0 - start.0).hypot(position.1 - start.1);
+                    if moved > TAP_RADIUS {
+                        let (start_x, start_y) = (start.0 as usize, start.1 as usize);
+                        self.events.push(Event::MousePressed {
+                            x: start_x,
+                            y: start_y,
+                            button: MouseButton::Right,
+                        });
+                        self.events.push(Event::MouseMoved { x, y });
+                        self.gesture = Gesture::Pan(id);
+                    }
+                }
+                Gesture::Pan(finger) if finger == id => {
+                    self.events.push(Event::MouseMoved { x, y });
+                }
+                Gesture::Pinch(spread) => {
+                    let now = self.spread();
+                    if spread > 0.0 && now > 0.0 {
+                        // spreading the fingers zooms in, by as many notches as the wheel would
+                        self.zoom += (now / spread).ln() / -ZOOM_PER_NOTCH.ln();
+                    }
+                    self.gesture = Gesture::Pinch(now);
+                }
+                _ => {}
+            }
+        }
+
+        fn finger_up(&mut self, id: u64) {
+            self.touches.remove(&id);
+            match self.gesture {
+                Gesture::Tap(finger, (x, y)) if finger == id => {
+                    let (x, y) = (x as usize, y as usize);
+                    let button = MouseButton::Left;
+                    self.events.push(Event::MousePressed { x, y, button });
+                    self.release(button);
+                    self.gesture = Gesture::Idle;
+                }
+                Gesture::Pan(finger) if finger == id => {
+                    self.release(MouseButton::Right);
+                    self.gesture = Gesture::Idle;
+                }
+                _ if self.touches.is_empty() => self.gesture = Gesture::Idle,
+                _ => self.gesture = Gesture::Done,
+            }
+        }
+
+        fn release(&mut self, button: MouseButton) {
+            self.events.push(Event::MouseReleased { button });
+        }
+
+        // distance of the first two fingers
+        fn spread(&self) -> f64 {
+            let mut fingers = self.touches.values();
+            match (fingers.next(), fingers.next()) {
+                (Some(a), Some(b)) => (a.0 - b.0).hypot(a.1 - b.1),
+                _ => 0.0,
+            }
+        }
+
+        fn middle(&self) -> Option<(f64, f64)> {
+            let mut fingers = self.touches.values();
+            match (fingers.next(), fingers.next()) {
+                (Some(a), Some(b)) => Some(((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)),
+                _ => None,
+            }
+        }
+    }
+
+    fn map_key(key: WinitKey) -> Option<Key> {
+        match key {
+            WinitKey::Character(text) => {
+                let c = text.chars().next()?.to_ascii_lowercase();
+                (c.is_ascii_alphanumeric() || c == ',' || c == '.').then_some(Key::Char(c))
+            }
+            WinitKey::Named(NamedKey::Enter) => Some(Key::Enter),
+            WinitKey::Named(NamedKey::Space) => Some(Key::Space),
+            WinitKey::Named(NamedKey::ArrowLeft) => Some(Key::Left),
+            WinitKey::Named(NamedKey::ArrowRight) => Some(Key::Right),
+            WinitKey::Named(NamedKey::ArrowUp) => Some(Key::Up),
+            WinitKey::Named(NamedKey::ArrowDown) => Some(Key::Down),
+            WinitKey::Named(NamedKey::F1) => Some(Key::Help),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(feature = "touch"))]
+mod silent {
+    use crate::display::{Display, Event};
+    use crate::error::Error;
+    use crate::i18n;
+    use crate::scaling::Upscale;
+
+    pub enum TouchDisplay {}
+
+    impl TouchDisplay {
+        pub fn open(_: bool, _: usize, _: Upscale) -> Result<Self, Error> {
+            let message = i18n::tr("this build has no touch window, rebuild with --features touch");
+            Err(Error::usage(message))
+        }
+    }
+
+    impl Display for TouchDisplay {
+        fn is_open(&self) -> bool {
+            match *self {}
+        }
+
+        fn size(&self) -> (usize, usize) {
+            match *self {}
+        }
+
+        fn present(&mut self, _: &[u32], _: usize, _: usize) {
+            match *self {}
+        }
+
+        fn poll_events(&mut self) -> Vec<Event> {
+            match *self {}
+        }
+    }
+}
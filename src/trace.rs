@@ -0,0 +1,60 @@
+// --trace: where the time goes, as tracing spans for the stages of the pipeline (map setup, kernel,
+// colorize, present, encode). FILE.folded gets folded stacks for inferno-flamegraph, any other name
+// Chrome trace JSON for ui.perfetto.dev or chrome://tracing. the spans are always compiled in and
+// cost a check each without --trace, writing them needs the trace feature.
+
+#[cfg(feature = "trace")]
+pub use recording::start;
+#[cfg(not(feature = "trace"))]
+pub use silent::start;
+
+#[cfg(feature = "trace")]
+mod recording {
+    use crate::error::Error;
+    use std::any::Any;
+    use std::fs::File;
+    use std::io::BufWriter;
+    use std::path::Path;
+    use tracing_chrome::ChromeLayerBuilder;
+    use tracing_flame::FlameLayer;
+    use tracing_subscriber::prelude::*;
+
+    // writes the rest of the trace when dropped, keep it until the program ends
+    pub type Guard = Box<dyn Any>;
+
+    pub fn start(path: &Path) -> Result<Guard, Error> {
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "folded")
+        {
+            let (layer, guard) = FlameLayer::with_file(path)
+                .map_err(|e| Error::io(path, std::io::Error::other(e.to_string())))?;
+            install(tracing_subscriber::registry().with(layer));
+            return Ok(Box::new(guard));
+        }
+        let file = File::create(path).map_err(|e| Error::io(path, e))?;
+        let (layer, guard) = ChromeLayerBuilder::new()
+            .writer(BufWriter::new(file))
+            .include_args(true)
+            .build();
+        install(tracing_subscriber::registry().with(layer));
+        Ok(Box::new(guard))
+    }
+
+    fn install(subscriber: impl tracing::Subscriber + Send + Sync) {
+        // only fails if there is a subscriber already, there is none before start
+        let _ = tracing::subscriber::set_global_default(subscriber);
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+mod silent {
+    use crate::error::Error;
+    use crate::i18n;
+    use std::path::Path;
+
+    pub fn start(_: &Path) -> Result<(), Error> {
+        let message = i18n::tr("this build cannot write traces, rebuild with --features trace");
+        Err(Error::usage(message))
+    }
+}
@@ -0,0 +1,226 @@
+// where animation frames go: numbered PNGs in a directory, or raw RGB piped into an ffmpeg child
+// process that encodes them into a single video. container and codec follow the file extension.
+// PNG frames are logged with a hash of their parameters in <out>/<prefix>_manifest.ndjson,
+// so an interrupted animation can be resumed without rendering the finished frames again.
+
+use crate::error::Error;
+use crate::export::save_png;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+pub enum Frames {
+    // <out>/<prefix>_0000.png, ...
+    Directory {
+        out: PathBuf,
+        prefix: &'static str,
+        size: (usize, usize),
+        manifest: Manifest,
+    },
+    Video(Video),
+}
+
+impl Frames {
+    pub fn new(
+        out: &Path,
+        prefix: &'static str,
+        video: Option<&Path>,
+        fps: u32,
+        size: (usize, usize),
+        resume: bool,
+    ) -> Result<Self, Error> {
+        match video {
+            Some(path) => Ok(Frames::Video(Video::start(path, size, fps)?)),
+            None => {
+                fs::create_dir_all(out).map_err(|e| Error::io(out, e))?;
+                let manifest = out.join(format!("{prefix}_manifest.ndjson"));
+                Ok(Frames::Directory {
+                    out: out.to_path_buf(),
+                    prefix,
+                    size,
+                    manifest: Manifest::open(&manifest, resume)?,
+                })
+            }
+        }
+    }
+
+    fn path(out: &Path, prefix: &str, index: usize) -> PathBuf {
+        out.join(format!("{prefix}_{index:04}.png"))
+    }
+
+    // frame already written by an earlier run with the same parameters, never for videos
+    pub fn is_done(&self, index: usize, hash: u64) -> Option<PathBuf> {
+        match self {
+            Frames::Directory {
+                out,
+                prefix,
+                manifest,
+                ..
+            } => {
+                let path = Frames::path(out, prefix, index);
+                (manifest.done.get(&index) == Some(&hash) && path.exists()).then_some(path)
+            }
+            Frames::Video(_) => None,
+        }
+    }
+
+    // 0RGB buffer of the size given to new and the hash of the parameters it was rendered with,
+    // returns the file it went to
+    pub fn write(&mut self, index: usize, buffer: &[u32], hash: u64) -> Result<PathBuf, Error> {
+        match self {
+            Frames::Directory {
+                out,
+                prefix,
+                size,
+                manifest,
+            } => {
+                let path = Frames::path(out, prefix, index);
+                save_png(&path, buffer, size.0, size.1).map_err(|e| Error::io(&path, e))?;
+                manifest.add(index, hash)?;
+                Ok(path)
+            }
+            Frames::Video(video) => {
+                video.write(buffer)?;
+                Ok(video.path.clone())
+            }
+        }
+    }
+
+    // for a video wait until ffmpeg has written the whole file
+    pub fn finish(self) -> Result<(), Error> {
+        match self {
+            Frames::Directory { .. } => Ok(()),
+            Frames::Video(video) => video.finish(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    frame: usize,
+    // hex, JSON numbers lose the lower bits of a u64
+    hash: String,
+}
+
+// one line per finished frame, appended right after its PNG was written
+pub struct Manifest {
+    path: PathBuf,
+    file: File,
+    done: HashMap<usize, u64>,
+}
+
+impl Manifest {
+    // without resume an old manifest is discarded, all frames get rendered again
+    fn open(path: &Path, resume: bool) -> Result<Self, Error> {
+        let mut done = HashMap::new();
+        if resume {
+            if let Ok(file) = File::open(path) {
+                for line in BufReader::new(file).lines() {
+                    let line = line.map_err(|e| Error::io(path, e))?;
+                    // a crash may leave half a line at the end
+                    let Ok(entry) = serde_json::from_str::<Entry>(&line) else {
+                        continue;
+                    };
+                    if let Ok(hash) = u64::from_str_radix(&entry.hash, 16) {
+                        done.insert(entry.frame, hash);
+                    }
+                }
+            }
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(resume)
+            .truncate(!resume)
+            .open(path)
+            .map_err(|e| Error::io(path, e))?;
+        Ok(Manifest {
+            path: path.to_path_buf(),
+            file,
+            done,
+        })
+    }
+
+    fn add(&mut self, frame: usize, hash: u64) -> Result<(), Error> {
+        let entry = Entry {
+            frame,
+            hash: format!("{hash:016x}"),
+        };
+        let line = serde_json::to_string(&entry).expect("entries serialize");
+        writeln!(self.file, "{line}").map_err(|e| Error::io(&self.path, e))?;
+        self.done.insert(frame, hash);
+        Ok(())
+    }
+}
+
+pub struct Video {
+    path: PathBuf,
+    child: Child,
+    stdin: ChildStdin,
+}
+
+impl Video {
+    pub fn start(path: &Path, (width, height): (usize, usize), fps: u32) -> Result<Self, Error> {
+        let mut child = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error"])
+            .args(["-f", "rawvideo", "-pixel_format", "rgb24", "-video_size"])
+            .arg(format!("{width}x{height}"))
+            .arg("-framerate")
+            .arg(fps.to_string())
+            .args(["-i", "-"])
+            // most players only handle 4:2:0, which needs an even size
+            .args([
+                "-pix_fmt",
+                "yuv420p",
+                "-vf",
+                "pad=ceil(iw/2)*2:ceil(ih/2)*2",
+            ])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::io(Path::new("ffmpeg"), e))?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        Ok(Video {
+            path: path.to_path_buf(),
+            child,
+            stdin,
+        })
+    }
+
+    pub fn write(&mut self, buffer: &[u32]) -> Result<(), Error> {
+        let data: Vec<u8> = buffer
+            .iter()
+            .flat_map(|&pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, pixel as u8])
+            .collect();
+        // a broken pipe means ffmpeg gave up, its exit status is the better error
+        if let Err(e) = self.stdin.write_all(&data) {
+            return match self.child.try_wait() {
+                Ok(Some(status)) => Err(ffmpeg_failed(&self.path, status)),
+                _ => Err(Error::io(&self.path, e)),
+            };
+        }
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<(), Error> {
+        let Video {
+            path,
+            mut child,
+            stdin,
+        } = self;
+        // closing stdin ends the input, ffmpeg finishes the file and exits
+        drop(stdin);
+        let status = child.wait().map_err(|e| Error::io(&path, e))?;
+        if !status.success() {
+            return Err(ffmpeg_failed(&path, status));
+        }
+        Ok(())
+    }
+}
+
+fn ffmpeg_failed(path: &Path, status: std::process::ExitStatus) -> Error {
+    Error::io(path, io::Error::other(format!("ffmpeg failed ({status})")))
+}
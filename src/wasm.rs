@@ -0,0 +1,144 @@
+// --wasm: the value of every pixel computed by a WebAssembly module instead of the built in maps, for
+// dynamical systems of your own. the module runs in the wasmi interpreter and only sees what it is
+// given: it has no imports, its memory is capped and every pixel gets a fuel budget proportional to
+// the depth, a pixel that runs out or traps is NaN. it exports
+//
+//     value(a: f64, b: f64, length: i32, depth: i32) -> f64
+//     sequence(length: i32) -> i32      optional, address for the letters of the sequence
+//     memory                            needed with sequence
+//
+// the letters of the sequence (A and B as ASCII) are written to the address sequence returns before the
+// first pixel and whenever the sequence changes. the result is colored like λ.
+// running modules needs the wasm feature (wasmi), without it --wasm only reports that.
+
+#[cfg(feature = "wasm")]
+pub use sandbox::load;
+#[cfg(not(feature = "wasm"))]
+pub use silent::load;
+
+#[cfg(feature = "wasm")]
+mod sandbox {
+    use crate::cache::StableHasher;
+    use crate::error::Error;
+    use crate::i18n;
+    use crate::render::External;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::Path;
+    use std::sync::Arc;
+    use wasmi::{
+        Config, Engine, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc,
+    };
+
+    const MEMORY_LIMIT: usize = 64 << 20;
+    // budget per pixel, plenty for a few dozen instructions per iteration
+    const FUEL_PER_STEP: u64 = 1000;
+    const FUEL_BASE: u64 = 100_000;
+
+    struct Instance {
+        store: Store<StoreLimits>,
+        value: TypedFunc<(f64, f64, i32, i32), f64>,
+        sequence: Option<(TypedFunc<i32, i32>, Memory)>,
+        // the sequence last written to the memory of the module
+        written: Vec<char>,
+    }
+
+    thread_local! {
+        // every render thread runs its own instances, by the key of the module
+        static INSTANCES: RefCell<HashMap<u64, Instance>> = RefCell::new(HashMap::new());
+    }
+
+    // the module at path, instantiated once to check its exports
+    pub fn load(path: &Path) -> Result<External, Error> {
+        let wasm = |message: &dyn std::fmt::Display| {
+            Error::Plugin(format!("{}: {message}", path.display()))
+        };
+        let bytes = fs::read(path).map_err(|e| Error::io(path, e))?;
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, &bytes).map_err(|e| wasm(&e))?;
+        instantiate(&engine, &module).map_err(|e| wasm(&e))?;
+
+        let key = StableHasher::default().bytes(&bytes).finish();
+        Ok(External {
+            key,
+            value: Arc::new(move |a, b, sequence, depth| {
+                INSTANCES.with_borrow_mut(|instances| {
+                    let instance = match instances.get_mut(&key) {
+                        Some(instance) => instance,
+                        None => match instantiate(&engine, &module) {
+                            Ok(instance) => instances.entry(key).or_insert(instance),
+                            Err(_) => return f64::NAN,
+                        },
+                    };
+                    instance.value(a, b, sequence, depth).unwrap_or(f64::NAN)
+                })
+            }),
+        })
+    }
+
+    fn instantiate(engine: &Engine, module: &Module) -> Result<Instance, wasmi::Error> {
+        let limits = StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT).build();
+        let mut store = Store::new(engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(FUEL_BASE)?;
+        let instance = Linker::new(engine)
+            .instantiate(&mut store, module)?
+            .start(&mut store)?;
+        let value = instance.get_typed_func(&store, "value")?;
+        let sequence = match instance.get_typed_func(&store, "sequence") {
+            Ok(sequence) => {
+                let memory = instance.get_memory(&store, "memory").ok_or_else(|| {
+                    wasmi::Error::new(i18n::tr("sequence is exported without memory"))
+                })?;
+                Some((sequence, memory))
+            }
+            Err(_) => None,
+        };
+        Ok(Instance {
+            store,
+            value,
+            sequence,
+            written: Vec::new(),
+        })
+    }
+
+    impl Instance {
+        fn value(
+            &mut self,
+            a: f64,
+            b: f64,
+            sequence: &[char],
+            depth: u32,
+        ) -> Result<f64, wasmi::Error> {
+            let fuel = FUEL_BASE + FUEL_PER_STEP * depth as u64;
+            self.store.set_fuel(fuel)?;
+            if let Some((function, memory)) = self.sequence.filter(|_| self.written != sequence) {
+                let address = function.call(&mut self.store, sequence.len() as i32)?;
+                let letters: String = sequence.iter().collect();
+                memory
+                    .write(&mut self.store, address as u32 as usize, letters.as_bytes())
+                    .map_err(|e| wasmi::Error::new(e.to_string()))?;
+                self.written = sequence.to_vec();
+            }
+            self.value
+                .call(&mut self.store, (a, b, sequence.len() as i32, depth as i32))
+        }
+    }
+}
+
+// without the wasm feature no module can be run
+#[cfg(not(feature = "wasm"))]
+mod silent {
+    use crate::error::Error;
+    use crate::i18n;
+    use crate::render::External;
+    use std::path::Path;
+
+    pub fn load(_: &Path) -> Result<External, Error> {
+        let message = i18n::tr("this build cannot run WebAssembly, rebuild with --features wasm");
+        Err(Error::Plugin(message.into()))
+    }
+}
@@ -0,0 +1,175 @@
+// --workspace: the whole state of a window session in a JSON file, written when the window closes and
+// restored on the next start, so long explorations survive restarts. it holds the view (sequence,
+// region, palette, depth), the panels shown, the bookmarks and the definitions of custom palettes in
+// use. the λ grid of the view goes next to it as FILE.lgrid and is shown right away on restore if
+// window size, view and kernel still match, otherwise the view is computed again.
+
+use crate::color;
+use crate::config::PaletteDef;
+use crate::error::Error;
+use crate::frame::Frame;
+use crate::grid_file::{GridReader, GridWriter, Header, Storage};
+use crate::i18n;
+use crate::render::{Alphabet, Kernel, Region};
+use crate::session::{array_to_region, check_depth, region_to_array};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub sequence: String,
+    pub region: [f64; 4],
+    pub depth: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub sequence: String,
+    pub region: [f64; 4],
+    pub palette: String,
+    pub depth: u32,
+    #[serde(default = "shown")]
+    pub minimap: bool,
+    #[serde(default)]
+    pub histogram: bool,
+    #[serde(default)]
+    pub terrain: bool,
+    #[serde(default)]
+    pub equalize: bool,
+    #[serde(default)]
+    pub colorbar: bool,
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    #[serde(default)]
+    pub palettes: BTreeMap<String, PaletteDef>,
+    // kernel the λ grid next to the file was computed with, see Kernel::key
+    #[serde(default)]
+    pub grid_kernel: Option<u64>,
+}
+
+fn shown() -> bool {
+    true
+}
+
+impl Workspace {
+    // None if there is no workspace file yet. sequences have to be made of the letters of alphabet
+    pub fn load(path: &Path, alphabet: &Alphabet) -> Result<Option<Self>, Error> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(Error::io(path, e)),
+        };
+        let mut workspace: Workspace = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| Error::usage(format!("{}: not a workspace file: {e}", path.display())))?;
+        let invalid = |e: String| Error::usage(format!("{}: {e}", path.display()));
+        workspace.start_region().check().map_err(invalid)?;
+        check_depth(workspace.depth).map_err(invalid)?;
+        alphabet
+            .sequence(&workspace.sequence)
+            .map_err(|e| invalid(e.to_string()))?;
+        // a broken bookmark, e.g. one of letters no longer defined, is left out and the rest restored
+        let mut index = 0;
+        workspace.bookmarks.retain(|bookmark| {
+            index += 1;
+            let checked = array_to_region(bookmark.region)
+                .check()
+                .and_then(|_| check_depth(bookmark.depth))
+                .and_then(|_| {
+                    let sequence = alphabet.sequence(&bookmark.sequence);
+                    sequence.map(|_| ()).map_err(|e| e.to_string())
+                });
+            if let Err(e) = &checked {
+                eprintln!(
+                    "{}",
+                    i18n::trf(
+                        "{0}: bookmark {1} left out: {2}",
+                        &[&path.display(), &index, e]
+                    )
+                );
+            }
+            checked.is_ok()
+        });
+        Ok(Some(workspace))
+    }
+
+    // the view and its λ grid, the grid only if it is complete
+    pub fn save(&self, path: &Path, frame: &Frame) -> Result<(), Error> {
+        let file = File::create(path).map_err(|e| Error::io(path, e))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)
+            .map_err(|e| Error::io(path, e.into()))?;
+        if self.grid_kernel.is_some() {
+            let grid = grid_path(path);
+            save_grid(&grid, frame, self.start_region()).map_err(|e| Error::io(&grid, e))?;
+        }
+        Ok(())
+    }
+
+    pub fn start_region(&self) -> Region {
+        array_to_region(self.region)
+    }
+
+    // makes the palettes of the workspace usable by name before its palette is looked up
+    pub fn register_palettes(&self) -> Result<(), Error> {
+        for (name, palette) in &self.palettes {
+            let ramp = palette
+                .ramp()
+                .map_err(|e| Error::usage(format!("palette {name}: {e}")))?;
+            color::register(name, ramp).map_err(Error::usage)?;
+        }
+        Ok(())
+    }
+
+    // the saved λ grid if it was computed for this size, view and kernel
+    pub fn grid(
+        &self,
+        path: &Path,
+        (width, height): (usize, usize),
+        kernel: &Kernel,
+    ) -> Option<Vec<f64>> {
+        if self.grid_kernel != Some(kernel.key()) {
+            return None;
+        }
+        let reader = GridReader::open(&grid_path(path)).ok()?;
+        let header = reader.header;
+        if (header.width, header.height) != (width, height) || header.region != self.start_region()
+        {
+            return None;
+        }
+        Some((0..height).flat_map(|y| reader.row(y)).collect())
+    }
+}
+
+// FILE.lgrid next to the workspace FILE
+fn grid_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".lgrid");
+    PathBuf::from(name)
+}
+
+fn save_grid(path: &Path, frame: &Frame, region: Region) -> io::Result<()> {
+    let header = Header {
+        width: frame.width,
+        height: frame.height,
+        region,
+        storage: Storage::F64,
+        range: (0.0, 0.0),
+    };
+    let mut writer = GridWriter::create(path, header)?;
+    for (y, row) in frame.lambdas.chunks_exact(frame.width).enumerate() {
+        writer.write_row(y, row);
+    }
+    writer.finish()
+}
+
+impl Bookmark {
+    pub fn new(kernel: &Kernel, region: &Region) -> Self {
+        Bookmark {
+            sequence: kernel.sequence_rule.iter().collect(),
+            region: region_to_array(region),
+            depth: kernel.depth,
+        }
+    }
+}
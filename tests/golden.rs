@@ -0,0 +1,68 @@
+// golden image tests: small deterministic renders of all presets compared against committed hashes,
+// so refactors of the kernel and the colorizer can not silently change the output.
+// after an intended change run `UPDATE_GOLDEN=1 cargo test --test golden` and commit tests/golden.txt.
+
+use ljapunow::frame::Frame;
+use ljapunow::presets::{Preset, PRESETS};
+use ljapunow::render::{render_rows, Kernel};
+use std::env;
+use std::fs;
+
+const SIZE: usize = 64;
+const GOLDEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden.txt");
+
+// FNV-1a, unlike DefaultHasher stable across Rust versions
+fn fnv1a(bytes: impl Iterator<Item = u8>) -> u64 {
+    bytes.fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// hashes of the λ grid and of the colored buffer, so a mismatch tells kernel and colorizer apart
+fn render(preset: &Preset) -> (u64, u64) {
+    let mut frame = Frame::new(SIZE, SIZE);
+    let kernel = Kernel::new(preset.sequence.chars().collect());
+    render_rows(
+        &mut frame.lambdas,
+        SIZE,
+        SIZE,
+        &preset.region,
+        &kernel,
+        0..SIZE,
+    );
+    frame.colorize_rows(0..SIZE, preset.palette);
+
+    let lambdas = fnv1a(frame.lambdas.iter().flat_map(|l| l.to_bits().to_le_bytes()));
+    let colors = fnv1a(frame.buffer.iter().flat_map(|c| c.to_le_bytes()));
+    (lambdas, colors)
+}
+
+#[test]
+fn presets_match_golden_hashes() {
+    let actual: Vec<String> = PRESETS
+        .iter()
+        .map(|preset| {
+            let (lambdas, colors) = render(preset);
+            format!("{} {lambdas:016x} {colors:016x}", preset.name)
+        })
+        .collect();
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(GOLDEN, actual.join("\n") + "\n").unwrap();
+        return;
+    }
+
+    let golden = fs::read_to_string(GOLDEN).unwrap();
+    let golden: Vec<&str> = golden.lines().collect();
+    let mismatches: Vec<String> = actual
+        .iter()
+        .filter(|line| !golden.contains(&line.as_str()))
+        .cloned()
+        .collect();
+    assert!(
+        mismatches.is_empty(),
+        "renders differ from {GOLDEN} (name λ-hash color-hash):\n{}",
+        mismatches.join("\n")
+    );
+    assert_eq!(golden.len(), actual.len(), "presets added or removed");
+}
@@ -0,0 +1,22 @@
+// streaming rows must give exactly the same λ as rendering the whole grid
+
+use ljapunow::render::{render_rows, Kernel, Region, Renderer};
+
+#[test]
+fn rows_match_full_render() {
+    let (width, height) = (24, 16);
+    let kernel = Kernel::new("AB".chars().collect());
+    let region = Region::new(2.0, 4.0, 2.0, 4.0);
+
+    let mut grid = vec![f64::NAN; width * height];
+    render_rows(&mut grid, width, height, &region, &kernel, 0..height);
+
+    let renderer = Renderer::new(width, height, region, kernel);
+    let streamed: Vec<f64> = renderer.rows().flatten().collect();
+
+    assert_eq!(streamed.len(), grid.len());
+    assert!(streamed
+        .iter()
+        .zip(&grid)
+        .all(|(s, g)| s.to_bits() == g.to_bits()));
+}